@@ -1,8 +1,10 @@
-//! Re-exports Rust code of widgets and aitk's prelude.
+//! Re-exports Rust code of widgets, tool hooks and aitk's prelude.
 
 pub use crate::widgets::{
     chat::*, citation_list::*, message_markdown::*, messages::*, model_selector::*,
     model_selector_list::*, moly_modal::*, prompt_input::*, realtime::*,
 };
 
+pub use crate::utils::{tool_execution::*, tool_permissions::*};
+
 pub use aitk::prelude::*;
@@ -2,4 +2,6 @@
 
 pub(crate) mod audio;
 pub mod makepad;
-pub(crate) mod scraping;
+pub mod scraping;
+pub mod tool_execution;
+pub mod tool_permissions;
@@ -38,7 +38,7 @@ pub(crate) async fn fetch_text(url: &str) -> Result<String, ()> {
 /// The html type from the `scraper` crate would be better but it has some
 /// non-Send data, so holding a parsed tree for efficiency is not trivial inside
 /// multi-threaded code, at least with that representation.
-pub(crate) struct Html(String);
+pub struct Html(String);
 
 impl Html {
     /// Convert to a parsed HTML document.
@@ -48,13 +48,13 @@ impl Html {
 }
 
 /// Perform a GET request and try to parse it as an HTML document.
-pub(crate) async fn fetch_html(url: &str) -> Result<Html, ()> {
+pub async fn fetch_html(url: &str) -> Result<Html, ()> {
     let text = fetch_text(url).await?;
     Ok(Html(text))
 }
 
 /// Extract the title from a standard HTML document.
-pub(crate) fn extract_title(document: &Html) -> Option<String> {
+pub fn extract_title(document: &Html) -> Option<String> {
     let document = document.to_scraper();
     let title_selector = Selector::parse("title").unwrap();
     document
@@ -63,3 +63,79 @@ pub(crate) fn extract_title(document: &Html) -> Option<String> {
         .and_then(|element| element.text().next())
         .map(|text| text.trim().to_string())
 }
+
+/// Elements whose text is not part of the readable content of a page.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "nav", "header", "footer", "aside", "form",
+];
+
+/// Elements starting a new line of text.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "br",
+    "li",
+    "tr",
+    "pre",
+    "blockquote",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+];
+
+/// Extract the readable text of a standard HTML document, one line per block.
+///
+/// The main content is preferred over the whole body, and scripts, styles and
+/// navigation are skipped.
+pub fn extract_readable_text(document: &Html) -> String {
+    let document = document.to_scraper();
+    let root = ["article", "main", "body"]
+        .iter()
+        .find_map(|name| {
+            let selector = Selector::parse(name).unwrap();
+            document.select(&selector).next()
+        })
+        .unwrap_or_else(|| document.root_element());
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for node in root.descendants() {
+        if let Some(element) = node.value().as_element() {
+            if BLOCK_ELEMENTS.contains(&element.name()) && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            continue;
+        }
+
+        let Some(text) = node.value().as_text() else {
+            continue;
+        };
+
+        let skipped = node.ancestors().any(|ancestor| {
+            ancestor
+                .value()
+                .as_element()
+                .is_some_and(|element| SKIPPED_ELEMENTS.contains(&element.name()))
+        });
+        if skipped {
+            continue;
+        }
+
+        for word in text.split_whitespace() {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines.join("\n")
+}
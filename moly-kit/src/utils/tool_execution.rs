@@ -0,0 +1,428 @@
+//! Hooks to follow and limit approved tool calls, and the runner applying them.
+
+use futures::future::{self, Either};
+use std::future::Future;
+use std::pin::pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use crate::aitk::utils::asynchronous::{AbortOnDropHandle, sleep, spawn_abort_on_drop};
+use crate::aitk::utils::tool::display_name_from_namespaced;
+use crate::prelude::*;
+use crate::widgets::messages::FULL_OUTPUTS_KEY;
+
+/// Follows and limits the tool calls approved in a chat.
+///
+/// Tool names are the namespaced ones given by the tool manager.
+pub trait ToolExecution: Send + Sync {
+    /// Called right before approved calls run, telling if the user approved them
+    /// or [`ToolPermissions::decide`] allowed them.
+    fn approved(&self, _tool_calls: &[ToolCall], _by_user: bool) {}
+
+    /// How long a call to the tool can take before it's abandoned, if limited.
+    fn timeout(&self, _tool_name: &str) -> Option<Duration> {
+        None
+    }
+
+    /// Characters of a tool result given to the model, if limited.
+    ///
+    /// Longer results are cut and the full output is kept in the message of the
+    /// results instead, for the user to see.
+    fn max_output_chars(&self) -> Option<usize> {
+        None
+    }
+
+    /// Called with the results of approved calls once they are back, before they
+    /// are cut to [`Self::max_output_chars`].
+    fn finished(&self, _bot_id: Option<&BotId>, _results: &[ToolResult]) {}
+}
+
+/// Runs the approved tool calls of a chat controller in the background.
+///
+/// Registered as a plugin of the controller, which keeps it and its running calls
+/// alive. The chat is marked as streaming meanwhile, so it can be stopped and is
+/// kept around like when a response is streaming.
+#[derive(Clone)]
+pub struct ToolRunner(Arc<Mutex<RunnerState>>);
+
+struct RunnerState {
+    controller: Weak<Mutex<ChatController>>,
+    execution: Option<Arc<dyn ToolExecution>>,
+    running: Option<RunningTools>,
+}
+
+/// Approved tool calls being run.
+struct RunningTools {
+    /// Index of the message their results go to.
+    index: usize,
+    tool_calls: Vec<ToolCall>,
+    /// Aborts the calls when dropped.
+    _abort: AbortOnDropHandle,
+}
+
+impl ToolRunner {
+    pub fn new(controller: &Arc<Mutex<ChatController>>) -> Self {
+        Self(Arc::new(Mutex::new(RunnerState {
+            controller: Arc::downgrade(controller),
+            execution: None,
+            running: None,
+        })))
+    }
+
+    pub fn set_execution(&self, execution: Option<Arc<dyn ToolExecution>>) {
+        self.0.lock().unwrap().execution = execution;
+    }
+
+    /// Returns true if approved tool calls are running.
+    pub fn is_running(&self) -> bool {
+        self.0.lock().unwrap().running.is_some()
+    }
+
+    /// Approves and runs the tool request at `index`, approved either by the user
+    /// or by the permissions.
+    pub fn approve(&self, index: usize, by_user: bool) {
+        let Some(controller) = self.0.lock().unwrap().controller.upgrade() else {
+            return;
+        };
+        let execution = self.0.lock().unwrap().execution.clone();
+        let mut lock = controller.lock().unwrap();
+
+        let mut updated_message = lock.state().messages[index].clone();
+        for tool_call in &mut updated_message.content.tool_calls {
+            tool_call.permission_status = ToolCallPermissionStatus::Approved;
+        }
+        lock.dispatch_mutation(VecMutation::Update(index, updated_message));
+
+        let tool_calls = lock.state().messages[index].content.tool_calls.clone();
+        if let Some(execution) = &execution {
+            execution.approved(&tool_calls, by_user);
+        }
+
+        let Some(tool_manager) = lock.tool_manager().cloned() else {
+            let bot_id = lock.state().bot_id.clone();
+            lock.dispatch_task(ChatTask::Execute(tool_calls, bot_id));
+            return;
+        };
+
+        // Run here instead of by the controller, so calls can time out and be stopped.
+        let names = tool_calls
+            .iter()
+            .map(|tc| display_name_from_namespaced(&tc.name).to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        lock.dispatch_mutation(VecMutation::Push(Message {
+            from: EntityId::Tool,
+            content: MessageContent {
+                text: format!("⏳ Running {}...", names),
+                ..Default::default()
+            },
+            ..Default::default()
+        }));
+        let results_index = lock.state().messages.len() - 1;
+        lock.dispatch_mutation(ChatStateMutation::SetIsStreaming(true));
+        drop(lock);
+
+        let timeouts = tool_calls
+            .iter()
+            .map(|tc| execution.as_ref().and_then(|e| e.timeout(&tc.name)))
+            .collect::<Vec<_>>();
+
+        // Held while spawning, so the calls can't finish before they are known.
+        let mut state = self.0.lock().unwrap();
+        let calls = tool_calls.clone();
+        let runner = Arc::downgrade(&self.0);
+        let task = async move {
+            let executions = calls
+                .iter()
+                .zip(timeouts)
+                .map(|(tool_call, timeout)| execute_tool_call(&tool_manager, tool_call, timeout));
+            let results = future::join_all(executions).await;
+
+            if let Some(runner) = runner.upgrade() {
+                ToolRunner(runner).finish(results_index, results);
+            }
+        };
+
+        state.running = Some(RunningTools {
+            index: results_index,
+            tool_calls,
+            _abort: spawn_abort_on_drop(task),
+        });
+    }
+
+    /// Gives the results of the running tool calls to the bot.
+    fn finish(&self, index: usize, results: Vec<ToolResult>) {
+        let (running, controller, execution) = {
+            let mut state = self.0.lock().unwrap();
+            // Results of calls stopped meanwhile are ignored.
+            let Some(running) = state.running.take_if(|running| running.index == index) else {
+                return;
+            };
+            (running, state.controller.upgrade(), state.execution.clone())
+        };
+        let Some(controller) = controller else {
+            return;
+        };
+
+        let mut lock = controller.lock().unwrap();
+        let max_chars = execution.as_ref().and_then(|execution| {
+            execution.finished(lock.state().bot_id.as_ref(), &results);
+            execution.max_output_chars()
+        });
+        lock.dispatch_mutation(ChatStateMutation::SetIsStreaming(false));
+        if set_tool_results(&mut lock, &running, results, max_chars, "🔧 Tool results:") {
+            lock.dispatch_task(ChatTask::Send);
+        }
+    }
+
+    /// Stops the running tool calls, answering them as cancelled so the bot knows.
+    pub fn cancel(&self) {
+        let (running, controller, execution) = {
+            let mut state = self.0.lock().unwrap();
+            let Some(running) = state.running.take() else {
+                return;
+            };
+            (running, state.controller.upgrade(), state.execution.clone())
+        };
+        let Some(controller) = controller else {
+            return;
+        };
+
+        let results = running
+            .tool_calls
+            .iter()
+            .map(|tc| ToolResult {
+                tool_call_id: tc.id.clone(),
+                content: format!(
+                    "Tool '{}' was stopped by the user before it finished.",
+                    display_name_from_namespaced(&tc.name)
+                ),
+                is_error: true,
+            })
+            .collect::<Vec<_>>();
+
+        let mut lock = controller.lock().unwrap();
+        if let Some(execution) = &execution {
+            execution.finished(lock.state().bot_id.as_ref(), &results);
+        }
+        lock.dispatch_mutation(ChatStateMutation::SetIsStreaming(false));
+        set_tool_results(
+            &mut lock,
+            &running,
+            results,
+            None,
+            "🛑 Tool execution was stopped by the user.",
+        );
+    }
+}
+
+impl ChatControllerPlugin for ToolRunner {
+    fn on_state_ready(&mut self, _state: &ChatState, _mutations: &[ChatStateMutation]) {}
+}
+
+/// Runs a call approved outside of a chat controller, like the ones of realtime
+/// conversations, telling `execution` about it before and after `run`.
+pub(crate) async fn run_approved_call(
+    execution: Option<Arc<dyn ToolExecution>>,
+    bot_id: Option<BotId>,
+    tool_call: ToolCall,
+    by_user: bool,
+    run: impl Future<Output = ToolResult>,
+) -> ToolResult {
+    let timeout = execution
+        .as_ref()
+        .and_then(|execution| execution.timeout(&tool_call.name));
+    if let Some(execution) = &execution {
+        execution.approved(std::slice::from_ref(&tool_call), by_user);
+    }
+
+    let result = with_timeout(&tool_call, timeout, run).await;
+
+    if let Some(execution) = &execution {
+        execution.finished(bot_id.as_ref(), std::slice::from_ref(&result));
+    }
+    result
+}
+
+/// Runs the call with the tool manager, abandoning it after `timeout`.
+///
+/// The tool manager can't tell the server to cancel a call, so an abandoned call
+/// is only dropped from here.
+async fn execute_tool_call(
+    tool_manager: &McpManagerClient,
+    tool_call: &ToolCall,
+    timeout: Option<Duration>,
+) -> ToolResult {
+    let execution =
+        tool_manager.execute_tool_call(&tool_call.name, &tool_call.id, tool_call.arguments.clone());
+    with_timeout(tool_call, timeout, execution).await
+}
+
+async fn with_timeout(
+    tool_call: &ToolCall,
+    timeout: Option<Duration>,
+    execution: impl Future<Output = ToolResult>,
+) -> ToolResult {
+    let Some(timeout) = timeout else {
+        return execution.await;
+    };
+
+    match future::select(pin!(execution), pin!(sleep(timeout))).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => ToolResult {
+            tool_call_id: tool_call.id.clone(),
+            content: format!(
+                "Tool '{}' didn't finish within {} seconds and was abandoned.",
+                display_name_from_namespaced(&tool_call.name),
+                timeout.as_secs()
+            ),
+            is_error: true,
+        },
+    }
+}
+
+/// Puts the results of the running calls in their message, cutting the outputs
+/// longer than `max_chars` and keeping them in full in the data of the message
+/// instead, for [`Messages`] to show them.
+///
+/// Returns `false` if the message is gone, e.g. because the user deleted it.
+fn set_tool_results(
+    controller: &mut ChatController,
+    running: &RunningTools,
+    mut results: Vec<ToolResult>,
+    max_chars: Option<usize>,
+    heading: &str,
+) -> bool {
+    let Some(message) = controller.state().messages.get(running.index) else {
+        return false;
+    };
+    if message.from != EntityId::Tool || !message.content.tool_results.is_empty() {
+        return false;
+    }
+
+    let mut lines = vec![heading.to_string()];
+    let mut full_outputs = Vec::new();
+    for result in &mut results {
+        let name = running
+            .tool_calls
+            .iter()
+            .find(|tc| tc.id == result.tool_call_id)
+            .map(|tc| display_name_from_namespaced(&tc.name).to_string())
+            .unwrap_or_default();
+        let status = if result.is_error { "❌" } else { "✅" };
+
+        let truncated = max_chars.and_then(|max| truncate_output(&result.content, max));
+        match truncated {
+            Some(truncated) => {
+                lines.push(format!(
+                    "{} {}: the output was cut to {} of {} characters for the model.",
+                    status,
+                    name,
+                    max_chars.unwrap_or_default(),
+                    result.content.chars().count()
+                ));
+                full_outputs.push(serde_json::json!({
+                    "tool": name,
+                    "output": std::mem::replace(&mut result.content, truncated),
+                }));
+            }
+            None => lines.push(format!("{} {}", status, name)),
+        }
+    }
+
+    let mut message = message.clone();
+    message.update_content(|content| {
+        content.text = lines.join("\n");
+        content.tool_results = results;
+        if !full_outputs.is_empty() {
+            content.data = Some(serde_json::json!({ FULL_OUTPUTS_KEY: full_outputs }).to_string());
+        }
+    });
+    controller.dispatch_mutation(VecMutation::Update(running.index, message));
+    true
+}
+
+/// Cuts an output longer than `max_chars`, keeping its start and its end.
+fn truncate_output(output: &str, max_chars: usize) -> Option<String> {
+    let total = output.chars().count();
+    if total <= max_chars {
+        return None;
+    }
+
+    let tail = max_chars / 5;
+    let head = max_chars - tail;
+    let start = output.chars().take(head).collect::<String>();
+    let end = output.chars().skip(total - tail).collect::<String>();
+    Some(format!(
+        "{}\n\n[... {} characters omitted, the output is {} characters long ...]\n\n{}",
+        start,
+        total - head - tail,
+        total,
+        end
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    /// Records the hooks called, and the run of the call between them.
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Recorder {
+        fn push(&self, event: String) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    impl ToolExecution for Recorder {
+        fn approved(&self, tool_calls: &[ToolCall], by_user: bool) {
+            self.push(format!("approved {} by_user={}", tool_calls[0].id, by_user));
+        }
+
+        fn finished(&self, _bot_id: Option<&BotId>, results: &[ToolResult]) {
+            self.push(format!(
+                "finished {}: {}",
+                results[0].tool_call_id, results[0].content
+            ));
+        }
+    }
+
+    #[test]
+    fn test_realtime_call_is_approved_and_finished() {
+        let recorder = Arc::new(Recorder::default());
+        let tool_call = ToolCall {
+            id: "call_1".to_string(),
+            name: "weather__forecast".to_string(),
+            ..Default::default()
+        };
+        let run = async {
+            recorder.push("run".to_string());
+            ToolResult {
+                tool_call_id: "call_1".to_string(),
+                content: "Sunny".to_string(),
+                is_error: false,
+            }
+        };
+
+        let result = block_on(run_approved_call(
+            Some(recorder.clone()),
+            None,
+            tool_call,
+            false,
+            run,
+        ));
+
+        assert_eq!(result.content, "Sunny");
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                "approved call_1 by_user=false",
+                "run",
+                "finished call_1: Sunny"
+            ]
+        );
+    }
+}
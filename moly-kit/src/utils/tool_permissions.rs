@@ -0,0 +1,31 @@
+//! Hooks to decide tool calls without asking the user every time.
+
+/// What to do with a call to a tool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolDecision {
+    /// Run it without asking.
+    Allow,
+    /// Ask the user for permission, the default behavior.
+    Ask,
+    /// Refuse it without asking.
+    Deny,
+}
+
+/// How far an approval given by the user extends beyond the current call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolGrantScope {
+    /// Calls to the tool from the same chat.
+    Chat,
+    /// Calls to the tool from any chat.
+    Always,
+}
+
+/// Decides tool calls on behalf of the user, and remembers what they allowed.
+///
+/// Tool names are the namespaced ones given by the tool manager.
+pub trait ToolPermissions: Send + Sync {
+    fn decide(&self, tool_name: &str) -> ToolDecision;
+
+    /// Called when the user approves a call choosing to also allow future ones.
+    fn grant(&self, tool_name: &str, scope: ToolGrantScope);
+}
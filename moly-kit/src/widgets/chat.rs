@@ -56,6 +56,16 @@ pub struct Chat {
 
     #[rust]
     plugin_id: Option<ChatControllerPluginRegistrationId>,
+
+    #[rust]
+    tool_permissions: Option<Arc<dyn ToolPermissions>>,
+
+    #[rust]
+    tool_execution: Option<Arc<dyn ToolExecution>>,
+
+    /// Runs the approved tool calls, registered in the current controller.
+    #[rust]
+    tool_runner: Option<(ToolRunner, ChatControllerPluginRegistrationId)>,
 }
 
 impl Widget for Chat {
@@ -338,63 +348,16 @@ impl Chat {
                             .dispatch_task(ChatTask::Send);
                     }
                 }
-                MessagesAction::ToolApprove(index) => {
-                    let mut lock = chat_controller.lock().unwrap();
-
-                    let mut updated_message = lock.state().messages[index].clone();
-
-                    for tool_call in &mut updated_message.content.tool_calls {
-                        tool_call.permission_status = ToolCallPermissionStatus::Approved;
-                    }
-
-                    lock.dispatch_mutation(VecMutation::Update(index, updated_message));
-
-                    let tools = lock.state().messages[index].content.tool_calls.clone();
-                    let bot_id = lock.state().bot_id.clone();
-                    lock.dispatch_task(ChatTask::Execute(tools, bot_id));
+                MessagesAction::ToolApprove(index) => self.approve_tool_calls(index, true),
+                MessagesAction::ToolApproveForChat(index) => {
+                    self.grant_tool_calls(index, ToolGrantScope::Chat);
+                    self.approve_tool_calls(index, true);
                 }
-                MessagesAction::ToolDeny(index) => {
-                    let mut lock = chat_controller.lock().unwrap();
-
-                    let mut updated_message = lock.state().messages[index].clone();
-
-                    updated_message.update_content(|content| {
-                        for tool_call in &mut content.tool_calls {
-                            tool_call.permission_status = ToolCallPermissionStatus::Denied;
-                        }
-                    });
-
-                    lock.dispatch_mutation(VecMutation::Update(index, updated_message));
-
-                    // Create synthetic tool results indicating denial to maintain conversation flow
-                    let tool_results: Vec<ToolResult> = lock.state().messages[index]
-                        .content
-                        .tool_calls
-                        .iter()
-                        .map(|tc| {
-                            let display_name = display_name_from_namespaced(&tc.name);
-                            ToolResult {
-                                tool_call_id: tc.id.clone(),
-                                content: format!(
-                                    "Tool execution was denied by the user. Tool '{}' was not executed.",
-                                    display_name
-                                ),
-                                is_error: true,
-                            }
-                        })
-                        .collect();
-
-                    // Add tool result message with denial results
-                    lock.dispatch_mutation(VecMutation::Push(Message {
-                        from: EntityId::Tool,
-                        content: MessageContent {
-                            text: "🚫 Tool execution was denied by the user.".to_string(),
-                            tool_results,
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    }));
+                MessagesAction::ToolApproveAlways(index) => {
+                    self.grant_tool_calls(index, ToolGrantScope::Always);
+                    self.approve_tool_calls(index, true);
                 }
+                MessagesAction::ToolDeny(index) => self.deny_tool_calls(index, false),
                 MessagesAction::None => {}
             }
         }
@@ -440,10 +403,15 @@ impl Chat {
                 .unwrap()
                 .dispatch_task(ChatTask::Send);
         } else if prompt.read().has_stop_task() {
-            chat_controller
-                .lock()
-                .unwrap()
-                .dispatch_task(ChatTask::Stop);
+            let tool_runner = self.tool_runner.as_ref().map(|(runner, _)| runner);
+            if let Some(tool_runner) = tool_runner.filter(|runner| runner.is_running()) {
+                tool_runner.cancel();
+            } else {
+                chat_controller
+                    .lock()
+                    .unwrap()
+                    .dispatch_task(ChatTask::Stop);
+            }
         }
     }
 
@@ -502,6 +470,11 @@ impl Chat {
 
             let plugin = Plugin::new(self.ui_runner());
             self.plugin_id = Some(guard.append_plugin(plugin));
+
+            let tool_runner = ToolRunner::new(controller);
+            tool_runner.set_execution(self.tool_execution.clone());
+            let tool_runner_id = guard.append_plugin(tool_runner.clone());
+            self.tool_runner = Some((tool_runner, tool_runner_id));
         }
     }
 
@@ -509,7 +482,150 @@ impl Chat {
         self.chat_controller.as_ref()
     }
 
+    /// Lets tool calls be decided without asking, and offers the user to allow
+    /// tools beyond the current call.
+    pub fn set_tool_permissions(&mut self, tool_permissions: Option<Arc<dyn ToolPermissions>>) {
+        self.messages_ref().write().tool_grants_enabled = tool_permissions.is_some();
+        self.realtime(ids!(realtime))
+            .set_tool_permissions(tool_permissions.clone());
+        self.tool_permissions = tool_permissions;
+    }
+
+    /// Follows and limits the tool calls once approved.
+    pub fn set_tool_execution(&mut self, tool_execution: Option<Arc<dyn ToolExecution>>) {
+        self.realtime(ids!(realtime))
+            .set_tool_execution(tool_execution.clone());
+        if let Some((tool_runner, _)) = &self.tool_runner {
+            tool_runner.set_execution(tool_execution.clone());
+        }
+        self.tool_execution = tool_execution;
+    }
+
+    /// Runs the tool request at `index`, approved either by the user or by the permissions.
+    fn approve_tool_calls(&mut self, index: usize, by_user: bool) {
+        if let Some((tool_runner, _)) = &self.tool_runner {
+            tool_runner.approve(index, by_user);
+        }
+    }
+
+    /// Denies the tool request at `index`, either by the user or by the permissions.
+    fn deny_tool_calls(&mut self, index: usize, by_permissions: bool) {
+        let chat_controller = self.chat_controller.clone().unwrap();
+        let mut lock = chat_controller.lock().unwrap();
+
+        let mut updated_message = lock.state().messages[index].clone();
+
+        updated_message.update_content(|content| {
+            for tool_call in &mut content.tool_calls {
+                tool_call.permission_status = ToolCallPermissionStatus::Denied;
+            }
+        });
+
+        lock.dispatch_mutation(VecMutation::Update(index, updated_message));
+
+        let denied_by = if by_permissions {
+            "the tool permissions"
+        } else {
+            "the user"
+        };
+
+        // Create synthetic tool results indicating denial to maintain conversation flow
+        let tool_results: Vec<ToolResult> = lock.state().messages[index]
+            .content
+            .tool_calls
+            .iter()
+            .map(|tc| {
+                let display_name = display_name_from_namespaced(&tc.name);
+                ToolResult {
+                    tool_call_id: tc.id.clone(),
+                    content: format!(
+                        "Tool execution was denied by {}. Tool '{}' was not executed.",
+                        denied_by, display_name
+                    ),
+                    is_error: true,
+                }
+            })
+            .collect();
+
+        // Add tool result message with denial results
+        lock.dispatch_mutation(VecMutation::Push(Message {
+            from: EntityId::Tool,
+            content: MessageContent {
+                text: format!("🚫 Tool execution was denied by {}.", denied_by),
+                tool_results,
+                ..Default::default()
+            },
+            ..Default::default()
+        }));
+    }
+
+    fn grant_tool_calls(&self, index: usize, scope: ToolGrantScope) {
+        let Some(tool_permissions) = &self.tool_permissions else {
+            return;
+        };
+
+        let chat_controller = self.chat_controller.clone().unwrap();
+        let lock = chat_controller.lock().unwrap();
+        for tool_call in &lock.state().messages[index].content.tool_calls {
+            tool_permissions.grant(&tool_call.name, scope);
+        }
+    }
+
+    /// Decides a tool request left pending at the end of a response, if the
+    /// permissions don't need to ask the user about any of its tools.
+    fn apply_tool_permissions(&mut self, cx: &mut Cx) {
+        let Some(tool_permissions) = self.tool_permissions.clone() else {
+            return;
+        };
+        let Some(chat_controller) = self.chat_controller.clone() else {
+            return;
+        };
+
+        let (index, decisions) = {
+            let lock = chat_controller.lock().unwrap();
+            let messages = &lock.state().messages;
+            let Some(message) = messages.last() else {
+                return;
+            };
+
+            let tool_calls = &message.content.tool_calls;
+            let pending = !tool_calls.is_empty()
+                && tool_calls
+                    .iter()
+                    .all(|tc| tc.permission_status == ToolCallPermissionStatus::Pending);
+            if !pending {
+                return;
+            }
+
+            let decisions: Vec<ToolDecision> = tool_calls
+                .iter()
+                .map(|tc| tool_permissions.decide(&tc.name))
+                .collect();
+            (messages.len() - 1, decisions)
+        };
+
+        if decisions.contains(&ToolDecision::Ask) {
+            return;
+        }
+
+        // A single denied tool denies the whole request, as it's answered at once.
+        if decisions.contains(&ToolDecision::Deny) {
+            self.deny_tool_calls(index, true);
+        } else {
+            self.approve_tool_calls(index, false);
+        }
+        self.redraw(cx);
+    }
+
     fn unlink_current_controller(&mut self) {
+        if let Some((tool_runner, tool_runner_id)) = self.tool_runner.take() {
+            // The calls would be left unanswered in the old chat otherwise.
+            tool_runner.cancel();
+            if let Some(controller) = self.chat_controller.as_ref() {
+                controller.lock().unwrap().remove_plugin(tool_runner_id);
+            }
+        }
+
         if let Some(plugin_id) = self.plugin_id {
             if let Some(controller) = self.chat_controller.as_ref() {
                 controller.lock().unwrap().remove_plugin(plugin_id);
@@ -592,6 +708,7 @@ impl ChatControllerPlugin for Plugin {
                 ChatStateMutation::SetIsStreaming(false) => {
                     self.ui.defer(|chat, cx, _| {
                         chat.handle_streaming_end(cx);
+                        chat.apply_tool_permissions(cx);
                     });
                 }
                 ChatStateMutation::MutateBots(_) => {
@@ -380,6 +380,16 @@ live_design! {
             text: "Approve",
             draw_bg: {color: #4CAF50, color_hover: #45a049}
         }
+        approve_for_chat = <ToolApprovalButton> {
+            visible: false
+            text: "Allow for this chat",
+            draw_bg: {color: #43A047, color_hover: #388E3C}
+        }
+        approve_always = <ToolApprovalButton> {
+            visible: false
+            text: "Always allow",
+            draw_bg: {color: #2E7D32, color_hover: #1B5E20}
+        }
         deny = <ToolApprovalButton> {
             text: "Deny",
             draw_bg: {color: #f44336, color_hover: #d32f2f}
@@ -425,6 +435,49 @@ live_design! {
                     grapheme = {draw_bg: {color: #1a5b9c}}
                 }
             }
+            content_section = {
+                flow: Down,
+                padding: {bottom: 10},
+                full_output_section = <View> {
+                    flow: Down,
+                    width: Fill,
+                    height: Fit,
+                    visible: false,
+
+                    full_output_toggle = <View> {
+                        width: Fit,
+                        height: Fit,
+                        cursor: Hand,
+                        margin: {top: 6},
+                        full_output_toggle_label = <Label> {
+                            text: "View full output",
+                            draw_text: {
+                                text_style: {font_size: 9.5},
+                                color: #1a5b9c,
+                            }
+                        }
+                    }
+                    full_output = <RoundedView> {
+                        width: Fill,
+                        height: Fit,
+                        visible: false,
+                        margin: {top: 4},
+                        padding: 8,
+                        draw_bg: {
+                            color: #0001,
+                            border_radius: 4.0,
+                        }
+                        full_output_text = <Label> {
+                            width: Fill,
+                            draw_text: {
+                                text_style: {font_size: 9},
+                                color: #333,
+                                wrap: Word,
+                            }
+                        }
+                    }
+                }
+            }
         }
     }
 }
@@ -438,9 +491,12 @@ pub enum ChatLineAction {
     SaveAndRegenerate,
     EditCancel,
     ToolApprove,
+    ToolApproveForChat,
+    ToolApproveAlways,
     ToolDeny,
     EditorChanged,
     ErrorDetailsToggle,
+    FullOutputToggle,
     None,
 }
 
@@ -494,6 +550,22 @@ impl Widget for ChatLine {
             cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::ToolApprove);
         }
 
+        if self.button(ids!(approve_for_chat)).clicked(actions) {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                ChatLineAction::ToolApproveForChat,
+            );
+        }
+
+        if self.button(ids!(approve_always)).clicked(actions) {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                ChatLineAction::ToolApproveAlways,
+            );
+        }
+
         if self.deny_ref().clicked(actions) {
             cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::ToolDeny);
         }
@@ -518,6 +590,18 @@ impl Widget for ChatLine {
             );
         }
 
+        if self
+            .view(ids!(full_output_toggle))
+            .finger_up(&actions)
+            .is_some()
+        {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                ChatLineAction::FullOutputToggle,
+            );
+        }
+
         if let Some(pos) = event.hits(cx, self.area()).secondary_pointer_action_pos() {
             self.dismiss_all_hovers(cx);
             self.actions_modal_ref().open_as_popup(cx, pos);
@@ -233,7 +233,10 @@ impl MessageThinkingBlock {
         let content_reasoning = content.reasoning.as_str();
         let content_text = content.text.as_str();
 
-        self.is_visible = !content_reasoning.is_empty();
+        let reasoning_tokens = reasoning_tokens(content);
+
+        // Some providers hide the reasoning but still report what it took.
+        self.is_visible = !content_reasoning.is_empty() || reasoning_tokens.is_some();
 
         self.markdown(ids!(thinking_text))
             .set_text(cx, content_reasoning);
@@ -252,13 +255,18 @@ impl MessageThinkingBlock {
             self.view(ids!(balls)).set_visible(cx, false);
             self.animator_play(cx, ids!(ball1.start));
             self.animator_play(cx, ids!(ball2.start));
-            self.view(ids!(thinking_title)).set_text(
-                cx,
-                &format!(
+            let mut title = if content_reasoning.is_empty() {
+                "Thought".to_string()
+            } else {
+                format!(
                     "Thought for {:0.2} seconds",
                     metadata.reasoning_time_taken_seconds()
-                ),
-            );
+                )
+            };
+            if let Some(tokens) = reasoning_tokens {
+                title.push_str(&format!(" · {} reasoning tokens", tokens));
+            }
+            self.view(ids!(thinking_title)).set_text(cx, &title);
         }
     }
 
@@ -318,3 +326,10 @@ impl MessageThinkingBlock {
         self.redraw(cx);
     }
 }
+
+/// Reasoning tokens reported for the message, read from a top level
+/// `reasoning_tokens` field of [`MessageContent::data`].
+fn reasoning_tokens(content: &MessageContent) -> Option<u64> {
+    let data: serde_json::Value = serde_json::from_str(content.data.as_deref()?).ok()?;
+    data.get("reasoning_tokens")?.as_u64()
+}
@@ -101,12 +101,44 @@ pub enum MessagesAction {
     /// The tool request at the given index should be approved and executed.
     ToolApprove(usize),
 
+    /// Like [MessagesAction::ToolApprove], also allowing the tools for the rest of the chat.
+    ToolApproveForChat(usize),
+
+    /// Like [MessagesAction::ToolApprove], also allowing the tools from now on.
+    ToolApproveAlways(usize),
+
     /// The tool request at the given index should be denied.
     ToolDeny(usize),
 
     None,
 }
 
+/// Key of the data of tool result messages holding the outputs cut for the model.
+pub(crate) const FULL_OUTPUTS_KEY: &str = "full_outputs";
+
+/// The full outputs kept in a tool result message, as `(tool, output)` pairs.
+pub(crate) fn full_tool_outputs(content: &MessageContent) -> Vec<(String, String)> {
+    let Some(data) = content
+        .data
+        .as_deref()
+        .and_then(|data| serde_json::from_str::<serde_json::Value>(data).ok())
+    else {
+        return Vec::new();
+    };
+
+    data[FULL_OUTPUTS_KEY]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|output| {
+            (
+                output["tool"].as_str().unwrap_or_default().to_string(),
+                output["output"].as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect()
+}
+
 /// Represents the current open editor for a message.
 #[derive(Debug)]
 struct Editor {
@@ -160,6 +192,14 @@ pub struct Messages {
     /// Tracks which error message indices have their details expanded.
     #[rust]
     expanded_error_details: HashSet<usize>,
+
+    /// Tracks which tool result message indices have their full output expanded.
+    #[rust]
+    expanded_full_outputs: HashSet<usize>,
+
+    /// Whether tool requests offer to allow the tools beyond the current call.
+    #[rust]
+    pub tool_grants_enabled: bool,
 }
 
 impl Widget for Messages {
@@ -328,6 +368,29 @@ impl Messages {
                             .current()
                             .as_standard_message_content()
                             .set_content(cx, &message.content);
+
+                        let full_outputs = full_tool_outputs(&message.content);
+                        item.view(ids!(full_output_section))
+                            .set_visible(cx, !full_outputs.is_empty());
+
+                        let is_expanded = self.expanded_full_outputs.contains(&index);
+                        item.view(ids!(full_output)).set_visible(cx, is_expanded);
+                        let toggle_text = if is_expanded {
+                            "Hide full output"
+                        } else {
+                            "View full output"
+                        };
+                        item.label(ids!(full_output_toggle_label))
+                            .set_text(cx, toggle_text);
+
+                        if is_expanded {
+                            let text = full_outputs
+                                .iter()
+                                .map(|(tool, output)| format!("{}:\n{}", tool, output))
+                                .collect::<Vec<_>>()
+                                .join("\n\n");
+                            item.label(ids!(full_output_text)).set_text(cx, &text);
+                        }
                     }
 
                     self.apply_editor_visibility(cx, &item, index);
@@ -497,6 +560,10 @@ impl Messages {
 
                             // Show/hide tool actions based on status
                             item.view(ids!(tool_actions)).set_visible(cx, has_pending);
+                            item.button(ids!(approve_for_chat))
+                                .set_visible(cx, self.tool_grants_enabled);
+                            item.button(ids!(approve_always))
+                                .set_visible(cx, self.tool_grants_enabled);
 
                             // Set status text, only show if denied
                             if has_denied {
@@ -722,6 +789,20 @@ impl Messages {
                             MessagesAction::ToolApprove(index),
                         );
                     }
+                    ChatLineAction::ToolApproveForChat => {
+                        cx.widget_action(
+                            self.widget_uid(),
+                            &scope.path,
+                            MessagesAction::ToolApproveForChat(index),
+                        );
+                    }
+                    ChatLineAction::ToolApproveAlways => {
+                        cx.widget_action(
+                            self.widget_uid(),
+                            &scope.path,
+                            MessagesAction::ToolApproveAlways(index),
+                        );
+                    }
                     ChatLineAction::ToolDeny => {
                         cx.widget_action(
                             self.widget_uid(),
@@ -739,6 +820,12 @@ impl Messages {
                         }
                         self.redraw(cx);
                     }
+                    ChatLineAction::FullOutputToggle => {
+                        if !self.expanded_full_outputs.remove(&index) {
+                            self.expanded_full_outputs.insert(index);
+                        }
+                        self.redraw(cx);
+                    }
                     ChatLineAction::None => {}
                 }
             }
@@ -46,6 +46,31 @@ live_design! {
         }
     }
 
+    CapabilityFilterButton = <Button> {
+        width: Fit,
+        height: Fit,
+        padding: {left: 8, right: 8, top: 4, bottom: 4}
+
+        draw_bg: {
+            color: #fff
+            color_hover: #f2
+            color_down: #0000
+            border_radius: 10.
+            border_size: 1.
+            border_color: #D0D5DD
+        }
+
+        draw_text: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: 9.
+            }
+            color: #344054,
+            color_hover: #111,
+            color_focus: #111
+            color_down: #000
+        }
+    }
+
     ModelSelectorOptions = <RoundedShadowView> {
         width: Fill, height: Fit,
         padding: 8,
@@ -97,6 +122,19 @@ live_design! {
             }
         }
 
+        capability_filters = <View> {
+            width: Fill, height: Fit,
+            visible: false,
+            flow: Right,
+            spacing: 4,
+
+            vision = <CapabilityFilterButton> { text: "Vision" }
+            tools = <CapabilityFilterButton> { text: "Tools" }
+            reasoning = <CapabilityFilterButton> { text: "Reasoning" }
+            image_output = <CapabilityFilterButton> { text: "Image output" }
+            audio = <CapabilityFilterButton> { text: "Audio" }
+        }
+
         list_container = <ScrollYView> {
             width: Fill,
             height: 200,
@@ -218,6 +256,14 @@ impl Widget for ModelSelector {
             }
         }
 
+        // Capability filters are only useful if the app can tell bot capabilities
+        let has_capability_check = self
+            .widget(ids!(options.list_container.list))
+            .borrow::<ModelSelectorList>()
+            .map_or(false, |list| list.capability_check.is_some());
+        self.view(ids!(options.capability_filters))
+            .set_visible(cx, has_capability_check);
+
         // Set the chat controller on the list before drawing
         if let Some(controller) = &self.chat_controller
             && let Some(mut list) = self
@@ -256,6 +302,41 @@ impl WidgetMatchEvent for ModelSelector {
             }
         }
 
+        // Handle capability filter toggles
+        for capability in ModelCapability::ALL {
+            let button = self.capability_filter_button(capability);
+            if !button.clicked(actions) {
+                continue;
+            }
+
+            let mut required = false;
+            if let Some(mut list) = self
+                .widget(ids!(options.list_container.list))
+                .borrow_mut::<ModelSelectorList>()
+            {
+                required = list.required_capabilities.insert(capability);
+                if !required {
+                    list.required_capabilities.remove(&capability);
+                }
+                list.items.clear();
+                list.total_height = None;
+            }
+
+            let (bg_color, text_color) = if required {
+                (vec4(0.204, 0.251, 0.329, 1.0), vec4(1.0, 1.0, 1.0, 1.0))
+            } else {
+                (vec4(1.0, 1.0, 1.0, 1.0), vec4(0.204, 0.251, 0.329, 1.0))
+            };
+            button.apply_over(
+                cx,
+                live! {
+                    draw_bg: { color: (bg_color) }
+                    draw_text: { color: (text_color) }
+                },
+            );
+            self.redraw(cx);
+        }
+
         // Handle bot selection from list items
         // Only process actions from our own list widget to avoid handling global actions
         let list_widget = self.widget(ids!(options.list_container.list));
@@ -299,11 +380,18 @@ impl ModelSelector {
 
         const LIST_HEIGHT: f64 = 200.0;
         const SEARCH_HEIGHT: f64 = 40.0;
+        const CAPABILITY_FILTERS_HEIGHT: f64 = 32.0;
         const PADDING_HEIGHT: f64 = 68.0;
-
-        const MODAL_CONTENT_HEIGHT: f64 = LIST_HEIGHT + SEARCH_HEIGHT + PADDING_HEIGHT;
         const GAP: f64 = 25.0;
 
+        let capability_filters_height = if self.view(ids!(options.capability_filters)).visible() {
+            CAPABILITY_FILTERS_HEIGHT
+        } else {
+            0.0
+        };
+        let modal_content_height =
+            LIST_HEIGHT + SEARCH_HEIGHT + capability_filters_height + PADDING_HEIGHT;
+
         let modal_x;
         let modal_y;
         let mut bg_view_visible = false;
@@ -311,11 +399,11 @@ impl ModelSelector {
         // On desktop, align left edge with button, position above with gap
         if cx.display_context.is_desktop() {
             modal_x = button_rect.pos.x - GAP;
-            modal_y = button_rect.pos.y - MODAL_CONTENT_HEIGHT - GAP - 5.0 // gap;
+            modal_y = button_rect.pos.y - modal_content_height - GAP - 5.0 // gap;
         } else {
             // On mobile, position the modal in the horizontal center, vertical bottom of the screen
             modal_x = 0.0;
-            modal_y = cx.display_context.screen_size.y - MODAL_CONTENT_HEIGHT - 5.0;
+            modal_y = cx.display_context.screen_size.y - modal_content_height - 5.0;
             bg_view_visible = true;
         }
 
@@ -356,6 +444,18 @@ impl ModelSelector {
         modal.open(cx);
     }
 
+    fn capability_filter_button(&self, capability: ModelCapability) -> ButtonRef {
+        match capability {
+            ModelCapability::Vision => self.button(ids!(options.capability_filters.vision)),
+            ModelCapability::Tools => self.button(ids!(options.capability_filters.tools)),
+            ModelCapability::Reasoning => self.button(ids!(options.capability_filters.reasoning)),
+            ModelCapability::ImageOutput => {
+                self.button(ids!(options.capability_filters.image_output))
+            }
+            ModelCapability::Audio => self.button(ids!(options.capability_filters.audio)),
+        }
+    }
+
     fn close_modal(&mut self, cx: &mut Cx) {
         self.open = false;
         self.moly_modal(ids!(modal)).close(cx);
@@ -406,6 +506,43 @@ impl ModelSelectorRef {
             }
         }
     }
+
+    /// Set a function telling if a bot has a given capability.
+    ///
+    /// Once set, the selector shows toggles to only list bots having the chosen
+    /// capabilities.
+    pub fn set_capability_check<F>(&mut self, capability_check: F)
+    where
+        F: Fn(&Bot, ModelCapability) -> bool + 'static,
+    {
+        if let Some(inner) = self.borrow_mut() {
+            if let Some(mut list) = inner
+                .widget(ids!(options.list_container.list))
+                .borrow_mut::<ModelSelectorList>()
+            {
+                list.capability_check = Some(Box::new(capability_check));
+            }
+        }
+    }
+
+    /// Set a function telling which bots should be marked as stale in the list.
+    ///
+    /// Stale bots are still listed and selectable, but their information may be
+    /// outdated, for example, because it was loaded from a cache while their
+    /// provider was unreachable.
+    pub fn set_stale_check<F>(&mut self, stale_check: F)
+    where
+        F: Fn(&Bot) -> bool + 'static,
+    {
+        if let Some(inner) = self.borrow_mut() {
+            if let Some(mut list) = inner
+                .widget(ids!(options.list_container.list))
+                .borrow_mut::<ModelSelectorList>()
+            {
+                list.stale_check = Some(Box::new(stale_check));
+            }
+        }
+    }
 }
 
 /// Default grouping: groups all bots under "All" category.
@@ -432,3 +569,36 @@ pub struct BotGroup {
     /// Optional icon displayed next to the group label
     pub icon: Option<EntityAvatar>,
 }
+
+/// Capabilities the model selector can filter bots by.
+///
+/// Bots don't carry this information themselves, so applications tell which
+/// capabilities each bot has with [`ModelSelectorRef::set_capability_check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModelCapability {
+    Vision,
+    Tools,
+    Reasoning,
+    ImageOutput,
+    Audio,
+}
+
+impl ModelCapability {
+    pub const ALL: [ModelCapability; 5] = [
+        ModelCapability::Vision,
+        ModelCapability::Tools,
+        ModelCapability::Reasoning,
+        ModelCapability::ImageOutput,
+        ModelCapability::Audio,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ModelCapability::Vision => "Vision",
+            ModelCapability::Tools => "Tools",
+            ModelCapability::Reasoning => "Reasoning",
+            ModelCapability::ImageOutput => "Image output",
+            ModelCapability::Audio => "Audio",
+        }
+    }
+}
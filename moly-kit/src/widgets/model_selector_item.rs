@@ -54,6 +54,16 @@ live_design! {
             }
         }
 
+        stale_label = <Label> {
+            width: Fit
+            visible: false
+            text: "cached"
+            draw_text: {
+                text_style: <THEME_FONT_ITALIC>{font_size: 9},
+                color: #989898
+            }
+        }
+
         icon_tick_view = <View> {
             width: Fit, height: Fit
             visible: false
@@ -89,6 +99,9 @@ pub struct ModelSelectorItem {
     #[rust]
     selected: bool,
 
+    #[rust]
+    stale: bool,
+
     #[animator]
     animator: Animator,
 }
@@ -135,6 +148,9 @@ impl Widget for ModelSelectorItem {
             // Show tick icon if this bot is selected
             self.view(ids!(icon_tick_view))
                 .set_visible(cx, self.selected);
+
+            // Show a hint if this bot's info may be outdated
+            self.label(ids!(stale_label)).set_visible(cx, self.stale);
         }
 
         self.view.draw_walk(cx, scope, walk)
@@ -153,4 +169,10 @@ impl ModelSelectorItemRef {
             inner.selected = selected;
         }
     }
+
+    pub fn set_stale(&mut self, stale: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.stale = stale;
+        }
+    }
 }
@@ -1,14 +1,16 @@
 use super::model_selector_item::{ModelSelectorItemAction, ModelSelectorItemWidgetRefExt};
 use crate::{
     aitk::{controllers::chat::ChatController, protocol::*},
-    widgets::model_selector::{BotGroup, default_grouping},
+    widgets::model_selector::{BotGroup, ModelCapability, default_grouping},
 };
 use makepad_widgets::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 // We need a type alias, so Makepad's `#[rust(...)]` macro attribute works.
 type ErasedGroupingClosure = Box<dyn Fn(&Bot) -> BotGroup>;
+type ErasedStaleCheckClosure = Box<dyn Fn(&Bot) -> bool>;
+type ErasedCapabilityCheckClosure = Box<dyn Fn(&Bot, ModelCapability) -> bool>;
 
 /// Trait for filtering which bots to show in the model selector
 pub trait BotFilter {
@@ -107,6 +109,18 @@ pub struct ModelSelectorList {
 
     #[rust]
     pub filter: Option<Box<dyn BotFilter>>,
+
+    /// Tells if a bot's info may be outdated (e.g. loaded from a cache while offline).
+    #[rust]
+    pub stale_check: Option<ErasedStaleCheckClosure>,
+
+    /// Capabilities a bot must have to be listed.
+    #[rust]
+    pub required_capabilities: HashSet<ModelCapability>,
+
+    /// Tells if a bot has a capability. Without it, capabilities are not filtered.
+    #[rust]
+    pub capability_check: Option<ErasedCapabilityCheckClosure>,
 }
 
 impl Widget for ModelSelectorList {
@@ -186,7 +200,14 @@ impl ModelSelectorList {
                 // Filter by custom filter function (if provided)
                 let passes_filter = self.filter.as_ref().map_or(true, |f| f.should_show(bot));
 
-                matches_search && passes_filter
+                // Filter by required capabilities (if they can be checked)
+                let has_capabilities = self.capability_check.as_ref().map_or(true, |f| {
+                    self.required_capabilities
+                        .iter()
+                        .all(|capability| f(bot, *capability))
+                });
+
+                matches_search && passes_filter && has_capabilities
             })
             .collect();
 
@@ -265,6 +286,9 @@ impl ModelSelectorList {
                 let is_selected = selected_bot_id == Some(&bot.id);
                 item.set_selected(is_selected);
 
+                let is_stale = self.stale_check.as_ref().map_or(false, |f| f(bot));
+                item.set_stale(is_stale);
+
                 let _ = item_widget.draw_all(cx, &mut Scope::empty());
                 total_height += item_widget.area().rect(cx).size.y;
             }
@@ -310,4 +334,22 @@ impl ModelSelectorListRef {
             inner.grouping = Box::new(grouping);
         }
     }
+
+    pub fn set_stale_check<F>(&mut self, stale_check: F)
+    where
+        F: Fn(&Bot) -> bool + 'static,
+    {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.stale_check = Some(Box::new(stale_check));
+        }
+    }
+
+    pub fn set_capability_check<F>(&mut self, capability_check: F)
+    where
+        F: Fn(&Bot, ModelCapability) -> bool + 'static,
+    {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.capability_check = Some(Box::new(capability_check));
+        }
+    }
 }
@@ -264,11 +264,7 @@ impl Widget for PromptInput {
             Attachment::pick_multiple(move |result| match result {
                 Ok(attachments) => {
                     ui.defer_with_redraw(move |me, _, _| {
-                        let mut list = me.attachment_list_ref();
-                        list.write().attachments.extend(attachments);
-                        list.write().on_tap(move |list, index| {
-                            list.attachments.remove(index);
-                        });
+                        me.add_attachments(attachments);
                     });
                 }
                 Err(_) => {}
@@ -389,6 +385,30 @@ impl PromptInput {
         self.task = Task::Stop;
     }
 
+    /// Adds attachments to be sent with the message, removable by tapping them.
+    pub fn add_attachments(&mut self, attachments: impl IntoIterator<Item = Attachment>) {
+        let mut list = self.attachment_list_ref();
+        list.write().attachments.extend(attachments);
+        list.write().on_tap(move |list, index| {
+            list.attachments.remove(index);
+        });
+    }
+
+    /// Replaces an attachment, e.g. because its source changed.
+    ///
+    /// Returns `false` if the attachment was already removed from the prompt.
+    pub fn replace_attachment(&mut self, old: &Attachment, new: Attachment) -> bool {
+        let mut list = self.attachment_list_ref();
+        let mut list = list.write();
+        match list.attachments.iter_mut().find(|a| *a == old) {
+            Some(attachment) => {
+                *attachment = new;
+                true
+            }
+            None => false,
+        }
+    }
+
     pub(crate) fn attachment_list_ref(&self) -> AttachmentListRef {
         self.attachment_list(ids!(attachments))
     }
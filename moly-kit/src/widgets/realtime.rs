@@ -5,6 +5,7 @@ use crate::aitk::{
 use crate::prelude::*;
 use crate::{
     utils::makepad::events::EventExt,
+    utils::tool_execution::run_approved_call,
     widgets::{avatar::*, chat_line::*, slot::*, standard_message_content::*},
 };
 use makepad_widgets::permission::Permission;
@@ -596,6 +597,12 @@ pub struct Realtime {
     #[rust]
     pending_tool_call: Option<(String, String, String)>, // (name, call_id, arguments)
 
+    #[rust]
+    tool_permissions: Option<Arc<dyn ToolPermissions>>,
+
+    #[rust]
+    tool_execution: Option<Arc<dyn ToolExecution>>,
+
     #[rust]
     audio_devices: Vec<AudioDeviceDesc>,
 
@@ -804,6 +811,14 @@ impl WidgetMatchEvent for Realtime {
                 ChatLineAction::ToolApprove => {
                     self.approve_tool_call(cx);
                 }
+                ChatLineAction::ToolApproveForChat => {
+                    self.grant_tool_call(ToolGrantScope::Chat);
+                    self.approve_tool_call(cx);
+                }
+                ChatLineAction::ToolApproveAlways => {
+                    self.grant_tool_call(ToolGrantScope::Always);
+                    self.approve_tool_call(cx);
+                }
                 ChatLineAction::ToolDeny => {
                     self.deny_tool_call(cx);
                 }
@@ -883,6 +898,14 @@ impl Realtime {
         self.chat_controller = chat_controller;
     }
 
+    pub fn set_tool_permissions(&mut self, tool_permissions: Option<Arc<dyn ToolPermissions>>) {
+        self.tool_permissions = tool_permissions;
+    }
+
+    pub fn set_tool_execution(&mut self, tool_execution: Option<Arc<dyn ToolExecution>>) {
+        self.tool_execution = tool_execution;
+    }
+
     fn try_start_pending_conversation(&mut self, cx: &mut Cx) {
         if self.is_connected && !self.conversation_active && self.should_request_connection {
             // We can now start the conversation that was requested
@@ -1206,14 +1229,23 @@ impl Realtime {
                         })
                         .unwrap_or(false);
 
-                    if dangerous_mode_enabled {
-                        // Auto-approve function calls in dangerous mode
+                    let decision = self
+                        .tool_permissions
+                        .as_ref()
+                        .map(|permissions| permissions.decide(&name))
+                        .unwrap_or(ToolDecision::Ask);
+
+                    if decision == ToolDecision::Deny {
+                        self.pending_tool_call = Some((name, call_id, arguments));
+                        self.deny_tool_call(cx);
+                    } else if dangerous_mode_enabled || decision == ToolDecision::Allow {
+                        // Auto-approve function calls in dangerous mode or if allowed
                         let display_name = display_name_from_namespaced(&name);
                         self.label(ids!(status_label))
                             .set_text(cx, &format!("🔧 Auto-executing tool: {}", display_name));
 
                         // Execute the function call directly
-                        self.handle_function_call(cx, name, call_id, arguments);
+                        self.handle_function_call(cx, name, call_id, arguments, false);
                     } else {
                         // Show permission request as usual
                         self.label(ids!(status_label))
@@ -1295,6 +1327,13 @@ impl Realtime {
         tool_line
             .view(ids!(message_section.content_section.tool_actions))
             .set_visible(cx, true);
+        let grants_enabled = self.tool_permissions.is_some();
+        tool_line
+            .button(ids!(approve_for_chat))
+            .set_visible(cx, grants_enabled);
+        tool_line
+            .button(ids!(approve_always))
+            .set_visible(cx, grants_enabled);
 
         // Pause recording while waiting for permission
         *self.should_record.lock().unwrap() = false;
@@ -1308,6 +1347,7 @@ impl Realtime {
         name: String,
         call_id: String,
         arguments: String,
+        by_user: bool,
     ) {
         let Some(chat_controller) = self.chat_controller.as_ref().cloned() else {
             ::log::error!("No chat controller available for function call");
@@ -1344,6 +1384,8 @@ impl Realtime {
         };
 
         let channel = self.realtime_channel.clone();
+        let execution = self.tool_execution.clone();
+        let bot_id = chat_controller.lock().unwrap().state().bot_id.clone();
 
         let future = async move {
             // Parse the arguments JSON
@@ -1367,9 +1409,14 @@ impl Realtime {
                 }
             };
 
-            let result = tool_manager
-                .execute_tool_call(&name, &call_id, arguments_map)
-                .await;
+            let tool_call = ToolCall {
+                id: call_id.clone(),
+                name: name.clone(),
+                arguments: arguments_map.clone(),
+                ..Default::default()
+            };
+            let run = tool_manager.execute_tool_call(&name, &call_id, arguments_map);
+            let result = run_approved_call(execution, bot_id, tool_call, by_user, run).await;
 
             if let Some(channel) = &channel {
                 let output = if result.is_error {
@@ -1402,7 +1449,7 @@ impl Realtime {
                 .set_text(cx, &format!("🔧 Executing tool: {}", display_name));
 
             // Execute the tool
-            self.handle_function_call(cx, name, call_id, arguments);
+            self.handle_function_call(cx, name, call_id, arguments, true);
 
             // Resume recording if conversation is active
             if self.conversation_active {
@@ -1413,6 +1460,14 @@ impl Realtime {
         }
     }
 
+    fn grant_tool_call(&self, scope: ToolGrantScope) {
+        if let (Some(permissions), Some((name, _, _))) =
+            (&self.tool_permissions, &self.pending_tool_call)
+        {
+            permissions.grant(name, scope);
+        }
+    }
+
     fn deny_tool_call(&mut self, cx: &mut Cx) {
         if let Some((name, call_id, _arguments)) = self.pending_tool_call.take() {
             // Hide permission UI
@@ -1721,4 +1776,16 @@ impl RealtimeRef {
             inner.set_chat_controller(chat_controller);
         }
     }
+
+    pub fn set_tool_permissions(&mut self, tool_permissions: Option<Arc<dyn ToolPermissions>>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_tool_permissions(tool_permissions);
+        }
+    }
+
+    pub fn set_tool_execution(&mut self, tool_execution: Option<Arc<dyn ToolExecution>>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_tool_execution(tool_execution);
+        }
+    }
 }
@@ -1,9 +1,11 @@
 use crate::data::capture::register_capture_manager;
 use crate::data::downloads::DownloadPendingNotification;
 use crate::data::downloads::download::DownloadFileAction;
+use crate::data::mcp_inputs::McpInputsAction;
 use crate::data::moly_client::MolyClientAction;
 use crate::data::store::*;
 use crate::landing::model_files_item::ModelFileItemAction;
+use crate::mcp::mcp_inputs_modal::{McpInputsModalAction, McpInputsModalWidgetRefExt};
 use crate::my_models::delete_model_modal::DeleteModelModalAction;
 use crate::shared::actions::{ChatAction, DownloadAction};
 use crate::shared::download_notification_popup::{
@@ -12,6 +14,7 @@ use crate::shared::download_notification_popup::{
 };
 use crate::shared::moly_server_popup::MolyServerPopupAction;
 use crate::shared::popup_notification::PopupNotificationWidgetRefExt;
+use moly_kit::widgets::moly_modal::MolyModalWidgetRefExt;
 use moly_protocol::data::{File, FileId};
 
 use makepad_widgets::*;
@@ -35,6 +38,8 @@ live_design! {
     use crate::settings::moly_server_screen::MolyServerScreen;
     use crate::settings::providers_screen::ProvidersScreen;
     use crate::mcp::mcp_screen::McpScreen;
+    use crate::mcp::mcp_inputs_modal::McpInputsModal;
+    use moly_kit::widgets::moly_modal::MolyModal;
 
     ICON_CHAT = dep("crate://self/resources/icons/chat.svg")
     ICON_LOCAL = dep("crate://self/resources/icons/local.svg")
@@ -202,6 +207,12 @@ live_design! {
                         popup_moly_server = <MolyServerPopup> {}
                     }
                 }
+
+                mcp_inputs_modal = <MolyModal> {
+                    content: {
+                        mcp_inputs_modal_inner = <McpInputsModal> {}
+                    }
+                }
             }
         }
     }
@@ -430,6 +441,19 @@ impl MatchEvent for App {
                 self.ui.popup_notification(ids!(moly_server_popup)).open(cx);
             }
 
+            if let McpInputsAction::Requested(inputs) = action.cast() {
+                self.ui
+                    .mcp_inputs_modal(ids!(mcp_inputs_modal_inner))
+                    .set_inputs(cx, inputs);
+                self.ui
+                    .moly_modal(ids!(mcp_inputs_modal))
+                    .open_as_dialog(cx);
+            }
+
+            if let McpInputsModalAction::ModalDismissed = action.cast() {
+                self.ui.moly_modal(ids!(mcp_inputs_modal)).close(cx);
+            }
+
             if let MolyServerPopupAction::CloseButtonClicked = action.cast() {
                 self.ui
                     .popup_notification(ids!(moly_server_popup))
@@ -1,6 +1,7 @@
 use super::chat_history_card::ChatHistoryCardWidgetRefExt;
 use crate::chat::entity_button::EntityButtonWidgetRefExt;
 use crate::data::chats::chat::ChatId;
+use crate::data::providers::ProviderType;
 use crate::data::store::Store;
 use crate::shared::actions::ChatAction;
 use makepad_widgets::*;
@@ -32,22 +33,42 @@ live_design! {
         }
     }
 
+    AgentList = {{AgentList}} {
+        width: Fill, height: Fit
+        flow: Down
+
+        template: <EntityButton> {
+            server_url_visible: true,
+        }
+    }
+
     pub ChatHistory = {{ChatHistory}} {
         width: Fill, height: Fill
+        flow: Down
         show_bg: true
         draw_bg: {
             color: (MAIN_BG_COLOR)
         }
         padding: { left: 10, right: 10 }
 
+        // Agents are kept out of the portal list, since reusing its item ids for
+        // other templates as agents load breaks its drawlists.
+        agents_section = <View> {
+            visible: false
+            width: Fill, height: Fit
+            flow: Down
+            margin: {bottom: 10}
+
+            <HeadingLabel> { text: "AGENTS" }
+            no_agents_warning = <NoAgentsWarning> {
+                text: "No agents found in the enabled MoFa servers"
+            }
+            agents = <AgentList> {}
+        }
+
         list = <PortalList> {
             drag_scrolling: false,
-            AgentHeading = <HeadingLabel> { text: "AGENTS" }
-            NoAgentsWarning = <NoAgentsWarning> {}
-            Agent = <EntityButton> {
-                server_url_visible: true,
-            }
-            ChatsHeading = <HeadingLabel> { text: "CHATS", margin: {top: 10}, }
+            ChatsHeading = <HeadingLabel> { text: "CHATS" }
             ChatHistoryCard = <ChatHistoryCard> {
                 cursor: Default
             }
@@ -69,30 +90,24 @@ impl Widget for ChatHistory {
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
         let store = scope.data.get_mut::<Store>().unwrap();
-        // let agents = store.chats.get_mofa_agents_list(true);
+
+        let has_agents = !store.chats.get_mofa_agents_list(true).is_empty();
+        let has_mofa_servers = store
+            .chats
+            .providers
+            .values()
+            .any(|p| p.enabled && p.provider_type == ProviderType::MoFa);
+        self.view(ids!(agents_section))
+            .set_visible(cx, has_agents || has_mofa_servers);
+        self.label(ids!(no_agents_warning))
+            .set_visible(cx, !has_agents);
 
         enum Item<'a> {
             ChatsHeader,
-            // AgentsHeader,
-            // NoAgentsWarning(&'a str),
-            // AgentButton(&'a ProviderBot),
             ChatButton(&'a ChatId),
         }
 
-        let mut items: Vec<Item> = Vec::new();
-
-        // TODO: Temporarily disabling the agents section in the chat history.
-        // Reusing portal list items ids for different templates (e.g. a ChatsHeader becomes an AgentsHeader when agents are loaded after chats)
-        // causes drawlist issues: Drawlist id generation wrong index: 13 current gen:1 in pointer:0 / Drawlist id generation wrong 13 1 0
-
-        // if !agents.is_empty() {
-        //     items.push(Item::AgentsHeader);
-        //     for agent in &agents {
-        //         items.push(Item::AgentButton(agent));
-        //     }
-        // }
-
-        items.push(Item::ChatsHeader);
+        let mut items: Vec<Item> = vec![Item::ChatsHeader];
 
         let mut chat_ids = store
             .chats
@@ -120,15 +135,6 @@ impl Widget for ChatHistory {
                             let item = list.item(cx, item_id, live_id!(ChatsHeading));
                             item.draw_all(cx, scope);
                         }
-                        // Item::AgentsHeader => {
-                        //     let item = list.item(cx, item_id, live_id!(AgentHeading));
-                        //     item.draw_all(cx, scope);
-                        // }
-                        // Item::AgentButton(agent) => {
-                        //     let item = list.item(cx, item_id, live_id!(Agent));
-                        //     item.as_entity_button().set_bot_id(cx, &agent.id);
-                        //     item.draw_all(cx, scope);
-                        // }
                         Item::ChatButton(chat_id) => {
                             let mut item = list
                                 .item(cx, item_id, live_id!(ChatHistoryCard))
@@ -145,6 +151,82 @@ impl Widget for ChatHistory {
     }
 }
 
+/// The enabled MoFa agents, to start a chat with them.
+#[derive(Live, LiveHook, Widget)]
+pub struct AgentList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    /// Ids of the agents shown, in order.
+    #[rust]
+    agent_ids: Vec<LiveId>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for AgentList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        if let Event::Actions(actions) = event {
+            let clicked = self
+                .items
+                .values()
+                .map(|item| item.as_entity_button())
+                .find(|eb| eb.clicked(actions));
+
+            if let Some(bot_id) = clicked.and_then(|eb| eb.get_bot_id()) {
+                cx.action(ChatAction::Start(bot_id));
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        let store = scope.data.get::<Store>().unwrap();
+        let mut agents = store.chats.get_mofa_agents_list(true);
+        agents.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.agent_ids.clear();
+        for agent in &agents {
+            let id = LiveId::from_str(agent.id.as_str());
+            self.agent_ids.push(id);
+
+            if !self.items.contains_key(&id) {
+                let item = WidgetRef::new_from_ptr(cx, self.template);
+                item.as_entity_button().set_bot_id(cx, &agent.id);
+                self.items.insert(id, item);
+            }
+        }
+        self.items.retain(|id, _| {
+            agents
+                .iter()
+                .any(|a| LiveId::from_str(a.id.as_str()) == *id)
+        });
+
+        cx.begin_turtle(walk, self.layout);
+        for id in &self.agent_ids {
+            if let Some(item) = self.items.get(id) {
+                let _ = item.draw_all(cx, scope);
+            }
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
 impl WidgetMatchEvent for ChatHistory {
     fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
         let clicked_entity_button = self
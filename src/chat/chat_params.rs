@@ -1,8 +1,8 @@
 use makepad_widgets::*;
 
 use crate::{
-    data::{chats::chat::ChatId, store::Store},
-    shared::tooltip::TooltipWidgetExt,
+    data::{chats::chat::ChatId, failover_client::parse_fallback_models, store::Store},
+    shared::{actions::ChatAction, tooltip::TooltipWidgetExt},
 };
 
 live_design! {
@@ -13,6 +13,7 @@ live_design! {
     use crate::shared::styles::*;
     use crate::shared::widgets::*;
     use crate::shared::tooltip::*;
+    use crate::chat::reasoning_controls::*;
 
     ICON_CLOSE_PANEL = dep("crate://self/resources/icons/close_right_panel.svg")
     ICON_OPEN_PANEL = dep("crate://self/resources/icons/open_right_panel.svg")
@@ -90,6 +91,57 @@ live_design! {
                     }
                 }
 
+                <View> {
+                    flow: Down
+                    height: Fit
+                    width: Fill
+                    spacing: 12
+                    padding: {left: 4}
+                    fallback_models_label = <Label> {
+                        draw_text: {
+                            text_style: <BOLD_FONT>{font_size: 10},
+                            color: #000
+                        }
+                        text: "Fallback Models"
+                        hover_actions_enabled: true
+                    }
+                    <ChatParamsTextInputWrapper> {
+                        height: 65,
+                        scrolled_content = {
+                            fallback_models = <MolyTextInput> {
+                                width: Fill,
+                                height: Fit,
+                                empty_text: "e.g. gemini-2.5-pro, qwen3"
+                                draw_bg: {
+                                    border_radius: 0
+                                    color: #0000
+                                    border_size: 0
+                                }
+                                draw_text: {
+                                    text_style: <REGULAR_FONT>{font_size: 10},
+                                }
+                            }
+                        }
+                    }
+                }
+
+                <View> {
+                    flow: Down
+                    height: Fit
+                    width: Fill
+                    spacing: 12
+                    padding: {left: 4}
+                    reasoning_label = <Label> {
+                        draw_text: {
+                            text_style: <BOLD_FONT>{font_size: 10},
+                            color: #000
+                        }
+                        text: "Reasoning"
+                        hover_actions_enabled: true
+                    }
+                    <ReasoningControls> { flow: Down }
+                }
+
                 <Label> {
                     draw_text: {
                         text_style: <BOLD_FONT>{font_size: 10}
@@ -243,6 +295,9 @@ pub struct ChatParams {
 
     #[rust]
     current_chat_id: Option<ChatId>,
+
+    #[rust]
+    fallback_models_chat_id: Option<ChatId>,
 }
 
 impl Widget for ChatParams {
@@ -280,6 +335,13 @@ impl Widget for ChatParams {
             let system_prompt_value = chat.system_prompt.clone().unwrap_or_default();
             system_prompt.set_text(cx, &system_prompt_value);
 
+            // Only reset on chat switch, as parsing would fight the user while typing.
+            if self.fallback_models_chat_id != Some(chat.id) {
+                self.fallback_models_chat_id = Some(chat.id);
+                self.text_input(ids!(fallback_models))
+                    .set_text(cx, &chat.fallback_models.join(", "));
+            }
+
             // Currently, `active` and `set_active` interact with the animator of
             // the widget to do what they do. To avoid some visual issues, we should not
             // trigger the animator unnecessarily. This is a workaround.
@@ -345,6 +407,12 @@ impl WidgetMatchEvent for ChatParams {
                     chat.system_prompt = Some(value);
                 }
             }
+
+            if let Some(value) = self.text_input(ids!(fallback_models)).changed(&actions) {
+                chat.fallback_models = parse_fallback_models(&value);
+                chat.save_and_forget();
+                cx.action(ChatAction::FallbacksChanged);
+            }
         }
     }
 }
@@ -362,6 +430,20 @@ impl ChatParams {
             cx, actions
         );
 
+        self.handle_tooltip_actions_for_label(
+            ids!(fallback_models_label),
+            "Models to try, in order, when the selected one is rate limited or its server fails. Separate them with commas. When empty, the fallback models configured for the selected model are used.".to_string(),
+            TOOLTIP_OFFSET,
+            cx, actions
+        );
+
+        self.handle_tooltip_actions_for_label(
+            ids!(reasoning_label),
+            "How much reasoning models think before answering, as an effort level or a maximum of tokens spent thinking. Each provider takes the ones it supports, and uses its own defaults for the rest.".to_string(),
+            TOOLTIP_OFFSET,
+            cx, actions
+        );
+
         self.handle_tooltip_actions_for_slider(
             ids!(temperature),
             "Influences the randomness of the model’s output. A higher value leads to more random and diverse responses, while a lower value produces more predictable outputs.".to_string(),
@@ -4,12 +4,18 @@ use moly_kit::prelude::*;
 
 use std::collections::HashMap;
 
-use crate::data::bot_fetcher::should_include_bot;
+use crate::data::api_keys::{KeyRotation, KeyRotationClient};
 use crate::data::deep_inquire_client::DeepInquireClient;
+use crate::data::model_rules::ModelRules;
+use crate::data::mofa_client::MofaClient;
+use crate::data::openai_responses_client::OpenAiResponsesClient;
 use crate::data::openclaw_client::OpenClawClient;
 use crate::data::providers::{Provider, ProviderBot, ProviderId, ProviderType};
+use crate::data::reasoning::ReasoningFormat;
+use crate::data::reasoning_client::ReasoningClient;
 use crate::data::store::Store;
 use crate::data::supported_providers::{self, SupportedProvider};
+use crate::data::think_tags::ThinkTagsClient;
 use crate::settings::provider_view::ProviderViewWidgetExt;
 use crate::settings::providers::ConnectionSettingsAction;
 use crate::shared::actions::ChatAction;
@@ -142,9 +148,9 @@ impl ChatScreen {
     fn create_bot_context(&mut self, _cx: &mut Cx, scope: &mut Scope) {
         let store = scope.data.get_mut::<Store>().unwrap();
 
+        let supported_providers_list = supported_providers::load_supported_providers();
         let router_client = {
             let router_client = RouterClient::new();
-            let supported_providers_list = supported_providers::load_supported_providers();
 
             let available_bots = store.chats.available_bots.clone();
             let providers = store.chats.providers.clone();
@@ -157,16 +163,21 @@ impl ChatScreen {
                 .filter(|(_, p)| p.enabled && has_valid_credentials(p))
             {
                 let client: Option<Box<dyn BotClient>> = match provider.provider_type {
-                    ProviderType::OpenAi | ProviderType::MolyServer | ProviderType::MoFa => {
-                        create_openai_client(
-                            provider,
-                            &supported_providers_list,
-                            &available_bots,
-                            &providers,
-                            &store,
-                            ClientFilter::ChatModels,
-                        )
-                    }
+                    ProviderType::OpenAi | ProviderType::MolyServer => create_openai_client(
+                        provider,
+                        &supported_providers_list,
+                        &available_bots,
+                        &providers,
+                        &store,
+                        ClientFilter::None,
+                    ),
+                    ProviderType::MoFa => create_mofa_client(
+                        provider,
+                        &supported_providers_list,
+                        &available_bots,
+                        &providers,
+                        &store,
+                    ),
                     ProviderType::OpenAiImage => create_openai_image_client(
                         provider,
                         &supported_providers_list,
@@ -175,6 +186,13 @@ impl ChatScreen {
                         &store,
                     ),
                     ProviderType::OpenAiRealtime => create_openai_realtime_client(provider),
+                    ProviderType::OpenAiResponses => create_openai_responses_client(
+                        provider,
+                        &supported_providers_list,
+                        &available_bots,
+                        &providers,
+                        &store,
+                    ),
                     ProviderType::DeepInquire => create_deep_inquire_client(
                         provider,
                         &supported_providers_list,
@@ -199,10 +217,11 @@ impl ChatScreen {
             router_client
         };
 
+        let fallback_bots = cached_fallback_bots(store, &supported_providers_list);
+
         let mut context: BotContext = router_client.into();
-        let tool_manager = store.create_and_load_mcp_tool_manager();
-        tool_manager
-            .set_dangerous_mode_enabled(store.preferences.get_mcp_servers_dangerous_mode_enabled());
+        context.set_fallback_bots(fallback_bots);
+        let tool_manager = store.mcp_tool_manager();
         context.set_tool_manager(tool_manager);
 
         store.bot_context = Some(context.clone());
@@ -226,8 +245,6 @@ type BotMap = HashMap<BotId, ProviderBot>;
 
 #[derive(Clone)]
 enum ClientFilter {
-    // Apply should_include_model filter
-    ChatModels,
     // Check bot.enabled in available_bots
     BotEnabled,
     // No extra filtering beyond provider enabled
@@ -240,7 +257,10 @@ fn is_localhost(url: &str) -> bool {
 
 fn has_valid_credentials(provider: &Provider) -> bool {
     match provider.provider_type {
-        ProviderType::OpenAi | ProviderType::MolyServer | ProviderType::OpenAiRealtime => {
+        ProviderType::OpenAi
+        | ProviderType::MolyServer
+        | ProviderType::OpenAiRealtime
+        | ProviderType::OpenAiResponses => {
             provider.api_key.is_some() || is_localhost(&provider.url)
         }
         ProviderType::MoFa
@@ -250,6 +270,37 @@ fn has_valid_credentials(provider: &Provider) -> bool {
     }
 }
 
+/// Builds bots out of the stale models seeded from the models cache, so chats keep
+/// their models listed while the corresponding providers can't be reached.
+fn cached_fallback_bots(store: &Store, supported_providers_list: &[SupportedProvider]) -> Vec<Bot> {
+    store
+        .chats
+        .available_bots
+        .values()
+        .filter(|provider_bot| provider_bot.is_stale)
+        .filter_map(|provider_bot| {
+            let provider = store.chats.providers.get(&provider_bot.provider_id)?;
+            if !provider.enabled || !has_valid_credentials(provider) {
+                return None;
+            }
+            store.listed_bot_of(provider_bot, supported_providers_list)
+        })
+        .collect()
+}
+
+/// Extends the capabilities reported by the client with the known capabilities of
+/// each model, e.g. so images can be attached for vision models.
+fn apply_capabilities(bots: &mut Vec<Bot>, provider_id: &ProviderId, available_bots: &BotMap) {
+    for bot in bots.iter_mut() {
+        if let Some(provider_bot) = available_bots.get(&RouterClient::prefix(provider_id, &bot.id))
+        {
+            bot.capabilities = provider_bot
+                .capabilities
+                .extend_bot_capabilities(bot.capabilities.clone());
+        }
+    }
+}
+
 fn apply_icon(bots: &mut Vec<Bot>, icon_opt: &Option<LiveDependency>) {
     if let Some(icon) = icon_opt {
         for bot in bots.iter_mut() {
@@ -263,6 +314,7 @@ fn apply_bot_filters(
     available_bots: &BotMap,
     providers: &ProviderMap,
     filter: &ClientFilter,
+    rules: &ModelRules,
     supported_models: &Option<Vec<String>>,
 ) {
     // Filter by provider/bot enabled status
@@ -284,10 +336,8 @@ fn apply_bot_filters(
         });
     }
 
-    // Apply filter type
-    if matches!(filter, ClientFilter::ChatModels) {
-        bots.retain(|bot| should_include_bot(&bot.id));
-    }
+    // Apply the provider's include/exclude rules
+    bots.retain(|bot| rules.allows(bot.id.as_str()));
 
     // Apply supported models whitelist
     if let Some(models) = supported_models {
@@ -310,6 +360,8 @@ fn setup_map_client<C: BotClient + 'static>(
         .and_then(|sp| sp.supported_models.clone());
 
     let icon_opt = store.get_provider_icon(&provider.name);
+    let provider_id = provider.id.clone();
+    let rules = provider.model_rules();
     let available_bots = available_bots.clone();
     let providers = providers.clone();
 
@@ -319,8 +371,10 @@ fn setup_map_client<C: BotClient + 'static>(
             &available_bots,
             &providers,
             &filter,
+            &rules,
             &supported_models,
         );
+        apply_capabilities(&mut bots, &provider_id, &available_bots);
         apply_icon(&mut bots, &icon_opt);
         bots
     });
@@ -334,17 +388,33 @@ fn create_openai_client(
     store: &Store,
     filter: ClientFilter,
 ) -> Option<Box<dyn BotClient>> {
-    let mut client = OpenAiClient::new(provider.url.clone());
+    let reasoning_format = ReasoningFormat::for_provider(&provider.provider_type, &provider.url);
+    let new_client = |key: Option<&str>| {
+        let mut client = OpenAiClient::new(provider.url.clone());
+
+        if let Some(key) = key {
+            if let Err(e) = client.set_key(key) {
+                eprintln!("Failed to set API key for {}: {}", provider.name, e);
+                return None;
+            }
+        }
+        client.set_tools_enabled(provider.tools_enabled);
 
-    if let Some(key) = provider.api_key.as_ref() {
-        if let Err(e) = client.set_key(key) {
-            eprintln!("Failed to set API key for {}: {}", provider.name, e);
-            return None;
+        // Takes over the requests of chats with reasoning params.
+        let mut client =
+            ReasoningClient::new(Box::new(client), provider.url.clone(), reasoning_format);
+        if let Some(key) = key {
+            client.set_key(key).ok()?;
         }
-    }
-    client.set_tools_enabled(provider.tools_enabled);
+        client.set_tools_enabled(provider.tools_enabled);
+        Some(client)
+    };
 
-    let mut map_client = MapClient::from(client);
+    let mut map_client = if provider.api_keys.is_empty() {
+        MapClient::from(new_client(provider.api_key.as_deref())?)
+    } else {
+        MapClient::from(create_key_rotation_client(provider, new_client)?)
+    };
 
     setup_map_client(
         &mut map_client,
@@ -356,27 +426,78 @@ fn create_openai_client(
         filter,
     );
 
+    if provider.parses_think_tags() {
+        return Some(Box::new(ThinkTagsClient::new(Box::new(map_client))));
+    }
+
     Some(Box::new(map_client))
 }
 
-fn create_openai_image_client(
+fn create_openai_responses_client(
     provider: &Provider,
     supported_providers_list: &[SupportedProvider],
     available_bots: &BotMap,
     providers: &ProviderMap,
     store: &Store,
 ) -> Option<Box<dyn BotClient>> {
-    let client_url = provider.url.trim_start_matches('#').to_string();
-    let mut client = OpenAiImageClient::new(client_url);
+    let new_client = |key: Option<&str>| {
+        let mut client = OpenAiResponsesClient::new(provider.url.clone());
 
-    if let Some(key) = provider.api_key.as_ref() {
-        if let Err(e) = client.set_key(key) {
-            eprintln!("Failed to set API key for {}: {}", provider.name, e);
-            return None;
+        if let Some(key) = key {
+            if let Err(e) = client.set_key(key) {
+                eprintln!("Failed to set API key for {}: {}", provider.name, e);
+                return None;
+            }
         }
-    }
+        client.set_tools_enabled(provider.tools_enabled);
+        client.set_hosted_tools(provider.hosted_tools.clone());
+        Some(client)
+    };
 
-    let mut map_client = MapClient::from(client);
+    let mut map_client = if provider.api_keys.is_empty() {
+        MapClient::from(new_client(provider.api_key.as_deref())?)
+    } else {
+        MapClient::from(create_key_rotation_client(provider, new_client)?)
+    };
+
+    setup_map_client(
+        &mut map_client,
+        provider,
+        supported_providers_list,
+        available_bots,
+        providers,
+        store,
+        ClientFilter::None,
+    );
+
+    Some(Box::new(map_client))
+}
+
+fn create_openai_image_client(
+    provider: &Provider,
+    supported_providers_list: &[SupportedProvider],
+    available_bots: &BotMap,
+    providers: &ProviderMap,
+    store: &Store,
+) -> Option<Box<dyn BotClient>> {
+    let new_client = |key: Option<&str>| {
+        let client_url = provider.url.trim_start_matches('#').to_string();
+        let mut client = OpenAiImageClient::new(client_url);
+
+        if let Some(key) = key {
+            if let Err(e) = client.set_key(key) {
+                eprintln!("Failed to set API key for {}: {}", provider.name, e);
+                return None;
+            }
+        }
+        Some(client)
+    };
+
+    let mut map_client = if provider.api_keys.is_empty() {
+        MapClient::from(new_client(provider.api_key.as_deref())?)
+    } else {
+        MapClient::from(create_key_rotation_client(provider, new_client)?)
+    };
 
     setup_map_client(
         &mut map_client,
@@ -391,6 +512,24 @@ fn create_openai_image_client(
     Some(Box::new(map_client))
 }
 
+/// Creates one client per API key of the provider, choosing the one used by each
+/// request according to the provider's key strategy.
+fn create_key_rotation_client<C: BotClient + 'static>(
+    provider: &Provider,
+    new_client: impl Fn(Option<&str>) -> Option<C>,
+) -> Option<KeyRotationClient> {
+    let clients = provider
+        .api_keys
+        .keys
+        .iter()
+        .map(|k| Some(Box::new(new_client(Some(&k.key))?) as Box<dyn BotClient>))
+        .collect::<Option<Vec<_>>>()?;
+
+    let rotation = KeyRotation::for_provider(&provider.id);
+    rotation.configure(&provider.api_keys);
+    Some(KeyRotationClient::new(clients, rotation))
+}
+
 fn create_openai_realtime_client(provider: &Provider) -> Option<Box<dyn BotClient>> {
     let client_url = provider.url.trim_start_matches('#').to_string();
     let mut client = OpenAiRealtimeClient::new(client_url);
@@ -443,6 +582,37 @@ fn create_deep_inquire_client(
     Some(Box::new(map_client))
 }
 
+fn create_mofa_client(
+    provider: &Provider,
+    supported_providers_list: &[SupportedProvider],
+    available_bots: &BotMap,
+    providers: &ProviderMap,
+    store: &Store,
+) -> Option<Box<dyn BotClient>> {
+    let mut client = MofaClient::new(provider.url.clone());
+
+    if let Some(key) = provider.api_key.as_ref() {
+        if let Err(e) = client.set_key(key) {
+            eprintln!("Failed to set API key for {}: {}", provider.name, e);
+            return None;
+        }
+    }
+
+    let mut map_client = MapClient::from(client);
+
+    setup_map_client(
+        &mut map_client,
+        provider,
+        supported_providers_list,
+        available_bots,
+        providers,
+        store,
+        ClientFilter::None,
+    );
+
+    Some(Box::new(map_client))
+}
+
 fn create_openclaw_client(
     provider: &Provider,
     supported_providers_list: &[SupportedProvider],
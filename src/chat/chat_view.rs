@@ -4,9 +4,22 @@ use moly_kit::aitk::utils::asynchronous::spawn;
 use moly_kit::prelude::*;
 use moly_kit::widgets::stt_input::SttInputWidgetExt;
 
+use crate::chat::prompt_commands::PromptArgumentsFormWidgetExt;
+use crate::chat::resource_picker::ResourcePickerWidgetExt;
 use crate::data::chats::chat::ChatId;
 use crate::data::deep_inquire_client::DeepInquireCustomContent;
+use crate::data::failover_client::FailoverHandle;
+use crate::data::mcp_context::{self, McpContextAction, McpPrompt, McpResource};
+use crate::data::mcp_status::McpStatusAction;
+use crate::data::model_capabilities::ModelCapabilities;
+use crate::data::mofa_client::MofaCustomContent;
+use crate::data::openclaw_client::OpenClawCustomContent;
+use crate::data::reasoning::ReasoningHandle;
 use crate::data::store::{ProviderSyncingStatus, Store};
+use crate::data::tool_audit;
+use crate::data::tool_permissions::{ChatToolPermissions, ToolPermissionsHandle};
+use crate::data::tool_selection::ToolSelectionHandle;
+use crate::shared::actions::ChatAction;
 use crate::shared::bot_context::BotContext;
 use crate::shared::utils::attachments::{
     delete_attachment, generate_persistence_key, set_persistence_key_and_reader,
@@ -26,6 +39,13 @@ live_design! {
     use crate::chat::chat_history::ChatHistory;
     use crate::chat::chat_params::ChatParams;
     use crate::chat::deep_inquire_content::DeepInquireContent;
+    use crate::chat::openclaw_content::OpenClawContent;
+    use crate::chat::mofa_content::MofaContent;
+    use crate::chat::reasoning_controls::ReasoningControls;
+    use crate::chat::tool_picker::ToolPicker;
+    use crate::chat::resource_picker::ResourcePicker;
+    use crate::chat::prompt_commands::PromptArgumentsForm;
+    use crate::chat::prompt_commands::PromptCommandItem;
     use moly_kit::widgets::chat::Chat;
     use moly_kit::widgets::prompt_input::PromptInput;
     use moly_kit::widgets::stt_input::SttInput;
@@ -175,12 +195,51 @@ live_design! {
         spacing: 0
 
         deep_inquire_content: <DeepInquireContent> {}
+        openclaw_content: <OpenClawContent> {}
+        mofa_content: <MofaContent> {}
+
+        // Only shown for reasoning models.
+        reasoning_header = <View> {
+            visible: false
+            width: Fill, height: Fit
+            padding: {left: 20, right: 20, top: 8, bottom: 4}
+            <ReasoningControls> {}
+        }
+
+        // Shown after picking a prompt of an MCP server which takes arguments.
+        <View> {
+            width: Fill, height: Fit
+            padding: {left: 20, right: 20}
+            prompt_arguments = <PromptArgumentsForm> {}
+        }
 
         chat = <Chat> {
             messages = { padding: {left: 10, right: 10} }
-            prompt = <PromptInputWithShadow> {}
+            // Typing `/` suggests the prompts of the MCP servers.
+            prompt = <PromptInputWithShadow> {
+                trigger: "/"
+                inline_search: true
+                persistent = {
+                    bottom = {
+                        left = {
+                            // Only shown when the MCP servers have tools.
+                            tool_picker = <ToolPicker> { margin: {left: 5} }
+                        }
+                    }
+                }
+            }
             stt_input = <SttInputWithShadow> {}
         }
+
+        // Only shown when the MCP servers are connected.
+        <View> {
+            width: Fill, height: Fit
+            flow: Down, spacing: 6
+            padding: {left: 20, right: 20, bottom: 8}
+            resource_picker = <ResourcePicker> {}
+        }
+
+        prompt_command: <PromptCommandItem> {}
     }
 }
 
@@ -196,6 +255,15 @@ pub struct ChatView {
     #[live]
     deep_inquire_content: LivePtr,
 
+    #[live]
+    openclaw_content: LivePtr,
+
+    #[live]
+    mofa_content: LivePtr,
+
+    #[live]
+    prompt_command: Option<LivePtr>,
+
     #[rust]
     chat_id: ChatId,
 
@@ -230,6 +298,30 @@ pub struct ChatView {
 
     #[rust]
     stt_config: Option<Version>,
+
+    /// Failover settings of this chat, shared with the client of the controller.
+    #[rust]
+    failover: FailoverHandle,
+
+    /// Tools selected in this chat, shared with the client of the controller.
+    #[rust]
+    tool_selection: ToolSelectionHandle,
+
+    /// Reasoning params of this chat, shared with the client of the controller.
+    #[rust]
+    reasoning: ReasoningHandle,
+
+    /// Prompts of the MCP servers currently suggested, with their items.
+    #[rust]
+    prompt_commands: Vec<(WidgetRef, McpPrompt)>,
+
+    /// Whether the prompt suggestions are being shown.
+    #[rust]
+    prompt_commands_open: bool,
+
+    /// Attachments of the prompt read from resources, to refresh them on changes.
+    #[rust]
+    resource_attachments: Vec<(McpResource, Attachment)>,
 }
 
 impl LiveHook for ChatView {
@@ -237,6 +329,12 @@ impl LiveHook for ChatView {
         self.messages(ids!(chat.messages))
             .write()
             .register_custom_content(DeepInquireCustomContent::new(self.deep_inquire_content));
+        self.messages(ids!(chat.messages))
+            .write()
+            .register_custom_content(OpenClawCustomContent::new(self.openclaw_content));
+        self.messages(ids!(chat.messages))
+            .write()
+            .register_custom_content(MofaCustomContent::new(self.mofa_content));
         self.prompt_input(ids!(chat.prompt)).write().disable();
         let plugin_id = self
             .chat_controller
@@ -267,6 +365,7 @@ impl Drop for ChatView {
         }
 
         self.unbind_bot_context();
+        tool_audit::abandon(self.chat_id);
     }
 }
 
@@ -275,11 +374,33 @@ impl Widget for ChatView {
         self.bind_bot_context(scope);
         self.configure_stt(scope, cx);
 
+        self.sync_reasoning(cx, scope);
+        self.sync_tool_selection(scope);
+
         self.ui_runner().handle(cx, event, scope, self);
         self.view.handle_event(cx, event, scope);
 
         self.handle_current_bot(scope);
         self.handle_unread_messages(scope);
+
+        if let Event::Actions(actions) = event {
+            // The tool picker lists the tools of the connected servers.
+            if actions
+                .iter()
+                .any(|action| matches!(action.cast(), McpStatusAction::Changed))
+            {
+                self.redraw(cx);
+            }
+
+            if actions
+                .iter()
+                .any(|action| matches!(action.cast(), ChatAction::FallbacksChanged))
+            {
+                self.sync_fallback_bots(scope);
+            }
+
+            self.handle_mcp_context(cx, actions);
+        }
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
@@ -489,6 +610,298 @@ impl ChatView {
         }
     }
 
+    /// Resolves the fallback chain for the current bot and hands it to the client.
+    ///
+    /// The chat's own chain takes precedence over the one configured for the bot.
+    /// Called when the bot, the available bots or the fallback settings change.
+    fn sync_fallback_bots(&mut self, scope: &mut Scope) {
+        let store = scope.data.get_mut::<Store>().unwrap();
+
+        let Some(bot_id) = self.chat_controller.lock().unwrap().state().bot_id.clone() else {
+            self.failover.set_fallbacks(vec![]);
+            return;
+        };
+
+        let chat_fallbacks = store
+            .chats
+            .get_chat_by_id(self.chat_id)
+            .map(|chat| chat.borrow().fallback_models.clone())
+            .unwrap_or_default();
+
+        let fallback_models = if chat_fallbacks.is_empty() {
+            store.preferences.fallback_models_for(&bot_id).to_vec()
+        } else {
+            chat_fallbacks
+        };
+
+        let fallbacks = store
+            .chats
+            .resolve_fallback_bots(&fallback_models)
+            .into_iter()
+            .filter(|fallback| fallback != &bot_id)
+            .collect();
+
+        self.failover.set_fallbacks(fallbacks);
+    }
+
+    /// Shows the reasoning controls for reasoning models, and hands the reasoning
+    /// params of this chat to the client.
+    ///
+    /// Other models get no params, as providers may reject them.
+    fn sync_reasoning(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let store = scope.data.get_mut::<Store>().unwrap();
+
+        let bot_id = self.chat_controller.lock().unwrap().state().bot_id.clone();
+        let supports_reasoning = bot_id
+            .and_then(|bot_id| store.chats.available_bots.get(&bot_id))
+            .is_some_and(|bot| bot.capabilities.reasoning);
+
+        self.view(ids!(reasoning_header))
+            .set_visible(cx, supports_reasoning);
+
+        let params = store
+            .chats
+            .get_chat_by_id(self.chat_id)
+            .filter(|_| supports_reasoning)
+            .map(|chat| chat.borrow().inferences_params.reasoning.clone())
+            .unwrap_or_default();
+        self.reasoning.set(params);
+    }
+
+    /// Hands the tools selected in this chat to the client.
+    fn sync_tool_selection(&mut self, scope: &mut Scope) {
+        let store = scope.data.get_mut::<Store>().unwrap();
+        let selection = store.chat_tool_selection(self.chat_id);
+        self.tool_selection.set(selection);
+    }
+
+    /// Attaches resources and fills in prompts of the MCP servers.
+    fn handle_mcp_context(&mut self, cx: &mut Cx, actions: &Actions) {
+        if let Some(resource) = self
+            .resource_picker(ids!(resource_picker))
+            .attached(actions)
+        {
+            self.attach_resource(resource);
+        }
+
+        let mut prompt_input = self.prompt_input(ids!(chat.prompt));
+        if prompt_input.read().should_build_items(actions) {
+            self.prompt_commands_open = true;
+            mcp_context::load_catalog();
+            self.build_prompt_commands(cx);
+        }
+
+        let selected = prompt_input.read().item_selected(actions);
+        if let Some(item) = selected {
+            self.prompt_commands_open = false;
+
+            // Drop the typed command, the prompt takes its place.
+            let command = format!("/{}", prompt_input.read().search_text());
+            let text = prompt_input.text();
+            if let Some(text) = text.strip_suffix(&command) {
+                prompt_input.write().set_text(cx, text);
+            }
+
+            let prompt = self
+                .prompt_commands
+                .iter()
+                .find(|(command, _)| command.widget_uid() == item.widget_uid())
+                .map(|(_, prompt)| prompt.clone());
+            if let Some(prompt) = prompt {
+                if prompt.arguments.is_empty() {
+                    self.render_prompt(prompt, HashMap::new());
+                } else {
+                    let form = self.prompt_arguments_form(ids!(prompt_arguments));
+                    form.set_prompt(cx, prompt);
+                    form.set_visible(cx, true);
+                    self.redraw(cx);
+                }
+            }
+        }
+
+        let form = self.prompt_arguments_form(ids!(prompt_arguments));
+        if let Some((prompt, arguments)) = form.submitted(actions) {
+            form.set_visible(cx, false);
+            self.render_prompt(prompt, arguments);
+            self.redraw(cx);
+        }
+        if form.cancelled(actions) {
+            form.set_visible(cx, false);
+            self.redraw(cx);
+        }
+
+        for action in actions {
+            match action.cast() {
+                McpContextAction::CatalogChanged => {
+                    if self.prompt_commands_open {
+                        self.build_prompt_commands(cx);
+                    }
+                    self.redraw(cx);
+                }
+                McpContextAction::ResourceUpdated { server, uri } => {
+                    self.refresh_resource(&server, &uri);
+                }
+                McpContextAction::None => {}
+            }
+        }
+    }
+
+    /// Suggests the prompts matching what was typed after `/`.
+    fn build_prompt_commands(&mut self, cx: &mut Cx) {
+        let mut prompt_input = self.prompt_input(ids!(chat.prompt));
+        let search = prompt_input.read().search_text().to_lowercase();
+
+        prompt_input.write().clear_items();
+        self.prompt_commands.clear();
+
+        for prompt in mcp_context::prompts() {
+            if !prompt.name.to_lowercase().contains(&search) {
+                continue;
+            }
+
+            let item = WidgetRef::new_from_ptr(cx, self.prompt_command);
+            let name = format!("/{}", prompt.name);
+            let description = if prompt.description.is_empty() {
+                prompt.server.clone()
+            } else {
+                format!("{} · {}", prompt.server, prompt.description)
+            };
+            item.apply_over(
+                cx,
+                live! {
+                    name = { text: (name) }
+                    description = { text: (description) }
+                },
+            );
+            prompt_input.write().add_item(item.clone());
+            self.prompt_commands.push((item, prompt));
+        }
+    }
+
+    /// Reads the resource and attaches it to the prompt.
+    fn attach_resource(&mut self, resource: McpResource) {
+        let ui = self.ui_runner();
+        spawn(async move {
+            let attachments = match mcp_context::read_resource(&resource).await {
+                Ok(attachments) => attachments,
+                Err(e) => {
+                    ::log::error!("Failed to read MCP resource {}: {}", resource.uri, e);
+                    return;
+                }
+            };
+
+            mcp_context::subscribe(&resource).await;
+
+            ui.defer_with_redraw(move |me, _cx, _scope| {
+                for attachment in &attachments {
+                    me.resource_attachments
+                        .push((resource.clone(), attachment.clone()));
+                }
+                me.prompt_input(ids!(chat.prompt))
+                    .write()
+                    .add_attachments(attachments);
+            });
+        });
+    }
+
+    /// Replaces the attachments of a resource that changed with its new contents.
+    ///
+    /// Only attachments not sent yet are refreshed, messages keep what was sent.
+    fn refresh_resource(&mut self, server: &str, uri: &str) {
+        let Some(resource) = self
+            .resource_attachments
+            .iter()
+            .map(|(resource, _)| resource)
+            .find(|resource| resource.server == server && resource.uri == uri)
+            .cloned()
+        else {
+            return;
+        };
+        let outdated = self
+            .resource_attachments
+            .iter()
+            .filter(|(r, _)| *r == resource)
+            .map(|(_, attachment)| attachment.clone())
+            .collect::<Vec<_>>();
+
+        let ui = self.ui_runner();
+        spawn(async move {
+            let attachments = match mcp_context::read_resource(&resource).await {
+                Ok(attachments) => attachments,
+                Err(e) => {
+                    ::log::error!("Failed to refresh MCP resource {}: {}", resource.uri, e);
+                    return;
+                }
+            };
+
+            ui.defer_with_redraw(move |me, _cx, _scope| {
+                me.resource_attachments.retain(|(r, _)| *r != resource);
+
+                let mut prompt_input = me.prompt_input(ids!(chat.prompt));
+                for (old, new) in outdated.iter().zip(attachments) {
+                    if prompt_input.write().replace_attachment(old, new.clone()) {
+                        me.resource_attachments.push((resource.clone(), new));
+                    }
+                }
+            });
+        });
+    }
+
+    /// Renders the prompt of the MCP server into the prompt input.
+    fn render_prompt(&mut self, prompt: McpPrompt, arguments: HashMap<String, String>) {
+        let ui = self.ui_runner();
+        spawn(async move {
+            let rendered = match mcp_context::get_prompt(&prompt, arguments).await {
+                Ok(rendered) => rendered,
+                Err(e) => {
+                    ::log::error!("Failed to get MCP prompt '{}': {}", prompt.name, e);
+                    return;
+                }
+            };
+
+            ui.defer_with_redraw(move |me, cx, _scope| {
+                let mut prompt_input = me.prompt_input(ids!(chat.prompt));
+                let text = prompt_input.text();
+                let text = if text.trim().is_empty() {
+                    rendered.text
+                } else {
+                    format!("{}\n\n{}", text.trim_end(), rendered.text)
+                };
+                prompt_input.write().set_text(cx, &text);
+                prompt_input.write().add_attachments(rendered.attachments);
+            });
+        });
+    }
+
+    /// Attributes the last response to the bot that actually answered it, if the
+    /// requested one failed over. Called once streaming ends.
+    fn handle_failover_outcome(&mut self) {
+        let mut lock = self.chat_controller.lock().unwrap();
+        if lock.state().is_streaming {
+            return;
+        }
+
+        let Some(outcome) = self.failover.take_outcome() else {
+            return;
+        };
+
+        let requested = EntityId::Bot(outcome.requested);
+        let Some((index, message)) = lock
+            .state()
+            .messages
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, message)| message.from == requested)
+        else {
+            return;
+        };
+
+        let mut updated_message = message.clone();
+        updated_message.from = EntityId::Bot(outcome.answered_by);
+        lock.dispatch_mutation(VecMutation::Update(index, updated_message));
+    }
+
     /// Syncs the bot_id from Store's associated_bot to ChatController state.
     /// This ensures ChatController reflects the persisted bot selection.
     fn sync_bot_from_store(&mut self, scope: &mut Scope) {
@@ -519,7 +932,12 @@ impl ChatView {
         if self_bot_context_id != store_bot_context_id {
             self.bot_context = store.bot_context.clone();
             if let Some(bot_context) = &mut self.bot_context {
-                bot_context.add_chat_controller(self.chat_controller.clone());
+                bot_context.add_chat_controller(
+                    self.chat_controller.clone(),
+                    self.failover.clone(),
+                    self.tool_selection.clone(),
+                    self.reasoning.clone(),
+                );
             }
         }
 
@@ -565,6 +983,43 @@ impl ChatView {
                         .unwrap_or_else(|| moly_kit::widgets::model_selector::default_grouping(bot))
                 });
 
+            // Mark bots loaded from the models cache until their provider answers again
+            let stale_bots: HashSet<BotId> = store
+                .chats
+                .available_bots
+                .values()
+                .filter(|provider_bot| provider_bot.is_stale)
+                .map(|provider_bot| provider_bot.id.clone())
+                .collect();
+            let chat = self.chat(ids!(chat));
+            chat.read()
+                .prompt_input_ref()
+                .widget(ids!(model_selector))
+                .as_model_selector()
+                .set_stale_check(move |bot: &moly_kit::aitk::protocol::Bot| {
+                    stale_bots.contains(&bot.id)
+                });
+
+            // Let the capability filters of the ModelSelector know what each bot supports
+            let bot_capabilities: HashMap<BotId, ModelCapabilities> = store
+                .chats
+                .available_bots
+                .iter()
+                .map(|(bot_id, provider_bot)| (bot_id.clone(), provider_bot.capabilities))
+                .collect();
+            let chat = self.chat(ids!(chat));
+            chat.read()
+                .prompt_input_ref()
+                .widget(ids!(model_selector))
+                .as_model_selector()
+                .set_capability_check(move |bot: &moly_kit::aitk::protocol::Bot, capability| {
+                    bot_capabilities
+                        .get(&bot.id)
+                        .copied()
+                        .unwrap_or_else(|| ModelCapabilities::for_bot(bot, None))
+                        .has(capability)
+                });
+
             // Update filter when bot_context changes
             let chat = self.chat(ids!(chat));
             if let Some(mut list) = chat
@@ -622,11 +1077,15 @@ impl ChatView {
 }
 
 impl ChatViewRef {
-    pub fn set_chat_id(&mut self, chat_id: ChatId) {
+    pub fn set_chat_id(&mut self, chat_id: ChatId, tool_permissions: ToolPermissionsHandle) {
         if let Some(mut inner) = self.borrow_mut() {
             inner.chat_id = chat_id;
             // Reset sync flag so bot_id will be synced from Store on next draw
             inner.initial_bot_synced = false;
+            let permissions = Arc::new(ChatToolPermissions::new(chat_id, tool_permissions));
+            let mut chat = inner.chat(ids!(chat));
+            chat.write().set_tool_permissions(Some(permissions.clone()));
+            chat.write().set_tool_execution(Some(permissions));
         }
     }
 
@@ -663,6 +1122,16 @@ impl ChatControllerPlugin for Glue {
             }
             ChatStateMutation::SetBotId(bot_id) => {
                 self.replicate_bot_id_to_store(bot_id.clone());
+                self.ui
+                    .defer(|chat_view, _, scope| chat_view.sync_fallback_bots(scope));
+            }
+            ChatStateMutation::MutateBots(_) => {
+                self.ui
+                    .defer(|chat_view, _, scope| chat_view.sync_fallback_bots(scope));
+            }
+            ChatStateMutation::SetIsStreaming(false) => {
+                self.ui
+                    .defer(|chat_view, _, _| chat_view.handle_failover_outcome());
             }
             _ => {}
         }
@@ -740,6 +1209,7 @@ impl Glue {
         });
     }
 
+    /// Logs the tool calls whose results came back.
     fn mark_attachments(&mut self, mutation: &VecMutation<Message>, state: &ChatState) {
         self.marked_attachments.clear();
 
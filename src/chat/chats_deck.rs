@@ -9,6 +9,7 @@ use crate::data::capture::CaptureAction;
 use crate::data::chats::chat::Chat as ChatData;
 use crate::data::chats::chat::ChatId;
 use crate::data::store::Store;
+use crate::data::tool_permissions::ToolPermissionsHandle;
 use crate::shared::actions::ChatAction;
 
 live_design! {
@@ -107,14 +108,22 @@ impl WidgetMatchEvent for ChatsDeck {
                     let chat_id = store.chats.create_empty_chat(Some(bot_id.clone()));
                     let chat = store.chats.get_chat_by_id(chat_id);
                     if let Some(chat) = chat {
-                        self.create_or_update_chat_view(cx, &chat.borrow());
+                        self.create_or_update_chat_view(
+                            cx,
+                            &chat.borrow(),
+                            store.preferences.tool_permissions(),
+                        );
                     }
                 }
                 ChatAction::StartWithoutEntity => {
                     let chat_id = store.chats.create_empty_chat(None);
                     let chat = store.chats.get_chat_by_id(chat_id);
                     if let Some(chat) = chat {
-                        self.create_or_update_chat_view(cx, &chat.borrow());
+                        self.create_or_update_chat_view(
+                            cx,
+                            &chat.borrow(),
+                            store.preferences.tool_permissions(),
+                        );
                     }
                 }
                 _ => {}
@@ -130,7 +139,11 @@ impl WidgetMatchEvent for ChatsDeck {
                             .preferences
                             .set_current_chat_model(chat.borrow().associated_bot.clone());
 
-                        self.create_or_update_chat_view(cx, &chat.borrow());
+                        self.create_or_update_chat_view(
+                            cx,
+                            &chat.borrow(),
+                            store.preferences.tool_permissions(),
+                        );
                     }
                 }
                 _ => {}
@@ -153,7 +166,12 @@ impl WidgetMatchEvent for ChatsDeck {
 }
 
 impl ChatsDeck {
-    pub fn create_or_update_chat_view(&mut self, cx: &mut Cx, chat_data: &ChatData) {
+    pub fn create_or_update_chat_view(
+        &mut self,
+        cx: &mut Cx,
+        chat_data: &ChatData,
+        tool_permissions: ToolPermissionsHandle,
+    ) {
         // Check if an instance already exists for this chat
         if let Some(existing_view) = self.chat_view_refs.get_mut(&chat_data.id) {
             // Instance exists, just make it visible and focused
@@ -180,7 +198,7 @@ impl ChatsDeck {
         let mut chat_view = chat_view.as_chat_view();
 
         // Initialize new instance
-        chat_view.set_chat_id(chat_data.id);
+        chat_view.set_chat_id(chat_data.id, tool_permissions);
 
         // Load messages into the controller
         chat_view
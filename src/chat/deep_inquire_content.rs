@@ -1,6 +1,9 @@
 use super::deep_inquire_stages::StagesWidgetExt;
 use crate::data::deep_inquire_client::{Data, StageType};
+use crate::data::deep_inquire_report::{ReportFormat, ReportOptions, build_report};
+use crate::shared::utils::filesystem;
 use makepad_widgets::*;
+use moly_kit::aitk::utils::asynchronous::spawn;
 use moly_kit::prelude::*;
 
 live_design! {
@@ -11,8 +14,21 @@ live_design! {
     use moly_kit::widgets::message_markdown::*;
 
     use crate::shared::styles::*;
+    use crate::shared::widgets::*;
     use crate::chat::deep_inquire_stages::*;
 
+    ExportButton = <MolyButton> {
+        width: Fit, height: 28
+        padding: {left: 12, right: 12, top: 0, bottom: 0}
+        draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+        draw_text: {
+            text_style: <REGULAR_FONT>{font_size: 10},
+            fn get_color(self) -> vec4 {
+                return #344054;
+            }
+        }
+    }
+
     pub DeepInquireContent = {{DeepInquireContent}} {
         flow: Down, spacing: 10
         height: Fit,
@@ -31,6 +47,40 @@ live_design! {
             padding: {right: 18, top: 18, bottom: 14},
             completed_markdown = <MessageMarkdown> {}
         }
+
+        export_bar = <View> {
+            visible: false
+            width: Fill, height: Fit,
+            flow: Right, spacing: 10
+            align: {y: 0.5}
+
+            <Label> {
+                text: "Export report"
+                draw_text: {
+                    color: #x0,
+                    text_style: <THEME_FONT_BOLD>{font_size: 10},
+                }
+            }
+            export_markdown = <ExportButton> { text: "Markdown" }
+            export_html = <ExportButton> { text: "HTML" }
+            include_thinking = <CheckBox> {
+                text: "Include thinking"
+                draw_text: {
+                    text_style: <REGULAR_FONT>{font_size: 10},
+                    fn get_color(self) -> vec4 {
+                        return #344054;
+                    }
+                }
+            }
+            export_status = <Label> {
+                width: Fill
+                draw_text: {
+                    wrap: Ellipsis
+                    text_style: <REGULAR_FONT>{font_size: 10},
+                    color: #667085
+                }
+            }
+        }
     }
 }
 
@@ -38,11 +88,17 @@ live_design! {
 pub struct DeepInquireContent {
     #[deref]
     view: View,
+
+    /// The research shown, kept to export it.
+    #[rust]
+    data: Option<Data>,
 }
 
 impl Widget for DeepInquireContent {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.ui_runner().handle(cx, event, scope, self);
         self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
@@ -50,7 +106,60 @@ impl Widget for DeepInquireContent {
     }
 }
 
+impl WidgetMatchEvent for DeepInquireContent {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        if self.button(ids!(export_markdown)).clicked(actions) {
+            self.export(cx, ReportFormat::Markdown);
+        }
+        if self.button(ids!(export_html)).clicked(actions) {
+            self.export(cx, ReportFormat::Html);
+        }
+    }
+}
+
 impl DeepInquireContent {
+    fn export(&mut self, cx: &mut Cx, format: ReportFormat) {
+        let Some(data) = &self.data else {
+            return;
+        };
+
+        let options = ReportOptions {
+            format,
+            include_thinking: self.check_box(ids!(include_thinking)).active(cx),
+        };
+        let report = build_report(data, options);
+
+        let Some(path) = report_path(format) else {
+            cx.copy_to_clipboard(&report);
+            self.label(ids!(export_status))
+                .set_text(cx, "Copied to the clipboard");
+            return;
+        };
+
+        self.label(ids!(export_status)).set_text(cx, "Saving...");
+        let ui = self.ui_runner();
+        spawn(async move {
+            let mut fs = filesystem::global();
+            let saved = match fs.queue_write_string(path.clone(), report.clone()).await {
+                Ok(()) => true,
+                Err(e) => {
+                    ::log::error!("Failed to save the report to {}: {}", path.display(), e);
+                    false
+                }
+            };
+
+            ui.defer_with_redraw(move |me, cx, _scope| {
+                let status = if saved {
+                    format!("Saved to {}", path.display())
+                } else {
+                    cx.copy_to_clipboard(&report);
+                    "Copied to the clipboard".to_string()
+                };
+                me.label(ids!(export_status)).set_text(cx, &status);
+            });
+        });
+    }
+
     pub(crate) fn set_content(&mut self, cx: &mut Cx, content: &MessageContent) {
         let data = content
             .data
@@ -79,5 +188,32 @@ impl DeepInquireContent {
             self.markdown(ids!(completed_block.completed_markdown))
                 .set_text(cx, &final_text);
         }
+
+        // Reports are only worth exporting once the research is complete.
+        self.view(ids!(export_bar))
+            .set_visible(cx, completion_stage.is_some());
+        self.data = Some(data);
     }
 }
+
+/// Where to save a report, in the downloads folder.
+///
+/// Returns `None` when there is no such folder to write to, like on the web.
+/// The path is absolute, which the filesystem adapters keep as is.
+#[cfg(not(target_arch = "wasm32"))]
+fn report_path(format: ReportFormat) -> Option<std::path::PathBuf> {
+    let dirs = directories::UserDirs::new()?;
+    let dir = dirs.download_dir().unwrap_or(dirs.home_dir());
+
+    let file_name = format!(
+        "research-report-{}.{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    );
+    Some(dir.join(file_name))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn report_path(_format: ReportFormat) -> Option<std::path::PathBuf> {
+    None
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use makepad_widgets::*;
 
-use crate::data::deep_inquire_client::{Stage, StageType, SubStage};
+use crate::data::deep_inquire_client::{Stage, StageType, SubStage, get_human_readable_stage_name};
 use moly_kit::prelude::*;
 
 live_design! {
@@ -296,6 +296,22 @@ live_design! {
         }
     }
 
+    OtherStages = {{OtherStages}} {
+        flow: Down
+        width: Fill, height: Fit,
+
+        stage_template: <StageView> {
+            stage_type: Other
+            wrapper = {
+                header = {
+                    stage_toggle = {
+                        stage_bubble_text = { text: "📄" }
+                    }
+                }
+            }
+        }
+    }
+
     pub Stages = {{Stages}} {
         flow: Down
         visible: false,
@@ -324,6 +340,8 @@ live_design! {
                 }
             }
         }
+
+        other_stages = <OtherStages> {}
     }
 }
 
@@ -396,6 +414,14 @@ impl Stages {
             }
         }
 
+        let other_stages: Vec<Stage> = stages
+            .iter()
+            .filter(|s| s.stage_type == StageType::Other)
+            .cloned()
+            .collect();
+        self.other_stages(ids!(other_stages))
+            .set_stages(cx, &other_stages, !has_completion_stage);
+
         self.redraw(cx);
     }
 }
@@ -408,6 +434,76 @@ impl StagesRef {
     }
 }
 
+/// Stages of a type this version doesn't know about, shown with a generic look
+/// and their server-provided name as title.
+#[derive(Widget, Live, LiveHook)]
+pub struct OtherStages {
+    #[deref]
+    view: View,
+
+    #[live]
+    stage_template: Option<LivePtr>,
+
+    #[rust]
+    stage_names: Vec<String>,
+
+    #[rust]
+    stage_views: HashMap<String, WidgetRef>,
+}
+
+impl Widget for OtherStages {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for stage_view in self.stage_views.values() {
+            stage_view.handle_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+
+        for name in self.stage_names.iter() {
+            if let Some(stage_view) = self.stage_views.get(name) {
+                let _ = stage_view.draw_all(cx, scope);
+            }
+        }
+
+        cx.end_turtle();
+        DrawStep::done()
+    }
+}
+
+impl OtherStages {
+    fn set_stages(&mut self, cx: &mut Cx, stages: &[Stage], is_streaming: bool) {
+        self.stage_names = stages.iter().map(|s| s.type_name.clone()).collect();
+
+        for (index, stage) in stages.iter().enumerate() {
+            let stage_view = self
+                .stage_views
+                .entry(stage.type_name.clone())
+                .or_insert_with(|| WidgetRef::new_from_ptr(cx, self.stage_template));
+
+            stage_view
+                .label(ids!(stage_title))
+                .set_text(cx, &get_human_readable_stage_name(&stage.type_name));
+
+            let mut stage_view = stage_view.as_stage_view();
+            stage_view.set_stage(cx, stage);
+            // Only the latest stage can still be receiving content
+            stage_view.set_streaming_state(cx, is_streaming && index == stages.len() - 1);
+        }
+
+        self.redraw(cx);
+    }
+}
+
+impl OtherStagesRef {
+    pub fn set_stages(&mut self, cx: &mut Cx, stages: &[Stage], is_streaming: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_stages(cx, stages, is_streaming);
+        }
+    }
+}
+
 #[derive(Live, LiveHook, Widget)]
 pub struct StageView {
     #[deref]
@@ -688,17 +784,3 @@ impl SubStagesRef {
         }
     }
 }
-
-// Replaces underscores with spaces, and capitalizes the first letter of each word
-pub fn get_human_readable_stage_name(name: &str) -> String {
-    name.split('_')
-        .map(|word| {
-            let mut chars = word.chars();
-            match chars.next() {
-                Some(first) => first.to_uppercase().to_string() + chars.as_str(),
-                None => String::new(),
-            }
-        })
-        .collect::<Vec<_>>()
-        .join(" ")
-}
@@ -185,7 +185,17 @@ impl EntityButton {
         if store.chats.is_agent(&bot_id) {
             avatar.set_visible(true);
             avatar.set_bot(bot);
-            description_label.set_text(cx, &bot.description);
+            let mut description = bot.description.clone();
+            let required: Vec<&str> = bot
+                .agent
+                .iter()
+                .flat_map(|agent| agent.required_parameters())
+                .map(|p| p.name.as_str())
+                .collect();
+            if !required.is_empty() {
+                description.push_str(&format!(" · Requires {}", required.join(", ")));
+            }
+            description_label.set_text(cx, &description);
 
             let provider = store.chats.providers.get(&bot.provider_id);
 
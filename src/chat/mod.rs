@@ -12,18 +12,30 @@ pub mod deep_inquire_stages;
 pub mod delete_chat_modal;
 pub mod entity_button;
 pub mod model_info;
+pub mod mofa_content;
 pub mod moly_bot_filter;
+pub mod openclaw_content;
+pub mod prompt_commands;
+pub mod reasoning_controls;
+pub mod resource_picker;
 pub mod shared;
+pub mod tool_picker;
 
 use makepad_widgets::Cx;
 
 pub fn live_design(cx: &mut Cx) {
     deep_inquire_stages::live_design(cx);
     deep_inquire_content::live_design(cx);
+    openclaw_content::live_design(cx);
+    mofa_content::live_design(cx);
     entity_button::live_design(cx);
     chat_history_card::live_design(cx);
     chat_history::live_design(cx);
     chat_history_panel::live_design(cx);
+    reasoning_controls::live_design(cx);
+    tool_picker::live_design(cx);
+    resource_picker::live_design(cx);
+    prompt_commands::live_design(cx);
     chat_params::live_design(cx);
     chat_view::live_design(cx);
     chats_deck::live_design(cx);
@@ -0,0 +1,137 @@
+use crate::data::mofa_client::{MofaData, NodeOutput};
+use makepad_widgets::*;
+use moly_kit::prelude::*;
+
+/// Longest node output shown inline, in characters.
+const MAX_OUTPUT_CHARS: usize = 2000;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::shaders::*;
+
+    use moly_kit::widgets::message_markdown::*;
+
+    use crate::shared::styles::*;
+
+    pub MofaContent = {{MofaContent}} {
+        flow: Down, spacing: 10
+        height: Fit,
+
+        <RoundedView> {
+            width: Fill, height: Fit
+            flow: Down, spacing: 5
+            padding: 10
+            show_bg: true
+            draw_bg: {
+                color: #F5F5F5
+                border_radius: 3
+            }
+
+            dataflow_label = <Label> {
+                draw_text: {
+                    color: #x0,
+                    text_style: <THEME_FONT_BOLD>{font_size: 10},
+                }
+            }
+            nodes_markdown = <MessageMarkdown> {}
+            outputs_markdown = <MessageMarkdown> {}
+        }
+
+        text_markdown = <MessageMarkdown> {}
+    }
+}
+
+/// Shows the dataflow a MoFa agent runs as, with the status of its nodes and
+/// what they produced, followed by the agent's reply.
+#[derive(Widget, Live, LiveHook)]
+pub struct MofaContent {
+    #[deref]
+    view: View,
+}
+
+impl Widget for MofaContent {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl MofaContent {
+    pub(crate) fn set_content(&mut self, cx: &mut Cx, content: &MessageContent, data: &MofaData) {
+        let dataflow = &data.dataflow;
+        let title = match (dataflow.id.is_empty(), dataflow.status.is_empty()) {
+            (true, true) => "Dataflow".to_string(),
+            (true, false) => format!("Dataflow · {}", dataflow.status),
+            (false, true) => format!("Dataflow {}", dataflow.id),
+            (false, false) => format!("Dataflow {} · {}", dataflow.id, dataflow.status),
+        };
+        self.label(ids!(dataflow_label)).set_text(cx, &title);
+
+        let nodes = dataflow
+            .nodes
+            .iter()
+            .map(|node| {
+                format!(
+                    "- {} `{}` {}",
+                    status_icon(&node.status),
+                    node.id,
+                    node.status
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.markdown(ids!(nodes_markdown)).set_text(cx, &nodes);
+        self.widget(ids!(nodes_markdown))
+            .set_visible(cx, !nodes.is_empty());
+
+        let outputs = data
+            .outputs
+            .iter()
+            .map(output_markdown)
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        self.markdown(ids!(outputs_markdown)).set_text(cx, &outputs);
+        self.widget(ids!(outputs_markdown))
+            .set_visible(cx, !outputs.is_empty());
+
+        self.markdown(ids!(text_markdown))
+            .set_text(cx, &content.text);
+        self.widget(ids!(text_markdown))
+            .set_visible(cx, !content.text.is_empty());
+    }
+}
+
+fn status_icon(status: &str) -> &'static str {
+    match status {
+        "running" => "⏳",
+        "finished" | "completed" | "done" => "✅",
+        "failed" | "error" => "⚠️",
+        _ => "•",
+    }
+}
+
+/// Text outputs are shown as they are, anything else as JSON.
+fn output_markdown(output: &NodeOutput) -> String {
+    let (text, is_json) = match &output.output {
+        serde_json::Value::String(text) => (text.clone(), false),
+        value => (
+            serde_json::to_string_pretty(value).unwrap_or_default(),
+            true,
+        ),
+    };
+
+    let mut shown: String = text.chars().take(MAX_OUTPUT_CHARS).collect();
+    if shown.len() < text.len() {
+        shown.push('…');
+    }
+
+    if is_json {
+        format!("**{}**\n```json\n{}\n```", output.node, shown)
+    } else {
+        format!("**{}**\n\n{}", output.node, shown)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::data::openclaw_client::{OpenClawActivity, OpenClawData};
+use makepad_widgets::*;
+use moly_kit::prelude::*;
+
+/// Longest tool output shown inline, in characters.
+const MAX_TOOL_OUTPUT_CHARS: usize = 2000;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::shaders::*;
+
+    use moly_kit::widgets::message_markdown::*;
+
+    use crate::shared::styles::*;
+
+    pub OpenClawContent = {{OpenClawContent}} {
+        flow: Down, spacing: 10
+        height: Fit,
+
+        <RoundedView> {
+            width: Fill, height: Fit
+            flow: Down, spacing: 5
+            padding: 10
+            show_bg: true
+            draw_bg: {
+                color: #F5F5F5
+                border_radius: 3
+            }
+
+            <Label> {
+                text: "Activity"
+                draw_text: {
+                    color: #x0,
+                    text_style: <THEME_FONT_BOLD>{font_size: 10},
+                }
+            }
+            activity_markdown = <MessageMarkdown> {}
+        }
+
+        text_markdown = <MessageMarkdown> {}
+    }
+}
+
+/// Shows what an OpenClaw agent did while answering, like the tools it ran or the
+/// messages it relayed to other channels, followed by its reply.
+#[derive(Widget, Live, LiveHook)]
+pub struct OpenClawContent {
+    #[deref]
+    view: View,
+}
+
+impl Widget for OpenClawContent {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl OpenClawContent {
+    pub(crate) fn set_content(
+        &mut self,
+        cx: &mut Cx,
+        content: &MessageContent,
+        data: &OpenClawData,
+    ) {
+        let activity = data
+            .activity
+            .iter()
+            .map(activity_markdown)
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        self.markdown(ids!(activity_markdown))
+            .set_text(cx, &activity);
+
+        self.markdown(ids!(text_markdown))
+            .set_text(cx, &content.text);
+        self.widget(ids!(text_markdown))
+            .set_visible(cx, !content.text.is_empty());
+    }
+}
+
+fn activity_markdown(activity: &OpenClawActivity) -> String {
+    match activity {
+        OpenClawActivity::ToolCall {
+            name, arguments, ..
+        } => {
+            if arguments.is_empty() {
+                format!("🔧 **Called** `{}`", name)
+            } else {
+                format!("🔧 **Called** `{}` with args `{}`", name, arguments)
+            }
+        }
+        OpenClawActivity::ToolResult {
+            name,
+            output,
+            is_error,
+            ..
+        } => {
+            let heading = if *is_error {
+                format!("⚠️ `{}` failed", name)
+            } else {
+                format!("✅ `{}` finished", name)
+            };
+
+            if output.trim().is_empty() {
+                return heading;
+            }
+
+            let mut shown: String = output.chars().take(MAX_TOOL_OUTPUT_CHARS).collect();
+            if shown.len() < output.len() {
+                shown.push('…');
+            }
+            format!("{}\n```\n{}\n```", heading, shown.trim_end())
+        }
+        OpenClawActivity::Channel { channel, text } => {
+            format!("📡 **{}**: {}", channel, text)
+        }
+    }
+}
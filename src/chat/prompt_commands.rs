@@ -0,0 +1,315 @@
+use makepad_widgets::*;
+use std::collections::HashMap;
+
+use crate::data::mcp_context::McpPrompt;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    // Suggestion shown when typing `/` in the prompt.
+    pub PromptCommandItem = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 2
+        padding: {left: 8, right: 8, top: 6, bottom: 6}
+        cursor: Hand
+
+        name = <Label> {
+            draw_text: {
+                text_style: <BOLD_FONT>{font_size: 10},
+                color: #344054
+            }
+        }
+        description = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Ellipsis
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #667085
+            }
+        }
+    }
+
+    ArgumentField = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 5
+
+        label = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #344054
+            }
+        }
+
+        value = <MolyTextInput> {
+            width: Fill, height: Fit
+            padding: {top: 8, bottom: 8, left: 10, right: 10}
+            draw_bg: {
+                color: #fff
+                border_size: 1.0
+                border_color_1: #D0D5DD
+                border_radius: 2.0
+            }
+            draw_text: {
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #000
+            }
+        }
+    }
+
+    PromptArgumentFields = {{PromptArgumentFields}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 10
+
+        template: <ArgumentField> {}
+    }
+
+    // Asks for the arguments of a prompt of an MCP server.
+    pub PromptArgumentsForm = {{PromptArgumentsForm}} <RoundedView> {
+        visible: false
+        width: Fill, height: Fit
+        flow: Down, spacing: 10
+        padding: 15
+        show_bg: true
+        draw_bg: {
+            color: #fff
+            border_radius: 5.0
+            border_size: 1.0
+            border_color_1: #EAECF0
+        }
+
+        title = <Label> {
+            draw_text: {
+                text_style: <BOLD_FONT>{font_size: 11},
+                color: #000
+            }
+        }
+
+        description = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #667085
+            }
+        }
+
+        fields = <PromptArgumentFields> {}
+
+        error = <Label> {
+            visible: false
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #B42318
+            }
+        }
+
+        <View> {
+            width: Fill, height: Fit
+            align: {x: 1.0}
+            spacing: 10
+
+            cancel_button = <MolyButton> {
+                width: Fit, height: 30
+                padding: {left: 14, right: 14, top: 0, bottom: 0}
+                text: "Cancel"
+                draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+                draw_text: {
+                    text_style: <REGULAR_FONT>{font_size: 9},
+                    color: #344054
+                }
+            }
+
+            submit_button = <MolyButton> {
+                width: Fit, height: 30
+                padding: {left: 14, right: 14, top: 0, bottom: 0}
+                text: "Insert"
+                draw_bg: { color: (CTA_BUTTON_COLOR), border_size: 0 }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum PromptArgumentsFormAction {
+    None,
+    Submitted(McpPrompt, HashMap<String, String>),
+    Cancelled,
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct PromptArgumentFields {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for PromptArgumentFields {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct PromptArgumentsForm {
+    #[deref]
+    view: View,
+
+    #[rust]
+    prompt: Option<McpPrompt>,
+}
+
+impl Widget for PromptArgumentsForm {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for PromptArgumentsForm {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if self.button(ids!(cancel_button)).clicked(actions) {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                PromptArgumentsFormAction::Cancelled,
+            );
+        }
+
+        if self.button(ids!(submit_button)).clicked(actions) {
+            let Some(prompt) = self.prompt.clone() else {
+                return;
+            };
+
+            let arguments = self.arguments(&prompt);
+            let missing = prompt
+                .arguments
+                .iter()
+                .filter(|argument| argument.required && !arguments.contains_key(&argument.name))
+                .map(|argument| argument.name.as_str())
+                .collect::<Vec<_>>();
+
+            let error = self.label(ids!(error));
+            if !missing.is_empty() {
+                error.set_text(cx, &format!("Required: {}", missing.join(", ")));
+                error.set_visible(cx, true);
+                self.redraw(cx);
+                return;
+            }
+
+            error.set_visible(cx, false);
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                PromptArgumentsFormAction::Submitted(prompt, arguments),
+            );
+        }
+    }
+}
+
+impl PromptArgumentsForm {
+    /// The non-empty values of the fields, by argument name.
+    fn arguments(&self, prompt: &McpPrompt) -> HashMap<String, String> {
+        let fields = self.widget(ids!(fields));
+        let Some(fields) = fields.borrow::<PromptArgumentFields>() else {
+            return HashMap::new();
+        };
+
+        prompt
+            .arguments
+            .iter()
+            .enumerate()
+            .filter_map(|(index, argument)| {
+                let item = fields.items.get(&LiveId(index as u64))?;
+                let value = item.text_input(ids!(value)).text();
+                (!value.is_empty()).then(|| (argument.name.clone(), value))
+            })
+            .collect()
+    }
+}
+
+impl PromptArgumentsFormRef {
+    /// Shows a field for each argument of the prompt.
+    pub fn set_prompt(&self, cx: &mut Cx, prompt: McpPrompt) {
+        let Some(mut form) = self.borrow_mut() else {
+            return;
+        };
+
+        form.label(ids!(title))
+            .set_text(cx, &format!("/{}", prompt.name));
+        form.label(ids!(description))
+            .set_text(cx, &prompt.description);
+        form.label(ids!(error)).set_visible(cx, false);
+
+        if let Some(mut fields) = form
+            .widget(ids!(fields))
+            .borrow_mut::<PromptArgumentFields>()
+        {
+            fields.items.clear();
+            for (index, argument) in prompt.arguments.iter().enumerate() {
+                let item = WidgetRef::new_from_ptr(cx, fields.template);
+                let mut label = argument.name.clone();
+                if argument.required {
+                    label.push_str(" *");
+                }
+                if !argument.description.is_empty() {
+                    label = format!("{} - {}", label, argument.description);
+                }
+                item.apply_over(cx, live! { label = { text: (label) } });
+                fields.items.insert(LiveId(index as u64), item);
+            }
+        }
+
+        form.prompt = Some(prompt);
+        form.redraw(cx);
+    }
+
+    pub fn submitted(&self, actions: &Actions) -> Option<(McpPrompt, HashMap<String, String>)> {
+        let item = actions.find_widget_action(self.widget_uid())?;
+        match item.cast() {
+            PromptArgumentsFormAction::Submitted(prompt, arguments) => Some((prompt, arguments)),
+            _ => None,
+        }
+    }
+
+    pub fn cancelled(&self, actions: &Actions) -> bool {
+        actions
+            .find_widget_action(self.widget_uid())
+            .is_some_and(|item| matches!(item.cast(), PromptArgumentsFormAction::Cancelled))
+    }
+}
@@ -0,0 +1,171 @@
+use makepad_widgets::*;
+
+use crate::data::{
+    chats::chat::ChatId,
+    reasoning::{ReasoningEffort, ReasoningParams},
+    store::Store,
+};
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    EffortDropDown = <DropDownFlat> {
+        width: Fit
+        draw_text: {
+            text_style: <REGULAR_FONT>{font_size: 10}
+            fn get_color(self) -> vec4 {
+                return #000
+            }
+        }
+
+        popup_menu: {
+            width: 120, height: Fit,
+            flow: Down
+            padding: <THEME_MSPACE_1> {}
+
+            menu_item: <PopupMenuItem> {
+                width: Fill, height: Fit,
+                align: { y: 0.5 }
+                padding: {left: 15, right: 15, top: 8, bottom: 8}
+
+                draw_text: {
+                    fn get_color(self) -> vec4 {
+                        return mix(#3, #x0, self.hover)
+                    }
+                }
+
+                draw_bg: {
+                    instance color: #f
+                    instance color_active: #f2
+                }
+            }
+
+            draw_bg: {
+                instance color: #f9
+                border_size: 1.0
+            }
+        }
+    }
+
+    ControlLabel = <Label> {
+        draw_text: {
+            text_style: <REGULAR_FONT>{font_size: 10}
+            color: #555
+        }
+    }
+
+    // Effort and thinking budget of the reasoning of the current chat.
+    pub ReasoningControls = {{ReasoningControls}} {
+        width: Fill, height: Fit
+        flow: RightWrap, spacing: 8
+        align: {y: 0.5}
+
+        <ControlLabel> { text: "Reasoning effort" }
+        effort = <EffortDropDown> {
+            labels: ["Default", "Low", "Medium", "High"]
+        }
+
+        <ControlLabel> { text: "Max thinking tokens" }
+        max_thinking_tokens = <MolyTextInput> {
+            width: 90, height: Fit
+            empty_text: "Default"
+            draw_text: {
+                text_style: <REGULAR_FONT>{font_size: 10},
+            }
+        }
+    }
+}
+
+/// Options of the effort drop down, in order.
+const EFFORTS: [Option<ReasoningEffort>; 4] = [
+    None,
+    Some(ReasoningEffort::Low),
+    Some(ReasoningEffort::Medium),
+    Some(ReasoningEffort::High),
+];
+
+fn parse_max_thinking_tokens(text: &str) -> Option<u32> {
+    text.trim().parse().ok().filter(|tokens| *tokens > 0)
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct ReasoningControls {
+    #[deref]
+    view: View,
+
+    #[rust]
+    chat_id: Option<ChatId>,
+}
+
+impl Widget for ReasoningControls {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        let store = scope.data.get::<Store>().unwrap();
+
+        if let Some(chat) = store.chats.get_current_chat() {
+            let chat = chat.borrow();
+            let params = &chat.inferences_params.reasoning;
+            self.set_params(cx, params, self.chat_id != Some(chat.id));
+            self.chat_id = Some(chat.id);
+        }
+
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for ReasoningControls {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        let store = scope.data.get_mut::<Store>().unwrap();
+        let Some(chat) = store.chats.get_current_chat() else {
+            return;
+        };
+        let mut chat = chat.borrow_mut();
+
+        let mut changed = false;
+        if let Some(index) = self.drop_down(ids!(effort)).selected(actions) {
+            chat.inferences_params.reasoning.effort = EFFORTS[index];
+            changed = true;
+        }
+        if let Some(text) = self.text_input(ids!(max_thinking_tokens)).changed(actions) {
+            chat.inferences_params.reasoning.max_thinking_tokens = parse_max_thinking_tokens(&text);
+            changed = true;
+        }
+
+        if changed {
+            chat.save_and_forget();
+            // Other controls and the header show the same params.
+            cx.redraw_all();
+        }
+    }
+}
+
+impl ReasoningControls {
+    /// Shows the params. The budget input is only overwritten when switching chats
+    /// or when it disagrees with them, as rewriting it would fight the user while
+    /// typing.
+    fn set_params(&mut self, cx: &mut Cx, params: &ReasoningParams, chat_switched: bool) {
+        let effort = EFFORTS
+            .iter()
+            .position(|effort| *effort == params.effort)
+            .unwrap_or(0);
+        self.drop_down(ids!(effort)).set_selected_item(cx, effort);
+
+        let input = self.text_input(ids!(max_thinking_tokens));
+        if chat_switched || parse_max_thinking_tokens(&input.text()) != params.max_thinking_tokens {
+            let text = params
+                .max_thinking_tokens
+                .map(|tokens| tokens.to_string())
+                .unwrap_or_default();
+            input.set_text(cx, &text);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use makepad_widgets::*;
+
+use crate::data::mcp_context::{self, McpResource};
+use crate::data::mcp_status::{self, McpServerState};
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    ResourceEntry = <View> {
+        width: Fill, height: Fit
+        padding: {top: 4, bottom: 4}
+        spacing: 10
+        align: {x: 0.0, y: 0.5}
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Down, spacing: 2
+
+            name = <Label> {
+                width: Fill
+                draw_text: {
+                    text_style: <BOLD_FONT>{font_size: 10},
+                    color: #344054
+                }
+            }
+            details = <Label> {
+                width: Fill
+                draw_text: {
+                    wrap: Ellipsis
+                    text_style: <REGULAR_FONT>{font_size: 9},
+                    color: #667085
+                }
+            }
+        }
+
+        attach_button = <MolyButton> {
+            width: Fit, height: 24
+            padding: {left: 10, right: 10, top: 0, bottom: 0}
+            text: "Attach"
+            draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+            draw_text: {
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #344054
+            }
+        }
+    }
+
+    // Resources of the MCP servers.
+    ResourcePickerList = {{ResourcePickerList}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 2
+
+        template: <ResourceEntry> {}
+    }
+
+    // Attaches resources of the MCP servers to the message being written.
+    pub ResourcePicker = {{ResourcePicker}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 6
+
+        toggle_button = <MolyButton> {
+            width: Fit, height: 26
+            padding: {left: 10, right: 10, top: 0, bottom: 0}
+            draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+            draw_text: {
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #344054
+            }
+        }
+
+        options = <RoundedView> {
+            visible: false
+            width: Fill, height: Fit
+            flow: Down, spacing: 4
+            padding: 10
+            draw_bg: {
+                color: #fff
+                border_radius: 5.0
+                border_size: 1.0
+                border_color_1: #EAECF0
+            }
+
+            status = <Label> {
+                width: Fill
+                draw_text: {
+                    text_style: <REGULAR_FONT>{font_size: 9},
+                    color: #667085
+                }
+            }
+
+            <ScrollYView> {
+                width: Fill, height: Fit { max: 240 }
+                list = <ResourcePickerList> {}
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ResourcePickerAction {
+    None,
+    /// The user wants the resource attached to the prompt.
+    Attach(McpResource),
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct ResourcePickerList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    #[rust]
+    resources: Vec<McpResource>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for ResourcePickerList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl ResourcePickerList {
+    fn set_resources(&mut self, cx: &mut Cx, resources: Vec<McpResource>) {
+        if resources == self.resources {
+            return;
+        }
+
+        self.items.clear();
+        for (index, resource) in resources.iter().enumerate() {
+            let item = WidgetRef::new_from_ptr(cx, self.template);
+            let name = resource.name.clone();
+            let details = if resource.description.is_empty() {
+                format!("{} · {}", resource.server, resource.uri)
+            } else {
+                format!("{} · {}", resource.server, resource.description)
+            };
+            item.apply_over(
+                cx,
+                live! {
+                    name = { text: (name) }
+                    details = { text: (details) }
+                },
+            );
+            self.items.insert(LiveId(index as u64), item);
+        }
+        self.resources = resources;
+    }
+
+    /// The resource whose attach button was clicked, if any.
+    fn attached(&self, actions: &Actions) -> Option<McpResource> {
+        self.resources
+            .iter()
+            .enumerate()
+            .find(|(index, _)| {
+                self.items
+                    .get(&LiveId(*index as u64))
+                    .is_some_and(|item| item.button(ids!(attach_button)).clicked(actions))
+            })
+            .map(|(_, resource)| resource.clone())
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct ResourcePicker {
+    #[deref]
+    view: View,
+}
+
+impl Widget for ResourcePicker {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        // Hidden while there are no servers to browse.
+        let connected = mcp_status::statuses()
+            .iter()
+            .any(|(_, status)| status.state == McpServerState::Connected);
+        if !connected {
+            return DrawStep::done();
+        }
+
+        let resources = mcp_context::resources();
+        let loading = mcp_context::is_loading();
+
+        let text = if resources.is_empty() {
+            "Resources".to_string()
+        } else {
+            format!("Resources ({})", resources.len())
+        };
+        self.button(ids!(toggle_button)).set_text(cx, &text);
+
+        let status = if loading {
+            "Loading resources..."
+        } else if resources.is_empty() {
+            "The servers offer no resources. Only those reached over HTTP or SSE are listed."
+        } else {
+            ""
+        };
+        let status_label = self.label(ids!(status));
+        status_label.set_visible(cx, !status.is_empty());
+        status_label.set_text(cx, status);
+
+        if let Some(mut list) = self.widget(ids!(list)).borrow_mut::<ResourcePickerList>() {
+            list.set_resources(cx, resources);
+        }
+
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for ResourcePicker {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if self.button(ids!(toggle_button)).clicked(actions) {
+            let options = self.view(ids!(options));
+            let visible = !options.visible();
+            options.set_visible(cx, visible);
+            if visible {
+                mcp_context::load_catalog();
+            }
+            self.redraw(cx);
+        }
+
+        let attached = self
+            .widget(ids!(list))
+            .borrow::<ResourcePickerList>()
+            .and_then(|list| list.attached(actions));
+        if let Some(resource) = attached {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                ResourcePickerAction::Attach(resource),
+            );
+        }
+    }
+}
+
+impl ResourcePickerRef {
+    /// The resource to attach, if one was picked.
+    pub fn attached(&self, actions: &Actions) -> Option<McpResource> {
+        let item = actions.find_widget_action(self.widget_uid())?;
+        match item.cast() {
+            ResourcePickerAction::Attach(resource) => Some(resource),
+            ResourcePickerAction::None => None,
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use makepad_widgets::*;
+use moly_kit::widgets::moly_modal::MolyModalWidgetExt;
+
+use crate::data::mcp_status::{self, McpServerState};
+use crate::data::store::Store;
+use crate::data::tool_selection::ToolSelection;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+    use moly_kit::widgets::moly_modal::MolyModal;
+
+    ToolCheckBox = <CheckBox> {
+        draw_text: {
+            text_style: <REGULAR_FONT>{font_size: 10},
+            fn get_color(self) -> vec4 {
+                return #344054;
+            }
+        }
+    }
+
+    ServerEntry = <View> {
+        width: Fill, height: Fit
+        padding: {top: 4}
+        check = <ToolCheckBox> {
+            draw_text: { text_style: <BOLD_FONT>{font_size: 10} }
+        }
+    }
+
+    ToolEntry = <View> {
+        width: Fill, height: Fit
+        padding: {left: 20}
+        check = <ToolCheckBox> {}
+    }
+
+    // Servers of the tool manager and their tools, each with a check box.
+    ToolPickerList = {{ToolPickerList}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 2
+
+        server_template: <ServerEntry> {}
+        tool_template: <ToolEntry> {}
+    }
+
+    // Picks the MCP servers and tools offered to the model in the current chat,
+    // or in all the chats with its assistant.
+    pub ToolPicker = {{ToolPicker}} {
+        width: Fit, height: Fit
+        align: {y: 0.5}
+
+        toggle_button = <MolyButton> {
+            width: Fit, height: 26
+            padding: {left: 10, right: 10, top: 0, bottom: 0}
+            draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+            draw_text: {
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #344054
+            }
+        }
+
+        options_modal = <MolyModal> {
+            align: {x: 0.0, y: 0.0}
+            bg_view: {
+                visible: false
+            }
+            content: {
+                options = <RoundedView> {
+                    width: 320, height: Fit
+                    flow: Down, spacing: 4
+                    padding: 10
+                    draw_bg: {
+                        color: #fff
+                        border_radius: 5.0
+                        border_size: 1.0
+                        border_color_1: #EAECF0
+                    }
+
+                    for_assistant = <ToolCheckBox> {
+                        text: "Same tools in all chats with this assistant"
+                    }
+                    all_tools = <ToolCheckBox> { text: "All tools" }
+
+                    <ScrollYView> {
+                        width: Fill, height: Fit { max: 300 }
+                        list = <ToolPickerList> {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Servers with tools to pick from, with the names of their tools.
+fn available_tools() -> Vec<(String, Vec<String>)> {
+    mcp_status::statuses()
+        .into_iter()
+        .filter(|(_, status)| status.state == McpServerState::Connected && !status.tools.is_empty())
+        .map(|(id, status)| {
+            let tools = status.tools.into_iter().map(|tool| tool.name).collect();
+            (id, tools)
+        })
+        .collect()
+}
+
+/// Changes the tool selection of the current chat, saving it along with the chat,
+/// or along with its assistant if the chat uses the tools of its assistant.
+fn update_selection(cx: &mut Cx, scope: &mut Scope, f: impl FnOnce(&mut ToolSelection)) {
+    let Some(store) = scope.data.get_mut::<Store>() else {
+        return;
+    };
+    let Some(chat) = store.chats.get_current_chat() else {
+        return;
+    };
+
+    let (chat_id, bot_id) = {
+        let chat = chat.borrow();
+        let shared = chat.tool_selection.is_none();
+        (chat.id, chat.associated_bot.clone().filter(|_| shared))
+    };
+    let mut selection = store.chat_tool_selection(chat_id);
+    f(&mut selection);
+
+    match bot_id {
+        Some(bot_id) => store.preferences.set_bot_tool_selection(&bot_id, selection),
+        None => {
+            let mut chat = chat.borrow_mut();
+            chat.tool_selection = Some(selection);
+            chat.save_and_forget();
+        }
+    }
+    // Pickers of other chat views show the same selection.
+    cx.redraw_all();
+}
+
+/// Makes the current chat use the tools of its assistant, or its own copy of them.
+fn share_selection(cx: &mut Cx, scope: &mut Scope, shared: bool) {
+    let Some(store) = scope.data.get_mut::<Store>() else {
+        return;
+    };
+    let Some(chat_id) = store.chats.get_current_chat().map(|chat| chat.borrow().id) else {
+        return;
+    };
+
+    let selection = (!shared).then(|| store.chat_tool_selection(chat_id));
+    if let Some(chat) = store.chats.get_chat_by_id(chat_id) {
+        let mut chat = chat.borrow_mut();
+        chat.tool_selection = selection;
+        chat.save_and_forget();
+    }
+    cx.redraw_all();
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Entry {
+    Server(String),
+    Tool(String, String),
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct ToolPickerList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    server_template: Option<LivePtr>,
+
+    #[live]
+    tool_template: Option<LivePtr>,
+
+    #[rust]
+    available: Vec<(String, Vec<String>)>,
+
+    #[rust]
+    entries: Vec<Entry>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for ToolPickerList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        let Event::Actions(actions) = event else {
+            return;
+        };
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let Some(item) = self.items.get(&LiveId(index as u64)) else {
+                continue;
+            };
+            let Some(selected) = item.check_box(ids!(check)).changed(actions) else {
+                continue;
+            };
+
+            let available = &self.available;
+            match entry {
+                Entry::Server(server) => update_selection(cx, scope, |selection| {
+                    selection.set_server(server, selected, available)
+                }),
+                Entry::Tool(server, tool) => update_selection(cx, scope, |selection| {
+                    selection.set_tool(server, tool, selected, available)
+                }),
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl ToolPickerList {
+    /// Shows the available tools, checking the selected ones.
+    fn set_tools(
+        &mut self,
+        cx: &mut Cx,
+        available: Vec<(String, Vec<String>)>,
+        selection: &ToolSelection,
+    ) {
+        let entries = available
+            .iter()
+            .flat_map(|(server, tools)| {
+                std::iter::once(Entry::Server(server.clone())).chain(
+                    tools
+                        .iter()
+                        .map(|tool| Entry::Tool(server.clone(), tool.clone())),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if entries != self.entries {
+            self.items.clear();
+            for (index, entry) in entries.iter().enumerate() {
+                let (template, text) = match entry {
+                    Entry::Server(server) => (self.server_template, server.clone()),
+                    Entry::Tool(_, tool) => (self.tool_template, tool.clone()),
+                };
+                let item = WidgetRef::new_from_ptr(cx, template);
+                item.apply_over(cx, live! { check = { text: (text) } });
+                self.items.insert(LiveId(index as u64), item);
+            }
+            self.entries = entries;
+        }
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let Some(item) = self.items.get(&LiveId(index as u64)) else {
+                continue;
+            };
+            let selected = match entry {
+                Entry::Server(server) => selection.server_selected(server),
+                Entry::Tool(server, tool) => selection.tool_selected(server, tool),
+            };
+
+            // Avoid triggering the animator when nothing changed.
+            let check = item.check_box(ids!(check));
+            if check.active(cx) != selected {
+                check.set_active(cx, selected);
+            }
+        }
+
+        self.available = available;
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct ToolPicker {
+    #[deref]
+    view: View,
+}
+
+impl Widget for ToolPicker {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        // Drawn inside the prompt input, which passes the scope of the chat view.
+        let Some(store) = scope.data.get::<Store>() else {
+            return DrawStep::done();
+        };
+        let available = available_tools();
+
+        // Hidden while there are no tools to pick from.
+        let Some((chat_id, has_bot, shared)) = store
+            .chats
+            .get_current_chat()
+            .filter(|_| !available.is_empty())
+            .map(|chat| {
+                let chat = chat.borrow();
+                (
+                    chat.id,
+                    chat.associated_bot.is_some(),
+                    chat.tool_selection.is_none(),
+                )
+            })
+        else {
+            return DrawStep::done();
+        };
+        let selection = store.chat_tool_selection(chat_id);
+
+        let total = available
+            .iter()
+            .map(|(_, tools)| tools.len())
+            .sum::<usize>();
+        let selected = available
+            .iter()
+            .flat_map(|(server, tools)| tools.iter().map(move |tool| (server, tool)))
+            .filter(|(server, tool)| selection.tool_selected(server, tool))
+            .count();
+        let text = if selected == total {
+            format!("Tools: all ({})", total)
+        } else {
+            format!("Tools: {} of {}", selected, total)
+        };
+        self.button(ids!(toggle_button)).set_text(cx, &text);
+
+        let for_assistant = self.check_box(ids!(for_assistant));
+        for_assistant.set_visible(cx, has_bot);
+        if for_assistant.active(cx) != shared {
+            for_assistant.set_active(cx, shared);
+        }
+
+        let all_tools = self.check_box(ids!(all_tools));
+        if all_tools.active(cx) == selection.restricted {
+            all_tools.set_active(cx, !selection.restricted);
+        }
+
+        if let Some(mut list) = self.widget(ids!(list)).borrow_mut::<ToolPickerList>() {
+            list.set_tools(cx, available, &selection);
+        }
+
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for ToolPicker {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        let toggle_button = self.button(ids!(toggle_button));
+        if toggle_button.clicked(actions) {
+            let rect = toggle_button.area().rect(cx);
+            self.moly_modal(ids!(options_modal))
+                .open_as_popup(cx, rect.pos + dvec2(0.0, rect.size.y));
+        }
+
+        if let Some(shared) = self.check_box(ids!(for_assistant)).changed(actions) {
+            share_selection(cx, scope, shared);
+        }
+
+        if let Some(all) = self.check_box(ids!(all_tools)).changed(actions) {
+            update_selection(cx, scope, |selection| {
+                if all {
+                    selection.select_all();
+                } else {
+                    // Start from nothing, so tools can be picked one by one.
+                    *selection = ToolSelection {
+                        restricted: true,
+                        ..Default::default()
+                    };
+                }
+            });
+        }
+    }
+}
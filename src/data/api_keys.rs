@@ -0,0 +1,380 @@
+//! Multiple API keys per provider, with a strategy to choose the key of each request.
+//!
+//! Keys are configured in [`ApiKeys`] (persisted with the provider), while usage is
+//! tracked at runtime by a [`KeyRotation`] shared by the clients of the provider and
+//! the UI.
+
+use async_stream::stream;
+use chrono::{DateTime, Local};
+use futures::StreamExt;
+use moly_kit::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use super::failover_client::is_rate_limited;
+use super::providers::ProviderId;
+
+/// How the key used for each request is chosen.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum KeyStrategy {
+    /// Always use the selected key.
+    #[default]
+    Manual,
+    /// Use the next key on each request.
+    RoundRobin,
+    /// Keep using a key until it's rate limited, then move to the next one.
+    FailoverOnRateLimit,
+}
+
+impl KeyStrategy {
+    pub const ALL: [KeyStrategy; 3] = [
+        KeyStrategy::Manual,
+        KeyStrategy::RoundRobin,
+        KeyStrategy::FailoverOnRateLimit,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            KeyStrategy::Manual => "Manual",
+            KeyStrategy::RoundRobin => "Round robin",
+            KeyStrategy::FailoverOnRateLimit => "Failover on rate limit",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NamedApiKey {
+    pub name: String,
+    pub key: String,
+}
+
+/// The API keys of a provider.
+///
+/// When empty, the provider's single `api_key` is used as is.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ApiKeys {
+    #[serde(default)]
+    pub keys: Vec<NamedApiKey>,
+    #[serde(default)]
+    pub strategy: KeyStrategy,
+    /// Index of the key used by the manual strategy, and the first one tried by
+    /// the failover strategy.
+    #[serde(default)]
+    pub selected: usize,
+}
+
+impl ApiKeys {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Whether nothing was configured, not even a strategy, so there's nothing to save.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn selected_key(&self) -> Option<&NamedApiKey> {
+        self.keys.get(self.selected).or(self.keys.first())
+    }
+}
+
+/// Runtime usage of an API key.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyUsage {
+    pub requests: u64,
+    pub last_used: Option<DateTime<Local>>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct RotationState {
+    names: Vec<String>,
+    strategy: KeyStrategy,
+    selected: usize,
+    /// Key used by the manual and failover strategies, or the next one for round robin.
+    current: usize,
+    /// Usage by key name, so it survives keys being added or removed.
+    usage: HashMap<String, KeyUsage>,
+}
+
+/// Shared state deciding which key each request uses, and tracking their usage.
+#[derive(Clone, Default)]
+pub struct KeyRotation(Arc<Mutex<RotationState>>);
+
+static KEY_ROTATIONS: LazyLock<Mutex<HashMap<ProviderId, KeyRotation>>> =
+    LazyLock::new(Default::default);
+
+impl KeyRotation {
+    /// The rotation of the given provider, shared by its clients and the UI.
+    pub fn for_provider(provider_id: &ProviderId) -> Self {
+        KEY_ROTATIONS
+            .lock()
+            .unwrap()
+            .entry(provider_id.clone())
+            .or_default()
+            .clone()
+    }
+
+    /// Updates the rotation to the keys of the provider.
+    ///
+    /// The position of the rotation is kept unless the configuration changed, as
+    /// clients are recreated often.
+    pub fn configure(&self, api_keys: &ApiKeys) {
+        let names: Vec<String> = api_keys.keys.iter().map(|k| k.name.clone()).collect();
+        let mut state = self.0.lock().unwrap();
+        if state.names == names
+            && state.strategy == api_keys.strategy
+            && state.selected == api_keys.selected
+        {
+            return;
+        }
+
+        state.usage.retain(|name, _| names.contains(name));
+        state.current = api_keys.selected.min(names.len().saturating_sub(1));
+        state.selected = api_keys.selected;
+        state.strategy = api_keys.strategy;
+        state.names = names;
+    }
+
+    pub fn usage(&self, key_name: &str) -> KeyUsage {
+        self.0
+            .lock()
+            .unwrap()
+            .usage
+            .get(key_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Index of the key the next request will start with.
+    fn current(&self) -> usize {
+        let state = self.0.lock().unwrap();
+        state.current % state.names.len().max(1)
+    }
+
+    /// Indexes of the keys to try for a request, in order.
+    fn candidates(&self) -> Vec<usize> {
+        let mut state = self.0.lock().unwrap();
+        let count = state.names.len();
+        if count == 0 {
+            return vec![];
+        }
+
+        let current = state.current % count;
+        match state.strategy {
+            KeyStrategy::Manual => vec![current],
+            KeyStrategy::RoundRobin => {
+                state.current = (current + 1) % count;
+                vec![current]
+            }
+            KeyStrategy::FailoverOnRateLimit => (0..count).map(|i| (current + i) % count).collect(),
+        }
+    }
+
+    fn record(&self, index: usize, error: Option<String>) {
+        let mut state = self.0.lock().unwrap();
+        let Some(name) = state.names.get(index).cloned() else {
+            return;
+        };
+
+        let usage = state.usage.entry(name).or_default();
+        usage.requests += 1;
+        usage.last_used = Some(Local::now());
+        if error.is_some() {
+            usage.last_error = error;
+        }
+    }
+
+    /// Makes the failover strategy stick to a key that worked.
+    fn settle_on(&self, index: usize) {
+        let mut state = self.0.lock().unwrap();
+        if state.strategy == KeyStrategy::FailoverOnRateLimit {
+            state.current = index;
+        }
+    }
+}
+
+/// Wraps one client per API key, choosing the client of each request according to
+/// the provider's [`KeyStrategy`].
+pub struct KeyRotationClient {
+    clients: Vec<Box<dyn BotClient>>,
+    rotation: KeyRotation,
+}
+
+impl Clone for KeyRotationClient {
+    fn clone(&self) -> Self {
+        Self {
+            clients: self.clients.iter().map(|c| c.clone_box()).collect(),
+            rotation: self.rotation.clone(),
+        }
+    }
+}
+
+impl KeyRotationClient {
+    /// Clients must be given in the same order as the keys they use.
+    pub fn new(clients: Vec<Box<dyn BotClient>>, rotation: KeyRotation) -> Self {
+        Self { clients, rotation }
+    }
+}
+
+impl BotClient for KeyRotationClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        match self.clients.get_mut(self.rotation.current()) {
+            Some(client) => client.bots(),
+            None => Box::pin(async {
+                ClientError::new(
+                    ClientErrorKind::Unknown,
+                    "No API keys configured".to_string(),
+                )
+                .into()
+            }),
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let candidates: Vec<(usize, Box<dyn BotClient>)> = self
+            .rotation
+            .candidates()
+            .into_iter()
+            .filter_map(|index| Some((index, self.clients.get(index)?.clone_box())))
+            .collect();
+
+        let rotation = self.rotation.clone();
+        let bot_id = bot_id.clone();
+        let messages = messages.to_vec();
+        let tools = tools.to_vec();
+
+        let stream = stream! {
+            let mut last_errors = vec![ClientError::new(
+                ClientErrorKind::Unknown,
+                "No API keys configured".to_string(),
+            )];
+
+            for (index, mut client) in candidates {
+                let mut inner = client.send(&bot_id, &messages, &tools);
+                let mut first_error = None;
+                let mut rate_limited_errors = None;
+
+                while let Some(result) = inner.next().await {
+                    let (content, errors) = result.into_value_and_errors();
+
+                    // Move to the next key if this one is rate limited before answering.
+                    if first_error.is_none()
+                        && content.is_none()
+                        && !errors.is_empty()
+                        && errors.iter().all(is_rate_limited)
+                    {
+                        rate_limited_errors = Some(errors);
+                        break;
+                    }
+
+                    if let Some(error) = errors.first() {
+                        first_error.get_or_insert_with(|| error.to_string());
+                    }
+
+                    if let Some(content) = content {
+                        yield ClientResult::new_ok(content);
+                    }
+                    if !errors.is_empty() {
+                        yield ClientResult::new_err(errors);
+                    }
+                }
+
+                match rate_limited_errors {
+                    Some(errors) => {
+                        log::warn!("API key #{} is rate limited", index + 1);
+                        rotation.record(index, Some(errors[0].to_string()));
+                        last_errors = errors;
+                    }
+                    None => {
+                        rotation.record(index, first_error);
+                        rotation.settle_on(index);
+                        return;
+                    }
+                }
+            }
+
+            yield ClientResult::new_err(last_errors);
+        };
+
+        Box::pin(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_keys(count: usize, strategy: KeyStrategy, selected: usize) -> ApiKeys {
+        ApiKeys {
+            keys: (0..count)
+                .map(|i| NamedApiKey {
+                    name: format!("Key {}", i + 1),
+                    key: format!("sk-{}", i),
+                })
+                .collect(),
+            strategy,
+            selected,
+        }
+    }
+
+    #[test]
+    fn test_round_robin_advances() {
+        let rotation = KeyRotation::default();
+        rotation.configure(&api_keys(3, KeyStrategy::RoundRobin, 1));
+
+        assert_eq!(rotation.candidates(), vec![1]);
+        assert_eq!(rotation.candidates(), vec![2]);
+        assert_eq!(rotation.candidates(), vec![0]);
+
+        // Recreating clients with the same configuration keeps the position
+        rotation.configure(&api_keys(3, KeyStrategy::RoundRobin, 1));
+        assert_eq!(rotation.candidates(), vec![1]);
+    }
+
+    #[test]
+    fn test_failover_sticks_to_working_key() {
+        let rotation = KeyRotation::default();
+        rotation.configure(&api_keys(3, KeyStrategy::FailoverOnRateLimit, 0));
+
+        assert_eq!(rotation.candidates(), vec![0, 1, 2]);
+        rotation.settle_on(2);
+        assert_eq!(rotation.candidates(), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn test_usage_is_tracked_by_name() {
+        let rotation = KeyRotation::default();
+        rotation.configure(&api_keys(2, KeyStrategy::Manual, 1));
+
+        assert_eq!(rotation.candidates(), vec![1]);
+        rotation.record(1, Some("429 Too Many Requests".to_string()));
+        rotation.record(1, None);
+
+        let usage = rotation.usage("Key 2");
+        assert_eq!(usage.requests, 2);
+        assert_eq!(usage.last_error.as_deref(), Some("429 Too Many Requests"));
+        assert_eq!(rotation.usage("Key 1").requests, 0);
+    }
+
+    #[test]
+    fn test_strategy_is_kept_without_keys() {
+        let keys = api_keys(0, KeyStrategy::RoundRobin, 0);
+        assert!(keys.is_empty());
+        assert!(!keys.is_default());
+
+        let json = serde_json::to_string(&keys).unwrap();
+        let restored: ApiKeys = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.strategy, KeyStrategy::RoundRobin);
+    }
+}
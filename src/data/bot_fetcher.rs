@@ -1,9 +1,13 @@
 use makepad_widgets::Cx;
 use moly_kit::aitk::utils::asynchronous::spawn;
 use moly_kit::prelude::*;
+use std::collections::HashMap;
 
 use crate::data::providers::ProviderId;
 
+use super::model_capabilities::{ModelCapabilities, fetch_provider_models};
+use super::model_rules::ModelRules;
+use super::mofa_client::{MofaClient, agent_bot};
 use super::providers::{Provider, ProviderBot, ProviderFetchModelsResult, ProviderType};
 
 /// Fetches models for a provider using the appropriate MolyKit client
@@ -11,9 +15,14 @@ pub fn fetch_models_for_provider(provider: &Provider) {
     let provider_id = provider.id.clone();
     let url = provider.url.clone();
     let api_key = provider.api_key.clone();
+    let rules = provider.model_rules();
 
     match provider.provider_type {
-        ProviderType::OpenAi | ProviderType::MolyServer | ProviderType::MoFa => {
+        ProviderType::OpenAi | ProviderType::MolyServer | ProviderType::OpenAiResponses => {
+            // Only generic OpenAI-compatible providers may expose capabilities
+            // metadata (e.g. OpenRouter) in their models endpoint.
+            let capabilities_endpoint = (provider.provider_type == ProviderType::OpenAi)
+                .then(|| (url.clone(), api_key.clone()));
             fetch_models_with_client(
                 provider_id.clone(),
                 move || {
@@ -23,17 +32,28 @@ pub fn fetch_models_for_provider(provider: &Provider) {
                     }
                     Box::new(client)
                 },
-                move |bot| ProviderBot {
+                move |bot, capabilities| ProviderBot {
                     id: bot.id.clone(),
                     name: bot.name.clone(),
                     description: format!("Model from {}", provider_id),
                     provider_id: provider_id.clone(),
                     enabled: true,
                     is_recommended: false,
+                    is_stale: false,
+                    capabilities,
+                    agent: None,
                 },
-                Some(should_include_bot),
+                rules,
+                capabilities_endpoint,
             );
         }
+        ProviderType::MoFa => {
+            let mut client = MofaClient::new(url);
+            if let Some(key) = api_key {
+                let _ = client.set_key(&key);
+            }
+            fetch_mofa_agents(provider_id, client, rules);
+        }
         ProviderType::OpenAiImage => {
             fetch_models_with_client(
                 provider_id.clone(),
@@ -45,14 +65,18 @@ pub fn fetch_models_for_provider(provider: &Provider) {
                     }
                     Box::new(client)
                 },
-                move |bot| ProviderBot {
+                move |bot, capabilities| ProviderBot {
                     id: bot.id.clone(),
                     name: bot.name.clone(),
                     description: "OpenAI Image Generation Model".to_string(),
                     provider_id: provider_id.clone(),
                     enabled: true,
                     is_recommended: false,
+                    is_stale: false,
+                    capabilities,
+                    agent: None,
                 },
+                rules,
                 None,
             );
         }
@@ -67,14 +91,18 @@ pub fn fetch_models_for_provider(provider: &Provider) {
                     }
                     Box::new(client)
                 },
-                move |bot| ProviderBot {
+                move |bot, capabilities| ProviderBot {
                     id: bot.id.clone(),
                     name: bot.name.clone(),
                     description: "OpenAI Realtime Model".to_string(),
                     provider_id: provider_id.clone(),
                     enabled: true,
                     is_recommended: false,
+                    is_stale: false,
+                    capabilities,
+                    agent: None,
                 },
+                rules,
                 None,
             );
         }
@@ -88,14 +116,18 @@ pub fn fetch_models_for_provider(provider: &Provider) {
                     }
                     Box::new(client)
                 },
-                move |bot| ProviderBot {
+                move |bot, capabilities| ProviderBot {
                     id: bot.id.clone(),
                     name: bot.name.clone(),
                     description: "A search assistant".to_string(),
                     provider_id: provider_id.clone(),
                     enabled: true,
                     is_recommended: false,
+                    is_stale: false,
+                    capabilities,
+                    agent: None,
                 },
+                rules,
                 None,
             );
         }
@@ -109,83 +141,112 @@ pub fn fetch_models_for_provider(provider: &Provider) {
                     }
                     Box::new(client)
                 },
-                move |bot| ProviderBot {
+                move |bot, capabilities| ProviderBot {
                     id: bot.id.clone(),
                     name: bot.name.clone(),
-                    description: "OpenClaw local AI assistant".to_string(),
+                    description: "OpenClaw agent".to_string(),
                     provider_id: provider_id.clone(),
                     enabled: true,
                     is_recommended: false,
+                    is_stale: false,
+                    capabilities,
+                    agent: None,
                 },
+                rules,
                 None,
             );
         }
     }
 }
 
+/// Fetches the agents of a MoFa server, keeping what each of them expects to run
+fn fetch_mofa_agents(provider_id: ProviderId, client: MofaClient, rules: ModelRules) {
+    spawn(async move {
+        match client.agents().await {
+            Ok(agents) => {
+                let agents: Vec<ProviderBot> = agents
+                    .into_iter()
+                    .filter(|agent| rules.allows(&agent.id))
+                    .map(|agent| {
+                        let bot = agent_bot(&agent);
+                        ProviderBot {
+                            id: RouterClient::prefix(&provider_id, &bot.id),
+                            name: bot.name.clone(),
+                            description: agent.description,
+                            provider_id: provider_id.clone(),
+                            enabled: true,
+                            is_recommended: false,
+                            is_stale: false,
+                            capabilities: ModelCapabilities::for_bot(&bot, None),
+                            agent: Some(agent.info),
+                        }
+                    })
+                    .collect();
+
+                Cx::post_action(ProviderFetchModelsResult::Success(provider_id, agents));
+            }
+            Err(error) => {
+                Cx::post_action(ProviderFetchModelsResult::Failure(provider_id, error));
+            }
+        }
+    });
+}
+
 /// Generic function to fetch models using any BotClient implementation
+///
+/// Models not allowed by `rules` are dropped. If a `capabilities_endpoint` (url and
+/// api key) is given, models are listed from it directly along with their
+/// capabilities metadata, otherwise they are listed by the client and their
+/// capabilities are taken from the bundled capabilities table.
 fn fetch_models_with_client<F, M>(
     provider_id: ProviderId,
     client_factory: F,
     map_bot: M,
-    filter: Option<fn(&BotId) -> bool>,
+    rules: ModelRules,
+    capabilities_endpoint: Option<(String, Option<String>)>,
 ) where
     F: FnOnce() -> Box<dyn BotClient> + Send + 'static,
-    M: Fn(Bot) -> ProviderBot + Send + 'static,
+    M: Fn(Bot, ModelCapabilities) -> ProviderBot + Send + 'static,
 {
     spawn(async move {
-        let mut client = client_factory();
+        let listed = match capabilities_endpoint {
+            Some((url, api_key)) => fetch_provider_models(&url, api_key.as_deref()).await,
+            None => match client_factory().bots().await.into_result() {
+                Ok(bots) => Ok((bots, HashMap::new())),
+                Err(errors) => Err(errors.into_iter().next().unwrap_or_else(|| {
+                    ClientError::new(
+                        ClientErrorKind::Unknown,
+                        "An error occurred, but no details were provided".to_string(),
+                    )
+                })),
+            },
+        };
 
-        match client.bots().await.into_result() {
-            Ok(bots) => {
+        match listed {
+            Ok((bots, metadata)) => {
                 let models: Vec<ProviderBot> = bots
                     .into_iter()
-                    .filter(|bot| filter.map_or(true, |f| f(&bot.id)))
-                    .map(|bot| Bot {
-                        // The client Moly interacts with in the `Store` is a `RouterClient`.
-                        // This module is creating specific clients to obtain the bots that will
-                        // end up becoming `ProviderBot`s as expected by Moly.
-                        // So for now, let's ensure here that ids match.
-                        id: RouterClient::prefix(&provider_id, &bot.id),
-                        ..bot
+                    .filter(|bot| rules.allows(bot.id.as_str()))
+                    .map(|bot| {
+                        let capabilities =
+                            ModelCapabilities::for_bot(&bot, metadata.get(bot.id.as_str()));
+                        let bot = Bot {
+                            // The client Moly interacts with in the `Store` is a `RouterClient`.
+                            // This module is creating specific clients to obtain the bots that will
+                            // end up becoming `ProviderBot`s as expected by Moly.
+                            // So for now, let's ensure here that ids match.
+                            id: RouterClient::prefix(&provider_id, &bot.id),
+                            ..bot
+                        };
+                        map_bot(bot, capabilities)
                     })
-                    .map(map_bot)
                     .collect();
 
                 Cx::post_action(ProviderFetchModelsResult::Success(provider_id, models));
             }
-            Err(errors) => {
-                let error = if errors.is_empty() {
-                    ClientError::new(
-                        ClientErrorKind::Unknown,
-                        "An error occurred, but no details were provided".to_string(),
-                    )
-                } else {
-                    errors[0].clone()
-                };
+            Err(error) => {
                 Cx::post_action(ProviderFetchModelsResult::Failure(provider_id, error));
             }
         }
     });
 }
-
-/// Filter out non-chat models for OpenAI-compatible providers
-pub fn should_include_bot(bot_id: &BotId) -> bool {
-    // TODO: This also filters models like [`gpt-5-image`](https://openrouter.ai/openai/gpt-5-image)
-    // (which is just an alias to `gpt-5` with the `image` built-in tool enabled),
-    // and [`gpt-4o-audio`](https://developers.openai.com/api/docs/models/gpt-4o-audio-preview)
-    // (which is a model that works over the completions endpoint).
-
-    let keywords = [
-        "dall-e",
-        "whisper",
-        "tts",
-        "davinci",
-        "audio",
-        "babbage",
-        "moderation",
-        "embedding",
-    ];
-
-    !keywords.iter().any(|k| bot_id.as_str().contains(k))
-}
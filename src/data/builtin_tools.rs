@@ -0,0 +1,593 @@
+//! Tools run by Moly itself: filesystem access, web fetch, date/time and math.
+//!
+//! They are served by an MCP server running in-process on a loopback port, so the
+//! tool manager loads them like any other server, and their calls go through the
+//! same tool policies and approvals. The server only answers under a path that is
+//! random on each launch, and rejects requests from browsers, so other programs
+//! and web pages can't run the tools skipping those approvals.
+
+use axum::Router;
+use axum::extract::{Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use moly_kit::utils::scraping::{extract_readable_text, extract_title, fetch_html};
+use rmcp::model::{
+    CallToolRequestParam, CallToolResult, Content, JsonObject, ListToolsResult,
+    PaginatedRequestParam, ServerCapabilities, ServerInfo, Tool,
+};
+use rmcp::service::RequestContext;
+use rmcp::transport::streamable_http_server::{
+    StreamableHttpService, session::local::LocalSessionManager,
+};
+use rmcp::{ErrorData, RoleServer, ServerHandler};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use super::mcp_servers::{BuiltinTool, BuiltinToolsConfig};
+
+/// Files larger than this are not read.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Levels of nested parentheses, functions and signs a calculation may have.
+const MAX_NESTING: usize = 256;
+
+/// The config is read on each call, so folders apply without reloading the server.
+static CONFIG: LazyLock<Mutex<BuiltinToolsConfig>> = LazyLock::new(Default::default);
+
+/// Url of the server once started.
+static URL: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn set_config(config: BuiltinToolsConfig) {
+    *CONFIG.lock().unwrap() = config;
+}
+
+fn config() -> BuiltinToolsConfig {
+    CONFIG.lock().unwrap().clone()
+}
+
+/// Url of the MCP endpoint of the server, starting it the first time.
+pub fn url() -> Result<String, String> {
+    let mut url = URL.lock().unwrap();
+    if let Some(url) = url.as_ref() {
+        return Ok(url.clone());
+    }
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let address = listener.local_addr().map_err(|e| e.to_string())?;
+
+    let service = StreamableHttpService::new(
+        || Ok(BuiltinServer),
+        LocalSessionManager::default().into(),
+        Default::default(),
+    );
+    let path = format!("/{}/mcp", uuid::Uuid::new_v4().simple());
+    let router = Router::new()
+        .nest_service(&path, service)
+        .layer(middleware::from_fn_with_state(
+            Arc::<str>::from(address.to_string()),
+            check_request,
+        ));
+
+    crate::runtime::spawn(async move {
+        let result = match tokio::net::TcpListener::from_std(listener) {
+            Ok(listener) => axum::serve(listener, router).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            ::log::error!("The built-in tools server stopped: {}", e);
+            *URL.lock().unwrap() = None;
+        }
+    });
+
+    let started = format!("http://{}{}", address, path);
+    *url = Some(started.clone());
+    Ok(started)
+}
+
+/// Rejects requests made by browsers, which set an origin, and the ones for other
+/// hosts, as sent by pages rebinding their domain to loopback.
+async fn check_request(State(host): State<Arc<str>>, request: Request, next: Next) -> Response {
+    let headers = request.headers();
+    let foreign_host = headers
+        .get(header::HOST)
+        .is_none_or(|value| value.as_bytes() != host.as_bytes());
+    if foreign_host || headers.contains_key(header::ORIGIN) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    next.run(request).await
+}
+
+#[derive(Clone)]
+struct BuiltinServer;
+
+impl ServerHandler for BuiltinServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            instructions: Some("Tools built into Moly.".to_string()),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        let config = config();
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: config
+                .enabled_tools()
+                .into_iter()
+                .flat_map(tools_of)
+                .collect(),
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let arguments = request.arguments.unwrap_or_default();
+        let result = call(&config(), &request.name, &arguments).await;
+        Ok(match result {
+            Ok(text) => CallToolResult::success(vec![Content::text(text)]),
+            Err(e) => CallToolResult::error(vec![Content::text(e)]),
+        })
+    }
+}
+
+fn tools_of(tool: BuiltinTool) -> Vec<Tool> {
+    match tool {
+        BuiltinTool::Filesystem => vec![
+            Tool::new(
+                "list_directory",
+                "Lists the files and folders of a directory. Without a path, lists the folders that can be read.",
+                schema(
+                    json!({"path": {"type": "string", "description": "Absolute path of the directory"}}),
+                    &[],
+                ),
+            ),
+            Tool::new(
+                "read_file",
+                "Reads a text file inside the folders that can be read.",
+                schema(
+                    json!({"path": {"type": "string", "description": "Absolute path of the file"}}),
+                    &["path"],
+                ),
+            ),
+        ],
+        BuiltinTool::Fetch => vec![Tool::new(
+            "fetch_url",
+            "Downloads a web page and returns its title and readable text.",
+            schema(
+                json!({"url": {"type": "string", "description": "The http or https url of the page"}}),
+                &["url"],
+            ),
+        )],
+        BuiltinTool::DateTime => vec![Tool::new(
+            "current_datetime",
+            "Returns the current local date, time, weekday and timezone offset.",
+            schema(json!({}), &[]),
+        )],
+        BuiltinTool::Calculator => vec![Tool::new(
+            "calculate",
+            "Evaluates a math expression with + - * / % ^, parentheses, the constants pi and e, and the functions sqrt, abs, exp, ln, log, sin, cos, tan, floor, ceil and round.",
+            schema(
+                json!({"expression": {"type": "string", "description": "The expression, e.g. (2 + 3) * sqrt(16)"}}),
+                &["expression"],
+            ),
+        )],
+    }
+}
+
+fn schema(properties: Value, required: &[&str]) -> Arc<JsonObject> {
+    let schema = json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    });
+    Arc::new(schema.as_object().cloned().unwrap_or_default())
+}
+
+async fn call(
+    config: &BuiltinToolsConfig,
+    name: &str,
+    arguments: &JsonObject,
+) -> Result<String, String> {
+    let tool = BuiltinTool::ALL
+        .into_iter()
+        .find(|tool| tools_of(*tool).iter().any(|t| t.name == name))
+        .ok_or_else(|| format!("Unknown tool '{}'", name))?;
+    if !config.is_enabled(tool) {
+        return Err(format!("The {} tools are disabled", tool.name()));
+    }
+
+    match name {
+        "list_directory" => match argument(arguments, "path") {
+            Ok(path) => list_directory(&config.folders, path),
+            Err(_) => list_folders(&config.folders),
+        },
+        "read_file" => read_file(&config.folders, argument(arguments, "path")?),
+        "fetch_url" => fetch_url(argument(arguments, "url")?).await,
+        "current_datetime" => Ok(current_datetime()),
+        "calculate" => evaluate(argument(arguments, "expression")?).map(|value| value.to_string()),
+        _ => Err(format!("Unknown tool '{}'", name)),
+    }
+}
+
+fn argument<'a>(arguments: &'a JsonObject, name: &str) -> Result<&'a str, String> {
+    arguments
+        .get(name)
+        .and_then(Value::as_str)
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| format!("Missing the '{}' argument", name))
+}
+
+fn list_folders(folders: &[String]) -> Result<String, String> {
+    if folders.is_empty() {
+        return Err("No folders were allowed to be read".to_string());
+    }
+    Ok(folders.join("\n"))
+}
+
+/// The path, resolved, if it's inside one of the allowed folders.
+///
+/// Paths are canonicalized first, so `..` and symlinks can't point outside them.
+fn resolve_path(folders: &[String], path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(path);
+    let path = match folders {
+        [folder] if path.is_relative() => Path::new(folder).join(path),
+        _ => path.to_path_buf(),
+    };
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("Can't open {}: {}", path.display(), e))?;
+
+    let allowed = folders
+        .iter()
+        .filter_map(|folder| Path::new(folder).canonicalize().ok())
+        .any(|folder| path.starts_with(folder));
+
+    if allowed {
+        Ok(path)
+    } else {
+        Err(format!(
+            "{} is outside of the folders allowed to be read",
+            path.display()
+        ))
+    }
+}
+
+fn list_directory(folders: &[String], path: &str) -> Result<String, String> {
+    let path = resolve_path(folders, path)?;
+    let entries = std::fs::read_dir(&path).map_err(|e| e.to_string())?;
+
+    let mut lines = entries
+        .filter_map(Result::ok)
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => format!("{}/", name),
+                Ok(metadata) => format!("{} ({} bytes)", name, metadata.len()),
+                Err(_) => name,
+            }
+        })
+        .collect::<Vec<_>>();
+    lines.sort();
+
+    if lines.is_empty() {
+        return Ok(format!("{} is empty", path.display()));
+    }
+    Ok(lines.join("\n"))
+}
+
+fn read_file(folders: &[String], path: &str) -> Result<String, String> {
+    let path = resolve_path(folders, path)?;
+    let metadata = std::fs::metadata(&path).map_err(|e| e.to_string())?;
+    if metadata.is_dir() {
+        return Err(format!("{} is a directory", path.display()));
+    }
+    if metadata.len() > MAX_FILE_BYTES {
+        return Err(format!(
+            "{} is too large to read ({} bytes)",
+            path.display(),
+            metadata.len()
+        ));
+    }
+
+    let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|_| format!("{} is not a text file", path.display()))
+}
+
+async fn fetch_url(url: &str) -> Result<String, String> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err("Only http and https urls can be fetched".to_string());
+    }
+
+    let document = fetch_html(url)
+        .await
+        .map_err(|_| format!("Could not fetch {} as a text page", url))?;
+    let text = extract_readable_text(&document);
+
+    Ok(match extract_title(&document) {
+        Some(title) if !title.is_empty() => format!("# {}\n\n{}", title, text),
+        _ => text,
+    })
+}
+
+fn current_datetime() -> String {
+    let now = chrono::Local::now();
+    format!(
+        "{}\nWeekday: {}\nUTC offset: {}\nUTC: {}",
+        now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        now.format("%A"),
+        now.format("%:z"),
+        now.with_timezone(&chrono::Utc)
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    )
+}
+
+/// Evaluates a math expression, see the description of the `calculate` tool.
+pub fn evaluate(expression: &str) -> Result<f64, String> {
+    let mut parser = Parser {
+        chars: expression.chars().filter(|c| !c.is_whitespace()).collect(),
+        position: 0,
+        depth: 0,
+    };
+
+    let value = parser.expression()?;
+    if let Some(c) = parser.peek() {
+        return Err(format!("Unexpected '{}'", c));
+    }
+    if !value.is_finite() {
+        return Err("The result is not a finite number".to_string());
+    }
+    Ok(value)
+}
+
+/// Recursive descent parser, evaluating as it goes.
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    /// Nesting of the part being parsed, capped so it can't overflow the stack.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    /// Sums and differences of terms.
+    fn expression(&mut self) -> Result<f64, String> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Products, quotients and remainders of factors.
+    fn term(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*') {
+                value *= self.unary()?;
+            } else if self.eat('/') {
+                let divisor = self.unary()?;
+                if divisor == 0.0 {
+                    return Err("Division by zero".to_string());
+                }
+                value /= divisor;
+            } else if self.eat('%') {
+                let divisor = self.unary()?;
+                if divisor == 0.0 {
+                    return Err("Division by zero".to_string());
+                }
+                value %= divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Every nesting goes through here, so it's where the depth is tracked.
+    fn unary(&mut self) -> Result<f64, String> {
+        if self.depth == MAX_NESTING {
+            return Err("Expression is nested too deeply".to_string());
+        }
+        self.depth += 1;
+        let value = self.signed();
+        self.depth -= 1;
+        value
+    }
+
+    /// Signs bind looser than powers, so `-2^2` is `-4`.
+    fn signed(&mut self) -> Result<f64, String> {
+        if self.eat('-') {
+            Ok(-self.unary()?)
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    /// Powers are right associative.
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            let exponent = self.unary()?;
+            return Ok(base.powf(exponent));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<f64, String> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let value = self.expression()?;
+                if !self.eat(')') {
+                    return Err("Missing a closing parenthesis".to_string());
+                }
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let start = self.position;
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    self.position += 1;
+                }
+                let number = self.chars[start..self.position].iter().collect::<String>();
+                number
+                    .parse()
+                    .map_err(|_| format!("Invalid number '{}'", number))
+            }
+            Some(c) if c.is_alphabetic() => {
+                let start = self.position;
+                while self.peek().is_some_and(char::is_alphanumeric) {
+                    self.position += 1;
+                }
+                let name = self.chars[start..self.position].iter().collect::<String>();
+                self.identifier(&name.to_lowercase())
+            }
+            Some(c) => Err(format!("Unexpected '{}'", c)),
+            None => Err("Unexpected end of the expression".to_string()),
+        }
+    }
+
+    fn identifier(&mut self, name: &str) -> Result<f64, String> {
+        match name {
+            "pi" => return Ok(std::f64::consts::PI),
+            "e" => return Ok(std::f64::consts::E),
+            _ => {}
+        }
+
+        let function: fn(f64) -> f64 = match name {
+            "sqrt" => f64::sqrt,
+            "abs" => f64::abs,
+            "exp" => f64::exp,
+            "ln" => f64::ln,
+            "log" => f64::log10,
+            "sin" => f64::sin,
+            "cos" => f64::cos,
+            "tan" => f64::tan,
+            "floor" => f64::floor,
+            "ceil" => f64::ceil,
+            "round" => f64::round,
+            _ => return Err(format!("Unknown function or constant '{}'", name)),
+        };
+
+        if !self.eat('(') {
+            return Err(format!("Missing the arguments of '{}'", name));
+        }
+        let argument = self.expression()?;
+        if !self.eat(')') {
+            return Err("Missing a closing parenthesis".to_string());
+        }
+        Ok(function(argument))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(evaluate("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), Ok(512.0));
+        assert_eq!(evaluate("-2^2"), Ok(-4.0));
+        assert_eq!(evaluate("10 % 4 - -1"), Ok(3.0));
+        assert_eq!(evaluate("sqrt(16) + abs(-2.5)"), Ok(6.5));
+        assert_eq!(evaluate("round(pi * 100) / 100"), Ok(3.14));
+
+        assert!(evaluate("1 / 0").is_err());
+        assert!(evaluate("(1 + 2").is_err());
+        assert!(evaluate("2 +").is_err());
+        assert!(evaluate("foo(1)").is_err());
+        assert!(evaluate("sqrt(-1)").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_caps_nesting() {
+        let nested = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+        assert_eq!(evaluate(&nested), Ok(1.0));
+
+        let too_deep = format!("{}1{}", "(".repeat(50_000), ")".repeat(50_000));
+        assert_eq!(
+            evaluate(&too_deep),
+            Err("Expression is nested too deeply".to_string())
+        );
+        assert_eq!(
+            evaluate(&format!("{}1", "-".repeat(50_000))),
+            Err("Expression is nested too deeply".to_string())
+        );
+    }
+
+    #[test]
+    fn test_server_rejects_foreign_requests() {
+        use std::io::{Read, Write};
+
+        let url = url().unwrap();
+        let (address, path) = url["http://".len()..].split_once('/').unwrap();
+        let status = |host: &str, path: &str, headers: &str| {
+            let mut stream = std::net::TcpStream::connect(address).unwrap();
+            write!(
+                stream,
+                "POST {path} HTTP/1.1\r\nHost: {host}\r\n{headers}Content-Length: 0\r\nConnection: close\r\n\r\n"
+            )
+            .unwrap();
+            let mut response = String::new();
+            let _ = stream.read_to_string(&mut response);
+            response.split(' ').nth(1).unwrap_or_default().to_string()
+        };
+        let path = format!("/{}", path);
+
+        assert_eq!(status("evil.example", &path, ""), "403");
+        assert_eq!(
+            status(address, &path, "Origin: http://evil.example\r\n"),
+            "403"
+        );
+        assert_eq!(status(address, "/mcp", ""), "404");
+        assert!(!["403", "404"].contains(&status(address, &path, "").as_str()));
+    }
+
+    #[test]
+    fn test_paths_stay_inside_allowed_folders() {
+        let root = std::env::temp_dir().join(format!("moly-builtin-{}", std::process::id()));
+        let allowed = root.join("allowed");
+        std::fs::create_dir_all(&allowed).unwrap();
+        std::fs::write(allowed.join("notes.txt"), "hello").unwrap();
+        std::fs::write(root.join("secret.txt"), "secret").unwrap();
+
+        let folders = vec![allowed.to_string_lossy().to_string()];
+        assert_eq!(read_file(&folders, "notes.txt"), Ok("hello".to_string()));
+        assert!(read_file(&folders, "../secret.txt").is_err());
+        assert!(read_file(&folders, &root.join("secret.txt").to_string_lossy()).is_err());
+        assert!(list_directory(&folders, "..").is_err());
+        assert!(read_file(&[], "notes.txt").is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
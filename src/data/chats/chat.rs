@@ -1,3 +1,5 @@
+use crate::data::reasoning::ReasoningParams;
+use crate::data::tool_selection::ToolSelection;
 use crate::shared::utils::{
     attachments::{delete_attachment, persistence_reader},
     filesystem,
@@ -29,6 +31,12 @@ struct ChatData {
     title_state: TitleState,
     #[serde(default)]
     accessed_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    fallback_models: Vec<String>,
+    #[serde(default)]
+    reasoning: ReasoningParams,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_selection: Option<ToolSelection>,
 
     // Legacy field, it can be removed in the future.
     last_used_file_id: Option<FileId>,
@@ -43,6 +51,7 @@ pub struct ChatInferenceParams {
     pub top_p: f32,
     pub stream: bool,
     pub stop: String,
+    pub reasoning: ReasoningParams,
 }
 
 impl Default for ChatInferenceParams {
@@ -55,6 +64,7 @@ impl Default for ChatInferenceParams {
             top_p: 1.0,
             stream: true,
             stop: "".into(),
+            reasoning: ReasoningParams::default(),
         }
     }
 }
@@ -75,6 +85,14 @@ pub struct Chat {
     pub accessed_at: chrono::DateTime<chrono::Utc>,
     pub has_unread_messages: bool,
 
+    /// Names or ids of the models to fail over to, in order, when the associated
+    /// bot can't answer. Takes precedence over the bot's own fallback chain.
+    pub fallback_models: Vec<String>,
+
+    /// MCP tools offered to the model in this chat, or none to offer the ones
+    /// picked for the associated bot.
+    pub tool_selection: Option<ToolSelection>,
+
     title: String,
     title_state: TitleState,
     chats_dir: PathBuf,
@@ -96,6 +114,8 @@ impl Chat {
             system_prompt: None,
             accessed_at: chrono::Utc::now(),
             has_unread_messages: false,
+            fallback_models: vec![],
+            tool_selection: None,
         }
     }
 
@@ -122,10 +142,15 @@ impl Chat {
                     title: data.title,
                     title_state: data.title_state,
                     chats_dir: dir.to_path_buf(),
-                    inferences_params: ChatInferenceParams::default(),
+                    inferences_params: ChatInferenceParams {
+                        reasoning: data.reasoning,
+                        ..Default::default()
+                    },
                     system_prompt: data.system_prompt,
                     accessed_at: data.accessed_at,
                     has_unread_messages: false,
+                    fallback_models: data.fallback_models,
+                    tool_selection: data.tool_selection,
                 };
 
                 Ok(chat)
@@ -144,6 +169,9 @@ impl Chat {
             title: self.title.clone(),
             title_state: self.title_state,
             accessed_at: self.accessed_at,
+            fallback_models: self.fallback_models.clone(),
+            reasoning: self.inferences_params.reasoning.clone(),
+            tool_selection: self.tool_selection.clone(),
 
             // Legacy field, it can be removed in the future.
             last_used_file_id: None,
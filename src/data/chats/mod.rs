@@ -4,11 +4,12 @@ use chat::{Chat, ChatId};
 use futures::StreamExt;
 use moly_kit::prelude::*;
 use moly_protocol::data::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::{cell::RefCell, path::PathBuf};
 
 use crate::shared::utils::filesystem;
 
+use super::models_cache::ModelsCache;
 use super::moly_client::MolyClient;
 use super::preferences::Preferences;
 use super::providers::{
@@ -26,6 +27,9 @@ pub struct Chats {
     /// Map of providers keyed by their ID
     pub providers: HashMap<ProviderId, Provider>,
 
+    /// Last successfully fetched models of each provider, persisted on disk.
+    pub models_cache: ModelsCache,
+
     /// Set it thru `set_current_chat` method to trigger side effects.
     current_chat_id: Option<ChatId>,
     chats_dir: PathBuf,
@@ -44,12 +48,14 @@ impl Chats {
             chats_dir: PathBuf::from("chats"),
             available_bots: HashMap::new(),
             providers: HashMap::new(),
+            models_cache: ModelsCache::default(),
             unknown_bot: ProviderBot::unknown(),
         }
     }
 
     pub async fn load(moly_client: MolyClient) -> Self {
         let mut chats = Chats::new(moly_client);
+        chats.models_cache = ModelsCache::load().await;
 
         let fs = filesystem::global();
         let paths = fs
@@ -244,6 +250,15 @@ impl Chats {
                     }
                 }
 
+                // Drop cached models the provider no longer lists
+                let fetched_ids: HashSet<BotId> =
+                    fetched_models.iter().map(|m| m.id.clone()).collect();
+                self.available_bots
+                    .retain(|id, bot| bot.provider_id != provider_id || fetched_ids.contains(id));
+                if let Some(provider) = self.providers.get_mut(&provider_id) {
+                    provider.models.retain(|id| fetched_ids.contains(id));
+                }
+
                 // Insert the fetched models in memory, respecting preference "enabled" if it exists
                 for mut provider_bot in fetched_models {
                     // Set initial state
@@ -253,20 +268,11 @@ impl Chats {
                         true
                     };
 
-                    if let Some(pref_entry) = preferences
-                        .providers_preferences
-                        .iter()
-                        .find(|pp| pp.id == provider_id)
+                    // if there's a matching "(model_name, enabled)" in preferences, apply it
+                    if let Some(enabled_val) =
+                        preferred_model_status(preferences, &provider_id, &provider_bot)
                     {
-                        // if there's a matching "(model_name, enabled)" in preferences, apply it
-                        if let Some((_, enabled_val)) = pref_entry.models.iter().find(|(m, _)| {
-                            *m == provider_bot.name
-                                || RouterClient::unprefix(&provider_bot.id)
-                                    .map(|(_, id)| id.as_str() == *m)
-                                    .unwrap_or(false)
-                        }) {
-                            final_enabled = *enabled_val;
-                        }
+                        final_enabled = enabled_val;
                     }
                     provider_bot.enabled = final_enabled;
 
@@ -290,6 +296,9 @@ impl Chats {
                         .insert(provider_bot.id.clone(), provider_bot);
                 }
 
+                let fresh_models = self.get_provider_models(&provider_id);
+                self.models_cache.update(&provider_id, fresh_models);
+
                 if let Some(provider) = self.providers.get_mut(&provider_id) {
                     provider.connection_status = ProviderConnectionStatus::Connected;
                     // If the fetching was successful and the provider is MolyServer, sync status
@@ -299,6 +308,7 @@ impl Chats {
                 }
             }
             ProviderFetchModelsResult::Failure(address, error) => {
+                // Models seeded from the cache (if any) are kept, still marked as stale
                 let provider_id = address.clone();
                 if let Some(provider) = self.providers.get_mut(&provider_id) {
                     provider.connection_status =
@@ -357,6 +367,10 @@ impl Chats {
             existing_provider.connection_status = provider.connection_status.clone();
             existing_provider.system_prompt = provider.system_prompt.clone();
             existing_provider.tools_enabled = provider.tools_enabled;
+            existing_provider.model_rules = provider.model_rules.clone();
+            existing_provider.api_keys = provider.api_keys.clone();
+            existing_provider.think_tags = provider.think_tags;
+            existing_provider.hosted_tools = provider.hosted_tools.clone();
 
             if provider.enabled {
                 self.test_provider_and_fetch_models(&provider.id, provider_syncing_status);
@@ -370,6 +384,46 @@ impl Chats {
         self.available_bots
             .retain(|_, model| model.provider_id != *provider_id);
         self.providers.remove(provider_id);
+        self.models_cache.remove(provider_id);
+    }
+
+    /// Seeds the available bots of the registered providers from the models cache.
+    ///
+    /// Seeded bots are marked as stale until their provider is fetched successfully.
+    /// Bots already in memory are left untouched.
+    pub fn seed_cached_models(&mut self, preferences: &Preferences) {
+        for (provider_id, provider) in self.providers.iter_mut() {
+            let Some(cached) = self.models_cache.get(provider_id) else {
+                continue;
+            };
+
+            for cached_bot in &cached.models {
+                if self.available_bots.contains_key(&cached_bot.id) {
+                    continue;
+                }
+
+                let mut provider_bot = cached_bot.clone();
+                provider_bot.is_stale = true;
+                if let Some(enabled) =
+                    preferred_model_status(preferences, provider_id, &provider_bot)
+                {
+                    provider_bot.enabled = enabled;
+                }
+
+                if !provider.models.contains(&provider_bot.id) {
+                    provider.models.push(provider_bot.id.clone());
+                }
+                self.available_bots
+                    .insert(provider_bot.id.clone(), provider_bot);
+            }
+        }
+    }
+
+    /// Whether any of the provider's bots are still the stale ones seeded from the cache.
+    pub fn has_stale_models(&self, provider_id: &ProviderId) -> bool {
+        self.available_bots
+            .values()
+            .any(|bot| bot.provider_id == *provider_id && bot.is_stale)
     }
 
     /// Returns a list of remote models for a given server address.
@@ -391,10 +445,8 @@ impl Chats {
     /// if the remote model is not found in the available remote models list.
     ///
     /// This is useful when dealing with historical chat references to remote models that may
-    /// no longer be available (e.g., server disconnected or remote model deleted).
-    ///
-    /// In the future, we'll want a more sophisticated solution, by potentially storing
-    /// remote model information locally and updating it when a server is connected.
+    /// no longer be available (e.g., remote model deleted). Models of providers that can't be
+    /// reached are still found here, as they are seeded from the models cache on startup.
     pub fn get_bot_or_placeholder(&self, bot_id: &BotId) -> &ProviderBot {
         self.available_bots.get(bot_id).unwrap_or(&self.unknown_bot)
     }
@@ -411,6 +463,28 @@ impl Chats {
         }
     }
 
+    /// Resolves model names or ids into enabled bots, keeping their order.
+    ///
+    /// Entries not matching any enabled bot are skipped.
+    pub fn resolve_fallback_bots(&self, fallback_models: &[String]) -> Vec<BotId> {
+        let enabled_bots = self.get_all_bots(true);
+        fallback_models
+            .iter()
+            .filter_map(|model| {
+                let model = model.trim();
+                enabled_bots
+                    .iter()
+                    .find(|bot| bot.id.as_str() == model)
+                    .or_else(|| {
+                        enabled_bots
+                            .iter()
+                            .find(|bot| bot.name.eq_ignore_ascii_case(model))
+                    })
+                    .map(|bot| bot.id.clone())
+            })
+            .collect()
+    }
+
     /// Returns a list of all available agents.
     ///
     /// If [enabled_only] is set to true, then only enabled agents from enabled providers are returned.
@@ -498,3 +572,26 @@ impl Chats {
             .map(|m| m.id.clone())
     }
 }
+
+/// Returns the "enabled" status the user set for a model in the provider preferences, if any.
+fn preferred_model_status(
+    preferences: &Preferences,
+    provider_id: &ProviderId,
+    provider_bot: &ProviderBot,
+) -> Option<bool> {
+    let pref_entry = preferences
+        .providers_preferences
+        .iter()
+        .find(|pp| pp.id == *provider_id)?;
+
+    pref_entry
+        .models
+        .iter()
+        .find(|(m, _)| {
+            *m == provider_bot.name
+                || RouterClient::unprefix(&provider_bot.id)
+                    .map(|(_, id)| id.as_str() == *m)
+                    .unwrap_or(false)
+        })
+        .map(|(_, enabled)| *enabled)
+}
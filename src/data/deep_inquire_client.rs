@@ -1,17 +1,24 @@
 use async_stream::stream;
 use makepad_widgets::*;
 use makepad_widgets::{Cx, LiveNew, WidgetRef};
+use moly_kit::aitk::utils::asynchronous::sleep;
 use moly_kit::aitk::utils::sse::parse_sse;
 use moly_kit::prelude::*;
 use reqwest::header::{HeaderMap, HeaderName};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashSet,
     str::FromStr,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
 use crate::chat::deep_inquire_content::DeepInquireContentWidgetRefExt;
 
+/// How many times an interrupted research run is resumed before giving up.
+const MAX_RESUME_ATTEMPTS: u32 = 2;
+const INITIAL_RESUME_BACKOFF: Duration = Duration::from_millis(500);
+
 /// Article reference in a DeepInquire response
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Article {
@@ -88,6 +95,9 @@ struct DeltaChoice {
 /// Response from the DeepInquire API
 #[derive(Clone, Debug, Deserialize)]
 struct DeepInquireResponse {
+    /// Identifies the research run, used to resume it if the stream is interrupted.
+    #[serde(default)]
+    id: Option<String>,
     choices: Vec<DeltaChoice>,
 }
 
@@ -97,6 +107,9 @@ pub struct Stage {
     pub citations: Vec<Article>,
     pub substages: Vec<SubStage>,
     pub stage_type: StageType,
+    /// The stage type as sent by the server, to tell apart `Other` stages.
+    #[serde(default)]
+    pub type_name: String,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default, Live, LiveHook, LiveRead)]
@@ -106,6 +119,8 @@ pub enum StageType {
     #[pick]
     Content,
     Completion,
+    /// A stage type this version doesn't know about, shown as a generic stage.
+    Other,
 }
 
 impl FromStr for StageType {
@@ -121,6 +136,20 @@ impl FromStr for StageType {
     }
 }
 
+// Replaces underscores with spaces, and capitalizes the first letter of each word
+pub fn get_human_readable_stage_name(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct Data {
     pub stages: Vec<Stage>,
@@ -225,131 +254,194 @@ impl BotClient for DeepInquireClient {
             .filter_map(|m| m.clone().try_into().ok())
             .collect();
 
-        let request = inner
-            .client
-            .post(&url)
-            .headers(headers)
-            .json(&serde_json::json!({
-                "model": bot_id.id(),
-                "messages": moly_messages,
-                "stream": true
-            }));
+        let body = serde_json::json!({
+            "model": bot_id.id(),
+            "messages": moly_messages,
+            "stream": true
+        });
 
         let stream = stream! {
-            let response = match request.send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        response
-                    } else {
-                        let status_code = response.status();
-                        let body = response.text().await.unwrap();
-                        let message = format!(
-                            "Request failed with status {}",
-                            status_code,
-                        );
+            let mut content = MessageContent::default();
+            let mut progress = Progress::default();
+            let mut backoff = INITIAL_RESUME_BACKOFF;
+
+            for attempt in 0..=MAX_RESUME_ATTEMPTS {
+                if attempt > 0 {
+                    ::log::warn!(
+                        "Resuming DeepInquire response {:?} in {:?} (attempt {} of {})",
+                        progress.response_id,
+                        backoff,
+                        attempt,
+                        MAX_RESUME_ATTEMPTS
+                    );
+                    sleep(backoff).await;
+                    backoff *= 2;
+                }
 
-                        yield ClientError::new(
-                            ClientErrorKind::Response,
-                            message,
-                        ).with_details(body).into();
-                        return;
-                    }
+                let mut request = inner
+                    .client
+                    .post(&url)
+                    .headers(headers.clone());
+                let mut request_body = body.clone();
+                if let Some(response_id) = &progress.response_id {
+                    request_body["response_id"] = response_id.clone().into();
                 }
-                Err(error) => {
-                    ::log::error!("SSE stream unexpectedly interrupted while reading from {}: {:?}", url, error);
-                    yield ClientError::new_with_source(
-                        ClientErrorKind::Network,
-                        format!("The connection was unexpectedly closed while streaming the response from {url}. This could be due to network issues, server problems, or timeouts."),
-                        Some(error),
-                    ).into();
-                    return;
+                if let Some(last_event_id) = &progress.last_event_id {
+                    request = request.header("Last-Event-ID", last_event_id);
                 }
-            };
+                let request = request.json(&request_body);
+
+                let interruption = match request.send().await {
+                    Ok(response) if response.status().is_success() => {
+                        let events = parse_sse(response.bytes_stream());
+                        let mut consecutive_timeouts = 0;
+                        let max_consecutive_timeouts = 3;
+                        let mut message_count = 0;
+                        // Only yield to UI every 10 messages to reduce back-pressure
+                        let yield_frequency = 10;
+                        let mut interruption = None;
+
+                        for await event in events {
+                            let event = match event {
+                                Ok(chunk) => {
+                                    consecutive_timeouts = 0; // Reset timeout counter on success
+                                    message_count += 1;
+                                    chunk
+                                },
+                                Err(error) => {
+                                    if error.is_timeout() {
+                                        consecutive_timeouts += 1;
+                                        if consecutive_timeouts < max_consecutive_timeouts {
+                                            continue;
+                                        }
+                                    }
+
+                                    ::log::error!("SSE stream error while reading from {}: {:?}", url, error);
+                                    interruption = Some(ClientError::new_with_source(
+                                        ClientErrorKind::Network,
+                                        format!("The connection was unexpectedly closed while streaming the response from {url}. This could be due to network issues, server problems, or timeouts."),
+                                        Some(error),
+                                    ));
+                                    break;
+                                }
+                            };
+
+                            if event.trim() == "[DONE]" {
+                                break;
+                            }
 
-            let events = parse_sse(response.bytes_stream());
-            let mut content = MessageContent::default();
-            let mut consecutive_timeouts = 0;
-            let max_consecutive_timeouts = 3;
-            let mut message_count = 0;
-            // Only yield to UI every 10 messages to reduce back-pressure
-            let yield_frequency = 10;
-
-            for await event in events {
-                let event = match event {
-                    Ok(chunk) => {
-                        consecutive_timeouts = 0; // Reset timeout counter on success
-                        message_count += 1;
-                        chunk
-                    },
-                    Err(error) => {
-                        if error.is_timeout() {
-                            consecutive_timeouts += 1;
-
-                            if consecutive_timeouts >= max_consecutive_timeouts {
-                                ::log::error!("SSE stream error while reading from {}: {:?}", url, error);
-                                yield ClientError::new_with_source(
-                                    ClientErrorKind::Network,
-                                    format!("Too many consecutive timeouts ({}) while reading from {url}. Giving up.", consecutive_timeouts),
-                                    Some(error),
-                                ).into();
-                                return;
+                            let response: DeepInquireResponse = match serde_json::from_str(&event) {
+                                Ok(c) => c,
+                                Err(error) => {
+                                    ::log::warn!("Skipping an SSE message from {url} that is not valid JSON or does not match the expected format. {}\nEvent content: {}", error, event);
+                                    continue;
+                                }
+                            };
+
+                            progress.apply(response, &mut content);
+
+                            // Only yield to UI periodically to reduce back-pressure
+                            // The first 20 messages are yielded immediately to ensure the UI is updated
+                            if message_count % yield_frequency == 0 || message_count < 20 {
+                                yield ClientResult::new_ok(content.clone());
                             }
+                        }
 
-                            continue;
-                        } else {
-                            ::log::error!("SSE stream error while reading from {}: {:?}", url, error);
-                            yield ClientError::new_with_source(
-                                ClientErrorKind::Network,
-                                format!("The connection was unexpectedly closed while streaming the response from {url}. This could be due to network issues, server problems, or timeouts."),
-                                Some(error),
-                            ).into();
+                        interruption
+                    }
+                    Ok(response) => {
+                        let status_code = response.status();
+                        let body = response.text().await.unwrap_or_default();
+                        let error = ClientError::new(
+                            ClientErrorKind::Response,
+                            format!("Request failed with status {}", status_code),
+                        )
+                        .with_details(body);
+
+                        // Only server errors are worth retrying.
+                        if !status_code.is_server_error() {
+                            yield error.into();
                             return;
                         }
+                        Some(error)
                     }
-                };
-
-                let response: DeepInquireResponse = match serde_json::from_str(&event) {
-                    Ok(c) => c,
                     Err(error) => {
-                        ::log::error!("Could not parse the SSE message from {url} as JSON or its structure does not match the expected format. {}\nEvent content: {}", error, event);
-                        yield ClientError::new_with_source(
-                            ClientErrorKind::Format,
-                            format!("Could not parse the SSE message from {url} as JSON or its structure does not match the expected format."),
+                        ::log::error!("Request to {} failed: {:?}", url, error);
+                        Some(ClientError::new_with_source(
+                            ClientErrorKind::Network,
+                            format!("Could not reach {url}. This could be due to network issues, server problems, or timeouts."),
                             Some(error),
-                        ).into();
-                        return;
+                        ))
                     }
                 };
 
-                apply_response_to_content(response, &mut content);
-
-                // Only yield to UI periodically to reduce back-pressure
-                // The first 20 messages are yielded immediately to ensure the UI is updated
-                if message_count % yield_frequency == 0 || message_count < 20 {
+                let Some(error) = interruption else {
+                    // Final yield to ensure the last state is captured
                     yield ClientResult::new_ok(content.clone());
+                    return;
+                };
+
+                if attempt == MAX_RESUME_ATTEMPTS {
+                    // Keep the stages received so far in the message.
+                    if content.data.is_some() {
+                        yield ClientResult::new_ok(content.clone());
+                    }
+                    yield error.into();
+                    return;
                 }
             }
-
-            // Final yield to ensure the last state is captured
-            yield ClientResult::new_ok(content.clone());
         };
 
         Box::pin(stream)
     }
 }
 
-fn apply_response_to_content(response: DeepInquireResponse, content: &mut MessageContent) {
-    for choice in response.choices {
-        let delta = choice.delta;
+/// How far a research run got, to resume it without repeating content.
+#[derive(Default)]
+struct Progress {
+    response_id: Option<String>,
+    /// Id of the last delta received, sent as `Last-Event-ID` when resuming.
+    last_event_id: Option<String>,
+    /// Deltas already applied, so replayed ones are skipped.
+    seen_deltas: HashSet<String>,
+}
 
-        // The id from the server follows the format <substage_id>.<stream_chunk_id>, which is not useful for tracking
-        // the substages. Thefore we use the metadata 'stage' (a name for the substage) as the id to use for keeping track and upadting each substage.
-        // For the stage_id we use the id from the delta without the stream_chunk_id.
-        let stage_id = delta.id.split('.').next().unwrap_or(&delta.id).to_string();
-        let substage_id = delta.metadata.stage.clone();
-        let stage_type = StageType::from_str(&delta.r#type).unwrap(); // TODO: Handle this gracefully
+impl Progress {
+    fn apply(&mut self, response: DeepInquireResponse, content: &mut MessageContent) {
+        if let Some(id) = response.id {
+            self.response_id = Some(id);
+        }
+
+        for choice in response.choices {
+            let delta = choice.delta;
+            if !self.seen_deltas.insert(delta.id.clone()) {
+                continue;
+            }
 
-        create_or_update_stage(content, stage_type, stage_id, move |existing_stage| {
+            self.last_event_id = Some(delta.id.clone());
+            apply_delta_to_content(delta, content);
+        }
+    }
+}
+
+fn apply_delta_to_content(delta: DeltaContent, content: &mut MessageContent) {
+    // The id from the server follows the format <substage_id>.<stream_chunk_id>, which is not useful for tracking
+    // the substages. Thefore we use the metadata 'stage' (a name for the substage) as the id to use for keeping track and upadting each substage.
+    // For the stage_id we use the id from the delta without the stream_chunk_id.
+    let stage_id = delta.id.split('.').next().unwrap_or(&delta.id).to_string();
+    let substage_id = delta.metadata.stage.clone();
+    let stage_type = StageType::from_str(&delta.r#type).unwrap_or_else(|_| {
+        ::log::debug!("Unknown DeepInquire stage type {:?}", delta.r#type);
+        StageType::Other
+    });
+    let type_name = delta.r#type.clone();
+
+    create_or_update_stage(
+        content,
+        stage_type,
+        type_name,
+        stage_id,
+        move |existing_stage| {
             // Check if the substage arriving in the response is already present in the accumulated content
             let existing_substage = existing_stage
                 .substages
@@ -375,15 +467,14 @@ fn apply_response_to_content(response: DeepInquireResponse, content: &mut Messag
                 .collect();
 
             existing_stage.citations.extend(new_citations);
-
-            return;
-        });
-    }
+        },
+    );
 }
 
 fn create_or_update_stage(
     content: &mut MessageContent,
     stage_type: StageType,
+    type_name: String,
     stage_id: String,
     update_fn: impl FnOnce(&mut Stage),
 ) {
@@ -393,8 +484,11 @@ fn create_or_update_stage(
         .and_then(|d| serde_json::from_str(d).ok())
         .unwrap_or_default();
 
-    // Find the existing stage by matching the enum variant
-    if let Some(mut existing_stage) = data.stages.iter_mut().find(|s| s.stage_type == stage_type) {
+    // Find the existing stage by matching the enum variant, or the raw type for unknown ones
+    let existing_stage = data.stages.iter_mut().find(|s| {
+        s.stage_type == stage_type && (stage_type != StageType::Other || s.type_name == type_name)
+    });
+    if let Some(mut existing_stage) = existing_stage {
         update_fn(&mut existing_stage);
     } else {
         let mut new_stage = Stage {
@@ -402,6 +496,7 @@ fn create_or_update_stage(
             substages: vec![],
             citations: vec![],
             stage_type,
+            type_name,
         };
 
         update_fn(&mut new_stage);
@@ -471,3 +566,129 @@ impl CustomContent for DeepInquireCustomContent {
         Some(widget)
     }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::data::test_utils::{Response, block_on, stand_in_server};
+    use futures::StreamExt;
+    use std::sync::Mutex;
+
+    const COMPLETE: &str = include_str!("./deep_inquire_recordings/complete.sse");
+    const INTERRUPTED: &str = include_str!("./deep_inquire_recordings/interrupted.sse");
+    const RESUMED: &str = include_str!("./deep_inquire_recordings/resumed.sse");
+
+    /// Binds a stand-in server replaying one recording per request, in order.
+    fn replay_server(replays: Vec<Response>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let mut replays = replays.into_iter();
+        stand_in_server(move |_| {
+            replays
+                .next()
+                .unwrap_or_else(|| Response::error("500 Internal Server Error"))
+        })
+    }
+
+    fn interrupted(recording: &str) -> Response {
+        Response {
+            interrupted: true,
+            ..Response::event_stream(recording)
+        }
+    }
+
+    /// Sends a message and collects everything the client yields.
+    fn research(url: String) -> Vec<(Option<MessageContent>, Vec<ClientError>)> {
+        let mut client = DeepInquireClient::new(url);
+        let messages = vec![Message {
+            from: EntityId::User,
+            content: MessageContent {
+                text: "What is the answer?".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }];
+
+        block_on(
+            client
+                .send(&BotId::new("DeepInquire"), &messages, &[])
+                .map(|result| result.into_value_and_errors())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn stages(content: &MessageContent) -> Vec<Stage> {
+        serde_json::from_str::<Data>(content.data.as_deref().unwrap())
+            .unwrap()
+            .stages
+    }
+
+    fn stage_text(stage: &Stage) -> String {
+        stage.substages.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_complete_stream_skips_malformed_events() {
+        let (url, _) = replay_server(vec![Response::event_stream(COMPLETE)]);
+
+        let results = research(url);
+        let (content, errors) = results.last().unwrap();
+        assert!(errors.is_empty());
+
+        let stages = stages(content.as_ref().unwrap());
+        let types: Vec<_> = stages.iter().map(|s| s.stage_type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                StageType::Thinking,
+                StageType::Other,
+                StageType::Content,
+                StageType::Completion
+            ]
+        );
+        assert_eq!(stage_text(&stages[0]), "Looking into the question.");
+        assert_eq!(stages[1].type_name, "fact_checking");
+        assert_eq!(stage_text(&stages[1]), "All sources agree.");
+        assert_eq!(stages[2].citations.len(), 1);
+    }
+
+    #[test]
+    fn test_interrupted_stream_is_resumed() {
+        let (url, requests) = replay_server(vec![
+            interrupted(INTERRUPTED),
+            Response::event_stream(RESUMED),
+        ]);
+
+        let results = research(url);
+        assert!(results.iter().all(|(_, errors)| errors.is_empty()));
+
+        let (content, _) = results.last().unwrap();
+        let stages = stages(content.as_ref().unwrap());
+        assert_eq!(stages.len(), 3);
+        // The replayed delta is not appended twice.
+        assert_eq!(stage_text(&stages[1]), "The answer is 42.");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains(r#""response_id":"resp-2""#));
+        assert!(requests[1].to_lowercase().contains("last-event-id: 3.1"));
+    }
+
+    #[test]
+    fn test_partial_stages_are_kept_when_resuming_fails() {
+        let (url, requests) = replay_server(vec![interrupted(INTERRUPTED)]);
+
+        let results = research(url);
+        assert_eq!(
+            requests.lock().unwrap().len(),
+            1 + MAX_RESUME_ATTEMPTS as usize
+        );
+
+        let (last, errors) = results.last().unwrap();
+        assert!(last.is_none());
+        assert!(!errors.is_empty());
+
+        let (partial, _) = &results[results.len() - 2];
+        let stages = stages(partial.as_ref().unwrap());
+        assert_eq!(stages.len(), 2);
+        assert_eq!(stage_text(&stages[1]), "The answer ");
+    }
+}
@@ -0,0 +1,541 @@
+//! Builds shareable documents out of DeepInquire research results.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::deep_inquire_client::{Article, Data, Stage, StageType, get_human_readable_stage_name};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ReportOptions {
+    pub format: ReportFormat,
+    /// Adds the thinking stages as an appendix.
+    pub include_thinking: bool,
+}
+
+/// A part of the report with its text and the bibliography entries it cites.
+struct Section {
+    title: String,
+    /// Substage headings and their markdown text.
+    blocks: Vec<(String, String)>,
+    citations: Vec<usize>,
+}
+
+/// Sources cited by the report, numbered in order of first appearance.
+#[derive(Default)]
+struct Bibliography {
+    entries: Vec<Article>,
+}
+
+impl Bibliography {
+    /// Adds the articles not seen before and returns the numbers of all of them.
+    fn cite(&mut self, articles: &[Article]) -> Vec<usize> {
+        let mut numbers = Vec::new();
+        for article in articles {
+            let key = source_key(&article.url);
+            let index = match self.entries.iter().position(|e| source_key(&e.url) == key) {
+                Some(index) => {
+                    // Keep the best ranked duplicate's relevance.
+                    let entry = &mut self.entries[index];
+                    entry.relevance = entry.relevance.max(article.relevance);
+                    index
+                }
+                None => {
+                    self.entries.push(article.clone());
+                    self.entries.len() - 1
+                }
+            };
+
+            let number = index + 1;
+            if !numbers.contains(&number) {
+                numbers.push(number);
+            }
+        }
+        numbers
+    }
+}
+
+/// Identifies a source regardless of trivial URL differences, like a trailing
+/// slash, a fragment or the host's casing.
+fn source_key(url: &str) -> String {
+    match url::Url::parse(url.trim()) {
+        Ok(mut url) => {
+            url.set_fragment(None);
+            url.as_str().trim_end_matches('/').to_string()
+        }
+        Err(_) => url.trim().trim_end_matches('/').to_lowercase(),
+    }
+}
+
+/// Builds a report with the final answer, the analysis that led to it and a
+/// bibliography of the cited articles.
+pub fn build_report(data: &Data, options: ReportOptions) -> String {
+    let mut bibliography = Bibliography::default();
+
+    let answer = data
+        .stages
+        .iter()
+        .filter(|s| s.stage_type == StageType::Completion)
+        .map(stage_text)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    // Answers from the completion stage may cite too, right below the answer.
+    let mut sections: Vec<Section> = data
+        .stages
+        .iter()
+        .filter(|s| s.stage_type == StageType::Completion && !s.citations.is_empty())
+        .map(|s| Section {
+            title: String::new(),
+            blocks: vec![],
+            citations: bibliography.cite(&s.citations),
+        })
+        .collect();
+
+    sections.extend(
+        data.stages
+            .iter()
+            .filter(|s| matches!(s.stage_type, StageType::Content | StageType::Other))
+            .map(|s| section(s, &mut bibliography)),
+    );
+
+    let appendix: Vec<Section> = if options.include_thinking {
+        data.stages
+            .iter()
+            .filter(|s| s.stage_type == StageType::Thinking)
+            .map(|s| section(s, &mut bibliography))
+            .collect()
+    } else {
+        vec![]
+    };
+
+    match options.format {
+        ReportFormat::Markdown => markdown_report(&answer, &sections, &appendix, &bibliography),
+        ReportFormat::Html => html_report(&answer, &sections, &appendix, &bibliography),
+    }
+}
+
+fn stage_text(stage: &Stage) -> String {
+    stage.substages.iter().map(|s| s.text.as_str()).collect()
+}
+
+fn stage_title(stage: &Stage) -> String {
+    match stage.stage_type {
+        StageType::Thinking => "Thinking".to_string(),
+        StageType::Content => "Detailed analysis".to_string(),
+        StageType::Completion => "Answer".to_string(),
+        StageType::Other => get_human_readable_stage_name(&stage.type_name),
+    }
+}
+
+fn section(stage: &Stage, bibliography: &mut Bibliography) -> Section {
+    Section {
+        title: stage_title(stage),
+        blocks: stage
+            .substages
+            .iter()
+            .map(|s| (get_human_readable_stage_name(&s.name), s.text.clone()))
+            .collect(),
+        citations: bibliography.cite(&stage.citations),
+    }
+}
+
+fn markdown_report(
+    answer: &str,
+    sections: &[Section],
+    appendix: &[Section],
+    bibliography: &Bibliography,
+) -> String {
+    let mut report = String::from("# Research report\n\n");
+
+    if !answer.trim().is_empty() {
+        report.push_str(answer.trim());
+        report.push_str("\n\n");
+    }
+
+    let write_section = |report: &mut String, section: &Section, level: &str| {
+        if !section.title.is_empty() {
+            report.push_str(&format!("{} {}\n\n", level, section.title));
+        }
+        for (heading, text) in &section.blocks {
+            if !heading.is_empty() {
+                report.push_str(&format!("{}# {}\n\n", level, heading));
+            }
+            report.push_str(text.trim());
+            report.push_str("\n\n");
+        }
+        if !section.citations.is_empty() {
+            let links: Vec<String> = section
+                .citations
+                .iter()
+                .map(|n| format!("[{}](#ref-{})", n, n))
+                .collect();
+            report.push_str(&format!("Sources: {}\n\n", links.join(", ")));
+        }
+    };
+
+    for section in sections {
+        write_section(&mut report, section, "##");
+    }
+
+    if !appendix.is_empty() {
+        report.push_str("## Appendix\n\n");
+        for section in appendix {
+            write_section(&mut report, section, "###");
+        }
+    }
+
+    if !bibliography.entries.is_empty() {
+        report.push_str("## Bibliography\n\n");
+        for (index, article) in bibliography.entries.iter().enumerate() {
+            let number = index + 1;
+            report.push_str(&format!(
+                "{}. <a id=\"ref-{}\"></a>[{}]({})",
+                number,
+                number,
+                markdown_link_text(&article.title, &article.url),
+                article.url
+            ));
+            if !article.source.is_empty() {
+                report.push_str(&format!(" — {}", article.source));
+            }
+            report.push('\n');
+        }
+    }
+
+    report.trim_end().to_string() + "\n"
+}
+
+fn markdown_link_text(title: &str, url: &str) -> String {
+    let title = if title.trim().is_empty() { url } else { title };
+    title.replace('[', "\\[").replace(']', "\\]")
+}
+
+fn html_report(
+    answer: &str,
+    sections: &[Section],
+    appendix: &[Section],
+    bibliography: &Bibliography,
+) -> String {
+    let mut body = String::from("<h1>Research report</h1>\n");
+
+    if !answer.trim().is_empty() {
+        body.push_str(&markdown_to_html(answer));
+    }
+
+    let write_section = |body: &mut String, section: &Section, level: usize| {
+        if !section.title.is_empty() {
+            body.push_str(&format!(
+                "<h{}>{}</h{}>\n",
+                level,
+                escape_html(&section.title),
+                level
+            ));
+        }
+        for (heading, text) in &section.blocks {
+            if !heading.is_empty() {
+                body.push_str(&format!(
+                    "<h{}>{}</h{}>\n",
+                    level + 1,
+                    escape_html(heading),
+                    level + 1
+                ));
+            }
+            body.push_str(&markdown_to_html(text));
+        }
+        if !section.citations.is_empty() {
+            let links: Vec<String> = section
+                .citations
+                .iter()
+                .map(|n| format!("<a href=\"#ref-{}\">[{}]</a>", n, n))
+                .collect();
+            body.push_str(&format!(
+                "<p class=\"sources\">Sources: {}</p>\n",
+                links.join(", ")
+            ));
+        }
+    };
+
+    for section in sections {
+        write_section(&mut body, section, 2);
+    }
+
+    if !appendix.is_empty() {
+        body.push_str("<h2>Appendix</h2>\n");
+        for section in appendix {
+            write_section(&mut body, section, 3);
+        }
+    }
+
+    if !bibliography.entries.is_empty() {
+        body.push_str("<h2>Bibliography</h2>\n<ol>\n");
+        for (index, article) in bibliography.entries.iter().enumerate() {
+            let title = if article.title.trim().is_empty() {
+                &article.url
+            } else {
+                &article.title
+            };
+            let title = escape_html(title);
+            let entry = if is_web_url(&article.url) {
+                format!("<a href=\"{}\">{}</a>", escape_html(&article.url), title)
+            } else {
+                title
+            };
+            body.push_str(&format!("<li id=\"ref-{}\">{}", index + 1, entry));
+            if !article.source.is_empty() {
+                body.push_str(&format!(" — {}", escape_html(&article.source)));
+            }
+            body.push_str("</li>\n");
+        }
+        body.push_str("</ol>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Research report</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Converts the subset of markdown used by DeepInquire answers (headings, lists,
+/// paragraphs, emphasis, code and links) to HTML.
+fn markdown_to_html(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<String> = vec![];
+    let mut list: Option<&'static str> = None;
+
+    let flush_paragraph = |html: &mut String, paragraph: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", paragraph.join("<br>\n")));
+            paragraph.clear();
+        }
+    };
+    let close_list = |html: &mut String, list: &mut Option<&'static str>| {
+        if let Some(tag) = list.take() {
+            html.push_str(&format!("</{}>\n", tag));
+        }
+    };
+
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        let trimmed = line.trim_start();
+
+        if trimmed.is_empty() {
+            flush_paragraph(&mut html, &mut paragraph);
+            close_list(&mut html, &mut list);
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            flush_paragraph(&mut html, &mut paragraph);
+            close_list(&mut html, &mut list);
+            html.push_str(&format!(
+                "<h{}>{}</h{}>\n",
+                level,
+                inline_html(trimmed[level..].trim()),
+                level
+            ));
+            continue;
+        }
+
+        let item = if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            Some(("ul", item))
+        } else {
+            ORDERED_ITEM
+                .captures(trimmed)
+                .map(|c| ("ol", c.get(1).unwrap().as_str()))
+        };
+
+        if let Some((tag, item)) = item {
+            flush_paragraph(&mut html, &mut paragraph);
+            if list != Some(tag) {
+                close_list(&mut html, &mut list);
+                html.push_str(&format!("<{}>\n", tag));
+                list = Some(tag);
+            }
+            html.push_str(&format!("<li>{}</li>\n", inline_html(item)));
+            continue;
+        }
+
+        close_list(&mut html, &mut list);
+        paragraph.push(inline_html(trimmed));
+    }
+
+    flush_paragraph(&mut html, &mut paragraph);
+    close_list(&mut html, &mut list);
+    html
+}
+
+static ORDERED_ITEM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d+[.)]\s+(.*)$").unwrap());
+static INLINE_CODE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`([^`]+)`").unwrap());
+static BOLD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*\*([^*]+)\*\*").unwrap());
+static ITALIC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\*([^*]+)\*").unwrap());
+static LINK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap());
+
+fn inline_html(text: &str) -> String {
+    let text = escape_html(text);
+    let text = INLINE_CODE.replace_all(&text, "<code>$1</code>");
+    let text = BOLD.replace_all(&text, "<strong>$1</strong>");
+    let text = ITALIC.replace_all(&text, "<em>$1</em>");
+    LINK.replace_all(&text, |captures: &regex::Captures| {
+        if is_web_url(&captures[2]) {
+            format!("<a href=\"{}\">{}</a>", &captures[2], &captures[1])
+        } else {
+            captures[1].to_string()
+        }
+    })
+    .into_owned()
+}
+
+/// Only links to web pages are kept, so the document can't run scripts.
+fn is_web_url(url: &str) -> bool {
+    let url = url.trim_start().to_ascii_lowercase();
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::deep_inquire_client::SubStage;
+
+    fn article(title: &str, url: &str) -> Article {
+        Article {
+            title: title.to_string(),
+            url: url.to_string(),
+            snippet: String::new(),
+            source: "example".to_string(),
+            relevance: 1,
+        }
+    }
+
+    fn stage(stage_type: StageType, name: &str, text: &str, citations: Vec<Article>) -> Stage {
+        Stage {
+            id: name.to_string(),
+            citations,
+            substages: vec![SubStage {
+                id: name.to_string(),
+                name: name.to_string(),
+                text: text.to_string(),
+            }],
+            stage_type,
+            type_name: String::new(),
+        }
+    }
+
+    fn research() -> Data {
+        Data {
+            stages: vec![
+                stage(
+                    StageType::Thinking,
+                    "planning",
+                    "Looking into it.",
+                    vec![article("Notes", "https://example.com/notes")],
+                ),
+                stage(
+                    StageType::Content,
+                    "key_findings",
+                    "The answer is **42**.",
+                    vec![
+                        article("Deep Thought", "https://example.com/42"),
+                        article("Deep Thought (mirror)", "https://EXAMPLE.com/42/#intro"),
+                        article("Towels", "https://example.com/towels"),
+                    ],
+                ),
+                stage(StageType::Completion, "completion", "It's 42.", vec![]),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_markdown_report_merges_duplicate_sources() {
+        let report = build_report(
+            &research(),
+            ReportOptions {
+                format: ReportFormat::Markdown,
+                include_thinking: false,
+            },
+        );
+
+        assert!(report.starts_with("# Research report\n\nIt's 42.\n\n## Detailed analysis"));
+        assert!(report.contains("### Key Findings\n\nThe answer is **42**."));
+        assert!(report.contains("Sources: [1](#ref-1), [2](#ref-2)"));
+        assert!(report.contains(
+            "1. <a id=\"ref-1\"></a>[Deep Thought](https://example.com/42) — example\n\
+             2. <a id=\"ref-2\"></a>[Towels](https://example.com/towels) — example\n"
+        ));
+        assert!(!report.contains("Looking into it."));
+        assert!(!report.contains("notes"));
+    }
+
+    #[test]
+    fn test_thinking_appendix_is_optional() {
+        let report = build_report(
+            &research(),
+            ReportOptions {
+                format: ReportFormat::Markdown,
+                include_thinking: true,
+            },
+        );
+
+        let appendix = report.find("## Appendix").unwrap();
+        assert!(report[appendix..].contains("Looking into it."));
+        assert!(report.contains("3. <a id=\"ref-3\"></a>[Notes](https://example.com/notes)"));
+    }
+
+    #[test]
+    fn test_html_report() {
+        let report = build_report(
+            &research(),
+            ReportOptions {
+                format: ReportFormat::Html,
+                include_thinking: false,
+            },
+        );
+
+        assert!(report.contains("<p>The answer is <strong>42</strong>.</p>"));
+        assert!(report.contains("<a href=\"#ref-1\">[1]</a>, <a href=\"#ref-2\">[2]</a>"));
+        assert!(report.contains(
+            "<li id=\"ref-2\"><a href=\"https://example.com/towels\">Towels</a> — example</li>"
+        ));
+    }
+
+    #[test]
+    fn test_markdown_to_html() {
+        assert_eq!(
+            markdown_to_html("## Title\nSome <b> `code`\n\n- one\n- [two](https://a.b)\n1. first"),
+            "<h2>Title</h2>\n<p>Some &lt;b&gt; <code>code</code></p>\n\
+             <ul>\n<li>one</li>\n<li><a href=\"https://a.b\">two</a></li>\n</ul>\n\
+             <ol>\n<li>first</li>\n</ol>\n"
+        );
+
+        assert_eq!(
+            markdown_to_html("[click](javascript:alert(1)) [x](JavaScript:void)"),
+            "<p>click) x</p>\n"
+        );
+    }
+}
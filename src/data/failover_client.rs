@@ -0,0 +1,341 @@
+//! Bot client wrapper that retries transient failures and fails over to other bots.
+//!
+//! When the requested bot answers with a rate limit, a server error or a network
+//! failure, the same bot is retried with exponential backoff. If it keeps failing,
+//! the next bot of the configured fallback chain is tried instead.
+
+use async_stream::stream;
+use futures::StreamExt;
+use moly_kit::aitk::utils::asynchronous::sleep;
+use moly_kit::prelude::*;
+use regex::Regex;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+/// How many times a bot is retried on transient errors before moving to the next one.
+const MAX_RETRIES_PER_BOT: u32 = 2;
+
+/// Delay before the first retry, doubled on each subsequent one.
+const INITIAL_BACKOFF: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_secs(1)
+};
+
+/// Records that a response was produced by a fallback instead of the requested bot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailoverOutcome {
+    pub requested: BotId,
+    pub answered_by: BotId,
+}
+
+#[derive(Default)]
+struct FailoverState {
+    fallbacks: Vec<BotId>,
+    outcome: Option<FailoverOutcome>,
+}
+
+/// Failover configuration and result shared between a [`FailoverClient`] and the
+/// chat it serves.
+#[derive(Clone, Default)]
+pub struct FailoverHandle(Arc<Mutex<FailoverState>>);
+
+impl FailoverHandle {
+    /// Sets the bots to try, in order, when the requested one fails.
+    pub fn set_fallbacks(&self, fallbacks: Vec<BotId>) {
+        self.0.lock().unwrap().fallbacks = fallbacks;
+    }
+
+    pub fn fallbacks(&self) -> Vec<BotId> {
+        self.0.lock().unwrap().fallbacks.clone()
+    }
+
+    /// Takes the outcome of the last response if it was answered by a fallback.
+    pub fn take_outcome(&self) -> Option<FailoverOutcome> {
+        self.0.lock().unwrap().outcome.take()
+    }
+
+    fn set_outcome(&self, outcome: Option<FailoverOutcome>) {
+        self.0.lock().unwrap().outcome = outcome;
+    }
+}
+
+/// Wraps a [`BotClient`] adding retries with backoff and failover to other bots.
+///
+/// Failover only happens while nothing has been streamed yet, so a response is
+/// never mixed from different bots.
+pub struct FailoverClient {
+    client: Box<dyn BotClient>,
+    handle: FailoverHandle,
+}
+
+impl Clone for FailoverClient {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone_box(),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl FailoverClient {
+    pub fn new(client: Box<dyn BotClient>, handle: FailoverHandle) -> Self {
+        Self { client, handle }
+    }
+}
+
+impl BotClient for FailoverClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        self.client.bots()
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let mut candidates = vec![bot_id.clone()];
+        for fallback in self.handle.fallbacks() {
+            if !candidates.contains(&fallback) {
+                candidates.push(fallback);
+            }
+        }
+
+        let requested = bot_id.clone();
+        let mut client = self.client.clone_box();
+        let handle = self.handle.clone();
+        let messages = messages.to_vec();
+        let tools = tools.to_vec();
+
+        handle.set_outcome(None);
+
+        let stream = stream! {
+            let mut last_errors = Vec::new();
+
+            for (index, candidate) in candidates.iter().enumerate() {
+                let mut backoff = INITIAL_BACKOFF;
+
+                for attempt in 0..=MAX_RETRIES_PER_BOT {
+                    if attempt > 0 {
+                        log::info!(
+                            "Retrying bot {} in {:?} (attempt {} of {})",
+                            candidate.as_str(),
+                            backoff,
+                            attempt,
+                            MAX_RETRIES_PER_BOT
+                        );
+                        sleep(backoff).await;
+                        backoff *= 2;
+                    }
+
+                    let mut inner = client.send(candidate, &messages, &tools);
+                    let mut has_output = false;
+                    let mut transient_errors = None;
+
+                    while let Some(result) = inner.next().await {
+                        let (content, errors) = result.into_value_and_errors();
+
+                        if !has_output
+                            && content.is_none()
+                            && !errors.is_empty()
+                            && errors.iter().all(is_transient)
+                        {
+                            transient_errors = Some(errors);
+                            break;
+                        }
+
+                        if !has_output && index > 0 {
+                            handle.set_outcome(Some(FailoverOutcome {
+                                requested: requested.clone(),
+                                answered_by: candidate.clone(),
+                            }));
+                        }
+                        has_output = true;
+
+                        if let Some(content) = content {
+                            yield ClientResult::new_ok(content);
+                        }
+                        if !errors.is_empty() {
+                            yield ClientResult::new_err(errors);
+                        }
+                    }
+
+                    match transient_errors {
+                        Some(errors) => last_errors = errors,
+                        None => return,
+                    }
+                }
+
+                if index + 1 < candidates.len() {
+                    log::warn!(
+                        "Bot {} keeps failing, failing over to {}",
+                        candidate.as_str(),
+                        candidates[index + 1].as_str()
+                    );
+                }
+            }
+
+            yield ClientResult::new_err(last_errors);
+        };
+
+        Box::pin(stream)
+    }
+}
+
+/// Parses a comma or newline separated list of model names or ids, as typed by the user.
+pub fn parse_fallback_models(text: &str) -> Vec<String> {
+    text.split([',', '\n'])
+        .map(str::trim)
+        .filter(|model| !model.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether the error is worth retrying, like rate limits, server errors or
+/// network failures.
+fn is_transient(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::Network => true,
+        ClientErrorKind::Response => {
+            is_rate_limited(error) || matches!(http_status(error), Some(500..=599))
+        }
+        _ => false,
+    }
+}
+
+/// Whether the error reports that a rate limit or quota was hit.
+pub fn is_rate_limited(error: &ClientError) -> bool {
+    if !matches!(error.kind(), ClientErrorKind::Response) {
+        return false;
+    }
+
+    http_status(error) == Some(429) || error.to_string().to_lowercase().contains("rate limit")
+}
+
+/// The HTTP status the error was caused by, if known.
+///
+/// Taken from the underlying `reqwest` error when there is one. Clients only
+/// report the status in their messages otherwise, as "status 429" or as the
+/// status line, like "429 Too Many Requests".
+fn http_status(error: &ClientError) -> Option<u16> {
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        if let Some(status) = error
+            .downcast_ref::<reqwest::Error>()
+            .and_then(reqwest::Error::status)
+        {
+            return Some(status.as_u16());
+        }
+        source = error.source();
+    }
+
+    STATUS_PATTERN
+        .captures(&error.to_string())
+        .and_then(|captures| captures.get(1).or(captures.get(2)))
+        .and_then(|status| status.as_str().parse().ok())
+}
+
+static STATUS_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i:\bstatus(?: code)?):?\s*([1-5]\d\d)\b|\b([1-5]\d\d) [A-Z][a-z]+").unwrap()
+});
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::data::test_utils::{Response, block_on, stand_in_server};
+
+    const REPLY: &str = "data: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"b\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"Hi\"},\"finish_reason\":null}]}\n\ndata: [DONE]\n\n";
+
+    /// Sends a message to bot `a` of the stand-in server, with `b` as its fallback.
+    fn send(url: String) -> (Vec<ClientResult<MessageContent>>, FailoverHandle) {
+        let handle = FailoverHandle::default();
+        handle.set_fallbacks(vec![BotId::new("b")]);
+        let mut client = FailoverClient::new(Box::new(OpenAiClient::new(url)), handle.clone());
+
+        let messages = vec![Message {
+            from: EntityId::User,
+            content: MessageContent {
+                text: "Hello".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }];
+        let results = block_on(
+            client
+                .send(&BotId::new("a"), &messages, &[])
+                .collect::<Vec<_>>(),
+        );
+        (results, handle)
+    }
+
+    fn requests_to(requests: &[String], model: &str) -> usize {
+        let model = format!(r#""model":"{}""#, model);
+        requests.iter().filter(|r| r.contains(&model)).count()
+    }
+
+    #[test]
+    fn test_retries_then_fails_over() {
+        let (url, requests) = stand_in_server(|request| {
+            if request.contains(r#""model":"a""#) {
+                Response {
+                    body: r#"{"error":{"message":"Rate limit reached"}}"#.to_string(),
+                    ..Response::error("429 Too Many Requests")
+                }
+            } else {
+                Response::event_stream(REPLY)
+            }
+        });
+
+        let (results, handle) = send(url);
+        let (content, errors) = results.into_iter().last().unwrap().into_value_and_errors();
+        assert!(errors.is_empty());
+        assert_eq!(content.unwrap().text, "Hi");
+        assert_eq!(
+            handle.take_outcome(),
+            Some(FailoverOutcome {
+                requested: BotId::new("a"),
+                answered_by: BotId::new("b"),
+            })
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests_to(&requests, "a"),
+            1 + MAX_RETRIES_PER_BOT as usize
+        );
+        assert_eq!(requests_to(&requests, "b"), 1);
+    }
+
+    #[test]
+    fn test_client_errors_are_not_retried() {
+        let (url, requests) = stand_in_server(|_| Response::error("401 Unauthorized"));
+
+        let (results, handle) = send(url);
+        let (_, errors) = results.into_iter().last().unwrap().into_value_and_errors();
+        assert!(!errors.is_empty());
+        assert_eq!(handle.take_outcome(), None);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_http_status() {
+        let error = |message: &str| ClientError::new(ClientErrorKind::Response, message.into());
+        assert_eq!(
+            http_status(&error("Request failed with status 503")),
+            Some(503)
+        );
+        assert_eq!(http_status(&error("HTTP status: 429")), Some(429));
+        assert_eq!(
+            http_status(&error("Got 429 Too Many Requests from the server")),
+            Some(429)
+        );
+        assert_eq!(http_status(&error("Model gpt-4 is not available")), None);
+        assert!(is_transient(&error("Request failed with status 502")));
+        assert!(!is_transient(&error("Request failed with status 404")));
+    }
+}
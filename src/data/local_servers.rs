@@ -0,0 +1,199 @@
+//! Discovery of inference servers running on this machine.
+//!
+//! Well-known ports of popular local servers are probed concurrently through their
+//! OpenAI-compatible models endpoint.
+
+use futures::future::{self, Either};
+use moly_kit::aitk::utils::asynchronous::sleep;
+use serde_json::Value;
+use std::future::Future;
+use std::pin::pin;
+use std::time::Duration;
+
+use super::providers::ProviderType;
+
+/// How long to wait for a server to answer before considering it's not there.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+struct KnownLocalServer {
+    name: &'static str,
+    port: u16,
+    /// Path of the OpenAI-compatible API, without trailing slash.
+    api_path: &'static str,
+    provider_type: ProviderType,
+}
+
+const KNOWN_LOCAL_SERVERS: &[KnownLocalServer] = &[
+    KnownLocalServer {
+        name: "Ollama",
+        port: 11434,
+        api_path: "/v1",
+        provider_type: ProviderType::OpenAi,
+    },
+    KnownLocalServer {
+        name: "LM Studio",
+        port: 1234,
+        api_path: "/v1",
+        provider_type: ProviderType::OpenAi,
+    },
+    KnownLocalServer {
+        name: "llama.cpp",
+        port: 8080,
+        api_path: "/v1",
+        provider_type: ProviderType::OpenAi,
+    },
+    KnownLocalServer {
+        name: "vLLM",
+        port: 8000,
+        api_path: "/v1",
+        provider_type: ProviderType::OpenAi,
+    },
+    KnownLocalServer {
+        name: "MolyServer",
+        port: 8765,
+        api_path: "/api/v1",
+        provider_type: ProviderType::MolyServer,
+    },
+];
+
+/// An inference server found running locally.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiscoveredServer {
+    pub name: String,
+    /// Base URL of its OpenAI-compatible API, ready to be used as a provider URL.
+    pub url: String,
+    pub provider_type: ProviderType,
+    /// Ids of the models it currently serves.
+    pub models: Vec<String>,
+}
+
+/// State of the last local servers scan.
+#[derive(Clone, Debug, Default)]
+pub struct LocalServersScan {
+    pub scanning: bool,
+    pub servers: Vec<DiscoveredServer>,
+}
+
+/// Probes the well-known ports of local inference servers concurrently, returning
+/// the ones that answered.
+pub async fn scan_local_servers() -> Vec<DiscoveredServer> {
+    let probes = KNOWN_LOCAL_SERVERS.iter().map(|known| {
+        let url = format!("http://localhost:{}{}", known.port, known.api_path);
+        probe_server(known.name, url, known.provider_type.clone())
+    });
+
+    future::join_all(probes)
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Checks if an OpenAI-compatible server answers at the given base URL.
+///
+/// The `name` is only a guess based on the port, and it's replaced if the server
+/// identifies itself through the owner of its models.
+pub async fn probe_server(
+    name: &str,
+    url: String,
+    provider_type: ProviderType,
+) -> Option<DiscoveredServer> {
+    let models_url = format!("{}/models", url);
+    let body = with_timeout(PROBE_TIMEOUT, async {
+        reqwest::get(&models_url)
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await
+    })
+    .await?
+    .ok()?;
+
+    // Anything answering on these ports that doesn't list models is not an inference server.
+    let data = body["data"].as_array()?;
+
+    let models = data
+        .iter()
+        .filter_map(|model| model["id"].as_str().map(str::to_string))
+        .collect();
+
+    let name = data
+        .iter()
+        .find_map(|model| server_name_from_owner(model["owned_by"].as_str()?))
+        .unwrap_or(name);
+
+    Some(DiscoveredServer {
+        name: name.to_string(),
+        url,
+        provider_type,
+        models,
+    })
+}
+
+/// Identifies servers by the `owned_by` value they report for their models.
+fn server_name_from_owner(owner: &str) -> Option<&'static str> {
+    match owner {
+        "llamacpp" => Some("llama.cpp"),
+        "vllm" => Some("vLLM"),
+        "library" => Some("Ollama"),
+        _ => None,
+    }
+}
+
+async fn with_timeout<T>(timeout: Duration, future: impl Future<Output = T>) -> Option<T> {
+    match future::select(pin!(future), pin!(sleep(timeout))).await {
+        Either::Left((value, _)) => Some(value),
+        Either::Right(_) => None,
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::data::test_utils::{Response, block_on, stand_in_server};
+    use std::net::TcpListener;
+
+    /// Binds a stand-in server answering every request with `body`.
+    fn models_server(body: &'static str) -> String {
+        let (url, _) = stand_in_server(move |_| Response::json(body));
+        format!("{}/v1", url)
+    }
+
+    #[test]
+    fn test_probe_identifies_server() {
+        let url = models_server(
+            r#"{"object":"list","data":[{"id":"qwen3:8b","owned_by":"library"},{"id":"llama3.2","owned_by":"library"}]}"#,
+        );
+
+        let server = block_on(probe_server("LM Studio", url.clone(), ProviderType::OpenAi))
+            .expect("the stand-in server should be found");
+
+        assert_eq!(server.name, "Ollama");
+        assert_eq!(server.url, url);
+        assert_eq!(server.models, vec!["qwen3:8b", "llama3.2"]);
+    }
+
+    #[test]
+    fn test_probe_ignores_other_servers() {
+        let url = models_server(r#"{"status":"ok"}"#);
+        assert_eq!(
+            block_on(probe_server("vLLM", url, ProviderType::OpenAi)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_probe_closed_port() {
+        // Bind and drop to get a port nothing listens on.
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let url = format!("http://127.0.0.1:{}/v1", port);
+        assert_eq!(
+            block_on(probe_server("vLLM", url, ProviderType::OpenAi)),
+            None
+        );
+    }
+}
@@ -0,0 +1,630 @@
+//! Resources and prompts of the MCP servers, used as context of messages.
+//!
+//! The tool manager only exposes tools, so separate client sessions are opened
+//! to the servers the first time their resources or prompts are needed. Sessions
+//! are kept to receive the updates of subscribed resources, and dropped when their
+//! servers are restarted or stopped. Connecting is serialized per server.
+//!
+//! Only servers reached over HTTP or SSE get a session. A session to a server
+//! started as a command would spawn a second process of it, with its own state,
+//! so their resources and prompts are not offered until the tool manager shares
+//! the connection it already has.
+
+use indexmap::IndexMap;
+use makepad_widgets::{Cx, DefaultNone};
+use moly_kit::prelude::*;
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+use std::sync::{LazyLock, Mutex};
+
+use super::mcp_servers::McpServer;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct McpResource {
+    pub server: String,
+    pub uri: String,
+    pub name: String,
+    pub description: String,
+    pub mime_type: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct McpPromptArgument {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct McpPrompt {
+    pub server: String,
+    pub name: String,
+    pub description: String,
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+/// A prompt rendered by its server, ready to be put in the prompt input.
+#[derive(Clone, Debug, Default)]
+pub struct RenderedPrompt {
+    pub text: String,
+    /// Resources and images embedded in the prompt.
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CatalogState {
+    Unloaded,
+    Loading,
+    Loaded,
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum McpContextAction {
+    None,
+    /// The resources or prompts known of the servers changed.
+    CatalogChanged,
+    /// A subscribed resource changed, so attached copies of it are outdated.
+    ResourceUpdated {
+        server: String,
+        uri: String,
+    },
+}
+
+struct Context {
+    /// Servers to connect to, with their variables already expanded.
+    servers: IndexMap<String, McpServer>,
+    #[cfg(not(target_arch = "wasm32"))]
+    sessions: HashMap<String, Session>,
+    /// Held while connecting to a server, so concurrent requests share a session.
+    #[cfg(not(target_arch = "wasm32"))]
+    connecting: HashMap<String, Arc<futures::lock::Mutex<()>>>,
+    catalog: CatalogState,
+    resources: Vec<McpResource>,
+    prompts: Vec<McpPrompt>,
+    /// Bumped on every change of the servers, to discard work for older ones.
+    generation: u64,
+}
+
+static CONTEXT: LazyLock<Mutex<Context>> = LazyLock::new(|| {
+    Mutex::new(Context {
+        servers: IndexMap::new(),
+        #[cfg(not(target_arch = "wasm32"))]
+        sessions: HashMap::new(),
+        #[cfg(not(target_arch = "wasm32"))]
+        connecting: HashMap::new(),
+        catalog: CatalogState::Unloaded,
+        resources: Vec::new(),
+        prompts: Vec::new(),
+        generation: 0,
+    })
+});
+
+fn invalidate(context: &mut Context) {
+    context.generation += 1;
+    context.catalog = CatalogState::Unloaded;
+    context.resources.clear();
+    context.prompts.clear();
+}
+
+/// Registers a server, replacing its session if it was already connected.
+///
+/// Servers started as a command are forgotten instead, see the module docs.
+pub fn set_server(server_id: &str, server: McpServer) {
+    if server.command.is_some() {
+        remove_server(server_id);
+        return;
+    }
+
+    {
+        let mut context = CONTEXT.lock().unwrap();
+        context.servers.insert(server_id.to_string(), server);
+        #[cfg(not(target_arch = "wasm32"))]
+        context.sessions.remove(server_id);
+        invalidate(&mut context);
+    }
+    Cx::post_action(McpContextAction::CatalogChanged);
+}
+
+/// Forgets a server that was stopped, closing its session.
+pub fn remove_server(server_id: &str) {
+    {
+        let mut context = CONTEXT.lock().unwrap();
+        if context.servers.shift_remove(server_id).is_none() {
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        context.sessions.remove(server_id);
+        invalidate(&mut context);
+    }
+    Cx::post_action(McpContextAction::CatalogChanged);
+}
+
+pub fn resources() -> Vec<McpResource> {
+    CONTEXT.lock().unwrap().resources.clone()
+}
+
+pub fn prompts() -> Vec<McpPrompt> {
+    CONTEXT.lock().unwrap().prompts.clone()
+}
+
+pub fn is_loading() -> bool {
+    CONTEXT.lock().unwrap().catalog == CatalogState::Loading
+}
+
+/// Lists the resources and prompts of the servers, unless already known.
+///
+/// Posts [`McpContextAction::CatalogChanged`] when done.
+pub fn load_catalog() {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use moly_kit::aitk::utils::asynchronous::spawn;
+
+        let (generation, server_ids) = {
+            let mut context = CONTEXT.lock().unwrap();
+            if context.catalog != CatalogState::Unloaded || context.servers.is_empty() {
+                return;
+            }
+            context.catalog = CatalogState::Loading;
+            let server_ids = context.servers.keys().cloned().collect::<Vec<_>>();
+            (context.generation, server_ids)
+        };
+        Cx::post_action(McpContextAction::CatalogChanged);
+
+        spawn(async move {
+            let mut resources = Vec::new();
+            let mut prompts = Vec::new();
+
+            for server_id in server_ids {
+                match session(&server_id).await {
+                    Ok(session) => {
+                        resources.extend(session.list_resources(&server_id).await);
+                        prompts.extend(session.list_prompts(&server_id).await);
+                    }
+                    Err(e) => {
+                        ::log::warn!(
+                            "Could not connect to MCP server '{}' for its resources and prompts: {}",
+                            server_id,
+                            e
+                        );
+                    }
+                }
+            }
+
+            {
+                let mut context = CONTEXT.lock().unwrap();
+                if context.generation != generation {
+                    return;
+                }
+                context.catalog = CatalogState::Loaded;
+                context.resources = resources;
+                context.prompts = prompts;
+            }
+            Cx::post_action(McpContextAction::CatalogChanged);
+        });
+    }
+}
+
+/// Lists the catalog again, e.g. because a server notified it changed.
+#[cfg(not(target_arch = "wasm32"))]
+fn reload_catalog() {
+    {
+        let mut context = CONTEXT.lock().unwrap();
+        if context.catalog == CatalogState::Loading {
+            return;
+        }
+        context.catalog = CatalogState::Unloaded;
+    }
+    load_catalog();
+}
+
+/// Reads the resource as attachments, one for each of its contents.
+pub async fn read_resource(resource: &McpResource) -> Result<Vec<Attachment>, String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        session(&resource.server).await?.read(resource).await
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = resource;
+        Err("MCP servers are not supported on the web".to_string())
+    }
+}
+
+/// Asks the server to notify changes of the resource, if it supports it.
+pub async fn subscribe(resource: &McpResource) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        match session(&resource.server).await {
+            Ok(session) => session.subscribe(resource).await,
+            Err(e) => ::log::warn!("Could not subscribe to {}: {}", resource.uri, e),
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = resource;
+}
+
+/// Renders the prompt with the given arguments.
+pub async fn get_prompt(
+    prompt: &McpPrompt,
+    arguments: HashMap<String, String>,
+) -> Result<RenderedPrompt, String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        session(&prompt.server)
+            .await?
+            .get_prompt(prompt, arguments)
+            .await
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (prompt, arguments);
+        Err("MCP servers are not supported on the web".to_string())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+use session::{Session, session};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod session {
+    use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+    use rmcp::model::{
+        GetPromptRequestParam, PromptMessageContent, ReadResourceRequestParam, ResourceContents,
+        ResourceUpdatedNotificationParam, SubscribeRequestParam,
+    };
+    use rmcp::service::{NotificationContext, Peer, RunningService};
+    use rmcp::transport::sse_client::SseClientConfig;
+    use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
+    use rmcp::transport::{SseClientTransport, StreamableHttpClientTransport};
+    use rmcp::{ClientHandler, RoleClient, ServiceExt};
+    use std::str::FromStr;
+
+    use super::*;
+
+    /// Receives the notifications of a server.
+    #[derive(Clone)]
+    pub(super) struct Handler {
+        server_id: String,
+    }
+
+    impl ClientHandler for Handler {
+        fn on_resource_updated(
+            &self,
+            params: ResourceUpdatedNotificationParam,
+            _context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            Cx::post_action(McpContextAction::ResourceUpdated {
+                server: self.server_id.clone(),
+                uri: params.uri,
+            });
+            std::future::ready(())
+        }
+
+        fn on_resource_list_changed(
+            &self,
+            _context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            reload_catalog();
+            std::future::ready(())
+        }
+
+        fn on_prompt_list_changed(
+            &self,
+            _context: NotificationContext<RoleClient>,
+        ) -> impl Future<Output = ()> + Send + '_ {
+            reload_catalog();
+            std::future::ready(())
+        }
+    }
+
+    /// A connected session, cheap to clone out of the context.
+    #[derive(Clone)]
+    pub(super) struct SessionPeer {
+        peer: Peer<RoleClient>,
+        resources: bool,
+        subscribe: bool,
+        prompts: bool,
+    }
+
+    pub(super) struct Session {
+        /// Keeps the connection open, it's closed when dropped.
+        _service: RunningService<RoleClient, Handler>,
+        peer: SessionPeer,
+    }
+
+    /// The session of the server, connecting to it if needed.
+    pub(super) async fn session(server_id: &str) -> Result<SessionPeer, String> {
+        let connecting = CONTEXT
+            .lock()
+            .unwrap()
+            .connecting
+            .entry(server_id.to_string())
+            .or_default()
+            .clone();
+        // Requests waiting here find the session of the first one once it's done.
+        let _connecting = connecting.lock().await;
+
+        let (server, generation) = {
+            let context = CONTEXT.lock().unwrap();
+            if let Some(session) = context.sessions.get(server_id) {
+                return Ok(session.peer.clone());
+            }
+            let server = context
+                .servers
+                .get(server_id)
+                .cloned()
+                .ok_or_else(|| format!("Unknown MCP server '{}'", server_id))?;
+            (server, context.generation)
+        };
+
+        let session = connect(server_id, &server).await?;
+
+        let mut context = CONTEXT.lock().unwrap();
+        if context.generation != generation {
+            return Err("The MCP servers were reloaded".to_string());
+        }
+        let peer = session.peer.clone();
+        context.sessions.insert(server_id.to_string(), session);
+        Ok(peer)
+    }
+
+    /// Http client sending the headers configured for the server.
+    fn http_client(server: &McpServer) -> Result<reqwest::Client, String> {
+        let mut headers = HeaderMap::new();
+        for (key, value) in &server.headers {
+            let name = HeaderName::from_str(key).map_err(|e| e.to_string())?;
+            let value = HeaderValue::from_str(value).map_err(|e| e.to_string())?;
+            headers.insert(name, value);
+        }
+
+        reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .map_err(|e| e.to_string())
+    }
+
+    async fn connect(server_id: &str, server: &McpServer) -> Result<Session, String> {
+        let handler = Handler {
+            server_id: server_id.to_string(),
+        };
+
+        let service = match server.to_transport() {
+            Some(McpTransport::Stdio(_)) => {
+                return Err("Servers started as a command have no context session".to_string());
+            }
+            Some(McpTransport::Http(url)) => {
+                let transport = StreamableHttpClientTransport::with_client(
+                    http_client(server)?,
+                    StreamableHttpClientTransportConfig::with_uri(url),
+                );
+                handler.serve(transport).await
+            }
+            Some(McpTransport::Sse(url)) => {
+                let config = SseClientConfig {
+                    sse_endpoint: url.into(),
+                    ..Default::default()
+                };
+                let transport = SseClientTransport::start_with_client(http_client(server)?, config)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                handler.serve(transport).await
+            }
+            None => return Err("Missing a command or url to connect to".to_string()),
+        }
+        .map_err(|e| e.to_string())?;
+
+        let capabilities = service
+            .peer_info()
+            .map(|info| info.capabilities.clone())
+            .unwrap_or_default();
+        let peer = SessionPeer {
+            peer: service.peer().clone(),
+            resources: capabilities.resources.is_some(),
+            subscribe: capabilities
+                .resources
+                .as_ref()
+                .and_then(|resources| resources.subscribe)
+                .unwrap_or(false),
+            prompts: capabilities.prompts.is_some(),
+        };
+
+        Ok(Session {
+            _service: service,
+            peer,
+        })
+    }
+
+    impl SessionPeer {
+        pub(super) async fn list_resources(&self, server_id: &str) -> Vec<McpResource> {
+            if !self.resources {
+                return Vec::new();
+            }
+
+            match self.peer.list_all_resources().await {
+                Ok(resources) => resources
+                    .into_iter()
+                    .map(|resource| McpResource {
+                        server: server_id.to_string(),
+                        uri: resource.uri.clone(),
+                        name: resource.name.clone(),
+                        description: resource.description.clone().unwrap_or_default(),
+                        mime_type: resource.mime_type.clone(),
+                    })
+                    .collect(),
+                Err(e) => {
+                    ::log::warn!("Could not list resources of '{}': {}", server_id, e);
+                    Vec::new()
+                }
+            }
+        }
+
+        pub(super) async fn list_prompts(&self, server_id: &str) -> Vec<McpPrompt> {
+            if !self.prompts {
+                return Vec::new();
+            }
+
+            match self.peer.list_all_prompts().await {
+                Ok(prompts) => prompts
+                    .into_iter()
+                    .map(|prompt| McpPrompt {
+                        server: server_id.to_string(),
+                        name: prompt.name,
+                        description: prompt.description.unwrap_or_default(),
+                        arguments: prompt
+                            .arguments
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|argument| McpPromptArgument {
+                                name: argument.name,
+                                description: argument.description.unwrap_or_default(),
+                                required: argument.required.unwrap_or(false),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+                Err(e) => {
+                    ::log::warn!("Could not list prompts of '{}': {}", server_id, e);
+                    Vec::new()
+                }
+            }
+        }
+
+        pub(super) async fn read(&self, resource: &McpResource) -> Result<Vec<Attachment>, String> {
+            let result = self
+                .peer
+                .read_resource(ReadResourceRequestParam {
+                    uri: resource.uri.clone(),
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+
+            result
+                .contents
+                .into_iter()
+                .map(|contents| contents_to_attachment(&resource.name, contents))
+                .collect()
+        }
+
+        pub(super) async fn subscribe(&self, resource: &McpResource) {
+            if !self.subscribe {
+                return;
+            }
+
+            let params = SubscribeRequestParam {
+                uri: resource.uri.clone(),
+            };
+            if let Err(e) = self.peer.subscribe(params).await {
+                ::log::warn!("Could not subscribe to {}: {}", resource.uri, e);
+            }
+        }
+
+        pub(super) async fn get_prompt(
+            &self,
+            prompt: &McpPrompt,
+            arguments: HashMap<String, String>,
+        ) -> Result<RenderedPrompt, String> {
+            let arguments = arguments
+                .into_iter()
+                .map(|(name, value)| (name, serde_json::Value::String(value)))
+                .collect();
+            let result = self
+                .peer
+                .get_prompt(GetPromptRequestParam {
+                    name: prompt.name.clone(),
+                    arguments: Some(arguments),
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let mut rendered = RenderedPrompt::default();
+            let mut texts = Vec::new();
+            for message in result.messages {
+                match message.content {
+                    PromptMessageContent::Text { text } => texts.push(text),
+                    PromptMessageContent::Image { image } => {
+                        let data = BASE64.decode(&image.data).map_err(|e| e.to_string())?;
+                        rendered.attachments.push(Attachment::from_bytes(
+                            prompt.name.clone(),
+                            Some(image.mime_type.clone()),
+                            &data,
+                        ));
+                    }
+                    PromptMessageContent::Resource { resource } => {
+                        let contents = resource.resource.clone();
+                        rendered
+                            .attachments
+                            .push(contents_to_attachment(&prompt.name, contents)?);
+                    }
+                    // Links are left for the user to attach from the resources.
+                    _ => {}
+                }
+            }
+            rendered.text = texts.join("\n\n");
+
+            Ok(rendered)
+        }
+    }
+
+    /// Names the attachment after the last segment of the uri, if any.
+    fn attachment_name(fallback: &str, uri: &str) -> String {
+        uri.trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|segment| !segment.is_empty() && !segment.contains(':'))
+            .unwrap_or(fallback)
+            .to_string()
+    }
+
+    fn contents_to_attachment(
+        name: &str,
+        contents: ResourceContents,
+    ) -> Result<Attachment, String> {
+        match contents {
+            ResourceContents::TextResourceContents {
+                uri,
+                mime_type,
+                text,
+                ..
+            } => Ok(Attachment::from_bytes(
+                attachment_name(name, &uri),
+                // Servers may just say "text".
+                Some(
+                    mime_type
+                        .filter(|mime_type| mime_type.contains('/'))
+                        .unwrap_or_else(|| "text/plain".to_string()),
+                ),
+                text.as_bytes(),
+            )),
+            ResourceContents::BlobResourceContents {
+                uri,
+                mime_type,
+                blob,
+                ..
+            } => {
+                let data = BASE64.decode(blob).map_err(|e| e.to_string())?;
+                Ok(Attachment::from_bytes(
+                    attachment_name(name, &uri),
+                    mime_type,
+                    &data,
+                ))
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_attachment_name() {
+            assert_eq!(
+                attachment_name("readme", "file:///home/user/README.md"),
+                "README.md"
+            );
+            assert_eq!(attachment_name("schema", "postgres://db/users/"), "users");
+            assert_eq!(attachment_name("greeting", "str:hello"), "greeting");
+        }
+    }
+}
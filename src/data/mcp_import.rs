@@ -0,0 +1,327 @@
+//! Imports MCP servers configured in other clients.
+//!
+//! Claude Desktop and Cursor list servers under `mcpServers`, VS Code under
+//! `servers` in `mcp.json` or under `mcp` > `servers` in its settings. VS Code files
+//! may have comments and trailing commas.
+
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::path::PathBuf;
+
+use super::mcp_servers::{InputConfig, McpServer, McpServersConfig};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImportSource {
+    ClaudeDesktop,
+    VsCode,
+    Cursor,
+}
+
+impl ImportSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImportSource::ClaudeDesktop => "Claude Desktop",
+            ImportSource::VsCode => "VS Code",
+            ImportSource::Cursor => "Cursor",
+        }
+    }
+}
+
+/// Why an imported server may already be configured.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Duplicate {
+    /// A server with the same id exists, importing replaces it.
+    Id,
+    /// The server with this id runs the same command or url.
+    Command(String),
+}
+
+/// A server found in the config of another client.
+#[derive(Clone, Debug)]
+pub struct ImportCandidate {
+    pub source: ImportSource,
+    pub path: PathBuf,
+    pub id: String,
+    pub server: McpServer,
+    /// Inputs of the file the server references.
+    pub inputs: Vec<InputConfig>,
+    pub duplicate: Option<Duplicate>,
+}
+
+/// Config files of the other clients, in their standard locations.
+pub fn config_files() -> Vec<(ImportSource, PathBuf)> {
+    let mut files = Vec::new();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if let Some(dirs) = directories::BaseDirs::new() {
+            let config = dirs.config_dir();
+            let home = dirs.home_dir();
+
+            files.push((
+                ImportSource::ClaudeDesktop,
+                config.join("Claude").join("claude_desktop_config.json"),
+            ));
+            files.push((
+                ImportSource::VsCode,
+                config.join("Code").join("User").join("mcp.json"),
+            ));
+            files.push((
+                ImportSource::VsCode,
+                config.join("Code").join("User").join("settings.json"),
+            ));
+            files.push((ImportSource::Cursor, home.join(".cursor").join("mcp.json")));
+        }
+
+        // Workspace configs of the directory Moly was started from.
+        if let Ok(cwd) = std::env::current_dir() {
+            files.push((ImportSource::VsCode, cwd.join(".vscode").join("mcp.json")));
+            files.push((ImportSource::Cursor, cwd.join(".cursor").join("mcp.json")));
+        }
+    }
+
+    files
+}
+
+/// Servers found in the config files that exist, flagging the ones already in
+/// `config`.
+pub fn find_candidates(config: &McpServersConfig) -> Vec<ImportCandidate> {
+    let mut candidates: Vec<ImportCandidate> = Vec::new();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    for (source, path) in config_files() {
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let (servers, inputs) = match parse_servers(&text) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                ::log::warn!("Could not read MCP servers from {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        for (id, server) in servers {
+            let duplicate = find_duplicate(config, &candidates, &id, &server);
+            let ids = server.input_ids();
+            candidates.push(ImportCandidate {
+                source,
+                path: path.clone(),
+                inputs: inputs
+                    .iter()
+                    .filter(|input| ids.contains(&input.id))
+                    .cloned()
+                    .collect(),
+                id,
+                server,
+                duplicate,
+            });
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    let _ = config;
+
+    candidates
+}
+
+/// Servers and inputs declared in the config file of a client.
+pub fn parse_servers(
+    text: &str,
+) -> Result<(IndexMap<String, McpServer>, Vec<InputConfig>), String> {
+    let json: Value = serde_json::from_str(&strip_jsonc(text)).map_err(|e| e.to_string())?;
+
+    let servers = json
+        .get("mcpServers")
+        .or_else(|| json.get("servers"))
+        .or_else(|| json.get("mcp").and_then(|mcp| mcp.get("servers")))
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+
+    let parsed = servers
+        .into_iter()
+        .filter_map(
+            |(id, server)| match serde_json::from_value::<McpServer>(server) {
+                Ok(server) => Some((id, server)),
+                Err(e) => {
+                    ::log::warn!("Skipping MCP server '{}': {}", id, e);
+                    None
+                }
+            },
+        )
+        .collect();
+
+    let inputs = json
+        .get("inputs")
+        .or_else(|| json.get("mcp").and_then(|mcp| mcp.get("inputs")))
+        .cloned()
+        .and_then(|inputs| serde_json::from_value(inputs).ok())
+        .unwrap_or_default();
+
+    Ok((parsed, inputs))
+}
+
+fn find_duplicate(
+    config: &McpServersConfig,
+    candidates: &[ImportCandidate],
+    id: &str,
+    server: &McpServer,
+) -> Option<Duplicate> {
+    if config.servers.contains_key(id) || candidates.iter().any(|c| c.id == id) {
+        return Some(Duplicate::Id);
+    }
+
+    config
+        .servers
+        .iter()
+        .chain(candidates.iter().map(|c| (&c.id, &c.server)))
+        .find(|(_, existing)| same_command(existing, server))
+        .map(|(existing_id, _)| Duplicate::Command(existing_id.clone()))
+}
+
+fn same_command(a: &McpServer, b: &McpServer) -> bool {
+    match (&a.command, &b.command, &a.url, &b.url) {
+        (Some(a_command), Some(b_command), _, _) => a_command == b_command && a.args == b.args,
+        (None, None, Some(a_url), Some(b_url)) => {
+            a_url.trim_end_matches('/') == b_url.trim_end_matches('/')
+        }
+        _ => false,
+    }
+}
+
+/// Adds the servers to the config, replacing the ones with the same id.
+///
+/// Inputs they reference are declared too, unless already declared.
+pub fn merge(config: &mut McpServersConfig, candidates: Vec<ImportCandidate>) {
+    for candidate in candidates {
+        for input in candidate.inputs {
+            if config.get_input(&input.id).is_none() {
+                config.add_input(input);
+            }
+        }
+        config.add_server(candidate.id, candidate.server);
+    }
+}
+
+/// Removes comments and trailing commas, as allowed in VS Code files.
+fn strip_jsonc(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    // Where the last comma was written, until something other than whitespace
+    // or comments follows it.
+    let mut comma: Option<usize> = None;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                '\\' => output.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        let next = chars.peek().copied();
+        match (c, next) {
+            ('/', Some('/')) => while chars.next_if(|&c| c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ if c.is_whitespace() => output.push(c),
+            _ => {
+                if let Some(index) = comma.take().filter(|_| matches!(c, '}' | ']')) {
+                    output.remove(index);
+                }
+                if c == ',' {
+                    comma = Some(output.len());
+                }
+                in_string = c == '"';
+                output.push(c);
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_servers_of_other_clients() {
+        let claude = r#"{
+            "mcpServers": {
+                "filesystem": {
+                    "command": "npx",
+                    "args": ["-y", "@modelcontextprotocol/server-filesystem", "/tmp"]
+                }
+            }
+        }"#;
+        let (servers, _) = parse_servers(claude).unwrap();
+        assert_eq!(servers["filesystem"].command.as_deref(), Some("npx"));
+
+        let vscode_settings = r#"{
+            // Comments are fine in VS Code settings.
+            "editor.fontSize": 14,
+            "mcp": {
+                "inputs": [
+                    {"type": "promptString", "id": "token", "description": "Token", "password": true},
+                ],
+                "servers": {
+                    "github": {"type": "http", "url": "https://api.githubcopilot.com/mcp/"}, /* remote */
+                },
+            },
+        }"#;
+        let (servers, inputs) = parse_servers(vscode_settings).unwrap();
+        assert!(servers["github"].is_network());
+        assert_eq!(inputs[0].id, "token");
+
+        // A trailing comma can be followed by a comment.
+        let (servers, _) = parse_servers(
+            "{\"servers\": {\"web\": {\"url\": \"http://localhost/mcp\"}, // local\n}}",
+        )
+        .unwrap();
+        assert!(servers["web"].is_network());
+
+        // Slashes in strings are not comments.
+        let (servers, _) =
+            parse_servers(r#"{"servers": {"web": {"url": "http://localhost/mcp"}}}"#).unwrap();
+        assert_eq!(servers["web"].url.as_deref(), Some("http://localhost/mcp"));
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let mut config = McpServersConfig::new();
+        config.add_server(
+            "files".to_string(),
+            McpServer::stdio("npx".to_string(), vec!["server-filesystem".to_string()]),
+        );
+
+        let same_id = McpServer::stdio("uvx".to_string(), vec![]);
+        assert_eq!(
+            find_duplicate(&config, &[], "files", &same_id),
+            Some(Duplicate::Id)
+        );
+
+        let same_command =
+            McpServer::stdio("npx".to_string(), vec!["server-filesystem".to_string()]);
+        assert_eq!(
+            find_duplicate(&config, &[], "filesystem", &same_command),
+            Some(Duplicate::Command("files".to_string()))
+        );
+
+        assert_eq!(find_duplicate(&config, &[], "other", &same_id), None);
+    }
+}
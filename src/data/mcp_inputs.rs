@@ -0,0 +1,242 @@
+//! Answers to the `inputs` of the MCP servers config.
+//!
+//! Answers are kept out of the config, so they don't show up in its editor nor get
+//! synced. Passwords go to the keychain of the OS, other answers to a file next to
+//! the preferences.
+
+use makepad_widgets::{Cx, DefaultNone};
+use moly_kit::aitk::utils::asynchronous::spawn;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use crate::shared::utils::filesystem;
+
+use super::mcp_servers::{InputConfig, McpServersConfig};
+
+const INPUTS_DIR: &str = "preferences";
+const INPUTS_FILENAME: &str = "mcp_inputs.json";
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+const KEYCHAIN_SERVICE: &str = "org.moxin.moly.mcp-inputs";
+
+/// Answers that aren't passwords, as saved to the file.
+static ANSWERS: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(Default::default);
+
+/// Passwords read from the keychain on load or answered since, `None` when there
+/// is none for the input, so the keychain is only asked once.
+static SECRETS: LazyLock<Mutex<HashMap<String, Option<String>>>> = LazyLock::new(Default::default);
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum McpInputsAction {
+    None,
+    /// Servers reference inputs without an answer, which should be asked for.
+    Requested(Vec<InputConfig>),
+    /// The user answered the requested inputs.
+    Answered(Vec<(InputConfig, String)>),
+}
+
+fn inputs_path() -> PathBuf {
+    Path::new(INPUTS_DIR).join(INPUTS_FILENAME)
+}
+
+/// Reads the saved answers, to be called before loading the servers.
+///
+/// Passwords of inputs declared later are not read, they are asked for instead.
+pub async fn load(config: &McpServersConfig) {
+    let fs = filesystem::global();
+    if let Ok(answers) = fs
+        .read_json::<HashMap<String, String>>(&inputs_path())
+        .await
+    {
+        ANSWERS.lock().unwrap().extend(answers);
+    }
+
+    let ids = config
+        .inputs
+        .iter()
+        .filter(|input| input.password)
+        .map(|input| input.id.clone())
+        .collect::<Vec<_>>();
+    let secrets = keychain_get_all(ids).await;
+    SECRETS.lock().unwrap().extend(secrets);
+}
+
+/// The answers known for the inputs of the config, by input id.
+pub fn answers(config: &McpServersConfig) -> HashMap<String, String> {
+    config
+        .inputs
+        .iter()
+        .filter_map(|input| Some((input.id.clone(), answer(input)?)))
+        .collect()
+}
+
+fn answer(input: &InputConfig) -> Option<String> {
+    if !input.password {
+        return ANSWERS.lock().unwrap().get(&input.id).cloned();
+    }
+
+    SECRETS.lock().unwrap().get(&input.id).cloned().flatten()
+}
+
+pub fn set_answer(input: &InputConfig, value: String) {
+    if input.password {
+        SECRETS
+            .lock()
+            .unwrap()
+            .insert(input.id.clone(), Some(value.clone()));
+        let id = input.id.clone();
+        in_background(move || {
+            if let Err(e) = keychain_set(&id, &value) {
+                ::log::warn!(
+                    "Could not store MCP input '{}' in the keychain, it will be asked again on restart: {}",
+                    id,
+                    e
+                );
+            }
+        });
+    } else {
+        ANSWERS.lock().unwrap().insert(input.id.clone(), value);
+        save();
+    }
+}
+
+/// Forgets the answers to all the inputs of the config, so they are asked again.
+pub fn clear_answers(config: &McpServersConfig) {
+    for input in &config.inputs {
+        if input.password {
+            SECRETS.lock().unwrap().insert(input.id.clone(), None);
+            let id = input.id.clone();
+            in_background(move || {
+                if let Err(e) = keychain_delete(&id) {
+                    ::log::warn!(
+                        "Could not remove MCP input '{}' from the keychain: {}",
+                        id,
+                        e
+                    );
+                }
+            });
+        } else {
+            ANSWERS.lock().unwrap().remove(&input.id);
+        }
+    }
+    save();
+}
+
+/// Inputs referenced by the enabled servers that still need an answer.
+///
+/// References to inputs not declared in the config can't be asked for, so they
+/// are left out.
+fn missing_inputs(config: &McpServersConfig) -> Vec<InputConfig> {
+    let answers = answers(config);
+    let mut missing: Vec<InputConfig> = Vec::new();
+
+    for (_, server) in config.list_enabled_servers() {
+        for id in server.input_ids() {
+            if answers.contains_key(&id) || missing.iter().any(|input| input.id == id) {
+                continue;
+            }
+            if let Some(input) = config.get_input(&id) {
+                missing.push(input.clone());
+            }
+        }
+    }
+
+    missing
+}
+
+/// Asks for the missing inputs, if any.
+pub fn request_missing(config: &McpServersConfig) {
+    let missing = missing_inputs(config);
+    if !missing.is_empty() {
+        Cx::post_action(McpInputsAction::Requested(missing));
+    }
+}
+
+fn save() {
+    let answers = ANSWERS.lock().unwrap().clone();
+    spawn(async move {
+        if let Err(e) = filesystem::global()
+            .queue_write_json(inputs_path(), &answers)
+            .await
+        {
+            ::log::error!("Failed to write MCP inputs file: {:?}", e);
+        }
+    });
+}
+
+/// Runs keychain work off the UI thread, as the OS may block on it (e.g. to unlock
+/// the keychain).
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn in_background(f: impl FnOnce() + Send + 'static) {
+    crate::runtime::runtime_handle().spawn_blocking(f);
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+async fn keychain_get_all(ids: Vec<String>) -> Vec<(String, Option<String>)> {
+    crate::runtime::runtime_handle()
+        .spawn_blocking(move || {
+            ids.into_iter()
+                .map(|id| {
+                    let secret = keychain_get(&id);
+                    (id, secret)
+                })
+                .collect()
+        })
+        .await
+        .unwrap_or_default()
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn keychain_entry(id: &str) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, id)
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn keychain_get(id: &str) -> Option<String> {
+    match keychain_entry(id).and_then(|entry| entry.get_password()) {
+        Ok(secret) => Some(secret),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            ::log::warn!("Could not read MCP input '{}' from the keychain: {}", id, e);
+            None
+        }
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn keychain_set(id: &str, secret: &str) -> Result<(), String> {
+    keychain_entry(id)
+        .and_then(|entry| entry.set_password(secret))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn keychain_delete(id: &str) -> Result<(), String> {
+    match keychain_entry(id).and_then(|entry| entry.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Without a keychain, passwords only live for the session.
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn in_background(f: impl FnOnce() + Send + 'static) {
+    f();
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+async fn keychain_get_all(ids: Vec<String>) -> Vec<(String, Option<String>)> {
+    ids.into_iter().map(|id| (id, None)).collect()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn keychain_set(_id: &str, _secret: &str) -> Result<(), String> {
+    Err("No keychain available on this platform".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn keychain_delete(_id: &str) -> Result<(), String> {
+    Ok(())
+}
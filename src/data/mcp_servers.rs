@@ -1,6 +1,11 @@
 use indexmap::IndexMap;
 use moly_kit::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::chats::chat::ChatId;
+use super::mcp_variables::{self, Variables};
 
 /// Represents an input configuration for MCP servers
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,7 +19,7 @@ pub struct InputConfig {
 }
 
 /// Represents an MCP server configuration following the standard format
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct McpServer {
     // Stdio transport fields
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -40,6 +45,10 @@ pub struct McpServer {
     pub enabled: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub working_directory: Option<String>,
+    /// Seconds a call to a tool of the server can take, overriding the default of
+    /// the config. Zero means no limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
 }
 
 fn default_enabled() -> bool {
@@ -58,6 +67,14 @@ fn default_dangerous_mode_enabled() -> bool {
     false
 }
 
+fn default_tool_timeout() -> u64 {
+    120
+}
+
+fn default_max_tool_output_chars() -> usize {
+    20_000
+}
+
 impl McpServer {
     /// Create a new stdio-based MCP server
     pub fn stdio(command: String, args: Vec<String>) -> Self {
@@ -70,6 +87,7 @@ impl McpServer {
             headers: IndexMap::new(),
             enabled: true,
             working_directory: None,
+            timeout: None,
         }
     }
 
@@ -84,6 +102,7 @@ impl McpServer {
             headers: IndexMap::new(),
             enabled: true,
             working_directory: None,
+            timeout: None,
         }
     }
 
@@ -98,6 +117,7 @@ impl McpServer {
             headers: IndexMap::new(),
             enabled: true,
             working_directory: None,
+            timeout: None,
         }
     }
 
@@ -148,6 +168,93 @@ impl McpServer {
 }
 
 impl McpServer {
+    /// Ids of the inputs referenced by the fields of this server.
+    pub fn input_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = Vec::new();
+        for text in self.variable_fields() {
+            for id in mcp_variables::input_ids(text) {
+                if !ids.iter().any(|known| known == id) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        ids
+    }
+
+    /// A copy of this server with the variables of its fields expanded.
+    ///
+    /// Errs with the ids of the inputs without an answer.
+    pub fn with_variables_expanded(
+        &self,
+        inputs: &HashMap<String, String>,
+    ) -> Result<McpServer, Vec<String>> {
+        let mut variables = Variables {
+            inputs: inputs.clone(),
+            workspace_folder: None,
+            user_home: mcp_variables::user_home(),
+        };
+
+        let working_directory = self
+            .working_directory
+            .as_deref()
+            .map(|dir| variables.expand(dir))
+            .transpose()?;
+        variables.workspace_folder = working_directory
+            .clone()
+            .or_else(|| variables.user_home.clone());
+
+        let mut missing = Vec::new();
+        let mut expand = |text: &str| match variables.expand(text) {
+            Ok(expanded) => expanded,
+            Err(ids) => {
+                for id in ids {
+                    if !missing.contains(&id) {
+                        missing.push(id);
+                    }
+                }
+                text.to_string()
+            }
+        };
+
+        let server = McpServer {
+            command: self.command.as_deref().map(&mut expand),
+            args: self.args.iter().map(|arg| expand(arg)).collect(),
+            env: self
+                .env
+                .iter()
+                .map(|(key, value)| (key.clone(), expand(value)))
+                .collect(),
+            url: self.url.as_deref().map(&mut expand),
+            transport_type: self.transport_type.clone(),
+            headers: self
+                .headers
+                .iter()
+                .map(|(key, value)| (key.clone(), expand(value)))
+                .collect(),
+            enabled: self.enabled,
+            working_directory,
+            timeout: self.timeout,
+        };
+
+        if missing.is_empty() {
+            Ok(server)
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Fields where variables are expanded.
+    fn variable_fields(&self) -> impl Iterator<Item = &str> {
+        self.command
+            .iter()
+            .chain(&self.args)
+            .chain(self.env.values())
+            .chain(&self.url)
+            .chain(self.headers.values())
+            .chain(&self.working_directory)
+            .map(String::as_str)
+    }
+
     /// Convert this server configuration to a transport for the MCP manager
     #[cfg(not(target_arch = "wasm32"))]
     pub fn to_transport(&self) -> Option<McpTransport> {
@@ -184,6 +291,156 @@ impl McpServer {
     }
 }
 
+/// What to do with calls to a tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolPolicy {
+    Allow,
+    Ask,
+    Deny,
+}
+
+/// A policy for a tool of a server, optionally limited to one chat.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolPolicyRule {
+    pub server: String,
+    /// Name of the tool, or `*` for all the tools of the server.
+    pub tool: String,
+    pub policy: ToolPolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<ChatId>,
+}
+
+impl ToolPolicyRule {
+    fn matches(&self, server: &str, tool: &str) -> bool {
+        self.server == server && (self.tool == tool || self.tool == "*")
+    }
+}
+
+/// The tool name of a namespaced tool of `server_id`, if it's one of its tools.
+///
+/// Namespaced names start with the server id, followed by a separator.
+pub fn strip_server_prefix<'a>(namespaced: &'a str, server_id: &str) -> Option<&'a str> {
+    let rest = namespaced.strip_prefix(server_id)?;
+    if !rest.starts_with(|c: char| !c.is_alphanumeric()) {
+        return None;
+    }
+    Some(rest.trim_start_matches(|c: char| !c.is_alphanumeric()))
+}
+
+/// Servers to load, by id, with the variables of their fields expanded or the ids
+/// of the inputs they are missing.
+pub type ResolvedServers = IndexMap<String, Result<McpServer, Vec<String>>>;
+
+/// What to do with the loaded servers to apply a new config.
+#[derive(Debug, Default, PartialEq)]
+pub struct McpServerChanges {
+    /// Servers that are new or changed, to (re)start.
+    pub start: Vec<String>,
+    /// Servers that were removed or disabled.
+    pub stop: Vec<String>,
+}
+
+impl McpServerChanges {
+    /// Compares the servers loaded with the ones to load now.
+    pub fn between(loaded: &ResolvedServers, servers: &ResolvedServers) -> Self {
+        Self {
+            start: servers
+                .iter()
+                .filter(|(id, server)| loaded.get(*id) != Some(server))
+                .map(|(id, _)| id.clone())
+                .collect(),
+            stop: loaded
+                .keys()
+                .filter(|id| !servers.contains_key(*id))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// Id the built-in tools are namespaced with, reserved for them.
+pub const BUILTIN_SERVER_ID: &str = "builtin";
+
+/// Tools run by Moly itself, without spawning a server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuiltinTool {
+    Filesystem,
+    Fetch,
+    DateTime,
+    Calculator,
+}
+
+impl BuiltinTool {
+    pub const ALL: [BuiltinTool; 4] = [
+        BuiltinTool::Filesystem,
+        BuiltinTool::Fetch,
+        BuiltinTool::DateTime,
+        BuiltinTool::Calculator,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinTool::Filesystem => "Filesystem",
+            BuiltinTool::Fetch => "Web fetch",
+            BuiltinTool::DateTime => "Date and time",
+            BuiltinTool::Calculator => "Calculator",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            BuiltinTool::Filesystem => "List and read files inside the allowed folders",
+            BuiltinTool::Fetch => "Download web pages as readable text",
+            BuiltinTool::DateTime => "Tell the current local date and time",
+            BuiltinTool::Calculator => "Evaluate math expressions",
+        }
+    }
+}
+
+/// Which built-in tools are offered to the models.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuiltinToolsConfig {
+    #[serde(default)]
+    pub filesystem: bool,
+    #[serde(default)]
+    pub fetch: bool,
+    #[serde(default)]
+    pub datetime: bool,
+    #[serde(default)]
+    pub calculator: bool,
+    /// Folders the filesystem tools can read, with their subfolders.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<String>,
+}
+
+impl BuiltinToolsConfig {
+    pub fn is_enabled(&self, tool: BuiltinTool) -> bool {
+        match tool {
+            BuiltinTool::Filesystem => self.filesystem,
+            BuiltinTool::Fetch => self.fetch,
+            BuiltinTool::DateTime => self.datetime,
+            BuiltinTool::Calculator => self.calculator,
+        }
+    }
+
+    pub fn set_enabled(&mut self, tool: BuiltinTool, enabled: bool) {
+        match tool {
+            BuiltinTool::Filesystem => self.filesystem = enabled,
+            BuiltinTool::Fetch => self.fetch = enabled,
+            BuiltinTool::DateTime => self.datetime = enabled,
+            BuiltinTool::Calculator => self.calculator = enabled,
+        }
+    }
+
+    pub fn enabled_tools(&self) -> Vec<BuiltinTool> {
+        BuiltinTool::ALL
+            .into_iter()
+            .filter(|tool| self.is_enabled(*tool))
+            .collect()
+    }
+}
+
 /// Represents the complete MCP servers configuration (follows MCP standard format)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServersConfig {
@@ -194,6 +451,22 @@ pub struct McpServersConfig {
     pub enabled: bool,
     #[serde(default = "default_dangerous_mode_enabled")]
     pub dangerous_mode_enabled: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_policies: Vec<ToolPolicyRule>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub builtin_tools: BuiltinToolsConfig,
+    /// Seconds a tool call can take before it's abandoned, unless its server sets
+    /// its own timeout. Zero means no limit.
+    #[serde(default = "default_tool_timeout")]
+    pub tool_timeout: u64,
+    /// Characters of a tool result given to the model, the full output stays in
+    /// the chat. Zero means no limit.
+    #[serde(default = "default_max_tool_output_chars")]
+    pub max_tool_output_chars: usize,
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
 }
 
 impl Default for McpServersConfig {
@@ -203,6 +476,10 @@ impl Default for McpServersConfig {
             inputs: Vec::new(),
             enabled: true,
             dangerous_mode_enabled: false,
+            tool_policies: Vec::new(),
+            builtin_tools: BuiltinToolsConfig::default(),
+            tool_timeout: default_tool_timeout(),
+            max_tool_output_chars: default_max_tool_output_chars(),
         }
     }
 }
@@ -228,6 +505,25 @@ impl McpServersConfig {
         self.servers.iter().filter(|(_, server)| server.enabled)
     }
 
+    /// The enabled servers with the given answers to their inputs, or none if MCP is
+    /// disabled. Servers using the id of the built-in tools are skipped.
+    pub fn resolve_servers(&self, inputs: &HashMap<String, String>) -> ResolvedServers {
+        if !self.enabled {
+            return ResolvedServers::new();
+        }
+
+        self.list_enabled_servers()
+            .filter(|(id, _)| {
+                let reserved = id.as_str() == BUILTIN_SERVER_ID;
+                if reserved {
+                    ::log::warn!("Skipping MCP server '{}', the id is reserved", id);
+                }
+                !reserved
+            })
+            .map(|(id, server)| (id.clone(), server.with_variables_expanded(inputs)))
+            .collect()
+    }
+
     pub fn add_input(&mut self, input: InputConfig) {
         self.inputs.push(input);
     }
@@ -236,6 +532,88 @@ impl McpServersConfig {
         self.inputs.iter().find(|input| input.id == id)
     }
 
+    /// Splits a namespaced tool name into its server id and tool name, built-in tools
+    /// included.
+    pub fn split_tool_name<'a>(&'a self, namespaced: &'a str) -> Option<(&'a str, &'a str)> {
+        self.servers
+            .keys()
+            .map(String::as_str)
+            .chain(std::iter::once(BUILTIN_SERVER_ID))
+            .filter_map(|id| Some((id, strip_server_prefix(namespaced, id)?)))
+            // The longest id wins, in case an id is a prefix of another.
+            .max_by_key(|(id, _)| id.len())
+    }
+
+    /// The policy for calls to a namespaced tool from the given chat.
+    ///
+    /// Rules of the chat win over global ones, and rules of the tool over the ones
+    /// for all tools of its server. Without rules, dangerous mode decides.
+    pub fn tool_policy(&self, namespaced: &str, chat_id: Option<ChatId>) -> ToolPolicy {
+        let default = if self.dangerous_mode_enabled {
+            ToolPolicy::Allow
+        } else {
+            ToolPolicy::Ask
+        };
+        self.tool_rule(namespaced, chat_id)
+            .map(|rule| rule.policy)
+            .unwrap_or(default)
+    }
+
+    /// How long a call to a namespaced tool can take, if limited.
+    pub fn tool_timeout(&self, namespaced: &str) -> Option<Duration> {
+        let timeout = self
+            .split_tool_name(namespaced)
+            .and_then(|(server, _)| self.servers.get(server))
+            .and_then(|server| server.timeout)
+            .unwrap_or(self.tool_timeout);
+        (timeout > 0).then(|| Duration::from_secs(timeout))
+    }
+
+    /// The rule deciding calls to a namespaced tool from the given chat, if any.
+    pub fn tool_rule(&self, namespaced: &str, chat_id: Option<ChatId>) -> Option<&ToolPolicyRule> {
+        let (server, tool) = self.split_tool_name(namespaced)?;
+
+        self.tool_policies
+            .iter()
+            .filter(|rule| rule.matches(server, tool))
+            .filter(|rule| rule.chat_id.is_none() || rule.chat_id == chat_id)
+            .max_by_key(|rule| (rule.chat_id.is_some(), rule.tool != "*"))
+    }
+
+    /// Sets the policy of a namespaced tool, globally or only in a chat.
+    pub fn set_tool_policy(
+        &mut self,
+        namespaced: &str,
+        chat_id: Option<ChatId>,
+        policy: ToolPolicy,
+    ) {
+        let Some((server, tool)) = self.split_tool_name(namespaced) else {
+            return;
+        };
+        let (server, tool) = (server.to_string(), tool.to_string());
+
+        match self
+            .tool_policies
+            .iter_mut()
+            .find(|rule| rule.server == server && rule.tool == tool && rule.chat_id == chat_id)
+        {
+            Some(rule) => rule.policy = policy,
+            None => self.tool_policies.push(ToolPolicyRule {
+                server,
+                tool,
+                policy,
+                chat_id,
+            }),
+        }
+    }
+
+    /// Removes the rule for the tool of the server in the given chat, if any.
+    pub fn remove_tool_policy(&mut self, server: &str, tool: &str, chat_id: Option<ChatId>) {
+        self.tool_policies.retain(|rule| {
+            !(rule.server == server && rule.tool == tool && rule.chat_id == chat_id)
+        });
+    }
+
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string_pretty(self)
     }
@@ -311,6 +689,119 @@ impl McpServersConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_tool_policy_precedence() {
+        let mut config = McpServersConfig::create_sample();
+        config.add_server(
+            "filesystem_extra".to_string(),
+            McpServer::stdio("npx".to_string(), Vec::new()),
+        );
+        config.tool_policies = vec![
+            ToolPolicyRule {
+                server: "filesystem".to_string(),
+                tool: "*".to_string(),
+                policy: ToolPolicy::Deny,
+                chat_id: None,
+            },
+            ToolPolicyRule {
+                server: "filesystem".to_string(),
+                tool: "read_file".to_string(),
+                policy: ToolPolicy::Allow,
+                chat_id: None,
+            },
+        ];
+        config.set_tool_policy("filesystem__write_file", Some(7), ToolPolicy::Allow);
+
+        assert_eq!(
+            config.split_tool_name("filesystem_extra__list"),
+            Some(("filesystem_extra", "list"))
+        );
+        assert_eq!(
+            config.split_tool_name("builtin__calculate"),
+            Some((BUILTIN_SERVER_ID, "calculate"))
+        );
+        assert_eq!(
+            config.tool_policy("filesystem__read_file", None),
+            ToolPolicy::Allow
+        );
+        assert_eq!(
+            config.tool_policy("filesystem__write_file", None),
+            ToolPolicy::Deny
+        );
+        assert_eq!(
+            config.tool_policy("filesystem__write_file", Some(7)),
+            ToolPolicy::Allow
+        );
+        assert_eq!(
+            config.tool_policy("filesystem_extra__list", None),
+            ToolPolicy::Ask
+        );
+
+        config.dangerous_mode_enabled = true;
+        assert_eq!(
+            config.tool_policy("filesystem_extra__list", None),
+            ToolPolicy::Allow
+        );
+        assert_eq!(
+            config.tool_policy("filesystem__delete", None),
+            ToolPolicy::Deny
+        );
+    }
+
+    #[test]
+    fn test_server_changes() {
+        let inputs = HashMap::new();
+        let config = McpServersConfig::create_sample();
+        let loaded = config.resolve_servers(&inputs);
+
+        let mut edited = config.clone();
+        edited
+            .servers
+            .get_mut("filesystem")
+            .unwrap()
+            .args
+            .push("/tmp".to_string());
+        edited.servers.get_mut("browser").unwrap().enabled = true;
+        edited.remove_server("my-mcp-server-4b11bf70");
+
+        let changes = McpServerChanges::between(&loaded, &edited.resolve_servers(&inputs));
+        assert_eq!(changes.start, vec!["filesystem", "browser"]);
+        assert_eq!(changes.stop, vec!["my-mcp-server-4b11bf70"]);
+
+        let unchanged = McpServerChanges::between(&loaded, &config.resolve_servers(&inputs));
+        assert_eq!(unchanged, McpServerChanges::default());
+
+        let mut disabled = config.clone();
+        disabled.enabled = false;
+        let changes = McpServerChanges::between(&loaded, &disabled.resolve_servers(&inputs));
+        assert!(changes.start.is_empty());
+        assert_eq!(changes.stop.len(), loaded.len());
+    }
+
+    #[test]
+    fn test_tool_timeout() {
+        let mut config = McpServersConfig::new();
+        let mut slow = McpServer::stdio("npx".to_string(), Vec::new());
+        slow.timeout = Some(600);
+        config.add_server("slow".to_string(), slow);
+        config.add_server(
+            "fast".to_string(),
+            McpServer::stdio("uvx".to_string(), Vec::new()),
+        );
+
+        assert_eq!(
+            config.tool_timeout("slow__build"),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(
+            config.tool_timeout("fast__search"),
+            Some(Duration::from_secs(120))
+        );
+
+        config.tool_timeout = 0;
+        assert_eq!(config.tool_timeout("fast__search"), None);
+    }
+
     #[test]
     fn test_serialize_deserialize() {
         let config = McpServersConfig::create_sample();
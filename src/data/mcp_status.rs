@@ -0,0 +1,270 @@
+//! Live status of the MCP servers loaded by the tool manager.
+//!
+//! The manager only reports whether adding a server failed, so the store records
+//! each step here as it loads them, and the MCP screen shows what's recorded.
+
+use indexmap::IndexMap;
+use makepad_widgets::{Cx, DefaultNone};
+use moly_kit::prelude::*;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{LazyLock, Mutex};
+
+use super::mcp_servers::strip_server_prefix;
+
+/// Lines of stderr kept for each server.
+const STDERR_TAIL_LINES: usize = 50;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum McpServerState {
+    Connecting,
+    Connected,
+    Failed(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct McpToolInfo {
+    pub name: String,
+    pub description: String,
+    /// JSON schema of the tool's input.
+    pub input_schema: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct McpServerStatus {
+    pub state: McpServerState,
+    pub tools: Vec<McpToolInfo>,
+    /// Last lines the server wrote to stderr, only captured for stdio servers.
+    pub stderr: VecDeque<String>,
+}
+
+impl McpServerStatus {
+    fn connecting() -> Self {
+        Self {
+            state: McpServerState::Connecting,
+            tools: Vec::new(),
+            stderr: VecDeque::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum McpStatusAction {
+    None,
+    /// The status of some server changed.
+    Changed,
+}
+
+static STATUSES: LazyLock<Mutex<IndexMap<String, McpServerStatus>>> =
+    LazyLock::new(|| Mutex::new(IndexMap::new()));
+
+fn update(server_id: &str, f: impl FnOnce(&mut McpServerStatus)) {
+    {
+        let mut statuses = STATUSES.lock().unwrap();
+        let status = statuses
+            .entry(server_id.to_string())
+            .or_insert_with(McpServerStatus::connecting);
+        f(status);
+    }
+    Cx::post_action(McpStatusAction::Changed);
+}
+
+/// Statuses of the servers being loaded, in load order.
+pub fn statuses() -> Vec<(String, McpServerStatus)> {
+    STATUSES
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(id, status)| (id.clone(), status.clone()))
+        .collect()
+}
+
+/// Forgets a server that was stopped.
+pub fn remove(server_id: &str) {
+    STATUSES.lock().unwrap().shift_remove(server_id);
+    Cx::post_action(McpStatusAction::Changed);
+}
+
+pub fn set_connecting(server_id: &str) {
+    update(server_id, |status| *status = McpServerStatus::connecting());
+}
+
+pub fn set_connected(server_id: &str, tools: Vec<McpToolInfo>) {
+    update(server_id, |status| {
+        status.state = McpServerState::Connected;
+        status.tools = tools;
+    });
+}
+
+pub fn set_failed(server_id: &str, error: String) {
+    update(server_id, |status| {
+        status.state = McpServerState::Failed(error);
+        status.tools.clear();
+    });
+}
+
+pub fn push_stderr_line(server_id: &str, line: String) {
+    update(server_id, |status| {
+        if status.stderr.len() == STDERR_TAIL_LINES {
+            status.stderr.pop_front();
+        }
+        status.stderr.push_back(line);
+    });
+}
+
+/// The tools of `server_id` among the namespaced tools of the manager.
+pub fn server_tools(tools: &[Tool], server_id: &str) -> Vec<McpToolInfo> {
+    tools
+        .iter()
+        .filter_map(|tool| serde_json::to_value(tool).ok())
+        .filter_map(|tool| {
+            let name = strip_server_prefix(tool["name"].as_str()?, server_id)?;
+
+            let schema = tool
+                .get("inputSchema")
+                .or_else(|| tool.get("input_schema"))
+                .cloned()
+                .unwrap_or(Value::Null);
+
+            Some(McpToolInfo {
+                name: name.to_string(),
+                description: tool["description"].as_str().unwrap_or_default().to_string(),
+                input_schema: schema.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Servers to stop and to (re)start in the tool manager, applied as one update.
+#[cfg(not(target_arch = "wasm32"))]
+struct ServersUpdate {
+    tool_manager: McpManagerClient,
+    stop: Vec<String>,
+    start: Vec<(String, McpTransport)>,
+}
+
+/// Applies the updates to the tool manager one at a time, in the order queued, so
+/// a server is never stopped and started by two updates at once.
+#[cfg(not(target_arch = "wasm32"))]
+static UPDATES: LazyLock<futures::channel::mpsc::UnboundedSender<ServersUpdate>> =
+    LazyLock::new(|| {
+        use futures::StreamExt;
+
+        let (tx, mut rx) = futures::channel::mpsc::unbounded::<ServersUpdate>();
+        moly_kit::aitk::utils::asynchronous::spawn(async move {
+            while let Some(update) = rx.next().await {
+                for server_id in update.stop {
+                    update.tool_manager.remove_server(&server_id).await;
+                }
+                // Adding a server that is loaded already replaces it.
+                for (server_id, transport) in update.start {
+                    add_server(&update.tool_manager, &server_id, transport).await;
+                }
+            }
+        });
+        tx
+    });
+
+/// Queues stopping and (re)starting servers of the manager, after the updates
+/// queued before.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn queue_update(
+    tool_manager: &McpManagerClient,
+    stop: Vec<String>,
+    start: Vec<(String, McpTransport)>,
+) {
+    let update = ServersUpdate {
+        tool_manager: tool_manager.clone(),
+        stop,
+        start,
+    };
+    if UPDATES.unbounded_send(update).is_err() {
+        ::log::error!("The MCP servers update queue is closed");
+    }
+}
+
+/// Adds the server to the manager, recording its status along the way.
+#[cfg(not(target_arch = "wasm32"))]
+async fn add_server(tool_manager: &McpManagerClient, server_id: &str, transport: McpTransport) {
+    set_connecting(server_id);
+    let transport = capture_stderr(server_id, transport);
+
+    match tool_manager.add_server(server_id, transport).await {
+        Ok(()) => {
+            ::log::debug!("Successfully added MCP server: {}", server_id);
+            let tools = server_tools(&tool_manager.get_all_namespaced_tools(), server_id);
+            set_connected(server_id, tools);
+        }
+        Err(e) => {
+            ::log::error!("Failed to add MCP server '{}': {}", server_id, e);
+            set_failed(server_id, e.to_string());
+        }
+    }
+}
+
+/// Pipes the stderr of stdio servers into their status.
+#[cfg(not(target_arch = "wasm32"))]
+fn capture_stderr(server_id: &str, transport: McpTransport) -> McpTransport {
+    use std::io::BufRead;
+
+    let McpTransport::Stdio(mut command) = transport else {
+        return transport;
+    };
+
+    match std::io::pipe() {
+        Ok((reader, writer)) => {
+            command.stderr(writer);
+            let server_id = server_id.to_string();
+            std::thread::spawn(move || {
+                for line in std::io::BufReader::new(reader).lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+                    push_stderr_line(&server_id, line);
+                }
+            });
+        }
+        Err(e) => {
+            ::log::warn!(
+                "Could not capture stderr of MCP server '{}': {}",
+                server_id,
+                e
+            );
+        }
+    }
+
+    McpTransport::Stdio(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(name: &str) -> Tool {
+        serde_json::from_value(json!({
+            "name": name,
+            "description": "Reads a file",
+            "inputSchema": {"type": "object", "properties": {"path": {"type": "string"}}},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_server_tools_matches_whole_server_id() {
+        let tools = [
+            tool("git__status"),
+            tool("github__search"),
+            tool("fs__read_file"),
+        ];
+
+        let git = server_tools(&tools, "git");
+        assert_eq!(git.len(), 1);
+        assert_eq!(git[0].name, "status");
+        assert_eq!(git[0].description, "Reads a file");
+        assert!(git[0].input_schema.contains("\"path\""));
+
+        assert_eq!(server_tools(&tools, "fs")[0].name, "read_file");
+        assert!(server_tools(&tools, "memory").is_empty());
+    }
+}
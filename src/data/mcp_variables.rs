@@ -0,0 +1,124 @@
+//! Variables of the MCP servers config, expanded like VS Code does.
+//!
+//! Supported are `${input:id}`, `${env:NAME}`, `${workspaceFolder}`,
+//! `${workspaceFolderBasename}`, `${userHome}`, `${pathSeparator}` and `${/}`.
+//! Unknown variables are left as they are.
+
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::path::{MAIN_SEPARATOR_STR, Path};
+use std::sync::LazyLock;
+
+static VARIABLE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\$\{([^}]+)\}").unwrap());
+
+/// Values the variables of a server expand to.
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    /// Answers to the inputs, by input id.
+    pub inputs: HashMap<String, String>,
+    /// There are no workspaces in Moly, so this is the working directory of the
+    /// server, or the home of the user when it has none.
+    pub workspace_folder: Option<String>,
+    pub user_home: Option<String>,
+}
+
+impl Variables {
+    /// Expands the variables of `text`.
+    ///
+    /// Errs with the ids of the inputs without an answer.
+    pub fn expand(&self, text: &str) -> Result<String, Vec<String>> {
+        let mut missing = Vec::new();
+
+        let expanded = VARIABLE.replace_all(text, |captures: &Captures| {
+            let variable = &captures[1];
+            let value = match variable.split_once(':') {
+                Some(("input", id)) => {
+                    let value = self.inputs.get(id).cloned();
+                    if value.is_none() {
+                        missing.push(id.to_string());
+                    }
+                    value
+                }
+                // Unset variables expand to nothing, as in VS Code.
+                Some(("env", name)) => Some(std::env::var(name).unwrap_or_default()),
+                Some(_) => None,
+                None => match variable {
+                    "workspaceFolder" => self.workspace_folder.clone(),
+                    "workspaceFolderBasename" => self.workspace_folder.as_deref().map(|folder| {
+                        Path::new(folder)
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    }),
+                    "userHome" => self.user_home.clone(),
+                    "pathSeparator" | "/" => Some(MAIN_SEPARATOR_STR.to_string()),
+                    _ => None,
+                },
+            };
+
+            value.unwrap_or_else(|| captures[0].to_string())
+        });
+
+        if missing.is_empty() {
+            Ok(expanded.into_owned())
+        } else {
+            Err(missing)
+        }
+    }
+}
+
+/// Ids of the inputs referenced by `text`.
+pub fn input_ids(text: &str) -> impl Iterator<Item = &str> {
+    VARIABLE
+        .captures_iter(text)
+        .filter_map(|captures| captures.get(1)?.as_str().strip_prefix("input:"))
+}
+
+/// Home directory of the user, if known.
+pub fn user_home() -> Option<String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_string_lossy().to_string())
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_variables() {
+        let variables = Variables {
+            inputs: HashMap::from([("token".to_string(), "secret".to_string())]),
+            workspace_folder: Some("/home/user/project".to_string()),
+            user_home: Some("/home/user".to_string()),
+        };
+
+        assert_eq!(
+            variables.expand("Bearer ${input:token}").unwrap(),
+            "Bearer secret"
+        );
+        assert_eq!(
+            variables
+                .expand("${workspaceFolder}/src ${workspaceFolderBasename} ${userHome}")
+                .unwrap(),
+            "/home/user/project/src project /home/user"
+        );
+        assert_eq!(
+            variables.expand("${env:MOLY_UNSET_TEST_VARIABLE}").unwrap(),
+            ""
+        );
+        assert_eq!(variables.expand("${unknown}").unwrap(), "${unknown}");
+        assert_eq!(
+            variables.expand("${input:a} ${input:token} ${input:b}"),
+            Err(vec!["a".to_string(), "b".to_string()])
+        );
+
+        let ids = input_ids("${input:a}-${env:HOME}-${input:b}").collect::<Vec<_>>();
+        assert_eq!(ids, ["a", "b"]);
+    }
+}
@@ -1,13 +1,39 @@
+pub mod api_keys;
 pub mod bot_fetcher;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod builtin_tools;
 pub mod capture;
 pub mod chats;
 pub mod deep_inquire_client;
+pub mod deep_inquire_report;
 pub mod downloads;
+pub mod failover_client;
+pub mod local_servers;
+pub mod mcp_context;
+pub mod mcp_import;
+pub mod mcp_inputs;
 pub mod mcp_servers;
+pub mod mcp_status;
+pub mod mcp_variables;
+pub mod model_capabilities;
+pub mod model_rules;
+pub mod models_cache;
+pub mod mofa_client;
 pub mod moly_client;
+pub mod openai_responses_client;
 pub mod openclaw_client;
 pub mod preferences;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod provider_diagnostics;
 pub mod providers;
+pub mod reasoning;
+pub mod reasoning_client;
 pub mod search;
 pub mod store;
 pub mod supported_providers;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub mod test_utils;
+pub mod think_tags;
+pub mod tool_audit;
+pub mod tool_permissions;
+pub mod tool_selection;
@@ -0,0 +1,227 @@
+use moly_kit::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::model_rules::pattern_matches;
+
+/// What a model can do beyond plain text chat.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModelCapabilities {
+    /// Understands images given as input.
+    #[serde(default)]
+    pub vision: bool,
+    /// Supports tool (function) calling.
+    #[serde(default)]
+    pub tools: bool,
+    /// Thinks before answering.
+    #[serde(default)]
+    pub reasoning: bool,
+    /// Generates images.
+    #[serde(default)]
+    pub image_output: bool,
+    /// Understands or produces audio.
+    #[serde(default)]
+    pub audio: bool,
+}
+
+impl ModelCapabilities {
+    /// Capabilities of a bot as fetched from its provider.
+    ///
+    /// Uses the provider's metadata for the model if available, and the bundled
+    /// capabilities table otherwise. Capabilities reported by the client are
+    /// always kept.
+    pub fn for_bot(bot: &Bot, metadata: Option<&ModelCapabilities>) -> Self {
+        let known = metadata
+            .copied()
+            .unwrap_or_else(|| Self::from_table(bot.id.as_str()));
+
+        Self {
+            vision: known.vision
+                || bot
+                    .capabilities
+                    .has_capability(&BotCapability::AttachmentInput),
+            audio: known.audio || bot.capabilities.has_capability(&BotCapability::AudioCall),
+            ..known
+        }
+    }
+
+    /// Looks up the model in the bundled capabilities table.
+    ///
+    /// All matching entries are applied in order, so later entries can refine
+    /// earlier ones.
+    pub fn from_table(model_id: &str) -> Self {
+        let mut capabilities = Self::default();
+        for entry in capabilities_table() {
+            if pattern_matches(&entry.pattern, model_id) {
+                entry.apply_to(&mut capabilities);
+            }
+        }
+        capabilities
+    }
+
+    pub fn has(&self, capability: ModelCapability) -> bool {
+        match capability {
+            ModelCapability::Vision => self.vision,
+            ModelCapability::Tools => self.tools,
+            ModelCapability::Reasoning => self.reasoning,
+            ModelCapability::ImageOutput => self.image_output,
+            ModelCapability::Audio => self.audio,
+        }
+    }
+
+    /// Extends the capabilities a client reported for a bot, so the UI (e.g. the
+    /// attach button of the prompt input) reflects what the model supports.
+    pub fn extend_bot_capabilities(&self, capabilities: BotCapabilities) -> BotCapabilities {
+        if self.vision {
+            capabilities.with_capabilities([BotCapability::AttachmentInput])
+        } else {
+            capabilities
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CapabilitiesTableFile {
+    models: Vec<CapabilitiesTableEntry>,
+}
+
+/// Capabilities of the models matching a glob or `/regex/` pattern.
+///
+/// Missing fields leave the capability as set by previous entries.
+#[derive(Deserialize)]
+struct CapabilitiesTableEntry {
+    pattern: String,
+    vision: Option<bool>,
+    tools: Option<bool>,
+    reasoning: Option<bool>,
+    image_output: Option<bool>,
+    audio: Option<bool>,
+}
+
+impl CapabilitiesTableEntry {
+    fn apply_to(&self, capabilities: &mut ModelCapabilities) {
+        capabilities.vision = self.vision.unwrap_or(capabilities.vision);
+        capabilities.tools = self.tools.unwrap_or(capabilities.tools);
+        capabilities.reasoning = self.reasoning.unwrap_or(capabilities.reasoning);
+        capabilities.image_output = self.image_output.unwrap_or(capabilities.image_output);
+        capabilities.audio = self.audio.unwrap_or(capabilities.audio);
+    }
+}
+
+fn capabilities_table() -> &'static [CapabilitiesTableEntry] {
+    static TABLE: OnceLock<Vec<CapabilitiesTableEntry>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let data = include_str!("./model_capabilities.json");
+        let parsed: CapabilitiesTableFile =
+            serde_json::from_str(data).expect("Failed to parse model_capabilities.json");
+        parsed.models
+    })
+}
+
+/// Lists the models of an OpenAI-compatible provider, along with the capabilities
+/// some providers expose in their models endpoint (e.g. OpenRouter).
+///
+/// Both come from a single request. Capabilities are returned by model id, only for
+/// models with metadata.
+pub async fn fetch_provider_models(
+    url: &str,
+    api_key: Option<&str>,
+) -> Result<(Vec<Bot>, HashMap<String, ModelCapabilities>), ClientError> {
+    let models_url = format!("{}/models", url.trim_end_matches('/'));
+    let mut request = reqwest::Client::new().get(&models_url);
+    if let Some(key) = api_key {
+        request = request.bearer_auth(key);
+    }
+
+    let response = request.send().await.map_err(|e| {
+        ClientError::new(
+            ClientErrorKind::Network,
+            format!("Failed to fetch models from {}: {}", models_url, e),
+        )
+    })?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(ClientError::new(
+            ClientErrorKind::Response,
+            format!(
+                "Fetching models from {} failed with {}: {}",
+                models_url, status, body
+            ),
+        ));
+    }
+    let body = response.json::<Value>().await.map_err(|e| {
+        ClientError::new(
+            ClientErrorKind::Format,
+            format!(
+                "The models listed by {} are not valid JSON: {}",
+                models_url, e
+            ),
+        )
+    })?;
+
+    let mut bots = Vec::new();
+    let mut metadata = HashMap::new();
+    for model in body["data"].as_array().into_iter().flatten() {
+        let Some(id) = model["id"].as_str() else {
+            continue;
+        };
+        bots.push(Bot {
+            id: BotId::new(id),
+            name: id.to_string(),
+            avatar: EntityAvatar::from_first_grapheme(&id.to_uppercase())
+                .unwrap_or_else(|| EntityAvatar::Text("?".into())),
+            capabilities: BotCapabilities::new().with_capabilities([BotCapability::TextInput]),
+        });
+        if let Some(capabilities) = capabilities_from_metadata(model) {
+            metadata.insert(id.to_string(), capabilities);
+        }
+    }
+
+    Ok((bots, metadata))
+}
+
+/// Reads capabilities from a model entry of a models endpoint, if it has any.
+///
+/// Understands OpenRouter's `architecture` and `supported_parameters` fields, and
+/// a plain `capabilities` list of names, as used by some local servers.
+fn capabilities_from_metadata(model: &Value) -> Option<ModelCapabilities> {
+    let strings = |value: &Value| -> Vec<String> {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str().map(str::to_lowercase))
+            .collect()
+    };
+
+    let input_modalities = strings(&model["architecture"]["input_modalities"]);
+    let output_modalities = strings(&model["architecture"]["output_modalities"]);
+    let parameters = strings(&model["supported_parameters"]);
+    let names = strings(&model["capabilities"]);
+
+    if input_modalities.is_empty()
+        && output_modalities.is_empty()
+        && parameters.is_empty()
+        && names.is_empty()
+    {
+        return None;
+    }
+
+    let has = |list: &[String], values: &[&str]| list.iter().any(|v| values.contains(&v.as_str()));
+
+    Some(ModelCapabilities {
+        vision: has(&input_modalities, &["image"]) || has(&names, &["vision"]),
+        tools: has(&parameters, &["tools"])
+            || has(&names, &["tools", "tool_use", "function_calling"]),
+        reasoning: has(&parameters, &["reasoning", "include_reasoning"])
+            || has(&names, &["reasoning", "thinking"]),
+        image_output: has(&output_modalities, &["image"])
+            || has(&names, &["image_output", "image_generation"]),
+        audio: has(&input_modalities, &["audio"])
+            || has(&output_modalities, &["audio"])
+            || has(&names, &["audio"]),
+    })
+}
@@ -0,0 +1,191 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use super::providers::ProviderType;
+
+/// Models excluded by default from OpenAI-compatible providers, as they don't work
+/// over the chat completions endpoint.
+const DEFAULT_OPENAI_EXCLUDES: &[&str] = &[
+    "*dall-e*",
+    "*whisper*",
+    "*tts*",
+    "*transcribe*",
+    "*embedding*",
+    "*moderation*",
+    "babbage*",
+    "davinci*",
+];
+
+/// Include/exclude rules deciding which of the models listed by a provider are kept.
+///
+/// Patterns are globs (`*` matches any sequence, `?` a single character), or regular
+/// expressions when wrapped in slashes, like `/^gpt-(4o|5)/`. Matching is case
+/// insensitive and done against the model id as listed by the provider.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ModelRules {
+    /// If not empty, only models matching at least one of these patterns are kept.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Models matching any of these patterns are dropped.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl ModelRules {
+    /// The rules used for providers the user didn't configure rules for.
+    pub fn default_for(provider_type: &ProviderType) -> Self {
+        match provider_type {
+            ProviderType::OpenAi
+            | ProviderType::MolyServer
+            | ProviderType::MoFa
+            | ProviderType::OpenAiResponses => Self {
+                include: vec![],
+                exclude: DEFAULT_OPENAI_EXCLUDES
+                    .iter()
+                    .map(|pattern| pattern.to_string())
+                    .collect(),
+            },
+            ProviderType::OpenAiImage
+            | ProviderType::OpenAiRealtime
+            | ProviderType::DeepInquire
+            | ProviderType::OpenClaw => Self::default(),
+        }
+    }
+
+    pub fn allows(&self, model_id: &str) -> bool {
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| pattern_matches(pattern, model_id));
+
+        included
+            && !self
+                .exclude
+                .iter()
+                .any(|pattern| pattern_matches(pattern, model_id))
+    }
+}
+
+/// Whether an OpenAI-compatible model is likely to work over chat completions,
+/// whatever rules the user configured.
+pub fn is_chat_model(model_id: &str) -> bool {
+    !DEFAULT_OPENAI_EXCLUDES
+        .iter()
+        .any(|pattern| pattern_matches(pattern, model_id))
+}
+
+/// Parses a comma or newline separated list of patterns, as typed by the user.
+pub fn parse_patterns(text: &str) -> Vec<String> {
+    text.split([',', '\n'])
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Regular expressions of the patterns, compiled once. `None` for invalid ones.
+static COMPILED_REGEXES: LazyLock<Mutex<HashMap<String, Option<Regex>>>> =
+    LazyLock::new(Default::default);
+
+/// Checks a glob or `/regex/` pattern against the given text, ignoring case.
+///
+/// Invalid regular expressions never match.
+pub fn pattern_matches(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.trim();
+
+    if let Some(regex) = pattern
+        .strip_prefix('/')
+        .and_then(|rest| rest.strip_suffix('/'))
+        .filter(|regex| !regex.is_empty())
+    {
+        let mut compiled = COMPILED_REGEXES.lock().unwrap();
+        let regex = compiled.entry(regex.to_string()).or_insert_with(|| {
+            RegexBuilder::new(regex)
+                .case_insensitive(true)
+                .build()
+                .inspect_err(|e| log::warn!("Invalid model rule regex {}: {}", pattern, e))
+                .ok()
+        });
+        return regex.as_ref().is_some_and(|regex| regex.is_match(text));
+    }
+
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    glob_matches(&pattern, &text)
+}
+
+fn glob_matches(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` seen and the text position it was tried at.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_patterns() {
+        assert!(pattern_matches("gpt-4o*", "gpt-4o-mini"));
+        assert!(pattern_matches("*embedding*", "text-embedding-3-small"));
+        assert!(pattern_matches("gpt-?o", "GPT-4o"));
+        assert!(pattern_matches("*", "anything"));
+        assert!(!pattern_matches("gpt-4o", "gpt-4o-mini"));
+        assert!(!pattern_matches("*tts*", "gpt-4o-audio-preview"));
+    }
+
+    #[test]
+    fn test_regex_patterns() {
+        assert!(pattern_matches("/^gpt-(4o|5)/", "gpt-5-image"));
+        assert!(!pattern_matches("/^gpt-(4o|5)/", "chatgpt-4o-latest"));
+        assert!(!pattern_matches("/(unclosed/", "(unclosed"));
+    }
+
+    #[test]
+    fn test_default_rules_keep_multimodal_chat_models() {
+        let rules = ModelRules::default_for(&ProviderType::OpenAi);
+        assert!(rules.allows("gpt-5-image"));
+        assert!(rules.allows("gpt-4o-audio-preview"));
+        assert!(!rules.allows("text-embedding-3-large"));
+        assert!(!rules.allows("gpt-4o-mini-tts"));
+        assert!(!rules.allows("dall-e-3"));
+    }
+
+    #[test]
+    fn test_include_and_exclude() {
+        let rules = ModelRules {
+            include: parse_patterns("gpt-*, /claude/"),
+            exclude: parse_patterns("*-mini"),
+        };
+        assert!(rules.allows("gpt-5"));
+        assert!(rules.allows("anthropic/claude-sonnet-4"));
+        assert!(!rules.allows("gpt-5-mini"));
+        assert!(!rules.allows("gemini-2.5-pro"));
+    }
+}
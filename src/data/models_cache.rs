@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use moly_kit::aitk::utils::asynchronous::spawn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::shared::utils::filesystem;
+
+use super::providers::{ProviderBot, ProviderId};
+
+const MODELS_CACHE_DIR: &str = "cache";
+const MODELS_CACHE_FILENAME: &str = "models_cache.json";
+
+/// The last successful list of models fetched from a provider.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedProviderModels {
+    pub fetched_at: DateTime<Utc>,
+    pub models: Vec<ProviderBot>,
+}
+
+/// On-disk cache of provider model catalogs.
+///
+/// Used to seed the available bots on startup, so providers that can't be reached
+/// at launch keep showing their models (marked as stale) instead of vanishing.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ModelsCache {
+    #[serde(default)]
+    providers: HashMap<ProviderId, CachedProviderModels>,
+}
+
+impl ModelsCache {
+    pub async fn load() -> Self {
+        match filesystem::global()
+            .read_json::<ModelsCache>(&models_cache_path())
+            .await
+        {
+            Ok(cache) => cache,
+            Err(_e) => {
+                log::info!("No models cache file found, it will be created upon first fetch.");
+                ModelsCache::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let self_clone = self.clone();
+        spawn(async move {
+            match filesystem::global()
+                .queue_write_json(models_cache_path(), &self_clone)
+                .await
+            {
+                Ok(()) => (),
+                Err(e) => log::error!("Failed to write models cache file: {:?}", e),
+            }
+        });
+    }
+
+    pub fn get(&self, provider_id: &ProviderId) -> Option<&CachedProviderModels> {
+        self.providers.get(provider_id)
+    }
+
+    /// Replaces the cached models of a provider, stamping them with the current time.
+    pub fn update(&mut self, provider_id: &ProviderId, models: Vec<ProviderBot>) {
+        self.providers.insert(
+            provider_id.clone(),
+            CachedProviderModels {
+                fetched_at: Utc::now(),
+                models,
+            },
+        );
+        self.save();
+    }
+
+    pub fn remove(&mut self, provider_id: &ProviderId) {
+        if self.providers.remove(provider_id).is_some() {
+            self.save();
+        }
+    }
+}
+
+fn models_cache_path() -> PathBuf {
+    Path::new(MODELS_CACHE_DIR).join(MODELS_CACHE_FILENAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use moly_kit::prelude::BotId;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let bot = ProviderBot {
+            id: BotId::new("openai/gpt-5"),
+            name: "gpt-5".to_string(),
+            provider_id: "openai".to_string(),
+            enabled: true,
+            is_stale: true,
+            ..Default::default()
+        };
+        let mut cache = ModelsCache::default();
+        cache.providers.insert(
+            "openai".to_string(),
+            CachedProviderModels {
+                fetched_at: Utc::now(),
+                models: vec![bot],
+            },
+        );
+
+        let json = serde_json::to_string(&cache).unwrap();
+        let loaded: ModelsCache = serde_json::from_str(&json).unwrap();
+        let cached = loaded.get(&"openai".to_string()).unwrap();
+        assert_eq!(cached.models.len(), 1);
+        assert_eq!(cached.models[0].id, BotId::new("openai/gpt-5"));
+        assert!(cached.models[0].enabled);
+        // Staleness is decided when seeding, it's never read back from disk.
+        assert!(!cached.models[0].is_stale);
+        assert!(loaded.get(&"other".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_cache_without_providers() {
+        let cache: ModelsCache = serde_json::from_str("{}").unwrap();
+        assert!(cache.get(&"openai".to_string()).is_none());
+    }
+}
@@ -0,0 +1,498 @@
+//! Client for MoFa servers, which run agents as dataflows of nodes.
+//!
+//! MoFa servers speak the OpenAI chat completions protocol with a few additions:
+//!
+//! - Entries of `GET /models` may describe the agent with a `description`, the
+//!   `inputs` it accepts and the `parameters` it needs.
+//! - Streams of `POST /chat/completions` interleave the usual completion chunks
+//!   with `mofa.dataflow` chunks, reporting the status of the dataflow and its
+//!   nodes, and `mofa.output` chunks, carrying intermediate outputs of the nodes.
+
+use async_stream::stream;
+use makepad_widgets::*;
+use moly_kit::aitk::utils::sse::parse_sse;
+use moly_kit::prelude::*;
+use reqwest::header::{HeaderMap, HeaderName};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use crate::chat::mofa_content::MofaContentWidgetRefExt;
+
+/// An input an agent accepts, like a document or a topic.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentInput {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// A parameter to configure an agent with.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentParameter {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// What a MoFa agent expects to run.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentInfo {
+    #[serde(default)]
+    pub inputs: Vec<AgentInput>,
+    #[serde(default)]
+    pub parameters: Vec<AgentParameter>,
+}
+
+impl AgentInfo {
+    pub fn required_parameters(&self) -> impl Iterator<Item = &AgentParameter> {
+        self.parameters.iter().filter(|p| p.required)
+    }
+}
+
+/// An agent served by MoFa, as listed by the server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MofaAgent {
+    pub id: String,
+    pub description: String,
+    pub info: AgentInfo,
+}
+
+#[derive(Deserialize)]
+struct ModelsResponse {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(flatten)]
+    info: AgentInfo,
+}
+
+/// A node of a running dataflow.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DataflowNode {
+    pub id: String,
+    /// Like `pending`, `running`, `finished` or `failed`.
+    #[serde(default)]
+    pub status: String,
+}
+
+/// The dataflow an agent runs as, with the status of each of its nodes.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Dataflow {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub nodes: Vec<DataflowNode>,
+}
+
+/// Something a node produced while the agent ran.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NodeOutput {
+    pub node: String,
+    pub output: Value,
+}
+
+/// Data attached to the replies of MoFa agents, to show how they got to them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MofaData {
+    pub dataflow: Dataflow,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<NodeOutput>,
+}
+
+impl MofaData {
+    fn from_content(content: &MessageContent) -> Option<Self> {
+        serde_json::from_str(content.data.as_deref()?).ok()
+    }
+
+    fn write_to(&self, content: &mut MessageContent) {
+        content.data = serde_json::to_string(self).ok();
+    }
+}
+
+/// A message being sent to MoFa.
+#[derive(Clone, Debug, Serialize)]
+struct OutgoingMessage {
+    content: String,
+    role: &'static str,
+}
+
+impl TryFrom<&Message> for OutgoingMessage {
+    type Error = ();
+
+    fn try_from(message: &Message) -> Result<Self, Self::Error> {
+        let role = match message.from {
+            EntityId::User => "user",
+            EntityId::System => "system",
+            EntityId::Bot(_) => "assistant",
+            EntityId::Tool | EntityId::App => return Err(()),
+        };
+
+        Ok(Self {
+            content: message.content.text.clone(),
+            role,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct MofaClientInner {
+    url: String,
+    headers: HeaderMap,
+    client: reqwest::Client,
+}
+
+/// A client for agents served by MoFa.
+#[derive(Debug)]
+pub struct MofaClient(Arc<RwLock<MofaClientInner>>);
+
+impl Clone for MofaClient {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl MofaClient {
+    pub fn new(url: String) -> Self {
+        Self(Arc::new(RwLock::new(MofaClientInner {
+            url,
+            headers: HeaderMap::new(),
+            client: reqwest::Client::new(),
+        })))
+    }
+
+    pub fn set_header(&mut self, key: &str, value: &str) -> Result<(), &'static str> {
+        let header_name = HeaderName::from_str(key).map_err(|_| "Invalid header name")?;
+        let header_value = value.parse().map_err(|_| "Invalid header value")?;
+
+        self.0
+            .write()
+            .unwrap()
+            .headers
+            .insert(header_name, header_value);
+
+        Ok(())
+    }
+
+    pub fn set_key(&mut self, key: &str) -> Result<(), &'static str> {
+        self.set_header("Authorization", &format!("Bearer {}", key))
+    }
+
+    /// Lists the agents of the server along with what they expect to run.
+    pub fn agents(&self) -> BoxPlatformSendFuture<'static, Result<Vec<MofaAgent>, ClientError>> {
+        let inner = self.0.read().unwrap().clone();
+
+        Box::pin(async move {
+            let url = format!("{}/models", inner.url);
+            let response = inner
+                .client
+                .get(&url)
+                .headers(inner.headers)
+                .send()
+                .await
+                .map_err(|error| {
+                    ClientError::new_with_source(
+                        ClientErrorKind::Network,
+                        format!("Could not reach the MoFa server at {url}."),
+                        Some(error),
+                    )
+                })?;
+
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            if !status.is_success() {
+                return Err(ClientError::new(
+                    ClientErrorKind::Response,
+                    format!("Request to {url} failed with status {status}"),
+                )
+                .with_details(body));
+            }
+
+            parse_agents(&body).map_err(|error| {
+                ClientError::new(
+                    ClientErrorKind::Format,
+                    format!("Could not parse the agents listed by {url}: {error}"),
+                )
+            })
+        })
+    }
+}
+
+fn parse_agents(body: &str) -> Result<Vec<MofaAgent>, serde_json::Error> {
+    let response: ModelsResponse = serde_json::from_str(body)?;
+    Ok(response
+        .data
+        .into_iter()
+        .map(|entry| MofaAgent {
+            description: entry
+                .description
+                .filter(|d| !d.trim().is_empty())
+                .unwrap_or_else(|| format!("Agent {}", entry.id)),
+            id: entry.id,
+            info: entry.info,
+        })
+        .collect())
+}
+
+pub fn agent_bot(agent: &MofaAgent) -> Bot {
+    Bot {
+        id: BotId::new(&agent.id),
+        name: agent.id.clone(),
+        avatar: EntityAvatar::from_first_grapheme(&agent.id.to_uppercase())
+            .unwrap_or_else(|| EntityAvatar::Text("M".into())),
+        capabilities: BotCapabilities::new().with_capabilities([BotCapability::TextInput]),
+    }
+}
+
+/// Applies a chunk of the stream to the content, returning whether it changed.
+fn apply_chunk(chunk: &Value, content: &mut MessageContent, data: &mut Option<MofaData>) -> bool {
+    match chunk["object"].as_str() {
+        Some("mofa.dataflow") => {
+            let Ok(dataflow) = serde_json::from_value::<Dataflow>(chunk["dataflow"].clone()) else {
+                ::log::warn!("Skipping a malformed MoFa dataflow status: {}", chunk);
+                return false;
+            };
+            data.get_or_insert_default().dataflow = dataflow;
+        }
+        Some("mofa.output") => {
+            let Some(node) = chunk["node"].as_str() else {
+                ::log::warn!("Skipping a MoFa output without node: {}", chunk);
+                return false;
+            };
+            data.get_or_insert_default().outputs.push(NodeOutput {
+                node: node.to_string(),
+                output: chunk["output"].clone(),
+            });
+        }
+        _ => {
+            let text = chunk["choices"][0]["delta"]["content"].as_str();
+            match text {
+                Some(text) if !text.is_empty() => content.text.push_str(text),
+                _ => return false,
+            }
+        }
+    }
+
+    if let Some(data) = data {
+        data.write_to(content);
+    }
+    true
+}
+
+impl BotClient for MofaClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        let agents = self.agents();
+        Box::pin(async move {
+            match agents.await {
+                Ok(agents) => ClientResult::new_ok(agents.iter().map(agent_bot).collect()),
+                Err(error) => error.into(),
+            }
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        _tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let inner = self.0.read().unwrap().clone();
+        let url = format!("{}/chat/completions", inner.url);
+
+        let messages: Vec<OutgoingMessage> =
+            messages.iter().filter_map(|m| m.try_into().ok()).collect();
+        let body = serde_json::json!({
+            "model": bot_id.id(),
+            "messages": messages,
+            "stream": true,
+        });
+
+        let stream = stream! {
+            let request = inner.client.post(&url).headers(inner.headers).json(&body);
+
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(error) => {
+                    yield ClientError::new_with_source(
+                        ClientErrorKind::Network,
+                        format!("Could not reach the MoFa server at {url}."),
+                        Some(error),
+                    )
+                    .into();
+                    return;
+                }
+            };
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                yield ClientError::new(
+                    ClientErrorKind::Response,
+                    format!("Request to {url} failed with status {status}"),
+                )
+                .with_details(body)
+                .into();
+                return;
+            }
+
+            let mut content = MessageContent::default();
+            let mut data: Option<MofaData> = None;
+
+            for await event in parse_sse(response.bytes_stream()) {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(error) => {
+                        // Keep what the agent did so far.
+                        yield ClientResult::new_ok(content.clone());
+                        yield ClientError::new_with_source(
+                            ClientErrorKind::Network,
+                            format!("The connection to {url} was closed while the agent was running."),
+                            Some(error),
+                        )
+                        .into();
+                        return;
+                    }
+                };
+
+                if event.trim() == "[DONE]" {
+                    break;
+                }
+
+                let chunk: Value = match serde_json::from_str(&event) {
+                    Ok(chunk) => chunk,
+                    Err(error) => {
+                        ::log::warn!("Skipping a malformed MoFa event from {url}: {error}\nEvent content: {event}");
+                        continue;
+                    }
+                };
+
+                if apply_chunk(&chunk, &mut content, &mut data) {
+                    yield ClientResult::new_ok(content.clone());
+                }
+            }
+
+            yield ClientResult::new_ok(content);
+        };
+
+        Box::pin(stream)
+    }
+}
+
+pub struct MofaCustomContent {
+    template: LivePtr,
+}
+
+impl MofaCustomContent {
+    pub fn new(template: LivePtr) -> Self {
+        Self { template }
+    }
+}
+
+impl CustomContent for MofaCustomContent {
+    fn content_widget(
+        &mut self,
+        cx: &mut Cx,
+        previous_widget: WidgetRef,
+        content: &MessageContent,
+    ) -> Option<WidgetRef> {
+        let data = MofaData::from_content(content)?;
+
+        let widget = if previous_widget.as_mofa_content().borrow().is_some() {
+            previous_widget
+        } else {
+            WidgetRef::new_from_ptr(cx, Some(self.template))
+        };
+
+        widget
+            .as_mofa_content()
+            .borrow_mut()
+            .unwrap()
+            .set_content(cx, content, &data);
+
+        Some(widget)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_agents() {
+        let body = r#"{"object":"list","data":[
+            {"id":"reasoner","object":"model","description":"Thinks step by step",
+             "inputs":[{"name":"question"}],
+             "parameters":[{"name":"api_key","required":true},{"name":"depth"}]},
+            {"id":"plain","object":"model"}
+        ]}"#;
+
+        let agents = parse_agents(body).unwrap();
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[0].description, "Thinks step by step");
+        assert_eq!(agents[0].info.inputs[0].name, "question");
+        let required: Vec<_> = agents[0]
+            .info
+            .required_parameters()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(required, vec!["api_key"]);
+
+        assert_eq!(agents[1].description, "Agent plain");
+        assert_eq!(agents[1].info, AgentInfo::default());
+    }
+
+    #[test]
+    fn test_apply_chunks() {
+        let mut content = MessageContent::default();
+        let mut data = None;
+
+        let chunks = [
+            r#"{"object":"mofa.dataflow","dataflow":{"id":"df-1","status":"running","nodes":[{"id":"search","status":"running"},{"id":"writer","status":"pending"}]}}"#,
+            r#"{"object":"mofa.output","node":"search","output":{"results":3}}"#,
+            r#"{"object":"chat.completion.chunk","choices":[{"index":0,"delta":{"content":"Found "}}]}"#,
+            r#"{"object":"chat.completion.chunk","choices":[{"index":0,"delta":{"content":"3 results."}}]}"#,
+            r#"{"object":"mofa.dataflow","dataflow":{"id":"df-1","status":"finished","nodes":[{"id":"search","status":"finished"},{"id":"writer","status":"finished"}]}}"#,
+        ];
+        for chunk in chunks {
+            assert!(apply_chunk(
+                &serde_json::from_str(chunk).unwrap(),
+                &mut content,
+                &mut data
+            ));
+        }
+
+        assert_eq!(content.text, "Found 3 results.");
+        let data = MofaData::from_content(&content).unwrap();
+        assert_eq!(data.dataflow.status, "finished");
+        assert_eq!(data.dataflow.nodes[1].status, "finished");
+        assert_eq!(data.outputs[0].node, "search");
+        assert_eq!(data.outputs[0].output["results"], 3);
+    }
+
+    #[test]
+    fn test_plain_replies_have_no_data() {
+        let mut content = MessageContent::default();
+        let mut data = None;
+        let chunk = serde_json::json!({"choices": [{"delta": {"content": "Hi"}}]});
+
+        assert!(apply_chunk(&chunk, &mut content, &mut data));
+        assert_eq!(content.text, "Hi");
+        assert!(content.data.is_none());
+    }
+}
@@ -0,0 +1,785 @@
+//! Client for OpenAI's Responses API (`/v1/responses`).
+//!
+//! Unlike chat completions, responses are stored by OpenAI, so continuing a
+//! conversation only sends what's new since the last reply along with its id. It
+//! also gives access to reasoning summaries and to tools run by OpenAI itself, like
+//! web search, file search or the code interpreter, when enabled for the provider.
+
+use async_stream::stream;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use moly_kit::aitk::utils::sse::parse_sse;
+use moly_kit::prelude::*;
+use reqwest::header::{HeaderMap, HeaderName};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use super::providers::HostedTools;
+use super::reasoning::{self, ReasoningFormat, ReasoningParams};
+use super::tool_selection::function_definition;
+
+/// Data attached to the replies, to continue the conversation from them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponsesData {
+    pub response_id: String,
+}
+
+impl ResponsesData {
+    fn from_content(content: &MessageContent) -> Option<Self> {
+        serde_json::from_str(content.data.as_deref()?).ok()
+    }
+
+    fn write_to(&self, content: &mut MessageContent) {
+        content.data = serde_json::to_string(self).ok();
+    }
+}
+
+#[derive(Clone)]
+struct OpenAiResponsesClientInner {
+    url: String,
+    api_key: Option<String>,
+    headers: HeaderMap,
+    client: reqwest::Client,
+    tools_enabled: bool,
+    hosted_tools: HostedTools,
+}
+
+/// A client for OpenAI's Responses API.
+pub struct OpenAiResponsesClient(Arc<RwLock<OpenAiResponsesClientInner>>);
+
+impl Clone for OpenAiResponsesClient {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl OpenAiResponsesClient {
+    pub fn new(url: String) -> Self {
+        Self(Arc::new(RwLock::new(OpenAiResponsesClientInner {
+            url,
+            api_key: None,
+            headers: HeaderMap::new(),
+            client: reqwest::Client::new(),
+            tools_enabled: true,
+            hosted_tools: HostedTools::default(),
+        })))
+    }
+
+    pub fn set_header(&mut self, key: &str, value: &str) -> Result<(), &'static str> {
+        let header_name = HeaderName::from_str(key).map_err(|_| "Invalid header name")?;
+        let header_value = value.parse().map_err(|_| "Invalid header value")?;
+
+        self.0
+            .write()
+            .unwrap()
+            .headers
+            .insert(header_name, header_value);
+
+        Ok(())
+    }
+
+    pub fn set_key(&mut self, key: &str) -> Result<(), &'static str> {
+        self.set_header("Authorization", &format!("Bearer {}", key))?;
+        self.0.write().unwrap().api_key = Some(key.to_string());
+        Ok(())
+    }
+
+    /// Whether the model may use tools, both the ones run by OpenAI and the ones
+    /// given to [`BotClient::send`].
+    pub fn set_tools_enabled(&mut self, enabled: bool) {
+        self.0.write().unwrap().tools_enabled = enabled;
+    }
+
+    /// Which of the tools run (and billed) by OpenAI the model is offered.
+    pub fn set_hosted_tools(&mut self, hosted_tools: HostedTools) {
+        self.0.write().unwrap().hosted_tools = hosted_tools;
+    }
+}
+
+/// Reasoning summaries are only accepted by reasoning models.
+fn supports_reasoning(model: &str) -> bool {
+    let model = model.rsplit('/').next().unwrap_or(model);
+    ["o1", "o3", "o4", "gpt-5", "codex"]
+        .iter()
+        .any(|prefix| model.starts_with(prefix))
+}
+
+/// The reply the conversation can be continued from, with the messages after it.
+fn continuation(messages: &[Message]) -> Option<(String, &[Message])> {
+    let (index, data) = messages.iter().enumerate().rev().find_map(|(i, m)| {
+        if !matches!(m.from, EntityId::Bot(_)) {
+            return None;
+        }
+        Some((i, ResponsesData::from_content(&m.content)))
+    })?;
+
+    Some((data?.response_id, &messages[index + 1..]))
+}
+
+/// Images go as `input_image` parts, any other file as an `input_file` part, both
+/// inlined as data urls.
+async fn attachment_part(attachment: &Attachment) -> Option<Value> {
+    let bytes = attachment
+        .read()
+        .await
+        .inspect_err(|e| ::log::warn!("Could not read the attachment {}: {e}", attachment.name))
+        .ok()?;
+    let content_type = attachment
+        .content_type
+        .as_deref()
+        .unwrap_or("application/octet-stream");
+    let url = format!("data:{};base64,{}", content_type, BASE64.encode(&bytes));
+
+    if content_type.starts_with("image/") {
+        Some(json!({"type": "input_image", "image_url": url}))
+    } else {
+        Some(json!({"type": "input_file", "filename": attachment.name, "file_data": url}))
+    }
+}
+
+/// Converts messages to input items of the Responses API.
+async fn input_items(messages: &[Message]) -> Vec<Value> {
+    let mut items = Vec::new();
+
+    for message in messages {
+        let content = &message.content;
+        match &message.from {
+            EntityId::User => {
+                let mut parts = vec![json!({"type": "input_text", "text": content.text})];
+                for attachment in &content.attachments {
+                    parts.extend(attachment_part(attachment).await);
+                }
+                items.push(json!({"role": "user", "content": parts}));
+            }
+            EntityId::Bot(_) => {
+                if !content.text.is_empty() {
+                    items.push(json!({"role": "assistant", "content": content.text}));
+                }
+                for tool_call in &content.tool_calls {
+                    items.push(json!({
+                        "type": "function_call",
+                        "call_id": tool_call.id,
+                        "name": tool_call.name,
+                        "arguments": Value::Object(tool_call.arguments.clone()).to_string(),
+                    }));
+                }
+            }
+            EntityId::Tool => {
+                for result in &content.tool_results {
+                    items.push(json!({
+                        "type": "function_call_output",
+                        "call_id": result.tool_call_id,
+                        "output": result.content,
+                    }));
+                }
+            }
+            // Sent as instructions instead, since they are not kept by stored responses.
+            EntityId::System | EntityId::App => {}
+        }
+    }
+
+    items
+}
+
+fn instructions(messages: &[Message]) -> Option<String> {
+    let instructions = messages
+        .iter()
+        .filter(|m| matches!(m.from, EntityId::System))
+        .map(|m| m.content.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    (!instructions.is_empty()).then_some(instructions)
+}
+
+fn function_tool(tool: &Tool) -> Option<Value> {
+    let mut function = function_definition(tool)?;
+    function["type"] = "function".into();
+    Some(function)
+}
+
+async fn request_body(
+    model: &str,
+    messages: &[Message],
+    tools: &[Tool],
+    tools_enabled: bool,
+    hosted_tools: &HostedTools,
+    reasoning_params: &ReasoningParams,
+    previous_response_id: Option<&str>,
+) -> Value {
+    let mut body = json!({
+        "model": model,
+        "input": input_items(messages).await,
+        "stream": true,
+    });
+
+    if let Some(id) = previous_response_id {
+        body["previous_response_id"] = id.into();
+    }
+    if let Some(instructions) = instructions(messages) {
+        body["instructions"] = instructions.into();
+    }
+    if supports_reasoning(model) {
+        // Effort goes along with the summary in the same object.
+        let mut reasoning_body = json!({"summary": "auto"});
+        let mut fields =
+            reasoning::request_fields(reasoning_params, ReasoningFormat::OpenAiResponses);
+        if let (Some(object), Some(Value::Object(fields))) =
+            (reasoning_body.as_object_mut(), fields.remove("reasoning"))
+        {
+            object.extend(fields);
+        }
+        body["reasoning"] = reasoning_body;
+    }
+    if tools_enabled {
+        let mut tool_list = Vec::new();
+        let mut include = Vec::new();
+        if hosted_tools.web_search {
+            tool_list.push(json!({"type": "web_search_preview"}));
+        }
+        if hosted_tools.code_interpreter {
+            tool_list.push(json!({"type": "code_interpreter", "container": {"type": "auto"}}));
+            include.push("code_interpreter_call.outputs");
+        }
+        if !hosted_tools.file_search_vector_stores.is_empty() {
+            tool_list.push(json!({
+                "type": "file_search",
+                "vector_store_ids": hosted_tools.file_search_vector_stores,
+            }));
+            include.push("file_search_call.results");
+        }
+        tool_list.extend(tools.iter().filter_map(function_tool));
+        if !tool_list.is_empty() {
+            body["tools"] = tool_list.into();
+        }
+        if !include.is_empty() {
+            body["include"] = include.into();
+        }
+    }
+
+    body
+}
+
+/// What a stream event asks the client to do.
+#[derive(Debug, PartialEq)]
+enum EventOutcome {
+    Continue,
+    Updated,
+    Done,
+    Failed(String),
+}
+
+/// Applies a stream event to the content.
+fn apply_event(event: &Value, content: &mut MessageContent) -> EventOutcome {
+    match event["type"].as_str().unwrap_or_default() {
+        "response.created" => {
+            if let Some(id) = event["response"]["id"].as_str() {
+                ResponsesData {
+                    response_id: id.to_string(),
+                }
+                .write_to(content);
+            }
+            EventOutcome::Continue
+        }
+        "response.output_text.delta" => {
+            content
+                .text
+                .push_str(event["delta"].as_str().unwrap_or_default());
+            EventOutcome::Updated
+        }
+        "response.reasoning_summary_part.added" => {
+            if !content.reasoning.is_empty() {
+                content.reasoning.push_str("\n\n");
+            }
+            EventOutcome::Continue
+        }
+        "response.reasoning_summary_text.delta" => {
+            content
+                .reasoning
+                .push_str(event["delta"].as_str().unwrap_or_default());
+            EventOutcome::Updated
+        }
+        "response.output_text.annotation.added" => {
+            let annotation = &event["annotation"];
+            match annotation["url"].as_str() {
+                Some(url) if annotation["type"] == "url_citation" => {
+                    if !content.citations.iter().any(|c| c == url) {
+                        content.citations.push(url.to_string());
+                    }
+                    EventOutcome::Updated
+                }
+                _ => EventOutcome::Continue,
+            }
+        }
+        "response.output_item.done" => apply_output_item(&event["item"], content),
+        "response.completed" => {
+            let usage = &event["response"]["usage"];
+            if let Some(tokens) = usage["output_tokens_details"]["reasoning_tokens"].as_u64() {
+                reasoning::write_reasoning_tokens(&mut content.data, tokens);
+            }
+            EventOutcome::Done
+        }
+        "response.incomplete" => {
+            let reason = event["response"]["incomplete_details"]["reason"]
+                .as_str()
+                .unwrap_or("unknown reason");
+            EventOutcome::Failed(format!("The response is incomplete: {}", reason))
+        }
+        "response.failed" => EventOutcome::Failed(
+            event["response"]["error"]["message"]
+                .as_str()
+                .unwrap_or("The response failed")
+                .to_string(),
+        ),
+        "error" => EventOutcome::Failed(
+            event["message"]
+                .as_str()
+                .unwrap_or("Unknown error")
+                .to_string(),
+        ),
+        _ => EventOutcome::Continue,
+    }
+}
+
+/// Maps finished output items. Function calls go through the chat's tool approval,
+/// while calls of built-in tools, already run by OpenAI, are only reported.
+fn apply_output_item(item: &Value, content: &mut MessageContent) -> EventOutcome {
+    let report = match item["type"].as_str().unwrap_or_default() {
+        "function_call" => {
+            let arguments = item["arguments"]
+                .as_str()
+                .and_then(|a| serde_json::from_str::<Value>(a).ok())
+                .and_then(|a| a.as_object().cloned())
+                .unwrap_or_default();
+
+            content.tool_calls.push(ToolCall {
+                id: item["call_id"].as_str().unwrap_or_default().to_string(),
+                name: item["name"].as_str().unwrap_or_default().to_string(),
+                arguments,
+                ..Default::default()
+            });
+            return EventOutcome::Updated;
+        }
+        "web_search_call" => match item["action"]["query"].as_str() {
+            Some(query) => format!("🔎 Searched the web for “{}”", query),
+            None => "🔎 Searched the web".to_string(),
+        },
+        "file_search_call" => {
+            let queries: Vec<&str> = item["queries"]
+                .as_array()
+                .map(|q| q.iter().filter_map(|q| q.as_str()).collect())
+                .unwrap_or_default();
+            let results = item["results"].as_array().map_or(0, |r| r.len());
+
+            let mut report = if queries.is_empty() {
+                "📂 Searched files".to_string()
+            } else {
+                format!("📂 Searched files for “{}”", queries.join("”, “"))
+            };
+            if results > 0 {
+                report.push_str(&format!(" ({} results)", results));
+            }
+            report
+        }
+        "code_interpreter_call" => {
+            let mut report = "🧮 Ran code".to_string();
+            if let Some(code) = item["code"].as_str().filter(|c| !c.is_empty()) {
+                report.push_str(&format!("\n```python\n{}\n```", code.trim_end()));
+            }
+            for output in item["outputs"].as_array().into_iter().flatten() {
+                match output["type"].as_str() {
+                    Some("logs") => {
+                        let logs = output["logs"].as_str().unwrap_or_default().trim_end();
+                        if !logs.is_empty() {
+                            report.push_str(&format!("\n```\n{}\n```", logs));
+                        }
+                    }
+                    Some("image") => {
+                        if let Some(url) = output["url"].as_str() {
+                            report.push_str(&format!("\n![output]({})", url));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            report
+        }
+        _ => return EventOutcome::Continue,
+    };
+
+    if !content.text.is_empty() && !content.text.ends_with("\n\n") {
+        content.text.push_str("\n\n");
+    }
+    content.text.push_str(&report);
+    content.text.push_str("\n\n");
+    EventOutcome::Updated
+}
+
+impl BotClient for OpenAiResponsesClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        // Models are listed the same way as with chat completions.
+        let inner = self.0.read().unwrap().clone();
+        let mut models_client = OpenAiClient::new(inner.url);
+        if let Some(key) = inner.api_key {
+            let _ = models_client.set_key(&key);
+        }
+        models_client.bots()
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let inner = self.0.read().unwrap().clone();
+        let url = format!("{}/responses", inner.url);
+        let model = bot_id.id().to_string();
+        let reasoning_params = reasoning::request_params(messages);
+
+        let messages = messages.to_vec();
+        let tools = tools.to_vec();
+
+        let stream = stream! {
+            let full_body = request_body(
+                &model,
+                &messages,
+                &tools,
+                inner.tools_enabled,
+                &inner.hosted_tools,
+                &reasoning_params,
+                None,
+            )
+            .await;
+            // Only what's new since the last reply is sent when continuing from it.
+            let continued_body = match continuation(&messages) {
+                Some((id, new_messages)) => {
+                    let mut body = request_body(
+                        &model,
+                        new_messages,
+                        &tools,
+                        inner.tools_enabled,
+                        &inner.hosted_tools,
+                        &reasoning_params,
+                        Some(&id),
+                    )
+                    .await;
+                    // Instructions aren't kept by stored responses, so they go every time.
+                    if let Some(instructions) = instructions(&messages) {
+                        body["instructions"] = instructions.into();
+                    }
+                    Some(body)
+                }
+                None => None,
+            };
+
+            let mut bodies = continued_body.into_iter().chain(std::iter::once(full_body));
+            let response = loop {
+                let Some(body) = bodies.next() else {
+                    return;
+                };
+                let continued = body.get("previous_response_id").is_some();
+
+                let request = inner
+                    .client
+                    .post(&url)
+                    .headers(inner.headers.clone())
+                    .json(&body);
+                let response = match request.send().await {
+                    Ok(response) => response,
+                    Err(error) => {
+                        yield ClientError::new_with_source(
+                            ClientErrorKind::Network,
+                            format!("Could not reach {url}."),
+                            Some(error),
+                        )
+                        .into();
+                        return;
+                    }
+                };
+
+                if response.status().is_success() {
+                    break response;
+                }
+
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                // Stored responses expire or may belong to another key, in which
+                // case the whole conversation is sent instead.
+                if continued && status.is_client_error() && body.contains("previous_response") {
+                    ::log::warn!("Could not continue from the previous response, sending the whole conversation: {body}");
+                    continue;
+                }
+
+                yield ClientError::new(
+                    ClientErrorKind::Response,
+                    format!("Request to {url} failed with status {status}"),
+                )
+                .with_details(body)
+                .into();
+                return;
+            };
+
+            let mut content = MessageContent::default();
+            for await event in parse_sse(response.bytes_stream()) {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(error) => {
+                        yield ClientResult::new_ok(content.clone());
+                        yield ClientError::new_with_source(
+                            ClientErrorKind::Network,
+                            format!("The connection was unexpectedly closed while streaming the response from {url}."),
+                            Some(error),
+                        )
+                        .into();
+                        return;
+                    }
+                };
+
+                let event: Value = match serde_json::from_str(&event) {
+                    Ok(event) => event,
+                    Err(error) => {
+                        ::log::warn!("Skipping a malformed event from {url}: {error}\nEvent content: {event}");
+                        continue;
+                    }
+                };
+
+                match apply_event(&event, &mut content) {
+                    EventOutcome::Continue => {}
+                    EventOutcome::Updated => yield ClientResult::new_ok(content.clone()),
+                    EventOutcome::Done => break,
+                    EventOutcome::Failed(message) => {
+                        yield ClientResult::new_ok(content.clone());
+                        yield ClientError::new(ClientErrorKind::Response, message).into();
+                        return;
+                    }
+                }
+            }
+
+            yield ClientResult::new_ok(content);
+        };
+
+        Box::pin(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::test_utils::block_on;
+
+    fn message(from: EntityId, text: &str, response_id: Option<&str>) -> Message {
+        let mut content = MessageContent {
+            text: text.to_string(),
+            ..Default::default()
+        };
+        if let Some(id) = response_id {
+            ResponsesData {
+                response_id: id.to_string(),
+            }
+            .write_to(&mut content);
+        }
+        Message {
+            from,
+            content,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_continuation_sends_only_new_messages() {
+        let bot = EntityId::Bot(BotId::new("gpt-5"));
+        let messages = vec![
+            message(EntityId::System, "Be brief.", None),
+            message(EntityId::User, "Hi", None),
+            message(bot, "Hello!", Some("resp_1")),
+            message(EntityId::User, "How are you?", None),
+        ];
+
+        let (id, new_messages) = continuation(&messages).unwrap();
+        assert_eq!(id, "resp_1");
+        assert_eq!(new_messages.len(), 1);
+
+        let reasoning_params = ReasoningParams {
+            effort: Some(reasoning::ReasoningEffort::Low),
+            max_thinking_tokens: None,
+        };
+        let body = block_on(request_body(
+            "gpt-5",
+            new_messages,
+            &[],
+            false,
+            &HostedTools::default(),
+            &reasoning_params,
+            Some(&id),
+        ));
+        assert_eq!(body["previous_response_id"], "resp_1");
+        assert_eq!(body["input"][0]["content"][0]["text"], "How are you?");
+        assert_eq!(
+            body["reasoning"],
+            json!({"summary": "auto", "effort": "low"})
+        );
+        assert_eq!(instructions(&messages).as_deref(), Some("Be brief."));
+
+        // Replies without a stored response can't be continued from.
+        let messages = vec![message(EntityId::User, "Hi", None)];
+        assert!(continuation(&messages).is_none());
+    }
+
+    #[test]
+    fn test_attachments_are_sent() {
+        let mut message = message(EntityId::User, "What's in these?", None);
+        message.content.attachments = vec![
+            Attachment::from_bytes(
+                "cat.png".to_string(),
+                Some("image/png".to_string()),
+                &[1, 2, 3],
+            ),
+            Attachment::from_bytes(
+                "report.pdf".to_string(),
+                Some("application/pdf".to_string()),
+                b"%PDF",
+            ),
+        ];
+
+        let items = block_on(input_items(&[message]));
+        assert_eq!(
+            items[0]["content"],
+            json!([
+                {"type": "input_text", "text": "What's in these?"},
+                {"type": "input_image", "image_url": "data:image/png;base64,AQID"},
+                {
+                    "type": "input_file",
+                    "filename": "report.pdf",
+                    "file_data": "data:application/pdf;base64,JVBERg==",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_hosted_tools_are_opt_in() {
+        let messages = vec![message(EntityId::User, "Hi", None)];
+        let params = ReasoningParams::default();
+
+        let body = block_on(request_body(
+            "gpt-4o",
+            &messages,
+            &[],
+            true,
+            &HostedTools::default(),
+            &params,
+            None,
+        ));
+        assert!(body.get("tools").is_none());
+        assert!(body.get("include").is_none());
+
+        let web_search = HostedTools {
+            web_search: true,
+            ..Default::default()
+        };
+        let body = block_on(request_body(
+            "gpt-4o",
+            &messages,
+            &[],
+            true,
+            &web_search,
+            &params,
+            None,
+        ));
+        assert_eq!(body["tools"], json!([{"type": "web_search_preview"}]));
+        assert!(body.get("include").is_none());
+
+        let all = HostedTools {
+            web_search: true,
+            code_interpreter: true,
+            file_search_vector_stores: vec!["vs_1".to_string()],
+        };
+        let body = block_on(request_body(
+            "gpt-4o",
+            &messages,
+            &[],
+            true,
+            &all,
+            &params,
+            None,
+        ));
+        assert_eq!(body["tools"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            body["tools"][2],
+            json!({"type": "file_search", "vector_store_ids": ["vs_1"]})
+        );
+        assert_eq!(
+            body["include"],
+            json!(["code_interpreter_call.outputs", "file_search_call.results"])
+        );
+
+        // Disabling tools for the chat also leaves out the hosted ones.
+        let body = block_on(request_body(
+            "gpt-4o",
+            &messages,
+            &[],
+            false,
+            &all,
+            &params,
+            None,
+        ));
+        assert!(body.get("tools").is_none());
+    }
+
+    #[test]
+    fn test_stream_events() {
+        let events = [
+            json!({"type": "response.created", "response": {"id": "resp_2"}}),
+            json!({"type": "response.reasoning_summary_part.added", "summary_index": 0}),
+            json!({"type": "response.reasoning_summary_text.delta", "delta": "Checking."}),
+            json!({"type": "response.reasoning_summary_part.added", "summary_index": 1}),
+            json!({"type": "response.reasoning_summary_text.delta", "delta": "Done."}),
+            json!({"type": "response.output_item.done", "item": {"type": "web_search_call", "action": {"query": "moly"}}}),
+            json!({"type": "response.output_item.done", "item": {"type": "file_search_call", "queries": ["moly docs"], "results": [{}, {}]}}),
+            json!({"type": "response.output_text.delta", "delta": "Moly is an app."}),
+            json!({"type": "response.output_text.annotation.added", "annotation": {"type": "url_citation", "url": "https://moly.ai"}}),
+            json!({"type": "response.output_item.done", "item": {"type": "code_interpreter_call", "code": "print(1)", "outputs": [{"type": "logs", "logs": "1\n"}]}}),
+            json!({"type": "response.output_item.done", "item": {"type": "function_call", "call_id": "call_1", "name": "read_file", "arguments": "{\"path\":\"a.txt\"}"}}),
+        ];
+
+        let mut content = MessageContent::default();
+        for event in &events {
+            assert_ne!(apply_event(event, &mut content), EventOutcome::Done);
+        }
+        let completed = json!({"type": "response.completed", "response": {"usage": {"output_tokens_details": {"reasoning_tokens": 128}}}});
+        assert_eq!(apply_event(&completed, &mut content), EventOutcome::Done);
+
+        assert_eq!(
+            ResponsesData::from_content(&content).unwrap().response_id,
+            "resp_2"
+        );
+        assert_eq!(content.reasoning, "Checking.\n\nDone.");
+        assert_eq!(
+            content.text,
+            "🔎 Searched the web for “moly”\n\n📂 Searched files for “moly docs” (2 results)\n\nMoly is an app.\n\n🧮 Ran code\n```python\nprint(1)\n```\n```\n1\n```\n\n"
+        );
+        assert_eq!(content.citations, vec!["https://moly.ai".to_string()]);
+        assert_eq!(content.tool_calls.len(), 1);
+        assert_eq!(content.tool_calls[0].id, "call_1");
+        assert_eq!(content.tool_calls[0].arguments["path"], "a.txt");
+    }
+
+    #[test]
+    fn test_failed_response() {
+        let mut content = MessageContent::default();
+        let event =
+            json!({"type": "response.failed", "response": {"error": {"message": "Rate limited"}}});
+        assert_eq!(
+            apply_event(&event, &mut content),
+            EventOutcome::Failed("Rate limited".to_string())
+        );
+    }
+}
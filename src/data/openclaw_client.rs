@@ -2,21 +2,37 @@
 //!
 //! This module provides a client for interacting with the OpenClaw Gateway,
 //! a local AI assistant platform that supports multiple messaging channels.
+//!
+//! Each agent configured in the Gateway is exposed as a separate bot. Conversations
+//! live in Gateway sessions, so only the new user message is sent on each turn, over
+//! a single authenticated WebSocket shared by all the clones of a client.
 
 use async_stream::stream;
-use futures::{FutureExt, SinkExt, StreamExt};
-use moly_kit::aitk::utils::asynchronous::sleep;
+use makepad_widgets::{Cx, LiveNew, LivePtr, WidgetRef};
 use moly_kit::prelude::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashSet;
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
 use uuid::Uuid;
 
+use crate::chat::openclaw_content::OpenClawContentWidgetRefExt;
+
+#[cfg(not(target_arch = "wasm32"))]
+use futures::{FutureExt, SinkExt, StreamExt, channel::mpsc, lock::Mutex as AsyncMutex};
+#[cfg(not(target_arch = "wasm32"))]
+use moly_kit::aitk::utils::asynchronous::{sleep, spawn};
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashSet;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
 
+/// Agent that every Gateway runs, used when agents can't be listed.
+const DEFAULT_AGENT_ID: &str = "main";
+/// Id of the single bot exposed before agents were listed from the Gateway.
+const LEGACY_BOT_ID: &str = "openclaw/assistant";
+
 /// OpenClaw protocol request wrapper.
 #[derive(Debug, Clone, Serialize)]
 struct Request<T> {
@@ -67,13 +83,60 @@ struct AuthInfo {
 struct AgentParams {
     message: String,
     idempotency_key: String,
-    agent_id: &'static str,
+    agent_id: String,
+    session_key: String,
+}
+
+/// Data attached to the replies of OpenClaw bots, so the next turns of the
+/// conversation continue the same Gateway session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenClawData {
+    pub session_key: String,
+    /// What the agent did while answering, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub activity: Vec<OpenClawActivity>,
+}
+
+impl OpenClawData {
+    fn from_content(content: &MessageContent) -> Option<Self> {
+        serde_json::from_str(content.data.as_deref()?).ok()
+    }
+
+    fn write_to(&self, content: &mut MessageContent) {
+        content.data = serde_json::to_string(self).ok();
+    }
+}
+
+/// Something an OpenClaw agent did on the user's machine besides replying.
+///
+/// Tools are run by the Gateway itself, so these are only informative and never go
+/// through the tool approval of the chat.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OpenClawActivity {
+    ToolCall {
+        id: String,
+        name: String,
+        /// Arguments as compact JSON, empty if there were none.
+        arguments: String,
+    },
+    ToolResult {
+        id: String,
+        name: String,
+        output: String,
+        is_error: bool,
+    },
+    /// Activity on a messaging channel, like a message relayed to it.
+    Channel { channel: String, text: String },
 }
 
 #[derive(Debug, Clone)]
 struct OpenClawClientInner {
     url: String,
     token: Option<String>,
+    /// Shared by all the clones of the client, opened on first use.
+    #[cfg(not(target_arch = "wasm32"))]
+    connection: Arc<AsyncMutex<Option<Connection>>>,
 }
 
 /// A client for interacting with the OpenClaw Gateway.
@@ -95,18 +158,39 @@ impl From<OpenClawClientInner> for OpenClawClient {
 impl OpenClawClient {
     /// Creates a new OpenClaw client with the given Gateway URL.
     pub fn new(url: String) -> Self {
-        OpenClawClientInner { url, token: None }.into()
+        OpenClawClientInner {
+            url,
+            token: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            connection: Default::default(),
+        }
+        .into()
     }
 
     /// Sets the authentication token for the client.
     pub fn set_key(&mut self, token: &str) -> Result<(), &'static str> {
-        self.0
+        let mut inner = self
+            .0
             .write()
-            .map_err(|_| "OpenClaw client lock poisoned")?
-            .token = Some(token.to_string());
+            .map_err(|_| "OpenClaw client lock poisoned")?;
+        inner.token = Some(token.to_string());
+        // Authenticate again with the new token.
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            inner.connection = Default::default();
+        }
         Ok(())
     }
 
+    fn inner(&self) -> Result<OpenClawClientInner, ClientError> {
+        self.0.read().map(|inner| inner.clone()).map_err(|_| {
+            ClientError::new(
+                ClientErrorKind::Unknown,
+                "OpenClaw client lock poisoned".to_string(),
+            )
+        })
+    }
+
     fn build_connect_request(token: Option<&str>) -> Request<ConnectParams> {
         Request::new(
             "connect",
@@ -128,17 +212,24 @@ impl OpenClawClient {
         )
     }
 
-    fn build_agent_request(message: String) -> Request<AgentParams> {
+    fn build_agent_request(
+        message: String,
+        agent_id: &str,
+        session_key: &str,
+    ) -> Request<AgentParams> {
         Request::new(
             "agent",
             AgentParams {
                 message,
                 idempotency_key: Uuid::new_v4().to_string(),
-                agent_id: "main",
+                agent_id: agent_id.to_string(),
+                session_key: session_key.to_string(),
             },
         )
     }
 
+    /// Flattens the conversation into a single message, used to seed a new session
+    /// when the conversation didn't start with this agent.
     fn build_history_message(messages: &[Message]) -> String {
         let mut combined = String::new();
         let start = messages.len().saturating_sub(MAX_HISTORY_MESSAGES);
@@ -164,16 +255,7 @@ impl OpenClawClient {
         }
 
         if combined.is_empty() {
-            let fallback = messages
-                .iter()
-                .rev()
-                .find(|m| matches!(m.from, EntityId::User))
-                .map(|m| m.content.text.trim())
-                .filter(|text| !text.is_empty());
-
-            if let Some(text) = fallback {
-                combined.push_str(text);
-            }
+            combined.push_str(&latest_user_message(messages));
         }
 
         combined
@@ -182,15 +264,374 @@ impl OpenClawClient {
 
 #[cfg(not(target_arch = "wasm32"))]
 const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+#[cfg(not(target_arch = "wasm32"))]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_CONNECT_RETRIES: u32 = 2;
+#[cfg(not(target_arch = "wasm32"))]
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_millis(500);
 const MAX_HISTORY_MESSAGES: usize = 32;
 
+/// The agent behind a bot, mapping the legacy single bot to the default agent.
+fn agent_id(bot_id: &BotId) -> &str {
+    match bot_id.as_str() {
+        LEGACY_BOT_ID => DEFAULT_AGENT_ID,
+        id => id,
+    }
+}
+
+fn agent_bot(id: &str, name: Option<&str>, emoji: Option<&str>) -> Bot {
+    let name = match name {
+        Some(name) => name.to_string(),
+        None if id == DEFAULT_AGENT_ID => "OpenClaw Assistant".to_string(),
+        None => format!("OpenClaw {}", id),
+    };
+
+    Bot {
+        id: BotId::new(id),
+        name,
+        avatar: EntityAvatar::Text(emoji.unwrap_or("🦞").into()),
+        capabilities: BotCapabilities::new().with_capabilities([BotCapability::TextInput]),
+    }
+}
+
+/// Builds a bot per agent out of an `agents.list` response.
+fn parse_agents(payload: &Value) -> Vec<Bot> {
+    let agents = payload["agents"].as_array().or(payload.as_array());
+    let bots: Vec<Bot> = agents
+        .into_iter()
+        .flatten()
+        .filter_map(|agent| {
+            let id = agent["id"].as_str()?;
+            let name = agent["name"]
+                .as_str()
+                .or(agent["identity"]["name"].as_str());
+            let emoji = agent["identity"]["emoji"].as_str();
+            Some(agent_bot(id, name, emoji))
+        })
+        .collect();
+
+    if bots.is_empty() {
+        vec![agent_bot(DEFAULT_AGENT_ID, None, None)]
+    } else {
+        bots
+    }
+}
+
+fn new_session_key(agent_id: &str) -> String {
+    format!("agent:{}:moly-{}", agent_id, Uuid::new_v4())
+}
+
+fn session_agent(session_key: &str) -> Option<&str> {
+    session_key.strip_prefix("agent:")?.split(':').next()
+}
+
+/// The Gateway session of the conversation, if its last OpenClaw reply came from
+/// the same agent.
+fn find_session(messages: &[Message], agent_id: &str) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .filter(|m| matches!(m.from, EntityId::Bot(_)))
+        .find_map(|m| OpenClawData::from_content(&m.content))
+        .map(|data| data.session_key)
+        .filter(|key| session_agent(key) == Some(agent_id))
+}
+
+fn latest_user_message(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .rev()
+        .find(|m| matches!(m.from, EntityId::User))
+        .map(|m| m.content.text.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Whether an event belongs to the given run, as streams share the connection.
+///
+/// Events without identifiers are accepted, for Gateways that don't send them.
+#[cfg(not(target_arch = "wasm32"))]
+fn belongs_to_run(payload: Option<&Value>, session_key: &str, run_id: Option<&str>) -> bool {
+    let Some(payload) = payload else {
+        return true;
+    };
+
+    if let Some(key) = payload["sessionKey"].as_str() {
+        return key == session_key;
+    }
+
+    match (payload["runId"].as_str(), run_id) {
+        (Some(event_run), Some(run)) => event_run == run,
+        _ => true,
+    }
+}
+
+fn response_error(json: &Value) -> ClientError {
+    let msg = json["error"]
+        .as_str()
+        .or(json["error"]["message"].as_str())
+        .unwrap_or("Unknown error");
+    ClientError::new(
+        ClientErrorKind::Response,
+        format!("OpenClaw error: {}", msg),
+    )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn connection_lost() -> ClientError {
+    ClientError::new(
+        ClientErrorKind::Network,
+        "Lost the connection to the OpenClaw Gateway".to_string(),
+    )
+}
+
+/// An authenticated WebSocket to the Gateway.
+///
+/// Handles are cheap to clone. The socket is closed once every handle is dropped, and
+/// a handle stops being open if the Gateway closes the socket.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+struct Connection {
+    outgoing: mpsc::UnboundedSender<String>,
+    subscribers: Arc<std::sync::Mutex<Vec<mpsc::UnboundedSender<Value>>>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Connection {
+    async fn open(url: &str, token: Option<&str>) -> Result<Self, ClientError> {
+        log::debug!("OpenClaw: connecting to {}", url);
+
+        let (ws_stream, _) = connect_async(url).await.map_err(|e| {
+            ClientError::new(
+                ClientErrorKind::Network,
+                format!("Failed to connect to OpenClaw Gateway: {}", e),
+            )
+        })?;
+        let (mut write, read) = ws_stream.split();
+        let mut read = read.fuse();
+
+        // Authenticate right away, and again if the Gateway sends a challenge.
+        let mut connect_ids = Vec::new();
+        let request = OpenClawClient::build_connect_request(token);
+        connect_ids.push(request.id.clone());
+        write
+            .send(WsMessage::Text(to_json(&request)?.into()))
+            .await
+            .map_err(send_error)?;
+
+        let handshake_deadline = std::pin::pin!(sleep(HANDSHAKE_TIMEOUT));
+        let mut handshake_deadline = handshake_deadline.fuse();
+        loop {
+            let msg = futures::select! {
+                _ = handshake_deadline => {
+                    return Err(ClientError::new(
+                        ClientErrorKind::Network,
+                        "Timed out waiting for OpenClaw handshake".to_string(),
+                    ));
+                }
+                msg = read.next() => msg,
+            };
+
+            let json: Value = match msg {
+                Some(Ok(WsMessage::Text(text))) => match serde_json::from_str(&text) {
+                    Ok(json) => json,
+                    Err(_) => continue,
+                },
+                Some(Ok(WsMessage::Close(_))) | None => return Err(connection_lost()),
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    return Err(ClientError::new(
+                        ClientErrorKind::Network,
+                        format!("WebSocket error: {}", e),
+                    ));
+                }
+            };
+
+            match json["type"].as_str().unwrap_or("") {
+                "event" if json["event"].as_str() == Some("connect.challenge") => {
+                    log::debug!("OpenClaw: received connect.challenge");
+                    let request = OpenClawClient::build_connect_request(token);
+                    connect_ids.push(request.id.clone());
+                    write
+                        .send(WsMessage::Text(to_json(&request)?.into()))
+                        .await
+                        .map_err(send_error)?;
+                }
+                "res"
+                    if connect_ids
+                        .iter()
+                        .any(|id| json["id"].as_str() == Some(id.as_str())) =>
+                {
+                    if !json["ok"].as_bool().unwrap_or(false) {
+                        return Err(response_error(&json));
+                    }
+                    log::debug!("OpenClaw: connected");
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        let (outgoing, mut outgoing_rx) = mpsc::unbounded::<String>();
+        let subscribers: Arc<std::sync::Mutex<Vec<mpsc::UnboundedSender<Value>>>> =
+            Default::default();
+
+        let pump_subscribers = subscribers.clone();
+        spawn(async move {
+            loop {
+                futures::select! {
+                    msg = read.next() => match msg {
+                        Some(Ok(WsMessage::Text(text))) => {
+                            let Ok(json) = serde_json::from_str::<Value>(&text) else {
+                                continue;
+                            };
+                            pump_subscribers
+                                .lock()
+                                .unwrap()
+                                .retain(|s| s.unbounded_send(json.clone()).is_ok());
+                        }
+                        Some(Ok(WsMessage::Close(_))) | None => break,
+                        Some(Ok(_)) => {}
+                        Some(Err(e)) => {
+                            log::warn!("OpenClaw: WebSocket error: {}", e);
+                            break;
+                        }
+                    },
+                    json = outgoing_rx.next() => match json {
+                        Some(json) => {
+                            if let Err(e) = write.send(WsMessage::Text(json.into())).await {
+                                log::warn!("OpenClaw: failed to send request: {}", e);
+                                break;
+                            }
+                        }
+                        // Every handle was dropped, nobody uses this connection anymore.
+                        None => {
+                            let _ = write.close().await;
+                            break;
+                        }
+                    },
+                }
+            }
+
+            log::debug!("OpenClaw: connection closed");
+            // Ends the streams still waiting on this connection.
+            pump_subscribers.lock().unwrap().clear();
+        });
+
+        Ok(Self {
+            outgoing,
+            subscribers,
+        })
+    }
+
+    fn is_open(&self) -> bool {
+        !self.outgoing.is_closed()
+    }
+
+    /// Receives every message from the Gateway from now on, until the connection
+    /// closes.
+    fn subscribe(&self) -> mpsc::UnboundedReceiver<Value> {
+        let (tx, rx) = mpsc::unbounded();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    fn send<T: Serialize>(&self, request: &Request<T>) -> Result<(), ClientError> {
+        self.outgoing
+            .unbounded_send(to_json(request)?)
+            .map_err(|_| connection_lost())
+    }
+
+    /// Sends a request and waits for its final response payload.
+    async fn request<T: Serialize>(&self, method: &str, params: T) -> Result<Value, ClientError> {
+        let request = Request::new(method, params);
+        let mut incoming = self.subscribe();
+        self.send(&request)?;
+
+        let deadline = std::pin::pin!(sleep(REQUEST_TIMEOUT));
+        let mut deadline = deadline.fuse();
+        loop {
+            let json = futures::select! {
+                _ = deadline => {
+                    return Err(ClientError::new(
+                        ClientErrorKind::Network,
+                        format!("Timed out waiting for OpenClaw to answer {}", method),
+                    ));
+                }
+                json = incoming.next() => json.ok_or_else(connection_lost)?,
+            };
+
+            if json["type"].as_str() != Some("res")
+                || json["id"].as_str() != Some(request.id.as_str())
+            {
+                continue;
+            }
+            if !json["ok"].as_bool().unwrap_or(false) {
+                return Err(response_error(&json));
+            }
+            if json["payload"]["status"].as_str() == Some("accepted") {
+                continue;
+            }
+            return Ok(json["payload"].clone());
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl OpenClawClientInner {
+    /// The open connection to the Gateway, reconnecting if it was lost.
+    async fn connection(&self) -> Result<Connection, ClientError> {
+        let mut connection = self.connection.lock().await;
+        if let Some(open) = connection.as_ref().filter(|c| c.is_open()) {
+            return Ok(open.clone());
+        }
+
+        let mut backoff = INITIAL_RECONNECT_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            match Connection::open(&self.url, self.token.as_deref()).await {
+                Ok(open) => {
+                    *connection = Some(open.clone());
+                    return Ok(open);
+                }
+                Err(e)
+                    if attempt < MAX_CONNECT_RETRIES
+                        && matches!(e.kind(), ClientErrorKind::Network) =>
+                {
+                    log::warn!("OpenClaw: {}, reconnecting in {:?}", e, backoff);
+                    sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn to_json<T: Serialize>(request: &Request<T>) -> Result<String, ClientError> {
+    serde_json::to_string(request).map_err(|e| {
+        ClientError::new(
+            ClientErrorKind::Format,
+            format!("Failed to serialize request: {}", e),
+        )
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn send_error(e: tokio_tungstenite::tungstenite::Error) -> ClientError {
+    ClientError::new(
+        ClientErrorKind::Network,
+        format!("Failed to send request: {}", e),
+    )
+}
+
 /// Result of processing a WebSocket message.
 enum ProcessResult {
     Continue,
     Yield(MessageContent),
     Error(ClientError),
-    SendConnect,
-    SendAgent,
     Done,
 }
 
@@ -247,12 +688,33 @@ fn process_event(
     event: &str,
     payload: Option<&Value>,
     content: &mut MessageContent,
+    data: &mut OpenClawData,
 ) -> ProcessResult {
-    match event {
-        "connect.challenge" => {
-            log::debug!("OpenClaw: received connect.challenge");
-            ProcessResult::SendConnect
+    let activity = match (event, payload) {
+        ("agent", Some(p)) => match p["stream"].as_str() {
+            Some("tool") => Some(tool_activity(p["data"]["phase"].as_str(), &p["data"])),
+            Some("channel") => Some(channel_activity(&p["data"])),
+            _ => None,
+        },
+        ("agent.tool" | "agent.tool.start" | "agent.tool.call", Some(p)) => {
+            Some(tool_activity(p["phase"].as_str().or(Some("start")), p))
         }
+        ("agent.tool.result" | "agent.tool.end", Some(p)) => Some(tool_activity(Some("result"), p)),
+        ("channel" | "channel.message" | "channel.status", Some(p)) => Some(channel_activity(p)),
+        _ => None,
+    };
+
+    if let Some(activity) = activity {
+        let Some(activity) = activity else {
+            // Known activity without anything worth showing, like partial tool output.
+            return ProcessResult::Continue;
+        };
+        data.activity.push(activity);
+        data.write_to(content);
+        return ProcessResult::Yield(content.clone());
+    }
+
+    match event {
         "agent.text" | "agent.content" => {
             if let Some(text) = payload.and_then(|p| p["text"].as_str()) {
                 merge_text_content(&mut content.text, text);
@@ -286,10 +748,10 @@ fn process_event(
         "agent.done" | "agent.complete" | "agent.end" => ProcessResult::Continue,
         "chat" => {
             let state = payload.and_then(|p| p["state"].as_str());
-            if state != Some("done") && state != Some("complete") {
-                return ProcessResult::Continue;
+            match state {
+                Some("done") | Some("complete") | Some("final") => ProcessResult::Done,
+                _ => ProcessResult::Continue,
             }
-            ProcessResult::Done
         }
         "agent.error" => {
             let msg = payload
@@ -307,19 +769,85 @@ fn process_event(
     }
 }
 
-/// Processes a response message from OpenClaw.
-fn process_response(
-    json: &Value,
-    content: &mut MessageContent,
-    connected: &mut bool,
-) -> ProcessResult {
+/// Maps a tool event to activity, for the phases worth showing.
+fn tool_activity(phase: Option<&str>, data: &Value) -> Option<OpenClawActivity> {
+    let id = data["toolCallId"]
+        .as_str()
+        .or(data["id"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    let name = data["name"]
+        .as_str()
+        .or(data["tool"].as_str())
+        .unwrap_or("tool")
+        .to_string();
+
+    match phase {
+        Some("start") | Some("call") => {
+            let arguments = match data.get("args").or(data.get("arguments")) {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::Object(args)) if args.is_empty() => String::new(),
+                Some(args) => args.to_string(),
+            };
+            Some(OpenClawActivity::ToolCall {
+                id,
+                name,
+                arguments,
+            })
+        }
+        Some("result") | Some("end") => {
+            let result = data.get("result").or(data.get("output"));
+            Some(OpenClawActivity::ToolResult {
+                id,
+                name,
+                output: result.map(tool_output_text).unwrap_or_default(),
+                is_error: data["isError"].as_bool().unwrap_or(false),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Text of a tool output, which may be MCP-like content parts.
+fn tool_output_text(output: &Value) -> String {
+    if let Some(text) = output.as_str() {
+        return text.to_string();
+    }
+
+    let parts = output["content"].as_array().or(output.as_array());
+    let texts: Vec<&str> = parts
+        .into_iter()
+        .flatten()
+        .filter_map(|part| part["text"].as_str())
+        .collect();
+    if !texts.is_empty() {
+        return texts.join("\n");
+    }
+
+    serde_json::to_string_pretty(output).unwrap_or_default()
+}
+
+fn channel_activity(data: &Value) -> Option<OpenClawActivity> {
+    let channel = data["channel"]
+        .as_str()
+        .or(data["provider"].as_str())
+        .unwrap_or("channel");
+    let text = data["text"]
+        .as_str()
+        .or(data["status"].as_str())
+        .or(data["message"].as_str())?;
+
+    Some(OpenClawActivity::Channel {
+        channel: channel.to_string(),
+        text: text.to_string(),
+    })
+}
+
+/// Processes the response to the agent request.
+fn process_response(json: &Value, content: &mut MessageContent) -> ProcessResult {
     let ok = json["ok"].as_bool().unwrap_or(false);
     if !ok {
-        let msg = json["error"].as_str().unwrap_or("Unknown error");
-        return ProcessResult::Error(ClientError::new(
-            ClientErrorKind::Response,
-            format!("OpenClaw error: {}", msg),
-        ));
+        return ProcessResult::Error(response_error(json));
     }
 
     let payload = match json.get("payload") {
@@ -327,13 +855,6 @@ fn process_response(
         None => return ProcessResult::Continue,
     };
 
-    // Handle hello-ok (connection successful)
-    if payload["type"].as_str() == Some("hello-ok") && !*connected {
-        *connected = true;
-        log::debug!("OpenClaw: connected, sending agent request");
-        return ProcessResult::SendAgent;
-    }
-
     // Handle agent response completion
     let status = payload["status"].as_str();
     if status == Some("ok") || status == Some("completed") {
@@ -367,14 +888,76 @@ fn process_response(
     ProcessResult::Continue
 }
 
+pub struct OpenClawCustomContent {
+    template: LivePtr,
+}
+
+impl OpenClawCustomContent {
+    pub fn new(template: LivePtr) -> Self {
+        Self { template }
+    }
+}
+
+impl CustomContent for OpenClawCustomContent {
+    fn content_widget(
+        &mut self,
+        cx: &mut Cx,
+        previous_widget: WidgetRef,
+        content: &MessageContent,
+    ) -> Option<WidgetRef> {
+        // Plain replies are left to the standard content.
+        let data = OpenClawData::from_content(content).filter(|d| !d.activity.is_empty())?;
+
+        let widget = if previous_widget.as_open_claw_content().borrow().is_some() {
+            previous_widget
+        } else {
+            WidgetRef::new_from_ptr(cx, Some(self.template))
+        };
+
+        widget
+            .as_open_claw_content()
+            .borrow_mut()
+            .unwrap()
+            .set_content(cx, content, &data);
+
+        Some(widget)
+    }
+}
+
 impl BotClient for OpenClawClient {
+    #[cfg(not(target_arch = "wasm32"))]
     fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
-        let bot = Bot {
-            id: BotId::new("openclaw/assistant"),
-            name: "OpenClaw Assistant".to_string(),
-            avatar: EntityAvatar::Text("🦞".into()),
-            capabilities: BotCapabilities::new().with_capabilities([BotCapability::TextInput]),
-        };
+        let inner = self.inner();
+        Box::pin(async move {
+            let connection = match inner {
+                Ok(inner) => inner.connection().await,
+                Err(e) => Err(e),
+            };
+            let connection = match connection {
+                Ok(connection) => connection,
+                Err(e) => return e.into(),
+            };
+
+            match connection
+                .request("agents.list", serde_json::json!({}))
+                .await
+            {
+                Ok(payload) => ClientResult::new_ok(parse_agents(&payload)),
+                Err(e) => {
+                    // Gateways without multi-agent support only run the default agent.
+                    log::warn!(
+                        "OpenClaw: failed to list agents, using the default one: {}",
+                        e
+                    );
+                    ClientResult::new_ok(vec![agent_bot(DEFAULT_AGENT_ID, None, None)])
+                }
+            }
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        let bot = agent_bot(DEFAULT_AGENT_ID, None, None);
         Box::pin(async move { ClientResult::new_ok(vec![bot]) })
     }
 
@@ -385,134 +968,88 @@ impl BotClient for OpenClawClient {
     #[cfg(not(target_arch = "wasm32"))]
     fn send(
         &mut self,
-        _bot_id: &BotId,
+        bot_id: &BotId,
         messages: &[Message],
         _tools: &[Tool],
     ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
-        let inner = match self.0.read() {
-            Ok(inner) => inner.clone(),
-            Err(_) => {
+        let inner = match self.inner() {
+            Ok(inner) => inner,
+            Err(e) => {
                 let stream = stream! {
-                    yield ClientError::new(
-                        ClientErrorKind::Unknown,
-                        "OpenClaw client lock poisoned".to_string(),
-                    ).into();
+                    yield e.into();
                 };
                 return Box::pin(stream);
             }
         };
-        let history_message = Self::build_history_message(messages);
 
-        let stream = stream! {
-            log::debug!("OpenClaw: connecting to {}", inner.url);
+        // Continue the session of the conversation, or seed a new one with its history.
+        let agent_id = agent_id(bot_id).to_string();
+        let session_key = find_session(messages, &agent_id);
+        let message = match session_key {
+            Some(_) => latest_user_message(messages),
+            None => Self::build_history_message(messages),
+        };
+        let session_key = session_key.unwrap_or_else(|| new_session_key(&agent_id));
 
-            let (ws_stream, _) = match connect_async(&inner.url).await {
-                Ok(r) => r,
+        let stream = stream! {
+            let connection = match inner.connection().await {
+                Ok(connection) => connection,
                 Err(e) => {
-                    yield ClientError::new(
-                        ClientErrorKind::Network,
-                        format!("Failed to connect to OpenClaw Gateway: {}", e),
-                    ).into();
+                    yield e.into();
                     return;
                 }
             };
 
-            let (mut write, read) = ws_stream.split();
-            let mut read = read.fuse();
-            let mut content = MessageContent::default();
-            let mut connected = false;
-            let mut history_message = history_message;
-            let mut seen_seqs: HashSet<u64> = HashSet::new();
-            let handshake_deadline = std::pin::pin!(sleep(HANDSHAKE_TIMEOUT));
-            let mut handshake_deadline = handshake_deadline.fuse();
-
-            let connect_req = Self::build_connect_request(inner.token.as_deref());
-            let connect_json = match serde_json::to_string(&connect_req) {
-                Ok(j) => j,
-                Err(e) => {
-                    yield ClientError::new(
-                        ClientErrorKind::Format,
-                        format!("Failed to serialize request: {}", e),
-                    ).into();
-                    return;
-                }
-            };
-            if let Err(e) = write.send(WsMessage::Text(connect_json.into())).await {
-                yield ClientError::new(
-                    ClientErrorKind::Network,
-                    format!("Failed to send request: {}", e),
-                ).into();
+            let mut incoming = connection.subscribe();
+            let request = Self::build_agent_request(message, &agent_id, &session_key);
+            if let Err(e) = connection.send(&request) {
+                yield e.into();
                 return;
             }
 
+            let mut data = OpenClawData {
+                session_key: session_key.clone(),
+                activity: vec![],
+            };
+            let mut content = MessageContent::default();
+            data.write_to(&mut content);
+            let mut run_id: Option<String> = None;
+            let mut seen_seqs: HashSet<u64> = HashSet::new();
+
             loop {
-                let msg_result = futures::select! {
-                    _ = handshake_deadline => {
-                        if !connected {
-                            yield ClientError::new(
-                                ClientErrorKind::Network,
-                                "Timed out waiting for OpenClaw handshake".to_string(),
-                            ).into();
-                            return;
-                        }
-                        // Handshake completed, ignore the timeout and continue
-                        continue;
+                let Some(json) = incoming.next().await else {
+                    // The connection will be opened again on the next message.
+                    if content.text.is_empty() {
+                        yield connection_lost().into();
+                    } else {
+                        yield ClientResult::new_ok(content.clone());
                     }
-                    msg = read.next() => msg,
+                    break;
                 };
 
-                let msg_result = match msg_result {
-                    Some(result) => result,
-                    None => {
-                        log::debug!("OpenClaw: connection closed");
-                        if !content.text.is_empty() {
-                            yield ClientResult::new_ok(content.clone());
+                let result = match json["type"].as_str().unwrap_or("") {
+                    "event" => {
+                        let payload = json.get("payload");
+                        if !belongs_to_run(payload, &session_key, run_id.as_deref()) {
+                            continue;
                         }
-                        break;
-                    }
-                };
-
-                let result = match msg_result {
-                    Ok(WsMessage::Text(text)) => {
-                        let json: Value = match serde_json::from_str(&text) {
-                            Ok(v) => v,
-                            Err(_) => continue,
-                        };
-
-                        match json["type"].as_str().unwrap_or("") {
-                            "event" => {
-                                // Deduplicate events by seq number
-                                if let Some(seq) = json["seq"].as_u64() {
-                                    if !seen_seqs.insert(seq) {
-                                        log::trace!(
-                                            "OpenClaw: skipping duplicate event seq={}",
-                                            seq
-                                        );
-                                        continue;
-                                    }
-                                }
-                                let event = json["event"].as_str().unwrap_or("");
-                                process_event(event, json.get("payload"), &mut content)
-                            }
-                            "res" => process_response(&json, &mut content, &mut connected),
-                            t => {
-                                log::trace!("OpenClaw: ignoring message type '{}'", t);
-                                ProcessResult::Continue
+                        // Deduplicate events by seq number
+                        if let Some(seq) = json["seq"].as_u64() {
+                            if !seen_seqs.insert(seq) {
+                                log::trace!("OpenClaw: skipping duplicate event seq={}", seq);
+                                continue;
                             }
                         }
+                        let event = json["event"].as_str().unwrap_or("");
+                        process_event(event, payload, &mut content, &mut data)
                     }
-                    Ok(WsMessage::Close(_)) => {
-                        log::debug!("OpenClaw: connection closed");
-                        if content.text.is_empty() {
-                            break;
+                    "res" if json["id"].as_str() == Some(request.id.as_str()) => {
+                        if let Some(id) = json["payload"]["runId"].as_str() {
+                            run_id = Some(id.to_string());
                         }
-                        ProcessResult::Done
+                        process_response(&json, &mut content)
                     }
-                    Ok(_) => ProcessResult::Continue,
-                    Err(e) => ProcessResult::Error(ClientError::new(
-                        ClientErrorKind::Network,
-                        format!("WebSocket error: {}", e),
-                    )),
+                    _ => ProcessResult::Continue,
                 };
 
                 match result {
@@ -522,50 +1059,6 @@ impl BotClient for OpenClawClient {
                         yield e.into();
                         break;
                     }
-                    ProcessResult::SendConnect => {
-                        if connected {
-                            continue;
-                        }
-                        let req = Self::build_connect_request(inner.token.as_deref());
-                        let json = match serde_json::to_string(&req) {
-                            Ok(j) => j,
-                            Err(e) => {
-                                yield ClientError::new(
-                                    ClientErrorKind::Format,
-                                    format!("Failed to serialize request: {}", e),
-                                ).into();
-                                return;
-                            }
-                        };
-                        if let Err(e) = write.send(WsMessage::Text(json.into())).await {
-                            yield ClientError::new(
-                                ClientErrorKind::Network,
-                                format!("Failed to send request: {}", e),
-                            ).into();
-                            return;
-                        }
-                    }
-                    ProcessResult::SendAgent => {
-                        let req =
-                            Self::build_agent_request(std::mem::take(&mut history_message));
-                        let json = match serde_json::to_string(&req) {
-                            Ok(j) => j,
-                            Err(e) => {
-                                yield ClientError::new(
-                                    ClientErrorKind::Format,
-                                    format!("Failed to serialize request: {}", e),
-                                ).into();
-                                return;
-                            }
-                        };
-                        if let Err(e) = write.send(WsMessage::Text(json.into())).await {
-                            yield ClientError::new(
-                                ClientErrorKind::Network,
-                                format!("Failed to send request: {}", e),
-                            ).into();
-                            return;
-                        }
-                    }
                     ProcessResult::Done => {
                         yield ClientResult::new_ok(content.clone());
                         break;
@@ -584,14 +1077,11 @@ impl BotClient for OpenClawClient {
         _messages: &[Message],
         _tools: &[Tool],
     ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
-        let inner = match self.0.read() {
-            Ok(inner) => inner.clone(),
-            Err(_) => {
+        let inner = match self.inner() {
+            Ok(inner) => inner,
+            Err(e) => {
                 let stream = stream! {
-                    yield ClientError::new(
-                        ClientErrorKind::Unknown,
-                        "OpenClaw client lock poisoned".to_string(),
-                    ).into();
+                    yield e.into();
                 };
                 return Box::pin(stream);
             }
@@ -611,3 +1101,142 @@ impl BotClient for OpenClawClient {
         Box::pin(stream)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(from: EntityId, text: &str, data: Option<&OpenClawData>) -> Message {
+        Message {
+            from,
+            content: MessageContent {
+                text: text.to_string(),
+                data: data.map(|d| serde_json::to_string(d).unwrap()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_agents() {
+        let payload = serde_json::json!({
+            "defaultId": "main",
+            "agents": [
+                {"id": "main"},
+                {"id": "work", "identity": {"name": "Work", "emoji": "💼"}},
+            ]
+        });
+
+        let bots = parse_agents(&payload);
+        assert_eq!(bots.len(), 2);
+        assert_eq!(bots[0].id.as_str(), "main");
+        assert_eq!(bots[0].name, "OpenClaw Assistant");
+        assert_eq!(bots[1].id.as_str(), "work");
+        assert_eq!(bots[1].name, "Work");
+
+        let bots = parse_agents(&serde_json::json!({"agents": []}));
+        assert_eq!(bots[0].id.as_str(), DEFAULT_AGENT_ID);
+    }
+
+    #[test]
+    fn test_session_is_continued_with_same_agent() {
+        let data = OpenClawData {
+            session_key: new_session_key("work"),
+            activity: vec![],
+        };
+        let bot = EntityId::Bot(BotId::new("work"));
+        let messages = vec![
+            message(EntityId::User, "Hi", None),
+            message(bot, "Hello!", Some(&data)),
+            message(EntityId::User, "What's next?", None),
+        ];
+
+        assert_eq!(
+            find_session(&messages, "work"),
+            Some(data.session_key.clone())
+        );
+        assert_eq!(latest_user_message(&messages), "What's next?");
+
+        // Switching to another agent starts a new session.
+        assert_eq!(find_session(&messages, "main"), None);
+    }
+
+    #[test]
+    fn test_tool_and_channel_events_become_activity() {
+        let mut content = MessageContent::default();
+        let mut data = OpenClawData::default();
+
+        let call = serde_json::json!({
+            "stream": "tool",
+            "data": {"phase": "start", "name": "exec", "toolCallId": "t1", "args": {"cmd": "ls"}}
+        });
+        let partial = serde_json::json!({
+            "stream": "tool",
+            "data": {"phase": "update", "name": "exec", "toolCallId": "t1"}
+        });
+        let result = serde_json::json!({
+            "stream": "tool",
+            "data": {
+                "phase": "result",
+                "name": "exec",
+                "toolCallId": "t1",
+                "result": {"content": [{"type": "text", "text": "Cargo.toml"}]}
+            }
+        });
+        let channel = serde_json::json!({
+            "stream": "channel",
+            "data": {"channel": "telegram", "text": "Sent the summary"}
+        });
+
+        for payload in [&call, &partial, &result, &channel] {
+            process_event("agent", Some(payload), &mut content, &mut data);
+        }
+
+        assert_eq!(
+            data.activity,
+            vec![
+                OpenClawActivity::ToolCall {
+                    id: "t1".to_string(),
+                    name: "exec".to_string(),
+                    arguments: r#"{"cmd":"ls"}"#.to_string(),
+                },
+                OpenClawActivity::ToolResult {
+                    id: "t1".to_string(),
+                    name: "exec".to_string(),
+                    output: "Cargo.toml".to_string(),
+                    is_error: false,
+                },
+                OpenClawActivity::Channel {
+                    channel: "telegram".to_string(),
+                    text: "Sent the summary".to_string(),
+                },
+            ]
+        );
+        // Tool activity never reaches the chat's own tool calls.
+        assert!(content.tool_calls.is_empty());
+        assert!(content.text.is_empty());
+        let stored = OpenClawData::from_content(&content).unwrap();
+        assert_eq!(stored.activity.len(), 3);
+    }
+
+    #[test]
+    fn test_legacy_bot_maps_to_default_agent() {
+        assert_eq!(agent_id(&BotId::new(LEGACY_BOT_ID)), DEFAULT_AGENT_ID);
+        assert_eq!(agent_id(&BotId::new("work")), "work");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_events_are_routed_by_run() {
+        let key = "agent:main:moly-1";
+        let ours = serde_json::json!({"sessionKey": key});
+        let other = serde_json::json!({"sessionKey": "agent:main:moly-2"});
+        let by_run = serde_json::json!({"runId": "run-2"});
+
+        assert!(belongs_to_run(Some(&ours), key, None));
+        assert!(!belongs_to_run(Some(&other), key, None));
+        assert!(belongs_to_run(Some(&by_run), key, None));
+        assert!(!belongs_to_run(Some(&by_run), key, Some("run-1")));
+    }
+}
@@ -7,8 +7,12 @@ use crate::data::providers::ProviderId;
 use crate::shared::utils::filesystem;
 use crate::shared::utils::version::Versioned;
 
+use super::api_keys::ApiKeys;
 use super::mcp_servers::McpServersConfig;
-use super::providers::{Provider, ProviderType};
+use super::model_rules::ModelRules;
+use super::providers::{HostedTools, Provider, ProviderType};
+use super::tool_permissions::ToolPermissionsHandle;
+use super::tool_selection::ToolSelection;
 
 const PREFERENCES_DIR: &str = "preferences";
 const PREFERENCES_FILENAME: &str = "preferences.json";
@@ -24,6 +28,13 @@ pub struct Preferences {
     pub mcp_servers_config: McpServersConfig,
     #[serde(default)]
     stt_config: Versioned<SttConfig>,
+    #[serde(default)]
+    pub fallback_chains: Vec<FallbackChain>,
+    #[serde(default)]
+    pub bot_tool_selections: Vec<BotToolSelection>,
+    /// Shares the MCP servers config with the tool permissions of the chats.
+    #[serde(skip)]
+    tool_permissions: ToolPermissionsHandle,
 }
 
 impl Default for Preferences {
@@ -34,6 +45,9 @@ impl Default for Preferences {
             providers_preferences: vec![],
             mcp_servers_config: McpServersConfig::new(),
             stt_config: Versioned::default(),
+            fallback_chains: vec![],
+            bot_tool_selections: vec![],
+            tool_permissions: ToolPermissionsHandle::default(),
         }
     }
 }
@@ -42,7 +56,7 @@ impl Preferences {
     pub async fn load() -> Self {
         let preferences_path = preferences_path();
         let fs = filesystem::global();
-        match fs.read_json::<Preferences>(&preferences_path).await {
+        let preferences = match fs.read_json::<Preferences>(&preferences_path).await {
             Ok(mut preferences) => {
                 // Migrate providers without IDs
                 preferences.migrate_provider_ids();
@@ -52,10 +66,17 @@ impl Preferences {
                 log::info!("No preferences file found, a default one will be created.");
                 Preferences::default()
             }
-        }
+        };
+        preferences
+            .tool_permissions
+            .set(&preferences.mcp_servers_config);
+        preferences
     }
 
+    /// Writes the preferences to disk, sharing the MCP servers config with the tool
+    /// permissions, as every change of the preferences is saved.
     pub fn save(&self) {
+        self.tool_permissions.set(&self.mcp_servers_config);
         let self_clone = self.clone();
         spawn(async move {
             match filesystem::global()
@@ -85,6 +106,51 @@ impl Preferences {
         self.save();
     }
 
+    pub fn tool_permissions(&self) -> ToolPermissionsHandle {
+        self.tool_permissions.clone()
+    }
+
+    /// Models to fail over to, in order, when the given bot can't answer.
+    pub fn fallback_models_for(&self, bot_id: &BotId) -> &[String] {
+        self.fallback_chains
+            .iter()
+            .find(|chain| &chain.bot_id == bot_id)
+            .map(|chain| chain.fallbacks.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn set_fallback_models(&mut self, bot_id: &BotId, fallbacks: Vec<String>) {
+        self.fallback_chains.retain(|chain| &chain.bot_id != bot_id);
+        if !fallbacks.is_empty() {
+            self.fallback_chains.push(FallbackChain {
+                bot_id: bot_id.clone(),
+                fallbacks,
+            });
+        }
+        self.save();
+    }
+
+    /// MCP tools offered in the chats with the given bot, unless a chat picks its own.
+    pub fn tool_selection_for(&self, bot_id: &BotId) -> ToolSelection {
+        self.bot_tool_selections
+            .iter()
+            .find(|entry| &entry.bot_id == bot_id)
+            .map(|entry| entry.selection.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_bot_tool_selection(&mut self, bot_id: &BotId, selection: ToolSelection) {
+        self.bot_tool_selections
+            .retain(|entry| &entry.bot_id != bot_id);
+        if selection != ToolSelection::default() {
+            self.bot_tool_selections.push(BotToolSelection {
+                bot_id: bot_id.clone(),
+                selection,
+            });
+        }
+        self.save();
+    }
+
     pub fn _set_downloaded_files_dir(&mut self, path: PathBuf) {
         self.downloaded_files_dir = path;
         self.save();
@@ -102,6 +168,10 @@ impl Preferences {
             existing_provider.enabled = provider.enabled;
             existing_provider.system_prompt = provider.system_prompt.clone();
             existing_provider.tools_enabled = provider.tools_enabled;
+            existing_provider.model_rules = provider.model_rules.clone();
+            existing_provider.api_keys = provider.api_keys.clone();
+            existing_provider.think_tags = provider.think_tags;
+            existing_provider.hosted_tools = provider.hosted_tools.clone();
         } else {
             self.providers_preferences.push(ProviderPreferences {
                 id: provider.id.clone(),
@@ -114,6 +184,10 @@ impl Preferences {
                 was_customly_added: provider.was_customly_added,
                 system_prompt: provider.system_prompt.clone(),
                 tools_enabled: provider.tools_enabled,
+                model_rules: provider.model_rules.clone(),
+                api_keys: provider.api_keys.clone(),
+                think_tags: provider.think_tags,
+                hosted_tools: provider.hosted_tools.clone(),
             });
         }
         self.save();
@@ -229,6 +303,21 @@ fn preferences_path() -> PathBuf {
     Path::new(PREFERENCES_DIR).join(PREFERENCES_FILENAME)
 }
 
+/// Fallback chain configured for a single bot (assistant).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FallbackChain {
+    pub bot_id: BotId,
+    /// Names or ids of the models to try, in order.
+    pub fallbacks: Vec<String>,
+}
+
+/// MCP tools picked for a single bot (assistant).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BotToolSelection {
+    pub bot_id: BotId,
+    pub selection: ToolSelection,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct ProviderPreferences {
     /// Unique identifier for the provider
@@ -248,6 +337,18 @@ pub struct ProviderPreferences {
     /// Whether tools (MCP) are enabled for this provider
     #[serde(default = "default_tools_enabled")]
     pub tools_enabled: bool,
+    /// Include/exclude rules for the fetched models, if customized by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_rules: Option<ModelRules>,
+    /// Additional named API keys, rotated according to their strategy
+    #[serde(default, skip_serializing_if = "ApiKeys::is_default")]
+    pub api_keys: ApiKeys,
+    /// Whether `<think>` tags in replies are parsed as reasoning, if set by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub think_tags: Option<bool>,
+    /// Tools run by the provider itself, enabled by the user (Responses API only)
+    #[serde(default, skip_serializing_if = "HostedTools::is_none")]
+    pub hosted_tools: HostedTools,
 }
 
 fn default_tools_enabled() -> bool {
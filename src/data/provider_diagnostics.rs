@@ -0,0 +1,535 @@
+//! Staged connection checks for a provider, to pinpoint why it can't be reached.
+//!
+//! Each stage depends on the previous one, so once a stage fails the remaining ones
+//! are skipped. Only available on native platforms, as browsers don't expose DNS
+//! resolution nor raw connections.
+
+use chrono::{DateTime, Local};
+use std::fmt::Write;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use url::Url;
+
+use super::model_rules::{ModelRules, is_chat_model};
+use super::providers::{Provider, ProviderType};
+
+/// Timeout for connecting and for each HTTP request.
+const STAGE_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Response bodies are truncated to this many characters in the report.
+const MAX_BODY_CHARS: usize = 2000;
+
+const URL_PARSE: &str = "URL parse";
+const DNS_RESOLUTION: &str = "DNS resolution";
+const TCP_CONNECT: &str = "TCP connect";
+const TLS_HANDSHAKE: &str = "TLS handshake";
+const MODELS_REQUEST: &str = "Models request";
+const TEST_COMPLETION: &str = "Test completion";
+
+const STAGES: &[&str] = &[
+    URL_PARSE,
+    DNS_RESOLUTION,
+    TCP_CONNECT,
+    TLS_HANDSHAKE,
+    MODELS_REQUEST,
+    TEST_COMPLETION,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StageOutcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Clone, Debug)]
+pub struct DiagnosticStage {
+    pub name: &'static str,
+    pub outcome: StageOutcome,
+    pub duration: Duration,
+    /// What was checked and found, including raw HTTP status and body when relevant.
+    pub detail: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct DiagnosticsReport {
+    pub provider_name: String,
+    pub provider_type: ProviderType,
+    pub url: String,
+    pub started_at: DateTime<Local>,
+    pub stages: Vec<DiagnosticStage>,
+}
+
+impl DiagnosticsReport {
+    fn new(provider: &Provider) -> Self {
+        Self {
+            provider_name: provider.name.clone(),
+            provider_type: provider.provider_type.clone(),
+            url: provider.url.clone(),
+            started_at: Local::now(),
+            stages: vec![],
+        }
+    }
+
+    /// Whether all the stages that ran passed.
+    pub fn passed(&self) -> bool {
+        self.stages
+            .iter()
+            .all(|stage| stage.outcome != StageOutcome::Failed)
+    }
+
+    /// Plain text version of the report, meant to be pasted in bug reports.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "Moly provider diagnostics");
+        let _ = writeln!(
+            text,
+            "Provider: {} ({})",
+            self.provider_name,
+            self.provider_type.to_human_readable()
+        );
+        let _ = writeln!(text, "URL: {}", self.url);
+        let _ = writeln!(
+            text,
+            "Date: {}",
+            self.started_at.format("%Y-%m-%d %H:%M:%S %z")
+        );
+        let _ = writeln!(text, "Moly version: {}", env!("CARGO_PKG_VERSION"));
+
+        for stage in &self.stages {
+            let label = match stage.outcome {
+                StageOutcome::Passed => "PASS",
+                StageOutcome::Failed => "FAIL",
+                StageOutcome::Skipped => "SKIP",
+            };
+            let _ = writeln!(text);
+            let _ = writeln!(
+                text,
+                "[{}] {} ({} ms)",
+                label,
+                stage.name,
+                stage.duration.as_millis()
+            );
+            for line in stage.detail.lines() {
+                let _ = writeln!(text, "    {}", line);
+            }
+        }
+
+        text
+    }
+
+    /// Records the result of a stage, returning its value if it passed.
+    fn record<T>(
+        &mut self,
+        name: &'static str,
+        started: Instant,
+        result: Result<(T, String), String>,
+    ) -> Option<T> {
+        let (value, outcome, detail) = match result {
+            Ok((value, detail)) => (Some(value), StageOutcome::Passed, detail),
+            Err(detail) => (None, StageOutcome::Failed, detail),
+        };
+
+        self.stages.push(DiagnosticStage {
+            name,
+            outcome,
+            duration: started.elapsed(),
+            detail,
+        });
+        value
+    }
+
+    fn skip(&mut self, name: &'static str, reason: &str) {
+        self.stages.push(DiagnosticStage {
+            name,
+            outcome: StageOutcome::Skipped,
+            duration: Duration::ZERO,
+            detail: reason.to_string(),
+        });
+    }
+
+    /// Marks all the stages that didn't run yet as skipped.
+    fn skip_remaining(mut self) -> Self {
+        for name in STAGES {
+            if !self.stages.iter().any(|stage| stage.name == *name) {
+                self.skip(name, "Skipped because a previous stage failed");
+            }
+        }
+        self
+    }
+}
+
+/// Runs all the diagnostic stages against the provider.
+///
+/// `test_model` is the model used for the test completion, defaulting to the first
+/// chat model listed by the provider and allowed by its rules.
+pub async fn diagnose_provider(
+    provider: Provider,
+    test_model: Option<String>,
+) -> DiagnosticsReport {
+    let mut report = DiagnosticsReport::new(&provider);
+
+    let started = Instant::now();
+    let Some(url) = report.record(URL_PARSE, started, parse_url(&provider.url)) else {
+        return report.skip_remaining();
+    };
+
+    let started = Instant::now();
+    let Some(addresses) = report.record(DNS_RESOLUTION, started, resolve(&url).await) else {
+        return report.skip_remaining();
+    };
+
+    let started = Instant::now();
+    if report
+        .record(TCP_CONNECT, started, connect(addresses).await)
+        .is_none()
+    {
+        return report.skip_remaining();
+    }
+
+    let client = match reqwest::Client::builder().timeout(STAGE_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            let started = Instant::now();
+            report.record::<()>(TLS_HANDSHAKE, started, Err(error_chain(&e)));
+            return report.skip_remaining();
+        }
+    };
+
+    if matches!(url.scheme(), "https" | "wss") {
+        let started = Instant::now();
+        if report
+            .record(TLS_HANDSHAKE, started, tls_handshake(&client, &url).await)
+            .is_none()
+        {
+            return report.skip_remaining();
+        }
+    } else {
+        report.skip(TLS_HANDSHAKE, "Not using TLS");
+    }
+
+    let Some(api_url) = openai_api_url(&provider.provider_type, &url) else {
+        report.skip(MODELS_REQUEST, "Not supported for this provider type");
+        report.skip(TEST_COMPLETION, "Not supported for this provider type");
+        return report;
+    };
+
+    let api_key = provider.api_key.as_deref();
+    let started = Instant::now();
+    let Some(listed_model) = report.record(
+        MODELS_REQUEST,
+        started,
+        models_request(&client, &api_url, api_key, &provider.model_rules()).await,
+    ) else {
+        return report.skip_remaining();
+    };
+
+    let supports_completions = matches!(
+        provider.provider_type,
+        ProviderType::OpenAi | ProviderType::MolyServer | ProviderType::MoFa
+    );
+    match test_model.or(listed_model) {
+        Some(model) if supports_completions => {
+            let started = Instant::now();
+            report.record(
+                TEST_COMPLETION,
+                started,
+                test_completion(&client, &api_url, api_key, &model).await,
+            );
+        }
+        Some(_) => report.skip(TEST_COMPLETION, "Not supported for this provider type"),
+        None => report.skip(TEST_COMPLETION, "No model available to test"),
+    }
+
+    report
+}
+
+fn parse_url(url: &str) -> Result<(Url, String), String> {
+    // Image providers are stored with a leading '#' to tell them apart.
+    let url = Url::parse(url.trim().trim_start_matches('#')).map_err(|e| e.to_string())?;
+
+    let Some(host) = url.host_str() else {
+        return Err(format!("The URL {} has no host", url));
+    };
+    let Some(port) = url.port_or_known_default() else {
+        return Err(format!("Unknown default port for scheme {}", url.scheme()));
+    };
+
+    let detail = format!(
+        "Scheme: {}\nHost: {}\nPort: {}\nPath: {}",
+        url.scheme(),
+        host,
+        port,
+        url.path()
+    );
+    Ok((url, detail))
+}
+
+async fn resolve(url: &Url) -> Result<(Vec<SocketAddr>, String), String> {
+    // Both are guaranteed by the URL parse stage.
+    let host = url.host_str().unwrap_or_default().to_string();
+    let port = url.port_or_known_default().unwrap_or_default();
+
+    let addresses = run_blocking(move || (host.as_str(), port).to_socket_addrs())
+        .await
+        .map_err(|e| e.to_string())?
+        .collect::<Vec<_>>();
+
+    if addresses.is_empty() {
+        return Err("The host resolved to no addresses".to_string());
+    }
+
+    let detail = addresses
+        .iter()
+        .map(|address| address.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok((addresses, detail))
+}
+
+async fn connect(addresses: Vec<SocketAddr>) -> Result<((), String), String> {
+    run_blocking(move || {
+        let mut errors = Vec::new();
+        for address in addresses {
+            match TcpStream::connect_timeout(&address, STAGE_TIMEOUT) {
+                Ok(_) => return Ok(((), format!("Connected to {}", address))),
+                Err(e) => errors.push(format!("{}: {}", address, e)),
+            }
+        }
+        Err(errors.join("\n"))
+    })
+    .await
+}
+
+/// Checks the TLS handshake by requesting the root of the server over HTTPS.
+///
+/// Any HTTP response, whatever its status, means the handshake succeeded.
+async fn tls_handshake(client: &reqwest::Client, url: &Url) -> Result<((), String), String> {
+    let mut origin = url.clone();
+    // `wss` doesn't take HTTP requests, but the TLS handshake is the same as `https`.
+    let _ = origin.set_scheme("https");
+    origin.set_path("/");
+    origin.set_query(None);
+
+    let response = client
+        .get(origin.as_str())
+        .send()
+        .await
+        .map_err(|e| error_chain(&e))?;
+
+    Ok((
+        (),
+        format!(
+            "Secure connection established (HTTP {} from {})",
+            response.status(),
+            origin
+        ),
+    ))
+}
+
+/// Lists the models, returning the first chat model to be used for the test completion.
+async fn models_request(
+    client: &reqwest::Client,
+    api_url: &str,
+    api_key: Option<&str>,
+    rules: &ModelRules,
+) -> Result<(Option<String>, String), String> {
+    let models_url = format!("{}/models", api_url);
+    let mut request = client.get(&models_url);
+    if let Some(key) = api_key {
+        request = request.bearer_auth(key);
+    }
+
+    let response = request.send().await.map_err(|e| error_chain(&e))?;
+    let status = response.status();
+    let body = response.text().await.map_err(|e| error_chain(&e))?;
+
+    let detail = format!("GET {}\nHTTP {}\n{}", models_url, status, truncate(&body));
+    if !status.is_success() {
+        return Err(detail);
+    }
+
+    let parsed: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| format!("{}\n\nThe response is not valid JSON: {}", detail, e))?;
+    let Some(models) = parsed["data"].as_array() else {
+        return Err(format!("{}\n\nThe response has no `data` list", detail));
+    };
+
+    // Listings often start with audio, image or embedding models, which would fail
+    // the test completion on a healthy provider.
+    let first_model = models
+        .iter()
+        .filter_map(|model| model["id"].as_str())
+        .find(|id| is_chat_model(id) && rules.allows(id))
+        .map(str::to_string);
+    Ok((
+        first_model,
+        format!("{} models listed\n{}", models.len(), detail),
+    ))
+}
+
+async fn test_completion(
+    client: &reqwest::Client,
+    api_url: &str,
+    api_key: Option<&str>,
+    model: &str,
+) -> Result<((), String), String> {
+    let completions_url = format!("{}/chat/completions", api_url);
+    // No token cap: reasoning models reject `max_tokens` and would use up a small
+    // `max_completion_tokens` before replying. The prompt keeps the reply short.
+    let mut request = client.post(&completions_url).json(&serde_json::json!({
+        "model": model,
+        "messages": [{ "role": "user", "content": "Reply with OK." }],
+        "stream": false,
+    }));
+    if let Some(key) = api_key {
+        request = request.bearer_auth(key);
+    }
+
+    let response = request.send().await.map_err(|e| error_chain(&e))?;
+    let status = response.status();
+    let body = response.text().await.map_err(|e| error_chain(&e))?;
+
+    let detail = format!(
+        "POST {} (model {})\nHTTP {}\n{}",
+        completions_url,
+        model,
+        status,
+        truncate(&body)
+    );
+    if status.is_success() {
+        Ok(((), detail))
+    } else {
+        Err(detail)
+    }
+}
+
+/// The base URL of the OpenAI-compatible API of the provider, if it has one.
+fn openai_api_url(provider_type: &ProviderType, url: &Url) -> Option<String> {
+    match provider_type {
+        ProviderType::OpenAi
+        | ProviderType::MolyServer
+        | ProviderType::MoFa
+        | ProviderType::OpenAiImage
+        | ProviderType::OpenAiResponses => Some(url.as_str().trim_end_matches('/').to_string()),
+        ProviderType::OpenAiRealtime => {
+            // e.g. wss://api.openai.com/v1/realtime -> https://api.openai.com/v1
+            let mut api_url = url.clone();
+            let _ = api_url.set_scheme(if url.scheme() == "ws" {
+                "http"
+            } else {
+                "https"
+            });
+            let api_url = api_url.as_str().trim_end_matches('/');
+            Some(api_url.trim_end_matches("/realtime").to_string())
+        }
+        ProviderType::DeepInquire | ProviderType::OpenClaw => None,
+    }
+}
+
+/// Formats an error along with its sources, which hold the useful low level details
+/// (e.g. the certificate error behind a failed request).
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut text = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        let _ = write!(text, "\nCaused by: {}", error);
+        source = error.source();
+    }
+    text
+}
+
+fn truncate(body: &str) -> String {
+    if body.chars().count() > MAX_BODY_CHARS {
+        let truncated: String = body.chars().take(MAX_BODY_CHARS).collect();
+        format!("{}... (truncated)", truncated)
+    } else {
+        body.to_string()
+    }
+}
+
+/// Runs blocking network operations (DNS, raw connections) on the blocking pool of
+/// the runtime, so they don't stall its workers.
+async fn run_blocking<T, F>(f: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    crate::runtime::runtime_handle()
+        .spawn_blocking(f)
+        .await
+        .expect("Blocking diagnostics task panicked")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::test_utils::{Response, block_on, stand_in_server};
+
+    fn provider(url: String) -> Provider {
+        Provider {
+            name: "Stand-in".to_string(),
+            url,
+            provider_type: ProviderType::OpenAi,
+            ..Default::default()
+        }
+    }
+
+    fn outcomes(report: &DiagnosticsReport) -> Vec<StageOutcome> {
+        report.stages.iter().map(|stage| stage.outcome).collect()
+    }
+
+    #[test]
+    fn test_diagnose_reachable_provider() {
+        let (url, requests) = stand_in_server(|request| {
+            if request.starts_with("GET /v1/models") {
+                Response::json(r#"{"data":[{"id":"whisper-1"},{"id":"tiny"}]}"#)
+            } else {
+                Response::json(r#"{"choices":[{"message":{"content":"OK"}}]}"#)
+            }
+        });
+
+        let report = block_on(diagnose_provider(provider(format!("{}/v1", url)), None));
+        assert!(report.passed());
+        assert_eq!(
+            outcomes(&report),
+            vec![
+                StageOutcome::Passed,
+                StageOutcome::Passed,
+                StageOutcome::Passed,
+                StageOutcome::Skipped,
+                StageOutcome::Passed,
+                StageOutcome::Passed,
+            ]
+        );
+
+        // The first connection is the TCP connect stage, which sends nothing.
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].starts_with("POST /v1/chat/completions"));
+        assert!(requests[2].contains(r#""model":"tiny""#));
+        assert!(!requests[2].contains("max_tokens"));
+    }
+
+    #[test]
+    fn test_diagnose_stops_at_failing_stage() {
+        let (url, requests) = stand_in_server(|_| Response::error("401 Unauthorized"));
+
+        let report = block_on(diagnose_provider(provider(format!("{}/v1", url)), None));
+        assert!(!report.passed());
+        assert_eq!(report.stages[4].outcome, StageOutcome::Failed);
+        assert!(report.stages[4].detail.contains("HTTP 401"));
+        assert_eq!(report.stages[5].outcome, StageOutcome::Skipped);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_realtime_api_url() {
+        let url = Url::parse("wss://api.openai.com/v1/realtime").unwrap();
+        assert_eq!(
+            openai_api_url(&ProviderType::OpenAiRealtime, &url).as_deref(),
+            Some("https://api.openai.com/v1")
+        );
+        assert_eq!(openai_api_url(&ProviderType::OpenClaw, &url), None);
+    }
+}
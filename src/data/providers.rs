@@ -1,4 +1,8 @@
+use crate::data::api_keys::ApiKeys;
 use crate::data::bot_fetcher;
+use crate::data::model_capabilities::ModelCapabilities;
+use crate::data::model_rules::ModelRules;
+use crate::data::mofa_client::AgentInfo;
 use makepad_widgets::*;
 use moly_kit::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -28,12 +32,64 @@ pub struct Provider {
     /// Whether tools (MCP) are enabled for this provider
     #[serde(default = "default_tools_enabled")]
     pub tools_enabled: bool,
+    /// Include/exclude rules for the fetched models, if customized by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_rules: Option<ModelRules>,
+    /// Additional named API keys, rotated according to their strategy
+    #[serde(default, skip_serializing_if = "ApiKeys::is_default")]
+    pub api_keys: ApiKeys,
+    /// Whether `<think>` tags in replies are parsed as reasoning, if set by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub think_tags: Option<bool>,
+    /// Tools run by the provider itself, enabled by the user (Responses API only)
+    #[serde(default, skip_serializing_if = "HostedTools::is_none")]
+    pub hosted_tools: HostedTools,
+}
+
+/// Tools run and billed by the provider, so each of them is opt-in.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct HostedTools {
+    #[serde(default)]
+    pub web_search: bool,
+    #[serde(default)]
+    pub code_interpreter: bool,
+    /// Vector stores searched by the file search tool, offered when any is set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub file_search_vector_stores: Vec<String>,
+}
+
+impl HostedTools {
+    pub fn is_none(&self) -> bool {
+        !self.web_search && !self.code_interpreter && self.file_search_vector_stores.is_empty()
+    }
 }
 
 fn default_tools_enabled() -> bool {
     true
 }
 
+impl Provider {
+    /// The rules deciding which of the models listed by this provider are kept,
+    /// falling back to the defaults for its type.
+    pub fn model_rules(&self) -> ModelRules {
+        self.model_rules
+            .clone()
+            .unwrap_or_else(|| ModelRules::default_for(&self.provider_type))
+    }
+
+    /// Whether `<think>` tags in replies are moved to the reasoning. By default,
+    /// only for local servers, as hosted APIs already separate the reasoning.
+    pub fn parses_think_tags(&self) -> bool {
+        self.think_tags.unwrap_or_else(|| match self.provider_type {
+            ProviderType::MolyServer => true,
+            ProviderType::OpenAi => {
+                self.url.contains("localhost") || self.url.contains("127.0.0.1")
+            }
+            _ => false,
+        })
+    }
+}
+
 /// Fetch models for a provider using MolyKit clients
 pub fn fetch_models_for_provider(provider: &Provider) {
     bot_fetcher::fetch_models_for_provider(provider);
@@ -48,6 +104,16 @@ pub struct ProviderBot {
     pub enabled: bool,
     #[serde(default)]
     pub is_recommended: bool,
+    /// Whether this bot was loaded from the models cache and hasn't been confirmed
+    /// by a successful fetch from its provider yet.
+    #[serde(skip)]
+    pub is_stale: bool,
+    /// What the model supports, from provider metadata or the bundled table.
+    #[serde(default)]
+    pub capabilities: ModelCapabilities,
+    /// What the bot expects to run, if it's a MoFa agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<AgentInfo>,
 }
 
 impl ProviderBot {
@@ -62,6 +128,9 @@ impl ProviderBot {
             provider_id: "unknown".to_string(),
             enabled: true,
             is_recommended: false,
+            is_stale: false,
+            capabilities: ModelCapabilities::default(),
+            agent: None,
         }
     }
 
@@ -170,6 +239,7 @@ pub enum ProviderType {
     OpenAiImage,
     #[serde(alias = "OpenAIRealtime")]
     OpenAiRealtime,
+    OpenAiResponses,
     MoFa,
     DeepInquire,
     MolyServer,
@@ -182,6 +252,7 @@ impl ProviderType {
             ProviderType::OpenAi => "OpenAI",
             ProviderType::OpenAiImage => "OpenAI (Image Generation)",
             ProviderType::OpenAiRealtime => "OpenAI (Realtime)",
+            ProviderType::OpenAiResponses => "OpenAI (Responses)",
             ProviderType::MoFa => "MoFa",
             ProviderType::DeepInquire => "DeepInquire",
             ProviderType::MolyServer => "MolyServer",
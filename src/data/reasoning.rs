@@ -0,0 +1,377 @@
+//! Reasoning controls of a chat, and how they are sent to each provider.
+//!
+//! Providers don't agree on how reasoning is configured: OpenAI takes an effort
+//! level, Gemini and Qwen a thinking budget, OpenRouter and Anthropic either.
+
+use moly_kit::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::sync::{Arc, Mutex};
+
+use super::providers::ProviderType;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReasoningEffort::Low => "low",
+            ReasoningEffort::Medium => "medium",
+            ReasoningEffort::High => "high",
+        }
+    }
+}
+
+/// How much a reasoning model should think before answering. Unset values are left
+/// to the provider's defaults.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReasoningParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effort: Option<ReasoningEffort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_thinking_tokens: Option<u32>,
+}
+
+impl ReasoningParams {
+    pub fn is_unset(&self) -> bool {
+        self.effort.is_none() && self.max_thinking_tokens.is_none()
+    }
+}
+
+/// The request fields a provider takes reasoning params in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReasoningFormat {
+    /// `reasoning_effort`, as taken by OpenAI's chat completions.
+    OpenAi,
+    /// `reasoning.effort`, as taken by OpenAI's Responses API.
+    OpenAiResponses,
+    /// `reasoning.effort` or `reasoning.max_tokens`.
+    OpenRouter,
+    /// `reasoning_effort`, or a thinking budget through Gemini's extra body.
+    Gemini,
+    /// `thinking.budget_tokens`, as taken by Anthropic's compatible API.
+    Anthropic,
+    /// `enable_thinking` and `thinking_budget`, as taken by Qwen models.
+    Qwen,
+    /// The provider can't be configured, e.g. DeepSeek R1 always reasons the same.
+    Unsupported,
+}
+
+impl ReasoningFormat {
+    pub fn for_provider(provider_type: &ProviderType, url: &str) -> Self {
+        match provider_type {
+            ProviderType::OpenAiResponses => return ReasoningFormat::OpenAiResponses,
+            ProviderType::OpenAi | ProviderType::MolyServer => {}
+            _ => return ReasoningFormat::Unsupported,
+        }
+
+        if url.contains("openrouter.ai") {
+            ReasoningFormat::OpenRouter
+        } else if url.contains("generativelanguage.googleapis.com") {
+            ReasoningFormat::Gemini
+        } else if url.contains("anthropic.com") {
+            ReasoningFormat::Anthropic
+        } else if url.contains("siliconflow") || url.contains("dashscope") {
+            ReasoningFormat::Qwen
+        } else if url.contains("deepseek.com") {
+            ReasoningFormat::Unsupported
+        } else {
+            ReasoningFormat::OpenAi
+        }
+    }
+}
+
+/// Budget used when a provider needs one but only an effort level was chosen.
+fn budget_for_effort(effort: ReasoningEffort) -> u32 {
+    match effort {
+        ReasoningEffort::Low => 1024,
+        ReasoningEffort::Medium => 8192,
+        ReasoningEffort::High => 24576,
+    }
+}
+
+/// The fields to merge into the request body to apply the params.
+pub fn request_fields(params: &ReasoningParams, format: ReasoningFormat) -> Map<String, Value> {
+    let mut fields = Map::new();
+    if params.is_unset() {
+        return fields;
+    }
+
+    let effort = params.effort.map(|e| e.as_str());
+    let budget = params
+        .max_thinking_tokens
+        .or_else(|| params.effort.map(budget_for_effort));
+
+    match format {
+        ReasoningFormat::OpenAi => {
+            if let Some(effort) = effort {
+                fields.insert("reasoning_effort".into(), effort.into());
+            }
+        }
+        ReasoningFormat::OpenAiResponses => {
+            if let Some(effort) = effort {
+                fields.insert("reasoning".into(), json!({"effort": effort}));
+            }
+        }
+        ReasoningFormat::OpenRouter => {
+            // Only one of them is accepted, the explicit budget wins.
+            let reasoning = match (params.max_thinking_tokens, effort) {
+                (Some(tokens), _) => json!({"max_tokens": tokens}),
+                (None, Some(effort)) => json!({"effort": effort}),
+                (None, None) => return fields,
+            };
+            fields.insert("reasoning".into(), reasoning);
+        }
+        ReasoningFormat::Gemini => match (params.max_thinking_tokens, effort) {
+            // Gemini rejects requests setting both.
+            (Some(tokens), _) => {
+                fields.insert(
+                    "extra_body".into(),
+                    json!({"google": {"thinking_config": {
+                        "thinking_budget": tokens,
+                        "include_thoughts": true,
+                    }}}),
+                );
+            }
+            (None, Some(effort)) => {
+                fields.insert("reasoning_effort".into(), effort.into());
+            }
+            (None, None) => {}
+        },
+        ReasoningFormat::Anthropic => {
+            if let Some(budget) = budget {
+                fields.insert(
+                    "thinking".into(),
+                    json!({"type": "enabled", "budget_tokens": budget}),
+                );
+            }
+        }
+        ReasoningFormat::Qwen => {
+            fields.insert("enable_thinking".into(), true.into());
+            if let Some(budget) = budget {
+                fields.insert("thinking_budget".into(), budget.into());
+            }
+        }
+        ReasoningFormat::Unsupported => {}
+    }
+
+    fields
+}
+
+/// Reasoning params of a chat, shared between a [`ReasoningParamsClient`] and the
+/// chat it serves.
+#[derive(Clone, Default)]
+pub struct ReasoningHandle(Arc<Mutex<ReasoningParams>>);
+
+impl ReasoningHandle {
+    pub fn set(&self, params: ReasoningParams) {
+        *self.0.lock().unwrap() = params;
+    }
+
+    pub fn get(&self) -> ReasoningParams {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Wraps a [`BotClient`] handing the reasoning params of the chat to the provider
+/// clients below it.
+///
+/// Clients are shared by all chats, so the params travel with the request, in the
+/// data of its last message, where [`request_params`] reads them from. The
+/// [`BotClient::send`] of aitk takes nothing else per request, so until it does,
+/// the message data is the only way through the router and map clients.
+pub struct ReasoningParamsClient {
+    client: Box<dyn BotClient>,
+    handle: ReasoningHandle,
+}
+
+impl Clone for ReasoningParamsClient {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone_box(),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl ReasoningParamsClient {
+    pub fn new(client: Box<dyn BotClient>, handle: ReasoningHandle) -> Self {
+        Self { client, handle }
+    }
+}
+
+impl BotClient for ReasoningParamsClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        self.client.bots()
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let params = self.handle.get();
+        if params.is_unset() {
+            return self.client.send(bot_id, messages, tools);
+        }
+
+        let mut messages = messages.to_vec();
+        if let Some(last) = messages.last_mut() {
+            write_request_params(&mut last.content.data, &params);
+        }
+        self.client.send(bot_id, &messages, tools)
+    }
+}
+
+/// Field of the message data the params of a request are passed in.
+const PARAMS_FIELD: &str = "reasoning_params";
+
+fn write_request_params(data: &mut Option<String>, params: &ReasoningParams) {
+    let mut value = match data.as_deref().map(serde_json::from_str::<Value>) {
+        None => json!({}),
+        Some(Ok(value)) if value.is_object() => value,
+        // Not ours to overwrite, the request goes with the provider's defaults.
+        Some(_) => {
+            ::log::warn!("Could not pass the reasoning params along with the request");
+            return;
+        }
+    };
+    value[PARAMS_FIELD] = json!(params);
+    *data = Some(value.to_string());
+}
+
+/// Reasoning params the chat sent the request with, if it went through a
+/// [`ReasoningParamsClient`].
+pub fn request_params(messages: &[Message]) -> ReasoningParams {
+    messages
+        .last()
+        .and_then(|message| message.content.data.as_deref())
+        .and_then(|data| serde_json::from_str::<Value>(data).ok())
+        .and_then(|mut value| serde_json::from_value(value[PARAMS_FIELD].take()).ok())
+        .unwrap_or_default()
+}
+
+/// Reasoning tokens spent on a reply, when the provider reports them.
+///
+/// Clients write them as a top level `reasoning_tokens` field of the message data,
+/// which is where the thinking block looks for them.
+pub fn write_reasoning_tokens(data: &mut Option<String>, tokens: u64) {
+    let mut value = data
+        .as_deref()
+        .and_then(|data| serde_json::from_str::<Value>(data).ok())
+        .filter(|value| value.is_object())
+        .unwrap_or_else(|| json!({}));
+    value["reasoning_tokens"] = tokens.into();
+    *data = Some(value.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(effort: Option<ReasoningEffort>, tokens: Option<u32>) -> ReasoningParams {
+        ReasoningParams {
+            effort,
+            max_thinking_tokens: tokens,
+        }
+    }
+
+    #[test]
+    fn test_request_fields_per_provider() {
+        let high = params(Some(ReasoningEffort::High), None);
+        let budget = params(Some(ReasoningEffort::Low), Some(4000));
+
+        assert!(request_fields(&ReasoningParams::default(), ReasoningFormat::OpenAi).is_empty());
+        assert_eq!(
+            Value::Object(request_fields(&high, ReasoningFormat::OpenAi)),
+            json!({"reasoning_effort": "high"})
+        );
+        assert_eq!(
+            Value::Object(request_fields(&high, ReasoningFormat::OpenAiResponses)),
+            json!({"reasoning": {"effort": "high"}})
+        );
+        assert_eq!(
+            Value::Object(request_fields(&budget, ReasoningFormat::OpenRouter)),
+            json!({"reasoning": {"max_tokens": 4000}})
+        );
+        assert_eq!(
+            request_fields(&budget, ReasoningFormat::Gemini)["extra_body"]["google"]["thinking_config"]
+                ["thinking_budget"],
+            4000
+        );
+        assert_eq!(
+            Value::Object(request_fields(&high, ReasoningFormat::Anthropic)),
+            json!({"thinking": {"type": "enabled", "budget_tokens": 24576}})
+        );
+        assert_eq!(
+            Value::Object(request_fields(&budget, ReasoningFormat::Qwen)),
+            json!({"enable_thinking": true, "thinking_budget": 4000})
+        );
+        assert!(request_fields(&high, ReasoningFormat::Unsupported).is_empty());
+    }
+
+    #[test]
+    fn test_format_for_provider() {
+        assert_eq!(
+            ReasoningFormat::for_provider(&ProviderType::OpenAi, "https://api.openai.com/v1"),
+            ReasoningFormat::OpenAi
+        );
+        assert_eq!(
+            ReasoningFormat::for_provider(
+                &ProviderType::OpenAi,
+                "https://generativelanguage.googleapis.com/v1beta/openai"
+            ),
+            ReasoningFormat::Gemini
+        );
+        assert_eq!(
+            ReasoningFormat::for_provider(&ProviderType::OpenAi, "https://api.deepseek.com/v1"),
+            ReasoningFormat::Unsupported
+        );
+        assert_eq!(
+            ReasoningFormat::for_provider(&ProviderType::MoFa, "http://localhost:8000"),
+            ReasoningFormat::Unsupported
+        );
+    }
+
+    #[test]
+    fn test_request_params_travel_with_the_last_message() {
+        let high = params(Some(ReasoningEffort::High), None);
+        let mut messages = vec![Message::default(), Message::default()];
+        messages[1].content.data = Some(r#"{"response_id":"resp_1"}"#.to_string());
+        assert_eq!(request_params(&messages), ReasoningParams::default());
+
+        write_request_params(&mut messages[1].content.data, &high);
+        assert_eq!(request_params(&messages), high);
+        assert!(
+            messages[1]
+                .content
+                .data
+                .as_deref()
+                .unwrap()
+                .contains("resp_1")
+        );
+        assert_eq!(request_params(&messages[..1]), ReasoningParams::default());
+    }
+
+    #[test]
+    fn test_write_reasoning_tokens_keeps_data() {
+        let mut data = Some(r#"{"response_id":"resp_1"}"#.to_string());
+        write_reasoning_tokens(&mut data, 42);
+        let value: Value = serde_json::from_str(data.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            value,
+            json!({"response_id": "resp_1", "reasoning_tokens": 42})
+        );
+    }
+}
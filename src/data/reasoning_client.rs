@@ -0,0 +1,426 @@
+//! Chat completions with the reasoning params of the chat.
+//!
+//! [`OpenAiClient`] has no way to add fields to its requests, so when the chat
+//! sent the request with reasoning params this client sends it itself, in the
+//! format of the provider. Otherwise it defers to the wrapped client.
+//!
+//! This is a stopgap: the client comes from aitk, re-exported by Moly Kit, and
+//! should take extra body fields itself. Once it does, this module and the params
+//! carried in the message data (see [`super::reasoning::ReasoningParamsClient`])
+//! should be replaced by that hook.
+
+use async_stream::stream;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use moly_kit::aitk::utils::sse::parse_sse;
+use moly_kit::prelude::*;
+use reqwest::header::{HeaderMap, HeaderName};
+use serde_json::{Map, Value, json};
+use std::str::FromStr;
+
+use super::reasoning::{self, ReasoningFormat, ReasoningParams};
+use super::tool_selection::function_definition;
+
+pub struct ReasoningClient {
+    client: Box<dyn BotClient>,
+    url: String,
+    headers: HeaderMap,
+    http: reqwest::Client,
+    tools_enabled: bool,
+    format: ReasoningFormat,
+}
+
+impl Clone for ReasoningClient {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone_box(),
+            url: self.url.clone(),
+            headers: self.headers.clone(),
+            http: self.http.clone(),
+            tools_enabled: self.tools_enabled,
+            format: self.format,
+        }
+    }
+}
+
+impl ReasoningClient {
+    /// Wraps `client`, which must target the same chat completions `url`.
+    pub fn new(client: Box<dyn BotClient>, url: String, format: ReasoningFormat) -> Self {
+        Self {
+            client,
+            url,
+            headers: HeaderMap::new(),
+            http: reqwest::Client::new(),
+            tools_enabled: true,
+            format,
+        }
+    }
+
+    pub fn set_header(&mut self, key: &str, value: &str) -> Result<(), &'static str> {
+        let header_name = HeaderName::from_str(key).map_err(|_| "Invalid header name")?;
+        let header_value = value.parse().map_err(|_| "Invalid header value")?;
+        self.headers.insert(header_name, header_value);
+        Ok(())
+    }
+
+    pub fn set_key(&mut self, key: &str) -> Result<(), &'static str> {
+        self.set_header("Authorization", &format!("Bearer {}", key))
+    }
+
+    pub fn set_tools_enabled(&mut self, enabled: bool) {
+        self.tools_enabled = enabled;
+    }
+}
+
+/// Whether the request must be sent by this client to apply the params.
+fn handles(params: &ReasoningParams, format: ReasoningFormat) -> bool {
+    !reasoning::request_fields(params, format).is_empty()
+}
+
+/// Images go as data urls, other attachments are inlined when they are text.
+async fn attachment_part(attachment: &Attachment) -> Option<Value> {
+    let bytes = attachment
+        .read()
+        .await
+        .inspect_err(|e| ::log::warn!("Could not read the attachment {}: {e}", attachment.name))
+        .ok()?;
+    let content_type = attachment.content_type.as_deref().unwrap_or_default();
+
+    if content_type.starts_with("image/") {
+        let url = format!("data:{};base64,{}", content_type, BASE64.encode(&bytes));
+        return Some(json!({"type": "image_url", "image_url": {"url": url}}));
+    }
+
+    let Ok(text) = std::str::from_utf8(&bytes) else {
+        ::log::warn!("Leaving out the binary attachment {}", attachment.name);
+        return None;
+    };
+    Some(json!({
+        "type": "text",
+        "text": format!("{}:\n```\n{}\n```", attachment.name, text),
+    }))
+}
+
+async fn user_content(content: &MessageContent) -> Value {
+    if content.attachments.is_empty() {
+        return content.text.clone().into();
+    }
+
+    let mut parts = vec![json!({"type": "text", "text": content.text})];
+    for attachment in &content.attachments {
+        parts.extend(attachment_part(attachment).await);
+    }
+    parts.into()
+}
+
+async fn chat_messages(messages: &[Message]) -> Vec<Value> {
+    let mut chat_messages = Vec::new();
+
+    for message in messages {
+        let content = &message.content;
+        match &message.from {
+            EntityId::System => {
+                chat_messages.push(json!({"role": "system", "content": content.text}));
+            }
+            EntityId::User => {
+                chat_messages.push(json!({"role": "user", "content": user_content(content).await}));
+            }
+            EntityId::Bot(_) => {
+                let mut chat_message = json!({"role": "assistant", "content": content.text});
+                if !content.tool_calls.is_empty() {
+                    chat_message["tool_calls"] = content
+                        .tool_calls
+                        .iter()
+                        .map(|tool_call| {
+                            json!({
+                                "id": tool_call.id,
+                                "type": "function",
+                                "function": {
+                                    "name": tool_call.name,
+                                    "arguments": Value::Object(tool_call.arguments.clone()).to_string(),
+                                },
+                            })
+                        })
+                        .collect();
+                }
+                chat_messages.push(chat_message);
+            }
+            EntityId::Tool => {
+                for result in &content.tool_results {
+                    chat_messages.push(json!({
+                        "role": "tool",
+                        "tool_call_id": result.tool_call_id,
+                        "content": result.content,
+                    }));
+                }
+            }
+            EntityId::App => {}
+        }
+    }
+
+    chat_messages
+}
+
+fn function_tool(tool: &Tool) -> Option<Value> {
+    Some(json!({"type": "function", "function": function_definition(tool)?}))
+}
+
+async fn request_body(
+    model: &str,
+    messages: &[Message],
+    tools: &[Tool],
+    tools_enabled: bool,
+    fields: Map<String, Value>,
+) -> Value {
+    let mut body = json!({
+        "model": model,
+        "messages": chat_messages(messages).await,
+        "stream": true,
+        "stream_options": {"include_usage": true},
+    });
+
+    if tools_enabled && !tools.is_empty() {
+        body["tools"] = tools.iter().filter_map(function_tool).collect();
+    }
+    if let Some(object) = body.as_object_mut() {
+        object.extend(fields);
+    }
+
+    body
+}
+
+/// A tool call being streamed, whose arguments come in pieces.
+#[derive(Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Applies a stream chunk to the content, returning whether it changed.
+fn apply_chunk(
+    chunk: &Value,
+    content: &mut MessageContent,
+    tool_calls: &mut Vec<PartialToolCall>,
+) -> bool {
+    let mut updated = false;
+    let delta = &chunk["choices"][0]["delta"];
+
+    if let Some(text) = delta["content"].as_str().filter(|t| !t.is_empty()) {
+        content.text.push_str(text);
+        updated = true;
+    }
+
+    // DeepSeek and Qwen use `reasoning_content`, OpenRouter `reasoning`.
+    let reasoning = delta["reasoning_content"]
+        .as_str()
+        .or_else(|| delta["reasoning"].as_str());
+    if let Some(reasoning) = reasoning.filter(|r| !r.is_empty()) {
+        content.reasoning.push_str(reasoning);
+        updated = true;
+    }
+
+    for tool_call in delta["tool_calls"].as_array().into_iter().flatten() {
+        let index = tool_call["index"].as_u64().unwrap_or(0) as usize;
+        if tool_calls.len() <= index {
+            tool_calls.resize_with(index + 1, PartialToolCall::default);
+        }
+
+        let partial = &mut tool_calls[index];
+        if let Some(id) = tool_call["id"].as_str() {
+            partial.id = id.to_string();
+        }
+        if let Some(name) = tool_call["function"]["name"].as_str() {
+            partial.name.push_str(name);
+        }
+        if let Some(arguments) = tool_call["function"]["arguments"].as_str() {
+            partial.arguments.push_str(arguments);
+        }
+    }
+
+    let usage = &chunk["usage"];
+    if let Some(tokens) = usage["completion_tokens_details"]["reasoning_tokens"].as_u64() {
+        reasoning::write_reasoning_tokens(&mut content.data, tokens);
+        updated = true;
+    }
+
+    updated
+}
+
+/// Turns the streamed tool calls into the ones of the content, once complete.
+fn finish_tool_calls(tool_calls: Vec<PartialToolCall>, content: &mut MessageContent) {
+    content.tool_calls = tool_calls
+        .into_iter()
+        .filter(|partial| !partial.name.is_empty())
+        .map(|partial| ToolCall {
+            id: partial.id,
+            name: partial.name,
+            arguments: serde_json::from_str::<Value>(&partial.arguments)
+                .ok()
+                .and_then(|a| a.as_object().cloned())
+                .unwrap_or_default(),
+            ..Default::default()
+        })
+        .collect();
+}
+
+impl BotClient for ReasoningClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        self.client.bots()
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let params = reasoning::request_params(messages);
+        if !handles(&params, self.format) {
+            return self.client.send(bot_id, messages, tools);
+        }
+
+        let url = format!("{}/chat/completions", self.url.trim_end_matches('/'));
+        let model = bot_id.id().to_string();
+        let messages = messages.to_vec();
+        let tools = tools.to_vec();
+        let tools_enabled = self.tools_enabled;
+        let fields = reasoning::request_fields(&params, self.format);
+        let request = self.http.post(&url).headers(self.headers.clone());
+
+        let stream = stream! {
+            let body = request_body(&model, &messages, &tools, tools_enabled, fields).await;
+            let response = match request.json(&body).send().await {
+                Ok(response) => response,
+                Err(error) => {
+                    yield ClientError::new_with_source(
+                        ClientErrorKind::Network,
+                        format!("Could not reach {url}."),
+                        Some(error),
+                    )
+                    .into();
+                    return;
+                }
+            };
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                yield ClientError::new(
+                    ClientErrorKind::Response,
+                    format!("Request to {url} failed with status {status}"),
+                )
+                .with_details(body)
+                .into();
+                return;
+            }
+
+            let mut content = MessageContent::default();
+            let mut tool_calls = Vec::new();
+            for await event in parse_sse(response.bytes_stream()) {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(error) => {
+                        yield ClientResult::new_ok(content.clone());
+                        yield ClientError::new_with_source(
+                            ClientErrorKind::Network,
+                            format!("The connection was unexpectedly closed while streaming the response from {url}."),
+                            Some(error),
+                        )
+                        .into();
+                        return;
+                    }
+                };
+
+                if event.trim() == "[DONE]" {
+                    break;
+                }
+
+                let chunk: Value = match serde_json::from_str(&event) {
+                    Ok(chunk) => chunk,
+                    Err(error) => {
+                        ::log::warn!("Skipping a malformed chunk from {url}: {error}\nChunk content: {event}");
+                        continue;
+                    }
+                };
+
+                if let Some(message) = chunk["error"]["message"].as_str() {
+                    yield ClientResult::new_ok(content.clone());
+                    yield ClientError::new(ClientErrorKind::Response, message.to_string()).into();
+                    return;
+                }
+
+                if apply_chunk(&chunk, &mut content, &mut tool_calls) {
+                    yield ClientResult::new_ok(content.clone());
+                }
+            }
+
+            finish_tool_calls(tool_calls, &mut content);
+            yield ClientResult::new_ok(content);
+        };
+
+        Box::pin(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::test_utils::block_on;
+
+    #[test]
+    fn test_stream_chunks() {
+        let chunks = [
+            json!({"choices": [{"delta": {"reasoning_content": "Let me see."}}]}),
+            json!({"choices": [{"delta": {"content": "It's 4."}}]}),
+            json!({"choices": [{"delta": {"tool_calls": [{"index": 0, "id": "call_1", "function": {"name": "calc", "arguments": "{\"a\":"}}]}}]}),
+            json!({"choices": [{"delta": {"tool_calls": [{"index": 0, "function": {"arguments": "2}"}}]}}]}),
+            json!({"choices": [], "usage": {"completion_tokens_details": {"reasoning_tokens": 64}}}),
+        ];
+
+        let mut content = MessageContent::default();
+        let mut tool_calls = Vec::new();
+        for chunk in &chunks {
+            apply_chunk(chunk, &mut content, &mut tool_calls);
+        }
+        finish_tool_calls(tool_calls, &mut content);
+
+        assert_eq!(content.reasoning, "Let me see.");
+        assert_eq!(content.text, "It's 4.");
+        assert_eq!(content.tool_calls.len(), 1);
+        assert_eq!(content.tool_calls[0].name, "calc");
+        assert_eq!(content.tool_calls[0].arguments["a"], 2);
+        assert_eq!(content.data.as_deref(), Some(r#"{"reasoning_tokens":64}"#));
+    }
+
+    #[test]
+    fn test_request_body_includes_reasoning_fields() {
+        let messages = vec![Message {
+            from: EntityId::User,
+            content: MessageContent {
+                text: "Hi".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }];
+        let params = ReasoningParams {
+            effort: Some(reasoning::ReasoningEffort::Medium),
+            max_thinking_tokens: None,
+        };
+
+        assert!(handles(&params, ReasoningFormat::OpenAi));
+        assert!(!handles(
+            &ReasoningParams::default(),
+            ReasoningFormat::OpenAi
+        ));
+
+        let fields = reasoning::request_fields(&params, ReasoningFormat::OpenAi);
+        let body = block_on(request_body("o3-mini", &messages, &[], true, fields));
+        assert_eq!(body["reasoning_effort"], "medium");
+        assert_eq!(body["messages"][0]["content"], "Hi");
+        assert!(body.get("tools").is_none());
+    }
+}
@@ -2,22 +2,37 @@ use crate::app::app_runner;
 use crate::data::providers::ProviderId;
 use crate::shared::actions::ChatAction;
 use crate::shared::bot_context::BotContext;
+use crate::shared::utils::version::Versioned;
 
+use super::api_keys::ApiKeys;
+#[cfg(not(target_arch = "wasm32"))]
+use super::builtin_tools;
 use super::chats::chat::ChatId;
 use super::downloads::download::DownloadFileAction;
-use super::mcp_servers::McpServersConfig;
+use super::local_servers::{self, DiscoveredServer, LocalServersScan};
+use super::mcp_context;
+use super::mcp_import::{self, ImportCandidate};
+use super::mcp_inputs::{self, McpInputsAction};
+use super::mcp_servers::{
+    BUILTIN_SERVER_ID, BuiltinTool, BuiltinToolsConfig, McpServerChanges, McpServersConfig,
+    ResolvedServers, ToolPolicy,
+};
+use super::mcp_status;
 use super::moly_client::MolyClient;
 use super::preferences::Preferences;
-use super::providers::{ProviderFetchModelsResult, ProviderType};
+use super::providers::{ProviderBot, ProviderFetchModelsResult, ProviderType};
 use super::search::SortCriteria;
-use super::supported_providers;
+use super::supported_providers::{self, SupportedProvider};
+use super::tool_audit;
+use super::tool_permissions::ToolPermissionsAction;
+use super::tool_selection::ToolSelection;
 use super::{chats::Chats, downloads::Downloads, search::Search};
 use chrono::{DateTime, Utc};
 use makepad_widgets::{Action, ActionDefaultRef, DefaultNone};
 use moly_kit::aitk::utils::asynchronous::spawn;
 use moly_kit::prelude::*;
 
-use super::providers::{Provider, ProviderConnectionStatus};
+use super::providers::{HostedTools, Provider, ProviderConnectionStatus};
 use moly_protocol::data::{Author, File, FileId, Model, ModelId, PendingDownload};
 
 use makepad_widgets::*;
@@ -77,6 +92,16 @@ pub struct Store {
     pub provider_syncing_status: ProviderSyncingStatus,
 
     pub provider_icons: Vec<LiveDependency>,
+
+    /// Inference servers found running on this machine by the last scan.
+    pub local_servers: Versioned<LocalServersScan>,
+
+    /// Tool manager kept across bot contexts, so their servers keep running.
+    mcp_tool_manager: McpManagerClient,
+    /// Servers applied to the tool manager, to only reload the ones that change.
+    loaded_mcp_servers: ResolvedServers,
+    /// Built-in tools offered by the tool manager, reloaded when they change.
+    loaded_builtin_tools: Vec<BuiltinTool>,
 }
 
 const MOLY_SERVER_VERSION_EXTENSION: &str = "/api/v1";
@@ -85,6 +110,9 @@ impl Store {
     pub fn load_into_app() {
         spawn(async move {
             let preferences = Preferences::load().await;
+            mcp_inputs::load(&preferences.mcp_servers_config).await;
+            tool_audit::load().await;
+            supported_providers::load_custom_catalogs().await;
 
             let server_port = std::env::var("MOLY_SERVER_PORT")
                 .ok()
@@ -104,17 +132,32 @@ impl Store {
                 bot_context: None,
                 provider_syncing_status: ProviderSyncingStatus::NotSyncing,
                 provider_icons: vec![],
+                local_servers: Versioned::default(),
+                mcp_tool_manager: McpManagerClient::new(),
+                loaded_mcp_servers: ResolvedServers::new(),
+                loaded_builtin_tools: Vec::new(),
             };
 
             store.init_current_chat();
             store.sync_with_moly_server();
             store.load_preference_connections();
+            // Browsers can't reach local servers without CORS, so only scan on native
+            #[cfg(not(target_arch = "wasm32"))]
+            store.scan_local_servers();
 
             app_runner().defer(move |app, cx, _| {
                 app.store = Some(store);
                 app.ui.view(ids!(body)).set_visible(cx, true);
                 cx.redraw_all(); // app.ui.redraw(cx) doesn't work as expected on web.
             });
+
+            if supported_providers::refresh_remote_catalog().await {
+                app_runner().defer(|app, _, _| {
+                    if let Some(store) = app.store.as_mut() {
+                        store.load_preference_connections();
+                    }
+                });
+            }
         })
     }
 
@@ -236,6 +279,20 @@ impl Store {
         self.search.handle_action(action);
         self.downloads.handle_action(action);
 
+        if let ToolPermissionsAction::Granted { chat_id, tool_name } = action.cast() {
+            self.set_mcp_tool_policy(&tool_name, chat_id, ToolPolicy::Allow);
+            Cx::post_action(ToolPermissionsAction::Saved);
+        }
+
+        if let McpInputsAction::Answered(answers) = action.cast()
+            && !answers.is_empty()
+        {
+            for (input, value) in answers {
+                mcp_inputs::set_answer(&input, value);
+            }
+            self.update_mcp_tool_manager();
+        }
+
         if let Some(_) = action.downcast_ref::<DownloadFileAction>() {
             self.update_downloads();
         }
@@ -289,6 +346,14 @@ impl Store {
     pub fn delete_chat(&mut self, chat_id: ChatId) {
         self.chats.remove_chat(chat_id);
 
+        // Tools allowed only in this chat are no longer needed.
+        let policies = &mut self.preferences.mcp_servers_config.tool_policies;
+        let policies_len = policies.len();
+        policies.retain(|rule| rule.chat_id != Some(chat_id));
+        if policies.len() != policies_len {
+            self.preferences.save();
+        }
+
         // TODO Decide proper behavior when deleting the current chat
         // For now, we just create a new empty chat because we don't fully
         // support having no chat selected
@@ -299,6 +364,18 @@ impl Store {
         if let ProviderFetchModelsResult::None = result {
             return;
         }
+
+        // If the bot context was built while this provider was unreachable, it's missing
+        // its live bots, so they must be added once the provider answers again.
+        let refreshed_stale_models = match &result {
+            ProviderFetchModelsResult::Success(provider_id, _)
+                if self.chats.has_stale_models(provider_id) =>
+            {
+                Some(provider_id.clone())
+            }
+            _ => None,
+        };
+
         let fetched_from_moly_server = self.chats.handle_provider_connection_result(
             result,
             &mut self.preferences,
@@ -307,6 +384,69 @@ impl Store {
         if fetched_from_moly_server && !self.moly_client.is_connected() {
             self.sync_with_moly_server();
         }
+        if let Some(provider_id) = refreshed_stale_models {
+            self.refresh_provider_bots(&provider_id);
+        }
+    }
+
+    /// Swaps the bots of a provider listed from the models cache for the ones it just
+    /// answered with, without rebuilding the bot context.
+    fn refresh_provider_bots(&mut self, provider_id: &ProviderId) {
+        let supported = supported_providers::load_supported_providers();
+        let bots = self
+            .chats
+            .available_bots
+            .values()
+            .filter(|provider_bot| provider_bot.provider_id == *provider_id)
+            .filter_map(|provider_bot| self.listed_bot_of(provider_bot, &supported))
+            .collect();
+        if let Some(bot_context) = self.bot_context.as_mut() {
+            bot_context.replace_provider_bots(provider_id, bots);
+        }
+    }
+
+    /// Like [`Self::bot_of`], but only for models the provider's client would list,
+    /// as filtered by the model being enabled, the provider's rules and its supported
+    /// models. Used for bots built without going through the client.
+    pub fn listed_bot_of(
+        &self,
+        provider_bot: &ProviderBot,
+        supported_providers: &[SupportedProvider],
+    ) -> Option<Bot> {
+        let provider = self.chats.providers.get(&provider_bot.provider_id)?;
+        let (_, model_id) = RouterClient::unprefix(&provider_bot.id)?;
+        let supported_models = supported_providers
+            .iter()
+            .find(|supported| supported.id == provider.id)
+            .and_then(|supported| supported.supported_models.as_ref());
+
+        let listed = provider_bot.enabled
+            && provider.model_rules().allows(&model_id)
+            && supported_models.is_none_or(|models| models.iter().any(|m| *m == *model_id));
+        if !listed {
+            return None;
+        }
+
+        self.bot_of(provider_bot)
+    }
+
+    /// The bot listed in chats for a model of a provider, with the provider's icon.
+    pub fn bot_of(&self, provider_bot: &ProviderBot) -> Option<Bot> {
+        let provider = self.chats.providers.get(&provider_bot.provider_id)?;
+        let avatar = self
+            .get_provider_icon(&provider.name)
+            .map(|icon| EntityAvatar::Image(icon.as_str().to_string()))
+            .or_else(|| EntityAvatar::from_first_grapheme(&provider.name.to_uppercase()))
+            .unwrap_or_else(|| EntityAvatar::Text("?".into()));
+
+        Some(Bot {
+            id: provider_bot.id.clone(),
+            name: provider_bot.name.clone(),
+            avatar,
+            capabilities: provider_bot.capabilities.extend_bot_capabilities(
+                BotCapabilities::new().with_capabilities([BotCapability::TextInput]),
+            ),
+        })
     }
 
     /// Loads the preference connections from the preferences and registers them in the chats.
@@ -338,6 +478,10 @@ impl Store {
                     was_customly_added: prefs.was_customly_added,
                     system_prompt: prefs.system_prompt.clone(),
                     tools_enabled: prefs.tools_enabled,
+                    model_rules: prefs.model_rules.clone(),
+                    api_keys: prefs.api_keys.clone(),
+                    think_tags: prefs.think_tags,
+                    hosted_tools: prefs.hosted_tools.clone(),
                 });
             } else {
                 // Known from supported_providers.json but user has no preferences
@@ -353,6 +497,10 @@ impl Store {
                     was_customly_added: false,
                     system_prompt: None,
                     tools_enabled: true,
+                    model_rules: None,
+                    api_keys: ApiKeys::default(),
+                    think_tags: None,
+                    hosted_tools: HostedTools::default(),
                 });
             }
         }
@@ -379,6 +527,10 @@ impl Store {
                     was_customly_added: pp_clone.was_customly_added,
                     system_prompt: pp_clone.system_prompt.clone(),
                     tools_enabled: pp_clone.tools_enabled,
+                    model_rules: pp_clone.model_rules.clone(),
+                    api_keys: pp_clone.api_keys.clone(),
+                    think_tags: pp_clone.think_tags,
+                    hosted_tools: pp_clone.hosted_tools.clone(),
                 });
             }
         }
@@ -387,6 +539,9 @@ impl Store {
             self.chats.providers.insert(provider.id.clone(), provider);
         }
 
+        // Show the last known models right away, in case some providers can't be reached
+        self.chats.seed_cached_models(&self.preferences);
+
         self.auto_fetch_for_enabled_providers();
     }
 
@@ -449,6 +604,88 @@ impl Store {
         self.preferences.remove_provider(provider_id);
     }
 
+    /// Generates a unique id for a custom provider, based on its name.
+    pub fn generate_provider_id(&self, name: &str) -> ProviderId {
+        // Create a base ID from the provider name
+        let base = name
+            .to_lowercase()
+            .replace(" ", "_")
+            .replace(|c: char| !c.is_alphanumeric() && c != '_', "");
+        let base = if base.is_empty() {
+            "custom_provider".to_string()
+        } else {
+            base
+        };
+
+        // Check if this ID already exists and append a number if needed
+        let mut id = base.clone();
+        let mut counter = 1;
+        while self.chats.providers.contains_key(&id) {
+            id = format!("{}_{}", base, counter);
+            counter += 1;
+        }
+        id
+    }
+
+    /// Probes well-known local ports for inference servers, in the background.
+    pub fn scan_local_servers(&mut self) {
+        if self.local_servers.data().scanning {
+            return;
+        }
+        self.local_servers
+            .update_and_notify(|scan| scan.scanning = true);
+
+        spawn(async move {
+            let servers = local_servers::scan_local_servers().await;
+            app_runner().defer(move |app, _, _| {
+                if let Some(store) = app.store.as_mut() {
+                    store.local_servers.set_and_notify(LocalServersScan {
+                        scanning: false,
+                        servers,
+                    });
+                }
+            });
+        });
+    }
+
+    /// Finds the provider already configured for a discovered local server, if any.
+    pub fn provider_for_local_server(&self, server: &DiscoveredServer) -> Option<&Provider> {
+        let server_url = normalize_local_url(&server.url);
+        self.chats
+            .providers
+            .values()
+            .find(|p| normalize_local_url(&p.url) == server_url)
+    }
+
+    /// Enables the provider matching a discovered local server, creating it if needed.
+    pub fn add_local_server(&mut self, server: &DiscoveredServer) {
+        let provider = match self.provider_for_local_server(server) {
+            Some(existing) => Provider {
+                enabled: true,
+                ..existing.clone()
+            },
+            None => Provider {
+                id: self.generate_provider_id(&server.name),
+                name: server.name.clone(),
+                url: server.url.clone(),
+                api_key: None,
+                provider_type: server.provider_type.clone(),
+                connection_status: ProviderConnectionStatus::Disconnected,
+                enabled: true,
+                models: vec![],
+                was_customly_added: true,
+                system_prompt: None,
+                tools_enabled: true,
+                model_rules: None,
+                api_keys: ApiKeys::default(),
+                think_tags: None,
+                hosted_tools: HostedTools::default(),
+            },
+        };
+
+        self.insert_or_update_provider(&provider);
+    }
+
     pub fn get_provider_icon(&self, provider_name: &str) -> Option<LiveDependency> {
         let base_name = normalize_provider_name(provider_name);
 
@@ -466,65 +703,205 @@ impl Store {
         &self.preferences.mcp_servers_config
     }
 
+    /// MCP tools offered in the chat: its own pick, or else the one of its bot.
+    pub fn chat_tool_selection(&self, chat_id: ChatId) -> ToolSelection {
+        let Some(chat) = self.chats.get_chat_by_id(chat_id) else {
+            return ToolSelection::default();
+        };
+
+        let chat = chat.borrow();
+        match (&chat.tool_selection, &chat.associated_bot) {
+            (Some(selection), _) => selection.clone(),
+            (None, Some(bot_id)) => self.preferences.tool_selection_for(bot_id),
+            (None, None) => ToolSelection::default(),
+        }
+    }
+
     pub fn get_mcp_servers_config_json(&self) -> String {
         self.preferences.get_mcp_servers_config_json()
     }
 
-    /// Creates a new MCP tool manager and loads servers asynchronously
-    /// Returns the manager immediately, loading happens in the background
-    pub fn create_and_load_mcp_tool_manager(&self) -> McpManagerClient {
-        let tool_manager = McpManagerClient::new();
+    /// The tool manager shared by the bot contexts, loading the servers of the config
+    /// in the background.
+    pub fn mcp_tool_manager(&mut self) -> McpManagerClient {
+        self.update_mcp_tool_manager();
+        self.mcp_tool_manager.clone()
+    }
+
+    pub fn update_mcp_servers_from_json(&mut self, json: &str) -> Result<(), serde_json::Error> {
+        self.preferences.update_mcp_servers_from_json(json)?;
+        self.update_mcp_tool_manager();
 
-        // Check if MCP servers are globally enabled
-        if !self.preferences.get_mcp_servers_enabled() {
-            // Return empty tool manager if globally disabled
-            return tool_manager;
+        Ok(())
+    }
+
+    /// Applies the MCP servers config to the tool manager.
+    ///
+    /// Only the servers that changed since they were loaded are started, stopped or
+    /// restarted, so the others keep their connections and running tool calls.
+    pub fn update_mcp_tool_manager(&mut self) {
+        // Dangerous mode is applied by the tool permissions of the chats, so
+        // policies denying tools still apply with it.
+        let config = self.get_mcp_servers_config();
+
+        let inputs = mcp_inputs::answers(config);
+        if config.enabled {
+            mcp_inputs::request_missing(config);
+        }
+
+        let servers = config.resolve_servers(&inputs);
+        let builtin_tools = if config.enabled {
+            config.builtin_tools.enabled_tools()
+        } else {
+            Vec::new()
+        };
+        let changes = McpServerChanges::between(&self.loaded_mcp_servers, &servers);
+
+        for server_id in &changes.stop {
+            mcp_status::remove(server_id);
+            mcp_context::remove_server(server_id);
         }
 
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let mcp_config = self.get_mcp_servers_config().clone();
-            tool_manager.set_dangerous_mode_enabled(mcp_config.dangerous_mode_enabled);
-            let tool_manager_clone = tool_manager.clone();
-
-            spawn(async move {
-                // Load MCP servers from configuration
-                for (server_id, server_config) in mcp_config.list_enabled_servers() {
-                    if let Some(transport) = server_config.to_transport() {
-                        match tool_manager_clone.add_server(server_id, transport).await {
-                            Ok(()) => {
-                                ::log::debug!("Successfully added MCP server: {}", server_id);
-                            }
-                            Err(e) => {
-                                ::log::error!("Failed to add MCP server '{}': {}", server_id, e);
-                            }
+            // Servers that can't start anymore are stopped too, so their old tools
+            // are not offered.
+            let mut stop = changes.stop;
+            let mut start = Vec::new();
+            for server_id in changes.start {
+                let transport = match &servers[&server_id] {
+                    Ok(server) => server.to_transport().map(|transport| (server, transport)),
+                    Err(missing) => {
+                        mcp_status::set_failed(&server_id, missing_inputs_error(config, missing));
+                        mcp_context::remove_server(&server_id);
+                        stop.push(server_id);
+                        continue;
+                    }
+                };
+
+                match transport {
+                    Some((server, transport)) => {
+                        mcp_status::set_connecting(&server_id);
+                        mcp_context::set_server(&server_id, server.clone());
+                        start.push((server_id, transport));
+                    }
+                    None => {
+                        mcp_status::set_failed(
+                            &server_id,
+                            "Missing a command or url to connect to".to_string(),
+                        );
+                        mcp_context::remove_server(&server_id);
+                        stop.push(server_id);
+                    }
+                }
+            }
+
+            // The server of the built-in tools only reloads when the tools offered
+            // change, the rest of their config is read on each call.
+            builtin_tools::set_config(config.builtin_tools.clone());
+            if builtin_tools != self.loaded_builtin_tools {
+                let server_id = BUILTIN_SERVER_ID.to_string();
+                if builtin_tools.is_empty() {
+                    mcp_status::remove(&server_id);
+                    stop.push(server_id);
+                } else {
+                    match builtin_tools::url() {
+                        Ok(url) => start.push((server_id, McpTransport::Http(url))),
+                        Err(e) => {
+                            mcp_status::set_failed(
+                                &server_id,
+                                format!("Could not start the built-in tools: {}", e),
+                            );
+                            stop.push(server_id);
                         }
                     }
                 }
-            });
+            }
+
+            mcp_status::queue_update(&self.mcp_tool_manager, stop, start);
         }
 
-        tool_manager
+        self.loaded_mcp_servers = servers;
+        self.loaded_builtin_tools = builtin_tools;
     }
 
-    pub fn update_mcp_servers_from_json(&mut self, json: &str) -> Result<(), serde_json::Error> {
-        self.preferences.update_mcp_servers_from_json(json)?;
+    /// Reconnects a single server in the tool manager.
+    pub fn restart_mcp_server(&self, server_id: &str) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(Ok(server)) = self.loaded_mcp_servers.get(server_id) else {
+                return;
+            };
+            let Some(transport) = server.to_transport() else {
+                return;
+            };
+            mcp_context::set_server(server_id, server.clone());
+
+            mcp_status::queue_update(
+                &self.mcp_tool_manager,
+                Vec::new(),
+                vec![(server_id.to_string(), transport)],
+            );
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = server_id;
+    }
+
+    /// Forgets the answers to the inputs of the config, asking for them again.
+    pub fn clear_mcp_inputs(&mut self) {
+        mcp_inputs::clear_answers(self.get_mcp_servers_config());
         self.update_mcp_tool_manager();
+    }
 
-        Ok(())
+    /// Adds servers imported from other clients, loading them in the tool manager.
+    pub fn import_mcp_servers(&mut self, candidates: Vec<ImportCandidate>) {
+        mcp_import::merge(&mut self.preferences.mcp_servers_config, candidates);
+        self.preferences.save();
+        self.update_mcp_tool_manager();
     }
 
-    pub fn update_mcp_tool_manager(&mut self) {
-        let new_tool_manager = self.create_and_load_mcp_tool_manager();
-        if let Some(ref mut bot_context_mut) = self.bot_context {
-            bot_context_mut.set_tool_manager(new_tool_manager);
-        }
+    /// Enables or disables a single server, starting or stopping it.
+    pub fn set_mcp_server_enabled(&mut self, server_id: &str, enabled: bool) {
+        let config = &mut self.preferences.mcp_servers_config;
+        let Some(server) = config.servers.get_mut(server_id) else {
+            return;
+        };
+        server.enabled = enabled;
+        self.preferences.save();
+        self.update_mcp_tool_manager();
+    }
+
+    /// Changes which built-in tools are offered and the folders they can read.
+    pub fn set_builtin_tools(&mut self, builtin_tools: BuiltinToolsConfig) {
+        self.preferences.mcp_servers_config.builtin_tools = builtin_tools;
+        self.preferences.save();
+        self.update_mcp_tool_manager();
+    }
+
+    /// Sets the policy of a namespaced tool, globally or only in a chat.
+    pub fn set_mcp_tool_policy(
+        &mut self,
+        tool_name: &str,
+        chat_id: Option<ChatId>,
+        policy: ToolPolicy,
+    ) {
+        self.preferences
+            .mcp_servers_config
+            .set_tool_policy(tool_name, chat_id, policy);
+        self.preferences.save();
+    }
+
+    /// Removes a rule of the tool policies, e.g. to revoke a grant.
+    pub fn remove_mcp_tool_policy(&mut self, server: &str, tool: &str, chat_id: Option<ChatId>) {
+        self.preferences
+            .mcp_servers_config
+            .remove_tool_policy(server, tool, chat_id);
+        self.preferences.save();
     }
 
     pub fn set_mcp_servers_enabled(&mut self, enabled: bool) {
         self.preferences.set_mcp_servers_enabled(enabled);
-        // Recreate bot context to apply the new MCP setting
-        self.reload_bot_context();
+        self.update_mcp_tool_manager();
     }
 
     pub fn set_mcp_servers_dangerous_mode_enabled(&mut self, enabled: bool) {
@@ -558,3 +935,27 @@ pub fn normalize_provider_name(name: &str) -> String {
         .unwrap_or(name)
         .to_lowercase()
 }
+
+/// Normalizes a provider URL so local servers match regardless of how their host is written.
+fn normalize_local_url(url: &str) -> String {
+    url.trim()
+        .trim_end_matches('/')
+        .to_lowercase()
+        .replace("127.0.0.1", "localhost")
+}
+
+/// Explains why a server can't be loaded without the given inputs.
+#[cfg(not(target_arch = "wasm32"))]
+fn missing_inputs_error(config: &McpServersConfig, missing: &[String]) -> String {
+    if let Some(id) = missing.iter().find(|id| config.get_input(id).is_none()) {
+        return format!(
+            "Input \"{}\" is not declared in the inputs of the config",
+            id
+        );
+    }
+
+    match missing {
+        [id] => format!("Waiting for input \"{}\"", id),
+        ids => format!("Waiting for inputs \"{}\"", ids.join("\", \"")),
+    }
+}
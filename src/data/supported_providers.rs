@@ -1,14 +1,36 @@
-use serde::Deserialize;
+use futures::future::{self, Either};
+use moly_kit::aitk::utils::asynchronous::sleep;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use crate::shared::utils::filesystem;
 
 use super::providers::ProviderType;
 
+const CATALOG_DIR: &str = "catalog";
+/// Catalog maintained by the user or shipped by their organization.
+const USER_CATALOG_FILENAME: &str = "providers.json";
+/// Last copy downloaded from the remote catalog URL.
+const REMOTE_CATALOG_FILENAME: &str = "remote_providers.json";
+/// Environment variable that can point to a remote catalog, overriding the one
+/// set in the user catalog file.
+const CATALOG_URL_ENV_VAR: &str = "MOLY_PROVIDERS_CATALOG_URL";
+/// How long to wait for the remote catalog before giving up until the next launch.
+const REMOTE_CATALOG_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Catalog entries loaded at runtime, applied in order over the built-in catalog.
+static CUSTOM_CATALOG: RwLock<Vec<CatalogEntry>> = RwLock::new(Vec::new());
+
 #[derive(Debug, Deserialize)]
 pub struct SupportedProvidersFile {
     pub providers: Vec<SupportedProvider>,
 }
 
 /// Represents a supported provider, used as a template.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct SupportedProvider {
     /// Unique identifier for the provider
     #[serde(default)]
@@ -19,10 +41,241 @@ pub struct SupportedProvider {
     pub supported_models: Option<Vec<String>>,
 }
 
-/// Utility to load from the JSON file
+/// A catalog file loaded at runtime, from the data directory or a URL.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CatalogFile {
+    /// Remote catalog to fetch and merge, only read from the user catalog file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub catalog_url: Option<String>,
+    #[serde(default)]
+    pub providers: Vec<CatalogEntry>,
+}
+
+/// A provider template from a runtime catalog.
+///
+/// If a provider with the same id exists already, only the given fields are
+/// overridden (e.g. just the `supported_models`). Otherwise, it's added as a new
+/// provider, for which `name`, `url` and `provider_type` are required.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_type: Option<ProviderType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supported_models: Option<Vec<String>>,
+}
+
+/// Utility to load from the JSON file, merged with the catalogs loaded at runtime
+/// by [`load_custom_catalogs`].
 pub fn load_supported_providers() -> Vec<SupportedProvider> {
     let data = include_str!("./supported_providers.json");
     let parsed: SupportedProvidersFile =
         serde_json::from_str(data).expect("Failed to parse supported_providers.json");
-    parsed.providers
+
+    let mut providers = parsed.providers;
+    merge_catalog(&mut providers, &CUSTOM_CATALOG.read().unwrap());
+    providers
+}
+
+/// Loads the catalogs from the data directory, to be merged over the built-in one.
+///
+/// The remote catalog (if configured) is applied first, so the user catalog has the
+/// final word. Only its cached copy is read here, the network is left to
+/// [`refresh_remote_catalog`] so a slow catalog URL doesn't hold the startup.
+pub async fn load_custom_catalogs() {
+    let user_catalog = read_catalog(&user_catalog_path()).await;
+    let remote_catalog = read_catalog(&remote_catalog_path()).await;
+    apply_catalogs(remote_catalog, user_catalog);
+}
+
+/// Downloads the remote catalog (if configured) and caches it for the next launch.
+///
+/// Returns true if there was no cached copy yet, in which case the downloaded one is
+/// applied right away and the providers should be reloaded.
+pub async fn refresh_remote_catalog() -> bool {
+    let user_catalog = read_catalog(&user_catalog_path()).await;
+    let Some(url) = std::env::var(CATALOG_URL_ENV_VAR)
+        .ok()
+        .or_else(|| user_catalog.as_ref()?.catalog_url.clone())
+        .filter(|url| !url.trim().is_empty())
+    else {
+        return false;
+    };
+
+    let cached = filesystem::global()
+        .exists(&remote_catalog_path())
+        .await
+        .unwrap_or(false);
+    let Some(remote_catalog) = fetch_remote_catalog(&url).await else {
+        return false;
+    };
+    if cached {
+        return false;
+    }
+
+    apply_catalogs(Some(remote_catalog), user_catalog);
+    true
+}
+
+fn apply_catalogs(remote_catalog: Option<CatalogFile>, user_catalog: Option<CatalogFile>) {
+    let mut entries = Vec::new();
+    entries.extend(remote_catalog.map(|c| c.providers).unwrap_or_default());
+    entries.extend(user_catalog.map(|c| c.providers).unwrap_or_default());
+    *CUSTOM_CATALOG.write().unwrap() = entries;
+}
+
+async fn read_catalog(path: &Path) -> Option<CatalogFile> {
+    let fs = filesystem::global();
+    if !fs.exists(path).await.unwrap_or(false) {
+        return None;
+    }
+
+    match fs.read_json::<CatalogFile>(path).await {
+        Ok(catalog) => Some(catalog),
+        Err(e) => {
+            log::error!("Failed to read provider catalog {:?}: {:?}", path, e);
+            None
+        }
+    }
+}
+
+/// Downloads the remote catalog and caches it in the data directory.
+async fn fetch_remote_catalog(url: &str) -> Option<CatalogFile> {
+    let request = async {
+        let response = reqwest::get(url).await.and_then(|r| r.error_for_status())?;
+        response.json::<CatalogFile>().await
+    };
+
+    let catalog = match future::select(pin!(request), pin!(sleep(REMOTE_CATALOG_TIMEOUT))).await {
+        Either::Left((Ok(catalog), _)) => catalog,
+        Either::Left((Err(e), _)) => {
+            log::error!("Failed to fetch provider catalog from {}: {}", url, e);
+            return None;
+        }
+        Either::Right(_) => {
+            log::error!("Timed out fetching provider catalog from {}", url);
+            return None;
+        }
+    };
+
+    if let Err(e) = filesystem::global()
+        .queue_write_json(remote_catalog_path(), &catalog)
+        .await
+    {
+        log::error!("Failed to cache provider catalog: {:?}", e);
+    }
+
+    Some(catalog)
+}
+
+/// Applies catalog entries, in order, over the given providers.
+fn merge_catalog(providers: &mut Vec<SupportedProvider>, entries: &[CatalogEntry]) {
+    for entry in entries {
+        if let Some(provider) = providers.iter_mut().find(|p| p.id == entry.id) {
+            if let Some(name) = &entry.name {
+                provider.name = name.clone();
+            }
+            if let Some(url) = &entry.url {
+                provider.url = url.clone();
+            }
+            if let Some(provider_type) = &entry.provider_type {
+                provider.provider_type = provider_type.clone();
+            }
+            if let Some(supported_models) = &entry.supported_models {
+                provider.supported_models = Some(supported_models.clone());
+            }
+            continue;
+        }
+
+        match (&entry.name, &entry.url, &entry.provider_type) {
+            (Some(name), Some(url), Some(provider_type)) if !entry.id.is_empty() => {
+                providers.push(SupportedProvider {
+                    id: entry.id.clone(),
+                    name: name.clone(),
+                    url: url.clone(),
+                    provider_type: provider_type.clone(),
+                    supported_models: entry.supported_models.clone(),
+                });
+            }
+            _ => log::warn!(
+                "Skipping catalog provider {:?}: new providers need an id, name, url and provider_type",
+                entry.id
+            ),
+        }
+    }
+}
+
+fn user_catalog_path() -> PathBuf {
+    Path::new(CATALOG_DIR).join(USER_CATALOG_FILENAME)
+}
+
+fn remote_catalog_path() -> PathBuf {
+    Path::new(CATALOG_DIR).join(REMOTE_CATALOG_FILENAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str) -> CatalogEntry {
+        CatalogEntry {
+            id: id.to_string(),
+            name: None,
+            url: None,
+            provider_type: None,
+            supported_models: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_overrides_only_given_fields() {
+        let mut providers = load_supported_providers();
+        let original = providers.iter().find(|p| p.id == "openai_chat").cloned();
+        let original = original.expect("openai_chat is a built-in provider");
+
+        merge_catalog(
+            &mut providers,
+            &[CatalogEntry {
+                supported_models: Some(vec!["gpt-approved".to_string()]),
+                ..entry("openai_chat")
+            }],
+        );
+
+        let merged = providers.iter().find(|p| p.id == "openai_chat").unwrap();
+        assert_eq!(merged.url, original.url);
+        assert_eq!(merged.name, original.name);
+        assert_eq!(
+            merged.supported_models,
+            Some(vec!["gpt-approved".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_merge_adds_complete_entries_only() {
+        let mut providers = Vec::new();
+
+        merge_catalog(
+            &mut providers,
+            &[
+                CatalogEntry {
+                    name: Some("Gateway".to_string()),
+                    url: Some("https://gateway.internal/v1".to_string()),
+                    provider_type: Some(ProviderType::OpenAi),
+                    ..entry("gateway")
+                },
+                CatalogEntry {
+                    name: Some("Incomplete".to_string()),
+                    ..entry("incomplete")
+                },
+            ],
+        );
+
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].id, "gateway");
+        assert_eq!(providers[0].provider_type, ProviderType::OpenAi);
+    }
 }
@@ -0,0 +1,112 @@
+//! Helpers for the tests of clients talking to HTTP servers.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+/// What a stand-in server answers to a request.
+pub struct Response {
+    pub status: &'static str,
+    pub content_type: &'static str,
+    pub body: String,
+    /// Announces more bytes than sent, so the client sees a dropped connection.
+    pub interrupted: bool,
+}
+
+impl Response {
+    pub fn json(body: impl Into<String>) -> Self {
+        Self {
+            status: "200 OK",
+            content_type: "application/json",
+            body: body.into(),
+            interrupted: false,
+        }
+    }
+
+    pub fn event_stream(body: impl Into<String>) -> Self {
+        Self {
+            content_type: "text/event-stream",
+            ..Self::json(body)
+        }
+    }
+
+    /// An error response, e.g. `Response::error("429 Too Many Requests")`.
+    pub fn error(status: &'static str) -> Self {
+        Self {
+            status,
+            ..Self::json("")
+        }
+    }
+}
+
+/// Binds a stand-in server on loopback answering each request with `respond`, and
+/// keeping the requests it received.
+///
+/// Returns the `http://127.0.0.1:<port>` url of the server.
+pub fn stand_in_server(
+    mut respond: impl FnMut(&str) -> Response + Send + 'static,
+) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let received = requests.clone();
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let request = read_request(&mut stream);
+            let response = respond(&request);
+            received.lock().unwrap().push(request);
+
+            let length = if response.interrupted {
+                response.body.len() + 1024
+            } else {
+                response.body.len()
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.status, response.content_type, length, response.body
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    (format!("http://127.0.0.1:{}", port), requests)
+}
+
+fn read_request(stream: &mut TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        let Ok(read) = stream.read(&mut buffer) else {
+            break;
+        };
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+
+        let text = String::from_utf8_lossy(&request);
+        if let Some(head_end) = text.find("\r\n\r\n") {
+            let length = text[..head_end]
+                .lines()
+                .find_map(|l| {
+                    let (name, value) = l.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if request.len() >= head_end + 4 + length {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&request).into_owned()
+}
+
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
@@ -0,0 +1,285 @@
+//! Reasoning streamed inline as `<think>...</think>` in the reply text.
+//!
+//! Local models like Qwen3 or the DeepSeek-R1 distills don't separate their
+//! reasoning from the reply. [`ThinkTagsClient`] moves it to the reasoning of the
+//! message, so it's shown in the thinking block instead of as raw text.
+
+use async_stream::stream;
+use futures::StreamExt;
+use moly_kit::prelude::*;
+
+const OPEN_TAG: &str = "<think>";
+const CLOSE_TAG: &str = "</think>";
+
+/// Splits streamed text into reply text and reasoning.
+///
+/// Tags may be split across chunks, so text that could be the start of a tag is
+/// held back until the next chunk tells what it is.
+#[derive(Debug, Default)]
+pub struct ThinkTagParser {
+    pub text: String,
+    pub reasoning: String,
+    in_think: bool,
+    seen_open_tag: bool,
+    pending: String,
+}
+
+impl ThinkTagParser {
+    pub fn push(&mut self, chunk: &str) {
+        self.pending.push_str(chunk);
+
+        loop {
+            let open = (!self.in_think)
+                .then(|| self.pending.find(OPEN_TAG))
+                .flatten();
+            let close = self.pending.find(CLOSE_TAG);
+
+            match (open, close) {
+                (Some(open), close) if close.is_none_or(|close| open < close) => {
+                    self.emit(open);
+                    self.pending.drain(..OPEN_TAG.len());
+                    self.in_think = true;
+                    self.seen_open_tag = true;
+                }
+                (_, Some(close)) if self.in_think => {
+                    self.emit(close);
+                    self.pending.drain(..CLOSE_TAG.len());
+                    self.in_think = false;
+                }
+                // Once the think block is over, the reply is only mentioning the tag.
+                (_, Some(close)) if self.seen_open_tag => {
+                    self.emit(close + CLOSE_TAG.len());
+                }
+                (_, Some(close)) => {
+                    // Chat templates may open the think block themselves, in which
+                    // case only the closing tag is streamed, on a line of its own
+                    // after the reasoning. Anywhere else it's the reply mentioning it.
+                    let after = &self.pending[close + CLOSE_TAG.len()..];
+                    if after.is_empty() {
+                        self.emit(close);
+                        return;
+                    }
+
+                    if self.closes_implicit_block(close) {
+                        self.emit(close);
+                        self.pending.drain(..CLOSE_TAG.len());
+                        let text = std::mem::take(&mut self.text);
+                        self.reasoning.insert_str(0, text.trim());
+                        self.seen_open_tag = true;
+                    } else {
+                        self.emit(close + CLOSE_TAG.len());
+                    }
+                }
+                _ => {
+                    let held = partial_tag_len(&self.pending);
+                    self.emit(self.pending.len() - held);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Whether a closing tag found at `close` in the pending text ends a think
+    /// block opened by the chat template.
+    ///
+    /// It must sit on its own line, with nothing like a code block before it.
+    fn closes_implicit_block(&self, close: usize) -> bool {
+        let before = format!("{}{}", self.text, &self.pending[..close]);
+        let after = &self.pending[close + CLOSE_TAG.len()..];
+
+        let line_start = before.trim_end_matches([' ', '\t']);
+        let line_end = after.trim_start_matches([' ', '\t']);
+
+        (line_start.is_empty() || line_start.ends_with('\n'))
+            && (line_end.starts_with('\n') || line_end.starts_with("\r\n"))
+            && !before.contains("```")
+    }
+
+    /// Flushes text held back as a possible tag, once the stream ended.
+    pub fn finish(&mut self) {
+        self.emit(self.pending.len());
+    }
+
+    /// Moves the first `len` bytes of the pending text to where they belong.
+    fn emit(&mut self, len: usize) {
+        let segment: String = self.pending.drain(..len).collect();
+        let target = if self.in_think {
+            &mut self.reasoning
+        } else {
+            &mut self.text
+        };
+
+        // Blank lines around the tags are not part of either.
+        if target.is_empty() {
+            target.push_str(segment.trim_start());
+        } else {
+            target.push_str(&segment);
+        }
+    }
+}
+
+/// Length of the longest end of `text` that may be the start of a tag.
+fn partial_tag_len(text: &str) -> usize {
+    (1..CLOSE_TAG.len())
+        .rev()
+        .filter(|len| *len <= text.len() && text.is_char_boundary(text.len() - len))
+        .find(|len| {
+            let end = &text[text.len() - len..];
+            OPEN_TAG.starts_with(end) || CLOSE_TAG.starts_with(end)
+        })
+        .unwrap_or(0)
+}
+
+/// Combines the reasoning reported by the provider with the one parsed from tags.
+fn merge_reasoning(reported: &str, parsed: &str) -> String {
+    match (reported.is_empty(), parsed.is_empty()) {
+        (_, true) => reported.to_string(),
+        (true, false) => parsed.trim_end().to_string(),
+        (false, false) => format!("{}\n\n{}", reported, parsed.trim_end()),
+    }
+}
+
+/// Wraps a [`BotClient`] moving `<think>` segments of the replies to their reasoning.
+pub struct ThinkTagsClient {
+    client: Box<dyn BotClient>,
+}
+
+impl Clone for ThinkTagsClient {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone_box(),
+        }
+    }
+}
+
+impl ThinkTagsClient {
+    pub fn new(client: Box<dyn BotClient>) -> Self {
+        Self { client }
+    }
+}
+
+impl BotClient for ThinkTagsClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        self.client.bots()
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let mut inner = self.client.send(bot_id, messages, tools);
+
+        let stream = stream! {
+            let mut parser = ThinkTagParser::default();
+            // Text given to the parser so far. Clients yield the whole content on
+            // each update, so only what was appended to it is parsed.
+            let mut parsed_text = String::new();
+            let mut last_content = None;
+
+            while let Some(result) = inner.next().await {
+                let (content, errors) = result.into_value_and_errors();
+
+                if let Some(mut content) = content {
+                    match content.text.strip_prefix(parsed_text.as_str()) {
+                        Some(new_text) => parser.push(new_text),
+                        None => {
+                            parser = ThinkTagParser::default();
+                            parser.push(&content.text);
+                        }
+                    }
+                    parsed_text = std::mem::take(&mut content.text);
+
+                    content.text = parser.text.clone();
+                    content.reasoning = merge_reasoning(&content.reasoning, &parser.reasoning);
+                    last_content = Some(content.clone());
+                    yield ClientResult::new_ok(content);
+                }
+
+                if !errors.is_empty() {
+                    yield ClientResult::new_err(errors);
+                    return;
+                }
+            }
+
+            // Text held back as a possible tag turned out to be text.
+            if let Some(mut content) = last_content {
+                parser.finish();
+                if content.text != parser.text {
+                    content.text = parser.text.clone();
+                    yield ClientResult::new_ok(content);
+                }
+            }
+        };
+
+        Box::pin(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chunks: &[&str]) -> ThinkTagParser {
+        let mut parser = ThinkTagParser::default();
+        for chunk in chunks {
+            parser.push(chunk);
+        }
+        parser.finish();
+        parser
+    }
+
+    #[test]
+    fn test_tags_split_across_chunks() {
+        let parser = parse(&["<th", "ink>\nLet me ", "think.</th", "ink>\n\nHello", "!"]);
+        assert_eq!(parser.reasoning, "Let me think.");
+        assert_eq!(parser.text, "Hello!");
+    }
+
+    #[test]
+    fn test_partial_tag_is_held_back_until_known() {
+        let mut parser = ThinkTagParser::default();
+        parser.push("1 <");
+        assert_eq!(parser.text, "1 ");
+        parser.push(" 2");
+        assert_eq!(parser.text, "1 < 2");
+        parser.push(" </t");
+        assert_eq!(parser.text, "1 < 2 ");
+        parser.finish();
+        assert_eq!(parser.text, "1 < 2 </t");
+    }
+
+    #[test]
+    fn test_missing_open_tag() {
+        let parser = parse(&["Hmm, the user ", "greets me.\n</think>", "\n\nHi there"]);
+        assert_eq!(parser.reasoning, "Hmm, the user greets me.");
+        assert_eq!(parser.text, "Hi there");
+    }
+
+    #[test]
+    fn test_closing_tag_mentioned_in_reply() {
+        let parser = parse(&[
+            "Reasoning models end their thoughts with ",
+            "</think>",
+            " and then answer.\n",
+            "```\n</think>\n```",
+        ]);
+        assert_eq!(parser.reasoning, "");
+        assert_eq!(
+            parser.text,
+            "Reasoning models end their thoughts with </think> and then answer.\n```\n</think>\n```"
+        );
+    }
+
+    #[test]
+    fn test_text_without_tags_is_untouched() {
+        let parser = parse(&["No ", "reasoning ", "here."]);
+        assert_eq!(parser.reasoning, "");
+        assert_eq!(parser.text, "No reasoning here.");
+    }
+}
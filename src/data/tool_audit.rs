@@ -0,0 +1,320 @@
+//! Audit log of the tools run from the chats.
+//!
+//! Calls are recorded when approved, and logged with their result once it comes
+//! back. The log is saved as JSON lines, one entry per call, appended as calls
+//! finish.
+//!
+//! Calls still running are saved aside, so the ones that never get a result
+//! (e.g. the chat was closed or Moly exited) are logged as such too.
+
+use chrono::{DateTime, Utc};
+use makepad_widgets::{Cx, DefaultNone};
+use moly_kit::aitk::utils::asynchronous::spawn;
+use moly_kit::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use crate::shared::utils::filesystem;
+
+use super::chats::chat::ChatId;
+
+const AUDIT_DIR: &str = "mcp";
+const AUDIT_FILENAME: &str = "tool_audit.jsonl";
+const RUNNING_FILENAME: &str = "tool_audit_running.json";
+
+/// Who allowed a tool call to run.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolApproval {
+    /// The user approved the request.
+    User,
+    /// A rule of the tool policies allowed it, e.g. a grant of the user.
+    Policy,
+    /// Dangerous mode allowed it without asking.
+    DangerousMode,
+}
+
+impl ToolApproval {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ToolApproval::User => "Approved by the user",
+            ToolApproval::Policy => "Allowed by a tool policy",
+            ToolApproval::DangerousMode => "Auto-approved by dangerous mode",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ToolAuditEntry {
+    /// When the call started.
+    pub timestamp: DateTime<Utc>,
+    pub chat_id: ChatId,
+    pub bot: Option<String>,
+    pub server: String,
+    pub tool: String,
+    pub arguments: Value,
+    /// The result given to the model, or the error if `is_error`.
+    pub result: String,
+    pub is_error: bool,
+    pub duration_ms: i64,
+    pub approval: ToolApproval,
+}
+
+impl ToolAuditEntry {
+    /// Whether every word of the query is found in the entry, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        let text = format!(
+            "{} {} {} {} {}",
+            self.server,
+            self.tool,
+            self.bot.as_deref().unwrap_or_default(),
+            self.arguments,
+            self.result
+        )
+        .to_lowercase();
+
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| text.contains(word))
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ToolAuditAction {
+    None,
+    /// An entry was added to the log.
+    Logged,
+}
+
+/// Calls approved and waiting for their result, by tool call id.
+static RUNNING: LazyLock<Mutex<HashMap<String, ToolAuditEntry>>> = LazyLock::new(Default::default);
+
+static ENTRIES: LazyLock<Mutex<Vec<ToolAuditEntry>>> = LazyLock::new(Default::default);
+
+/// Held while saving the running calls, so older snapshots don't overwrite newer ones.
+static SAVING_RUNNING: LazyLock<futures::lock::Mutex<()>> = LazyLock::new(Default::default);
+
+fn audit_path() -> PathBuf {
+    Path::new(AUDIT_DIR).join(AUDIT_FILENAME)
+}
+
+fn running_path() -> PathBuf {
+    Path::new(AUDIT_DIR).join(RUNNING_FILENAME)
+}
+
+/// Reads the saved log, to be called before any tool runs.
+///
+/// Calls left running by the previous session are logged without a result.
+pub async fn load() {
+    let mut fs = filesystem::global();
+    let entries = match fs.read_string(&audit_path()).await {
+        Ok(text) => text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    ::log::warn!("Skipping unreadable tool audit entry: {}", e);
+                    None
+                }
+            })
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+
+    {
+        let mut current = ENTRIES.lock().unwrap();
+        let logged = std::mem::take(&mut *current);
+        *current = entries;
+        current.extend(logged);
+    }
+
+    let Ok(abandoned) = fs.read_json::<Vec<ToolAuditEntry>>(&running_path()).await else {
+        return;
+    };
+
+    let abandoned = abandoned
+        .into_iter()
+        .map(|entry| without_result(entry, "Moly was closed before the tool finished"))
+        .collect::<Vec<_>>();
+    if let Err(e) = fs
+        .queue_append(audit_path(), to_json_lines(&abandoned).into_bytes())
+        .await
+    {
+        ::log::error!("Failed to save the tool audit log: {}", e);
+        return;
+    }
+
+    ENTRIES.lock().unwrap().extend(abandoned);
+    Cx::post_action(ToolAuditAction::Logged);
+    save_running().await;
+}
+
+/// Logged entries, oldest first.
+pub fn entries() -> Vec<ToolAuditEntry> {
+    ENTRIES.lock().unwrap().clone()
+}
+
+/// Records the start of an approved call of a tool of `server`.
+pub fn start(
+    chat_id: ChatId,
+    tool_call: &ToolCall,
+    server: &str,
+    tool: &str,
+    approval: ToolApproval,
+) {
+    let entry = ToolAuditEntry {
+        timestamp: Utc::now(),
+        chat_id,
+        bot: None,
+        server: server.to_string(),
+        tool: tool.to_string(),
+        arguments: Value::Object(tool_call.arguments.clone()),
+        result: String::new(),
+        is_error: false,
+        duration_ms: 0,
+        approval,
+    };
+    RUNNING.lock().unwrap().insert(tool_call.id.clone(), entry);
+    spawn(save_running());
+}
+
+/// Logs the calls the results are for, if they were started.
+pub fn finish(bot_id: Option<&BotId>, results: &[ToolResult]) {
+    let finished = {
+        let mut running = RUNNING.lock().unwrap();
+        results
+            .iter()
+            .filter_map(|result| {
+                let mut entry = running.remove(&result.tool_call_id)?;
+                entry.bot = bot_id.map(|bot_id| bot_id.id().to_string());
+                entry.result = result.content.clone();
+                entry.is_error = result.is_error;
+                entry.duration_ms = (Utc::now() - entry.timestamp).num_milliseconds();
+                Some(entry)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    log(finished);
+}
+
+/// Logs the calls still running in a chat without a result, e.g. once it's closed.
+pub fn abandon(chat_id: ChatId) {
+    let abandoned = {
+        let mut running = RUNNING.lock().unwrap();
+        let ids = running
+            .iter()
+            .filter(|(_, entry)| entry.chat_id == chat_id)
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        ids.iter()
+            .filter_map(|id| running.remove(id))
+            .map(|mut entry| {
+                entry.duration_ms = (Utc::now() - entry.timestamp).num_milliseconds();
+                without_result(
+                    entry,
+                    "The chat was closed or unloaded before the tool finished",
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    log(abandoned);
+}
+
+fn without_result(mut entry: ToolAuditEntry, reason: &str) -> ToolAuditEntry {
+    entry.result = format!("No result: {}", reason);
+    entry.is_error = true;
+    entry
+}
+
+/// Adds the entries to the log, appending them to the saved one.
+fn log(entries: Vec<ToolAuditEntry>) {
+    if entries.is_empty() {
+        return;
+    }
+
+    let lines = to_json_lines(&entries);
+    ENTRIES.lock().unwrap().extend(entries);
+    Cx::post_action(ToolAuditAction::Logged);
+
+    spawn(async move {
+        let mut fs = filesystem::global();
+        if let Err(e) = fs.queue_append(audit_path(), lines.into_bytes()).await {
+            ::log::error!("Failed to save the tool audit log: {}", e);
+        }
+        save_running().await;
+    });
+}
+
+/// Saves the calls currently running, removing the file once there are none.
+async fn save_running() {
+    let _saving = SAVING_RUNNING.lock().await;
+    let running = RUNNING
+        .lock()
+        .unwrap()
+        .values()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut fs = filesystem::global();
+    let result = if running.is_empty() {
+        if fs.exists(&running_path()).await.unwrap_or(false) {
+            fs.remove(&running_path()).await
+        } else {
+            Ok(())
+        }
+    } else {
+        fs.queue_write_json(running_path(), &running).await
+    };
+
+    if let Err(e) = result {
+        ::log::error!("Failed to save the running tool calls: {}", e);
+    }
+}
+
+/// The entries as JSON lines, as saved and exported.
+pub fn to_json_lines(entries: &[ToolAuditEntry]) -> String {
+    entries
+        .iter()
+        .filter_map(|entry| serde_json::to_string(entry).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_entries_round_trip_and_match() {
+        let entry = ToolAuditEntry {
+            timestamp: Utc::now(),
+            chat_id: 1,
+            bot: Some("gpt-4o".to_string()),
+            server: "github".to_string(),
+            tool: "create_issue".to_string(),
+            arguments: json!({"repo": "moly-ai/moly", "title": "Crash"}),
+            result: "Created issue #42".to_string(),
+            is_error: false,
+            duration_ms: 350,
+            approval: ToolApproval::DangerousMode,
+        };
+
+        let lines = to_json_lines(&[entry.clone(), entry.clone()]);
+        assert_eq!(lines.lines().count(), 2);
+        assert!(lines.contains("\"approval\":\"dangerous_mode\""));
+        let parsed: ToolAuditEntry = serde_json::from_str(lines.lines().next().unwrap()).unwrap();
+        assert_eq!(parsed, entry);
+
+        assert!(entry.matches("GitHub moly-ai/moly"));
+        assert!(entry.matches(""));
+        assert!(!entry.matches("github delete"));
+    }
+}
@@ -0,0 +1,117 @@
+//! Tool permissions and limits of the chats, backed by the policies of the MCP
+//! servers config.
+
+use makepad_widgets::{Cx, DefaultNone};
+use moly_kit::prelude::*;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use super::chats::chat::ChatId;
+use super::mcp_servers::{McpServersConfig, ToolPolicy};
+use super::tool_audit::{self, ToolApproval};
+
+/// The config the permissions of the chats are decided with.
+///
+/// Kept by the preferences of the store, which set it each time they are loaded or
+/// saved, and shared with the permissions of each chat, which can't reach the store.
+#[derive(Clone, Debug, Default)]
+pub struct ToolPermissionsHandle(Arc<Mutex<McpServersConfig>>);
+
+impl ToolPermissionsHandle {
+    pub fn set(&self, config: &McpServersConfig) {
+        *self.0.lock().unwrap() = config.clone();
+    }
+
+    fn config(&self) -> MutexGuard<'_, McpServersConfig> {
+        self.0.lock().unwrap()
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ToolPermissionsAction {
+    None,
+    /// The user allowed a namespaced tool, in a single chat or in all of them.
+    Granted {
+        chat_id: Option<ChatId>,
+        tool_name: String,
+    },
+    /// A grant was saved to the config of the store.
+    Saved,
+}
+
+/// The [`ToolPermissions`] and [`ToolExecution`] of a chat.
+pub struct ChatToolPermissions {
+    chat_id: ChatId,
+    handle: ToolPermissionsHandle,
+}
+
+impl ChatToolPermissions {
+    pub fn new(chat_id: ChatId, handle: ToolPermissionsHandle) -> Self {
+        Self { chat_id, handle }
+    }
+}
+
+impl ToolPermissions for ChatToolPermissions {
+    fn decide(&self, tool_name: &str) -> ToolDecision {
+        match self
+            .handle
+            .config()
+            .tool_policy(tool_name, Some(self.chat_id))
+        {
+            ToolPolicy::Allow => ToolDecision::Allow,
+            ToolPolicy::Ask => ToolDecision::Ask,
+            ToolPolicy::Deny => ToolDecision::Deny,
+        }
+    }
+
+    fn grant(&self, tool_name: &str, scope: ToolGrantScope) {
+        let chat_id = match scope {
+            ToolGrantScope::Chat => Some(self.chat_id),
+            ToolGrantScope::Always => None,
+        };
+
+        // The store saves the grant, which then reaches the handle like any other
+        // change of the config.
+        Cx::post_action(ToolPermissionsAction::Granted {
+            chat_id,
+            tool_name: tool_name.to_string(),
+        });
+    }
+}
+
+impl ToolExecution for ChatToolPermissions {
+    fn approved(&self, tool_calls: &[ToolCall], by_user: bool) {
+        let config = self.handle.config();
+        for tool_call in tool_calls {
+            // Without a rule allowing it, only dangerous mode allows a tool.
+            let approval = if by_user {
+                ToolApproval::User
+            } else if config
+                .tool_rule(&tool_call.name, Some(self.chat_id))
+                .is_some()
+            {
+                ToolApproval::Policy
+            } else {
+                ToolApproval::DangerousMode
+            };
+
+            let (server, tool) = config
+                .split_tool_name(&tool_call.name)
+                .unwrap_or(("", &tool_call.name));
+            tool_audit::start(self.chat_id, tool_call, server, tool, approval);
+        }
+    }
+
+    fn timeout(&self, tool_name: &str) -> Option<Duration> {
+        self.handle.config().tool_timeout(tool_name)
+    }
+
+    fn max_output_chars(&self) -> Option<usize> {
+        let max = self.handle.config().max_tool_output_chars;
+        (max > 0).then_some(max)
+    }
+
+    fn finished(&self, bot_id: Option<&BotId>, results: &[ToolResult]) {
+        tool_audit::finish(bot_id, results);
+    }
+}
@@ -0,0 +1,258 @@
+//! MCP tools offered to the model in a chat.
+//!
+//! The tool manager is shared by all chats, so by default every tool is sent along
+//! with each request. A chat can narrow that down to some servers or tools, which
+//! keeps prompts small for models that get confused by long tool lists. Chats that
+//! don't pick their own tools use the ones picked for their assistant.
+
+use moly_kit::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+
+use super::mcp_servers::strip_server_prefix;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SelectedTool {
+    pub server: String,
+    pub tool: String,
+}
+
+/// Tools of a chat or an assistant, persisted along with it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolSelection {
+    /// When unset, all tools are offered and the lists below are ignored.
+    #[serde(default)]
+    pub restricted: bool,
+    /// Servers whose tools are all offered, including tools they add later.
+    #[serde(default)]
+    pub servers: Vec<String>,
+    /// Single tools offered from servers not selected as a whole.
+    #[serde(default)]
+    pub tools: Vec<SelectedTool>,
+}
+
+impl ToolSelection {
+    /// Whether the namespaced tool should be offered.
+    pub fn includes(&self, tool_name: &str) -> bool {
+        if !self.restricted {
+            return true;
+        }
+
+        self.servers
+            .iter()
+            .any(|server| strip_server_prefix(tool_name, server).is_some())
+            || self.tools.iter().any(|selected| {
+                strip_server_prefix(tool_name, &selected.server) == Some(selected.tool.as_str())
+            })
+    }
+
+    pub fn server_selected(&self, server: &str) -> bool {
+        !self.restricted || self.servers.iter().any(|s| s == server)
+    }
+
+    pub fn tool_selected(&self, server: &str, tool: &str) -> bool {
+        self.server_selected(server)
+            || self
+                .tools
+                .iter()
+                .any(|selected| selected.server == server && selected.tool == tool)
+    }
+
+    /// Offers all tools again.
+    pub fn select_all(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Selects or deselects a whole server.
+    ///
+    /// `available` lists the servers that can be picked from, with their tools.
+    pub fn set_server(
+        &mut self,
+        server: &str,
+        selected: bool,
+        available: &[(String, Vec<String>)],
+    ) {
+        self.restrict(available);
+        self.tools.retain(|t| t.server != server);
+        self.servers.retain(|s| s != server);
+        if selected {
+            self.servers.push(server.to_string());
+        }
+    }
+
+    /// Selects or deselects a single tool of a server.
+    ///
+    /// Deselecting a tool of a selected server keeps its other tools selected.
+    pub fn set_tool(
+        &mut self,
+        server: &str,
+        tool: &str,
+        selected: bool,
+        available: &[(String, Vec<String>)],
+    ) {
+        self.restrict(available);
+
+        if self.servers.iter().any(|s| s == server) {
+            if selected {
+                return;
+            }
+
+            self.servers.retain(|s| s != server);
+            let server_tools = available
+                .iter()
+                .find(|(id, _)| id == server)
+                .map(|(_, tools)| tools.as_slice())
+                .unwrap_or_default();
+            for other in server_tools.iter().filter(|other| *other != tool) {
+                self.tools.push(SelectedTool {
+                    server: server.to_string(),
+                    tool: other.clone(),
+                });
+            }
+            return;
+        }
+
+        self.tools
+            .retain(|t| !(t.server == server && t.tool == tool));
+        if selected {
+            self.tools.push(SelectedTool {
+                server: server.to_string(),
+                tool: tool.to_string(),
+            });
+        }
+    }
+
+    /// Turns "all tools" into the explicit selection of every available server.
+    fn restrict(&mut self, available: &[(String, Vec<String>)]) {
+        if !self.restricted {
+            self.restricted = true;
+            self.servers = available.iter().map(|(id, _)| id.clone()).collect();
+            self.tools.clear();
+        }
+    }
+}
+
+/// Tool selection shared between a [`ToolSelectionClient`] and the chat it serves.
+#[derive(Clone, Default)]
+pub struct ToolSelectionHandle(Arc<Mutex<ToolSelection>>);
+
+impl ToolSelectionHandle {
+    pub fn set(&self, selection: ToolSelection) {
+        *self.0.lock().unwrap() = selection;
+    }
+
+    pub fn get(&self) -> ToolSelection {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Wraps a [`BotClient`] sending only the tools selected in the chat.
+pub struct ToolSelectionClient {
+    client: Box<dyn BotClient>,
+    handle: ToolSelectionHandle,
+}
+
+impl Clone for ToolSelectionClient {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone_box(),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl ToolSelectionClient {
+    pub fn new(client: Box<dyn BotClient>, handle: ToolSelectionHandle) -> Self {
+        Self { client, handle }
+    }
+}
+
+fn tool_name(tool: &Tool) -> Option<String> {
+    let tool = serde_json::to_value(tool).ok()?;
+    tool["name"].as_str().map(str::to_string)
+}
+
+/// Name, description and parameters of a tool, as taken by function calling APIs.
+pub fn function_definition(tool: &Tool) -> Option<Value> {
+    let tool = serde_json::to_value(tool).ok()?;
+    let parameters = tool
+        .get("inputSchema")
+        .or_else(|| tool.get("input_schema"))
+        .cloned()
+        .unwrap_or_else(|| json!({"type": "object", "properties": {}}));
+
+    Some(json!({
+        "name": tool["name"],
+        "description": tool.get("description").cloned().unwrap_or(Value::Null),
+        "parameters": parameters,
+    }))
+}
+
+impl BotClient for ToolSelectionClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        self.client.bots()
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let selection = self.handle.get();
+        if !selection.restricted {
+            return self.client.send(bot_id, messages, tools);
+        }
+
+        let tools = tools
+            .iter()
+            .filter(|tool| tool_name(tool).is_some_and(|name| selection.includes(&name)))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.client.send(bot_id, messages, &tools)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn available() -> Vec<(String, Vec<String>)> {
+        vec![
+            (
+                "git".to_string(),
+                vec!["status".to_string(), "log".to_string()],
+            ),
+            ("github".to_string(), vec!["search".to_string()]),
+        ]
+    }
+
+    #[test]
+    fn test_tool_selection() {
+        let available = available();
+        let mut selection = ToolSelection::default();
+        assert!(selection.includes("github__search"));
+
+        selection.set_server("github", false, &available);
+        assert!(selection.includes("git__status"));
+        assert!(!selection.includes("github__search"));
+
+        // Deselecting a tool keeps the rest of its server.
+        selection.set_tool("git", "status", false, &available);
+        assert!(!selection.includes("git__status"));
+        assert!(selection.includes("git__log"));
+        assert!(!selection.server_selected("git"));
+
+        selection.set_tool("github", "search", true, &available);
+        assert!(selection.includes("github__search"));
+        assert!(selection.tool_selected("github", "search"));
+
+        selection.select_all();
+        assert!(selection.includes("git__status"));
+    }
+}
@@ -0,0 +1,328 @@
+use makepad_widgets::*;
+
+use crate::data::mcp_servers::{BUILTIN_SERVER_ID, BuiltinTool, BuiltinToolsConfig};
+use crate::data::mcp_status::{self, McpServerState};
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    BuiltinToolRow = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 3
+
+        check = <CheckBox> {
+            draw_text: {
+                text_style: <BOLD_FONT>{font_size: 10},
+                fn get_color(self) -> vec4 {
+                    return #000;
+                }
+            }
+        }
+
+        description = <Label> {
+            width: Fill
+            margin: {left: 25}
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #667085
+            }
+        }
+    }
+
+    FolderRow = <View> {
+        width: Fill, height: Fit
+        spacing: 10
+        align: {y: 0.5}
+
+        path = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Ellipsis
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #000
+            }
+        }
+
+        remove_button = <MolyButton> {
+            width: Fit, height: 30
+            padding: {left: 14, right: 14, top: 0, bottom: 0}
+            text: "Remove"
+            draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+            draw_text: { color: #B42318 }
+        }
+    }
+
+    BuiltinToolsList = {{BuiltinToolsList}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 8
+    }
+
+    // Toggles for the tools built into Moly, and the folders they can read.
+    pub McpBuiltinTools = {{McpBuiltinTools}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 10
+
+        status = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #667085
+            }
+        }
+
+        tools = <BuiltinToolsList> {
+            template: <BuiltinToolRow> {}
+        }
+
+        <Label> {
+            margin: {top: 5}
+            text: "Folders the filesystem tools can read"
+            draw_text: {
+                text_style: <BOLD_FONT>{font_size: 10},
+                color: #000
+            }
+        }
+
+        folders = <BuiltinToolsList> {
+            template: <FolderRow> {}
+        }
+
+        <View> {
+            width: Fill, height: Fit
+            spacing: 10
+            align: {y: 0.5}
+
+            folder_input = <MolyTextInput> {
+                width: Fill, height: Fit
+                padding: {top: 6, bottom: 6, left: 10, right: 10}
+                empty_text: "Absolute path of a folder"
+                draw_bg: {
+                    color: #fff
+                    border_size: 1.0
+                    border_color_1: #D0D5DD
+                    border_radius: 2.0
+                }
+                draw_text: {
+                    text_style: <REGULAR_FONT>{font_size: 10},
+                    color: #000
+                }
+            }
+
+            add_folder_button = <MolyButton> {
+                width: Fit, height: 30
+                padding: {left: 14, right: 14, top: 0, bottom: 0}
+                text: "Add folder"
+                draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+                draw_text: { color: #000 }
+            }
+        }
+
+        folder_error = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #B42318
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum McpBuiltinToolsAction {
+    None,
+    /// The user changed the built-in tools config.
+    Changed(BuiltinToolsConfig),
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct BuiltinToolsList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for BuiltinToolsList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct McpBuiltinTools {
+    #[deref]
+    view: View,
+
+    #[rust]
+    config: BuiltinToolsConfig,
+}
+
+impl Widget for McpBuiltinTools {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for McpBuiltinTools {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        let mut config = self.config.clone();
+
+        if let Some(tools) = self.widget(ids!(tools)).borrow::<BuiltinToolsList>() {
+            for (index, tool) in BuiltinTool::ALL.into_iter().enumerate() {
+                let Some(item) = tools.items.get(&LiveId(index as u64)) else {
+                    continue;
+                };
+                if let Some(enabled) = item.check_box(ids!(check)).changed(actions) {
+                    config.set_enabled(tool, enabled);
+                }
+            }
+        }
+
+        if let Some(folders) = self.widget(ids!(folders)).borrow::<BuiltinToolsList>() {
+            let removed = folders
+                .items
+                .iter()
+                .find(|(_, item)| item.button(ids!(remove_button)).clicked(actions))
+                .map(|(id, _)| id.0 as usize);
+            if let Some(index) = removed.filter(|index| *index < config.folders.len()) {
+                config.folders.remove(index);
+            }
+        }
+
+        let input = self.text_input(ids!(folder_input));
+        if self.button(ids!(add_folder_button)).clicked(actions)
+            || input.returned(actions).is_some()
+        {
+            match validate_folder(&config, input.text().trim()) {
+                Ok(folder) => {
+                    config.folders.push(folder);
+                    input.set_text(cx, "");
+                    self.label(ids!(folder_error)).set_text(cx, "");
+                }
+                Err(e) => self.label(ids!(folder_error)).set_text(cx, &e),
+            }
+        }
+
+        if config != self.config {
+            self.config = config.clone();
+            cx.action(McpBuiltinToolsAction::Changed(config));
+        }
+    }
+}
+
+fn validate_folder(config: &BuiltinToolsConfig, folder: &str) -> Result<String, String> {
+    if folder.is_empty() {
+        return Err("Enter the path of a folder".to_string());
+    }
+    if config.folders.iter().any(|existing| existing == folder) {
+        return Err("The folder is already allowed".to_string());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let path = std::path::Path::new(folder);
+        if !path.is_absolute() {
+            return Err("The path must be absolute".to_string());
+        }
+        if !path.is_dir() {
+            return Err(format!("{} is not a folder", folder));
+        }
+    }
+
+    Ok(folder.to_string())
+}
+
+fn status_text(config: &BuiltinToolsConfig) -> String {
+    let status = mcp_status::statuses()
+        .into_iter()
+        .find(|(id, _)| id == BUILTIN_SERVER_ID)
+        .map(|(_, status)| status);
+
+    match status.map(|status| (status.state, status.tools.len())) {
+        _ if config.enabled_tools().is_empty() => "No built-in tools are enabled.".to_string(),
+        None => "Not loaded".to_string(),
+        Some((McpServerState::Connecting, _)) => "Starting...".to_string(),
+        Some((McpServerState::Connected, 1)) => "Running · 1 tool".to_string(),
+        Some((McpServerState::Connected, count)) => format!("Running · {} tools", count),
+        Some((McpServerState::Failed(e), _)) => format!("Failed: {}", e),
+    }
+}
+
+impl McpBuiltinToolsRef {
+    /// Shows the config and the status of the built-in tools.
+    pub fn set_config(&self, cx: &mut Cx, config: &BuiltinToolsConfig) {
+        let Some(mut inner) = self.borrow_mut() else {
+            return;
+        };
+
+        inner.label(ids!(status)).set_text(cx, &status_text(config));
+
+        if let Some(mut list) = inner.widget(ids!(tools)).borrow_mut::<BuiltinToolsList>() {
+            list.items.clear();
+            for (index, tool) in BuiltinTool::ALL.into_iter().enumerate() {
+                let item = WidgetRef::new_from_ptr(cx, list.template);
+                let name = tool.name();
+                let description = tool.description();
+                item.apply_over(
+                    cx,
+                    live! {
+                        check = { text: (name) }
+                        description = { text: (description) }
+                    },
+                );
+                item.check_box(ids!(check))
+                    .set_active(cx, config.is_enabled(tool));
+                list.items.insert(LiveId(index as u64), item);
+            }
+            list.redraw(cx);
+        }
+
+        if let Some(mut list) = inner.widget(ids!(folders)).borrow_mut::<BuiltinToolsList>() {
+            list.items.clear();
+            for (index, folder) in config.folders.iter().enumerate() {
+                let item = WidgetRef::new_from_ptr(cx, list.template);
+                let folder = folder.clone();
+                item.apply_over(cx, live! { path = { text: (folder) } });
+                list.items.insert(LiveId(index as u64), item);
+            }
+            list.redraw(cx);
+        }
+
+        inner.config = config.clone();
+    }
+}
@@ -0,0 +1,291 @@
+use makepad_widgets::*;
+
+use crate::data::mcp_import::{Duplicate, ImportCandidate};
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    ICON_CLOSE = dep("crate://self/resources/icons/close.svg")
+
+    CandidateEntry = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 3
+        padding: {top: 6, bottom: 6}
+
+        check = <CheckBox> {
+            draw_text: {
+                text_style: <BOLD_FONT>{font_size: 10},
+                fn get_color(self) -> vec4 {
+                    return #344054;
+                }
+            }
+        }
+
+        details = <Label> {
+            width: Fill
+            margin: {left: 25}
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #667085
+            }
+        }
+
+        duplicate = <Label> {
+            width: Fill
+            margin: {left: 25}
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #B54708
+            }
+        }
+    }
+
+    McpImportCandidates = {{McpImportCandidates}} {
+        width: Fill, height: Fit
+        flow: Down
+
+        template: <CandidateEntry> {}
+    }
+
+    // Previews the servers configured in other clients, to import some of them.
+    pub McpImportModal = {{McpImportModal}} <RoundedView> {
+        flow: Down
+        width: 550
+        height: Fit
+        show_bg: true
+        draw_bg: {
+            color: #fff
+            border_radius: 3.0
+        }
+
+        padding: 25
+        spacing: 15
+
+        header = <View> {
+            width: Fill, height: Fit
+            spacing: 10
+            align: {x: 0.0, y: 0.5}
+
+            <Label> {
+                width: Fill
+                draw_text: {
+                    text_style: <BOLD_FONT>{font_size: 13},
+                    color: #000
+                }
+                text: "Import MCP servers"
+            }
+
+            close_button = <MolyButton> {
+                width: Fit, height: Fit
+                icon_walk: {width: 14, height: Fit}
+                draw_icon: {
+                    svg_file: (ICON_CLOSE),
+                    fn get_color(self) -> vec4 {
+                        return #000;
+                    }
+                }
+            }
+        }
+
+        summary = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #667085
+            }
+        }
+
+        <ScrollYView> {
+            width: Fill, height: Fit { max: 400 }
+            candidates = <McpImportCandidates> {}
+        }
+
+        <View> {
+            width: Fill, height: Fit
+            align: {x: 1.0}
+
+            import_button = <MolyButton> {
+                width: Fit, height: 36
+                padding: {left: 20, right: 20, top: 0, bottom: 0}
+                text: "Import selected"
+                draw_bg: { color: (CTA_BUTTON_COLOR), border_size: 0 }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum McpImportModalAction {
+    None,
+    ModalDismissed,
+    /// The user picked these servers to import.
+    Import(Vec<ImportCandidate>),
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct McpImportCandidates {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for McpImportCandidates {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct McpImportModal {
+    #[deref]
+    view: View,
+
+    #[rust]
+    candidates: Vec<ImportCandidate>,
+}
+
+impl Widget for McpImportModal {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for McpImportModal {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        if self.button(ids!(close_button)).clicked(actions) {
+            cx.action(McpImportModalAction::ModalDismissed);
+        }
+
+        if self.button(ids!(import_button)).clicked(actions) {
+            let candidates = self.widget(ids!(candidates));
+            let Some(candidates) = candidates.borrow::<McpImportCandidates>() else {
+                return;
+            };
+
+            let selected = self
+                .candidates
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| {
+                    candidates
+                        .items
+                        .get(&LiveId(*index as u64))
+                        .is_some_and(|item| item.check_box(ids!(check)).active(cx))
+                })
+                .map(|(_, candidate)| candidate.clone())
+                .collect::<Vec<_>>();
+
+            if !selected.is_empty() {
+                cx.action(McpImportModalAction::Import(selected));
+            }
+            cx.action(McpImportModalAction::ModalDismissed);
+        }
+    }
+}
+
+/// What the server runs, to tell it apart from servers with other ids.
+fn describe(candidate: &ImportCandidate) -> String {
+    let target = match (&candidate.server.command, &candidate.server.url) {
+        (Some(command), _) => std::iter::once(command.as_str())
+            .chain(candidate.server.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" "),
+        (None, Some(url)) => url.clone(),
+        (None, None) => "No command or url".to_string(),
+    };
+    format!(
+        "{} · {}\n{}",
+        candidate.source.name(),
+        candidate.path.display(),
+        target
+    )
+}
+
+impl McpImportModalRef {
+    /// Lists the servers found, selecting the ones not configured yet.
+    pub fn set_candidates(&self, cx: &mut Cx, candidates: Vec<ImportCandidate>) {
+        let Some(mut modal) = self.borrow_mut() else {
+            return;
+        };
+
+        let summary = if candidates.is_empty() {
+            "No MCP servers were found in the configs of Claude Desktop, VS Code or Cursor."
+                .to_string()
+        } else {
+            "Servers found in the configs of other clients. Servers already configured in Moly are not selected, importing them replaces the existing ones with the same id.".to_string()
+        };
+        modal.label(ids!(summary)).set_text(cx, &summary);
+        modal
+            .button(ids!(import_button))
+            .set_visible(cx, !candidates.is_empty());
+
+        if let Some(mut list) = modal
+            .widget(ids!(candidates))
+            .borrow_mut::<McpImportCandidates>()
+        {
+            list.items.clear();
+            for (index, candidate) in candidates.iter().enumerate() {
+                let item = WidgetRef::new_from_ptr(cx, list.template);
+                let id = candidate.id.clone();
+                let details = describe(candidate);
+                let duplicate = match &candidate.duplicate {
+                    Some(Duplicate::Id) => format!("A server named \"{}\" already exists", id),
+                    Some(Duplicate::Command(existing)) => {
+                        format!("Same command as the server \"{}\"", existing)
+                    }
+                    None => String::new(),
+                };
+                let is_duplicate = candidate.duplicate.is_some();
+                item.apply_over(
+                    cx,
+                    live! {
+                        check = { text: (id) }
+                        details = { text: (details) }
+                        duplicate = { text: (duplicate), visible: (is_duplicate) }
+                    },
+                );
+                item.check_box(ids!(check)).set_active(cx, !is_duplicate);
+                list.items.insert(LiveId(index as u64), item);
+            }
+        }
+
+        modal.candidates = candidates;
+        modal.redraw(cx);
+    }
+}
@@ -0,0 +1,241 @@
+use makepad_widgets::*;
+
+use crate::data::mcp_inputs::McpInputsAction;
+use crate::data::mcp_servers::InputConfig;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    ICON_CLOSE = dep("crate://self/resources/icons/close.svg")
+
+    InputField = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 5
+
+        description = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #344054
+            }
+        }
+
+        value = <MolyTextInput> {
+            width: Fill, height: Fit
+            padding: {top: 8, bottom: 8, left: 10, right: 10}
+            draw_bg: {
+                color: #fff
+                border_size: 1.0
+                border_color_1: #D0D5DD
+                border_radius: 2.0
+            }
+            draw_text: {
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #000
+            }
+        }
+    }
+
+    McpInputFields = {{McpInputFields}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 12
+
+        template: <InputField> {}
+    }
+
+    // Asks for the inputs referenced by the MCP servers config.
+    pub McpInputsModal = {{McpInputsModal}} <RoundedView> {
+        flow: Down
+        width: 450
+        height: Fit
+        show_bg: true
+        draw_bg: {
+            color: #fff
+            border_radius: 3.0
+        }
+
+        padding: 25
+        spacing: 15
+
+        header = <View> {
+            width: Fill, height: Fit
+            spacing: 10
+            align: {x: 0.0, y: 0.5}
+
+            <Label> {
+                width: Fill
+                draw_text: {
+                    text_style: <BOLD_FONT>{font_size: 13},
+                    color: #000
+                }
+                text: "MCP server inputs"
+            }
+
+            close_button = <MolyButton> {
+                width: Fit, height: Fit
+                icon_walk: {width: 14, height: Fit}
+                draw_icon: {
+                    svg_file: (ICON_CLOSE),
+                    fn get_color(self) -> vec4 {
+                        return #000;
+                    }
+                }
+            }
+        }
+
+        <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #667085
+            }
+            text: "Some servers need these values to start. Passwords are stored in the keychain of your system."
+        }
+
+        fields = <McpInputFields> {}
+
+        <View> {
+            width: Fill, height: Fit
+            align: {x: 1.0}
+
+            submit_button = <MolyButton> {
+                width: Fit, height: 36
+                padding: {left: 20, right: 20, top: 0, bottom: 0}
+                text: "Save"
+                draw_bg: { color: (CTA_BUTTON_COLOR), border_size: 0 }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum McpInputsModalAction {
+    None,
+    ModalDismissed,
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct McpInputFields {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for McpInputFields {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct McpInputsModal {
+    #[deref]
+    view: View,
+
+    #[rust]
+    inputs: Vec<InputConfig>,
+}
+
+impl Widget for McpInputsModal {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for McpInputsModal {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        if self.button(ids!(close_button)).clicked(actions) {
+            cx.action(McpInputsModalAction::ModalDismissed);
+        }
+
+        if self.button(ids!(submit_button)).clicked(actions) {
+            let fields = self.widget(ids!(fields));
+            let Some(fields) = fields.borrow::<McpInputFields>() else {
+                return;
+            };
+
+            // Empty fields are left unanswered, so they are asked again.
+            let answers = self
+                .inputs
+                .iter()
+                .enumerate()
+                .filter_map(|(index, input)| {
+                    let item = fields.items.get(&LiveId(index as u64))?;
+                    let value = item.text_input(ids!(value)).text();
+                    (!value.is_empty()).then(|| (input.clone(), value))
+                })
+                .collect::<Vec<_>>();
+
+            cx.action(McpInputsAction::Answered(answers));
+            cx.action(McpInputsModalAction::ModalDismissed);
+        }
+    }
+}
+
+impl McpInputsModalRef {
+    /// Shows a field for each input, masking passwords.
+    pub fn set_inputs(&self, cx: &mut Cx, inputs: Vec<InputConfig>) {
+        let Some(mut modal) = self.borrow_mut() else {
+            return;
+        };
+
+        if let Some(mut fields) = modal.widget(ids!(fields)).borrow_mut::<McpInputFields>() {
+            fields.items.clear();
+            for (index, input) in inputs.iter().enumerate() {
+                let item = WidgetRef::new_from_ptr(cx, fields.template);
+                let description = if input.description.is_empty() {
+                    input.id.clone()
+                } else {
+                    input.description.clone()
+                };
+                let password = input.password;
+                item.apply_over(
+                    cx,
+                    live! {
+                        description = { text: (description) }
+                        value = { is_password: (password) }
+                    },
+                );
+                fields.items.insert(LiveId(index as u64), item);
+            }
+        }
+
+        modal.inputs = inputs;
+        modal.redraw(cx);
+    }
+}
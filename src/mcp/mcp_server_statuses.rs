@@ -0,0 +1,287 @@
+use makepad_widgets::*;
+
+use crate::data::mcp_servers::McpServersConfig;
+use crate::data::mcp_status::{self, McpServerState, McpServerStatus};
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    DetailsLabel = <Label> {
+        width: Fill
+        draw_text: {
+            wrap: Word
+            text_style: <REGULAR_FONT>{font_size: 10},
+            color: #667085
+        }
+    }
+
+    McpServerStatusRow = <RoundedView> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 6
+        padding: 12
+        draw_bg: {
+            color: #fff
+            border_radius: 5.0
+            border_size: 1.0
+            border_color_1: #EAECF0
+        }
+
+        <View> {
+            width: Fill, height: Fit
+            spacing: 10
+            align: {y: 0.5}
+
+            <View> {
+                width: Fill, height: Fit
+                flow: Down, spacing: 3
+
+                name = <Label> {
+                    draw_text: {
+                        text_style: <BOLD_FONT>{font_size: 11},
+                        color: #000
+                    }
+                }
+                state = <Label> {
+                    draw_text: {
+                        text_style: <REGULAR_FONT>{font_size: 10},
+                        color: #667085
+                    }
+                }
+            }
+
+            restart_button = <MolyButton> {
+                width: Fit, height: 30
+                padding: {left: 14, right: 14, top: 0, bottom: 0}
+                text: "Restart"
+                draw_bg: { color: (CTA_BUTTON_COLOR), border_size: 0 }
+            }
+
+            enable_button = <MolyButton> {
+                width: Fit, height: 30
+                padding: {left: 14, right: 14, top: 0, bottom: 0}
+                draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+                draw_text: { color: #000 }
+            }
+        }
+
+        error = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #B42318
+            }
+        }
+
+        tools = <DetailsLabel> {}
+
+        stderr = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #475467
+            }
+        }
+    }
+
+    // Status, tools and recent stderr of each configured server.
+    pub McpServerStatuses = {{McpServerStatuses}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 10
+
+        template: <McpServerStatusRow> {}
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum McpServerStatusesAction {
+    None,
+    Restart(String),
+    SetEnabled(String, bool),
+}
+
+/// A configured server along with what's known about it.
+#[derive(Clone, Debug)]
+struct ServerRow {
+    id: String,
+    enabled: bool,
+    status: Option<McpServerStatus>,
+}
+
+fn server_rows(config: &McpServersConfig) -> Vec<ServerRow> {
+    let mut statuses = mcp_status::statuses();
+    config
+        .servers
+        .iter()
+        .map(|(id, server)| {
+            let status = statuses
+                .iter()
+                .position(|(status_id, _)| status_id == id)
+                .map(|index| statuses.swap_remove(index).1);
+            ServerRow {
+                id: id.clone(),
+                enabled: server.enabled,
+                status: status.filter(|_| server.enabled),
+            }
+        })
+        .collect()
+}
+
+fn state_text(row: &ServerRow) -> (String, Vec3) {
+    let gray = vec3(0.4, 0.44, 0.52); //#667085
+    match row.status.as_ref().map(|status| &status.state) {
+        _ if !row.enabled => ("Disabled".to_string(), gray),
+        None => ("Not loaded".to_string(), gray),
+        Some(McpServerState::Connecting) => ("Connecting...".to_string(), vec3(0.86, 0.41, 0.04)), //#DC6803
+        Some(McpServerState::Connected) => {
+            let tools = row.status.as_ref().map_or(0, |status| status.tools.len());
+            let text = match tools {
+                1 => "Connected · 1 tool".to_string(),
+                count => format!("Connected · {} tools", count),
+            };
+            (text, vec3(0.035, 0.572, 0.314)) //#099250
+        }
+        Some(McpServerState::Failed(_)) => ("Failed".to_string(), vec3(0.706, 0.137, 0.094)), //#B42318
+    }
+}
+
+fn tools_text(status: &McpServerStatus) -> String {
+    status
+        .tools
+        .iter()
+        .map(|tool| {
+            if tool.description.is_empty() {
+                format!("{}\nInput: {}", tool.name, tool.input_schema)
+            } else {
+                format!(
+                    "{}: {}\nInput: {}",
+                    tool.name,
+                    tool.description.trim(),
+                    tool.input_schema
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct McpServerStatuses {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    #[rust]
+    rows: Vec<ServerRow>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for McpServerStatuses {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        if let Event::Actions(actions) = event {
+            for (index, row) in self.rows.iter().enumerate() {
+                let Some(item) = self.items.get(&LiveId(index as u64)) else {
+                    continue;
+                };
+                if item.button(ids!(restart_button)).clicked(actions) {
+                    cx.action(McpServerStatusesAction::Restart(row.id.clone()));
+                }
+                if item.button(ids!(enable_button)).clicked(actions) {
+                    cx.action(McpServerStatusesAction::SetEnabled(
+                        row.id.clone(),
+                        !row.enabled,
+                    ));
+                }
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl McpServerStatusesRef {
+    /// Shows the servers of the config, with their latest known status.
+    ///
+    /// Rows are updated in place, as this runs on every stderr line of a server.
+    pub fn refresh(&self, cx: &mut Cx, config: &McpServersConfig) {
+        let Some(mut list) = self.borrow_mut() else {
+            return;
+        };
+
+        let rows = server_rows(config);
+        if rows.len() != list.rows.len() {
+            list.items.clear();
+        }
+
+        for (index, row) in rows.iter().enumerate() {
+            let template = list.template;
+            let item = list
+                .items
+                .get_or_insert(cx, LiveId(index as u64), |cx| {
+                    WidgetRef::new_from_ptr(cx, template)
+                })
+                .clone();
+
+            let (state, state_color) = state_text(row);
+            let status = row.status.as_ref();
+            let error = match status.map(|status| &status.state) {
+                Some(McpServerState::Failed(error)) => error.clone(),
+                _ => String::new(),
+            };
+            let tools = status.map(tools_text).unwrap_or_default();
+            let stderr = status
+                .map(|status| status.stderr.iter().cloned().collect::<Vec<_>>().join("\n"))
+                .unwrap_or_default();
+
+            let name = &row.id;
+            let enable_text = if row.enabled { "Disable" } else { "Enable" };
+            let restartable = row.enabled;
+            let has_error = !error.is_empty();
+            let has_tools = !tools.is_empty();
+            let has_stderr = !stderr.is_empty();
+            item.apply_over(
+                cx,
+                live! {
+                    name = { text: (name) }
+                    state = { text: (state), draw_text: { color: (state_color) } }
+                    restart_button = { visible: (restartable) }
+                    enable_button = { text: (enable_text) }
+                    error = { visible: (has_error), text: (error) }
+                    tools = { visible: (has_tools), text: (tools) }
+                    stderr = { visible: (has_stderr), text: (stderr) }
+                },
+            );
+        }
+
+        list.rows = rows;
+        list.redraw(cx);
+    }
+}
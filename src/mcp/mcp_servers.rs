@@ -5,8 +5,16 @@ use makepad_code_editor::decoration::DecorationSet;
 use makepad_code_editor::{CodeDocument, CodeEditor, CodeSession};
 
 use makepad_widgets::*;
+use moly_kit::widgets::moly_modal::MolyModalWidgetExt;
 
+use crate::data::mcp_import;
 use crate::data::mcp_servers::McpServersConfig;
+use crate::data::mcp_status::McpStatusAction;
+use crate::data::tool_permissions::ToolPermissionsAction;
+use crate::mcp::mcp_builtin_tools::{McpBuiltinToolsAction, McpBuiltinToolsWidgetExt};
+use crate::mcp::mcp_import_modal::{McpImportModalAction, McpImportModalWidgetExt};
+use crate::mcp::mcp_server_statuses::{McpServerStatusesAction, McpServerStatusesWidgetExt};
+use crate::mcp::mcp_tool_policies::{McpToolPoliciesAction, McpToolPoliciesWidgetExt};
 
 live_design! {
     use link::widgets::*;
@@ -16,6 +24,13 @@ live_design! {
     use crate::shared::widgets::*;
     use crate::shared::styles::*;
     use makepad_code_editor::code_editor::*;
+    use moly_kit::widgets::moly_modal::MolyModal;
+
+    use crate::mcp::mcp_builtin_tools::McpBuiltinTools;
+    use crate::mcp::mcp_import_modal::McpImportModal;
+    use crate::mcp::mcp_server_statuses::McpServerStatuses;
+    use crate::mcp::mcp_tool_audit::McpToolAudit;
+    use crate::mcp::mcp_tool_policies::McpToolPolicies;
 
     MolyCodeView = {{MolyCodeView}}{
         editor: <CodeEditor>{
@@ -95,6 +110,14 @@ live_design! {
             align: {x: 1.0, y: 0.5}
             padding: {left: 0, right: 15, top: 8, bottom: 8}
 
+            import_button = <MolyButton> {
+                width: Fit, height: 30
+                padding: {left: 14, right: 14, top: 0, bottom: 0}
+                text: "Import from…"
+                draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+                draw_text: { color: #000 }
+            }
+
             save_button = <RoundedShadowView> {
                 cursor: Hand
                 margin: {left: 10, right: 10, bottom: 0, top: 0}
@@ -136,7 +159,7 @@ live_design! {
         flow: Down, spacing: 10
         instructions = <Label> {
             width: Fill, height: Fit
-            text: "Add new servers by editing the list under 'servers'. You can copy paste you configuration from other applications like Clade Desktop or VSCode.
+            text: "Add new servers by editing the list under 'servers'. You can copy paste you configuration from other applications like Clade Desktop or VSCode, or import it with 'Import from…'.
 You can also add an \"enabled\": false flag to disable a specific server."
             draw_text: {
                 text_style: {font_size: 11},
@@ -211,6 +234,83 @@ Only enable if you trust all configured MCP servers completely."
         }
     }
 
+    SectionHeader = <Label> {
+        draw_text: {
+            text_style: <BOLD_FONT> {font_size: 11}
+            color: #000
+        }
+    }
+
+    BuiltinToolsSection = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 10
+        <SectionHeader> { text: "Built-in tools" }
+        <Label> {
+            width: Fill
+            text: "Tools run by Moly itself, without installing a server. They ask for approval like the tools of other servers."
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10}
+                color: #667085
+            }
+        }
+        builtin_tools = <McpBuiltinTools> {}
+    }
+
+    ToolPoliciesSection = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 10
+        <SectionHeader> { text: "Tool permissions" }
+        <Label> {
+            width: Fill
+            text: "Tools allowed from tool requests are listed here. Add rules to \"tool_policies\" to deny or always allow the tools of a server, using \"*\" as the tool name for all of them."
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10}
+                color: #667085
+            }
+        }
+        tool_policies = <McpToolPolicies> {}
+    }
+
+    ToolAuditSection = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 10
+        <SectionHeader> { text: "Tool call log" }
+        <Label> {
+            width: Fill
+            text: "Every tool run from the chats, with its arguments, result, duration and who approved it."
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10}
+                color: #667085
+            }
+        }
+        <McpToolAudit> {}
+    }
+
+    InputsSection = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 10
+        <SectionHeader> { text: "Inputs" }
+        <Label> {
+            width: Fill
+            text: "Values for the \"${input:...}\" variables of the servers are asked once and stored outside of the config. Clear them to enter them again."
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10}
+                color: #667085
+            }
+        }
+        clear_inputs_button = <MolyButton> {
+            width: Fit, height: 30
+            padding: {left: 14, right: 14, top: 0, bottom: 0}
+            text: "Clear stored inputs"
+            draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+            draw_text: { color: #000 }
+        }
+    }
+
     pub McpServers = {{McpServers}} {
         <AdaptiveView> {
             Desktop = {
@@ -225,6 +325,17 @@ Only enable if you trust all configured MCP servers completely."
                     <Instructions> {}
                     <DangerousModeWrapper> {}
                     <SaveStatus> {}
+                    <ScrollYView> {
+                        width: Fill, height: Fill
+                        flow: Down, spacing: 10
+                        padding: {right: 20, bottom: 20}
+                        <SectionHeader> { text: "Server status" }
+                        server_statuses = <McpServerStatuses> {}
+                        inputs_section = <InputsSection> { margin: {top: 10} }
+                        <BuiltinToolsSection> { margin: {top: 10} }
+                        <ToolPoliciesSection> { margin: {top: 10} }
+                        <ToolAuditSection> { margin: {top: 10} }
+                    }
                 }
             }
             Mobile = {
@@ -247,6 +358,28 @@ Only enable if you trust all configured MCP servers completely."
                     <DangerousModeWrapper> {}
                     <ServersEditor> { width: Fill }
                     <SaveStatus> {}
+                    <View> {
+                        width: Fill, height: Fit
+                        flow: Down, spacing: 10
+                        padding: {right: 10, bottom: 20}
+                        <SectionHeader> { text: "Server status" }
+                        server_statuses = <McpServerStatuses> {}
+                        inputs_section = <InputsSection> { margin: {top: 10} }
+                        <BuiltinToolsSection> { margin: {top: 10} }
+                        <ToolPoliciesSection> { margin: {top: 10} }
+                        <ToolAuditSection> { margin: {top: 10} }
+                    }
+                }
+            }
+        }
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Overlay
+
+            import_modal = <MolyModal> {
+                content: {
+                    import_modal_inner = <McpImportModal> {}
                 }
             }
         }
@@ -291,6 +424,7 @@ impl Widget for McpServers {
                 store.preferences.get_mcp_servers_dangerous_mode_enabled();
 
             self.set_mcp_servers_config(cx, config);
+            self.refresh_server_statuses(cx, scope);
         }
     }
 
@@ -300,6 +434,18 @@ impl Widget for McpServers {
 }
 
 impl McpServers {
+    fn refresh_server_statuses(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let store = scope.data.get::<Store>().unwrap();
+        self.mcp_server_statuses(ids!(server_statuses))
+            .refresh(cx, store.get_mcp_servers_config());
+        self.mcp_builtin_tools(ids!(builtin_tools))
+            .set_config(cx, &store.get_mcp_servers_config().builtin_tools);
+        self.mcp_tool_policies(ids!(tool_policies))
+            .set_policies(cx, store);
+        self.view(ids!(inputs_section))
+            .set_visible(cx, !store.get_mcp_servers_config().inputs.is_empty());
+    }
+
     fn set_mcp_servers_config(&mut self, cx: &mut Cx, config: McpServersConfig) {
         self.mcp_servers_config = config;
         let display_json = self
@@ -394,12 +540,83 @@ impl WidgetMatchEvent for McpServers {
             self.redraw(cx);
         }
 
+        if self.button(ids!(import_button)).clicked(actions) {
+            let store = scope.data.get::<Store>().unwrap();
+            let candidates = mcp_import::find_candidates(store.get_mcp_servers_config());
+            self.mcp_import_modal(ids!(import_modal_inner))
+                .set_candidates(cx, candidates);
+            self.moly_modal(ids!(import_modal)).open_as_dialog(cx);
+        }
+
+        if self.button(ids!(clear_inputs_button)).clicked(actions) {
+            let store = scope.data.get_mut::<Store>().unwrap();
+            store.clear_mcp_inputs();
+        }
+
         for action in actions {
             if let SyncModalAction::McpServersUpdated = action.cast() {
                 let store = scope.data.get_mut::<Store>().unwrap();
                 self.set_mcp_servers_config(cx, store.get_mcp_servers_config().clone());
                 self.redraw(cx);
             }
+
+            match action.cast() {
+                McpImportModalAction::Import(candidates) => {
+                    let store = scope.data.get_mut::<Store>().unwrap();
+                    store.import_mcp_servers(candidates);
+                    self.set_mcp_servers_config(cx, store.get_mcp_servers_config().clone());
+                    self.refresh_server_statuses(cx, scope);
+                }
+                McpImportModalAction::ModalDismissed => {
+                    self.moly_modal(ids!(import_modal)).close(cx);
+                }
+                McpImportModalAction::None => {}
+            }
+
+            if let McpStatusAction::Changed = action.cast() {
+                self.refresh_server_statuses(cx, scope);
+            }
+
+            match action.cast() {
+                McpServerStatusesAction::Restart(server_id) => {
+                    let store = scope.data.get::<Store>().unwrap();
+                    store.restart_mcp_server(&server_id);
+                }
+                McpServerStatusesAction::SetEnabled(server_id, enabled) => {
+                    let store = scope.data.get_mut::<Store>().unwrap();
+                    store.set_mcp_server_enabled(&server_id, enabled);
+                    self.set_mcp_servers_config(cx, store.get_mcp_servers_config().clone());
+                    self.refresh_server_statuses(cx, scope);
+                }
+                McpServerStatusesAction::None => {}
+            }
+
+            if let McpBuiltinToolsAction::Changed(builtin_tools) = action.cast() {
+                let store = scope.data.get_mut::<Store>().unwrap();
+                store.set_builtin_tools(builtin_tools);
+                self.set_mcp_servers_config(cx, store.get_mcp_servers_config().clone());
+                self.refresh_server_statuses(cx, scope);
+            }
+
+            // Keep the editor in sync, so saving it doesn't drop the changes.
+            let policies_changed = match action.cast() {
+                McpToolPoliciesAction::Revoke {
+                    server,
+                    tool,
+                    chat_id,
+                } => {
+                    let store = scope.data.get_mut::<Store>().unwrap();
+                    store.remove_mcp_tool_policy(&server, &tool, chat_id);
+                    true
+                }
+                McpToolPoliciesAction::None => false,
+            } || matches!(action.cast(), ToolPermissionsAction::Saved);
+
+            if policies_changed {
+                let store = scope.data.get::<Store>().unwrap();
+                self.set_mcp_servers_config(cx, store.get_mcp_servers_config().clone());
+                self.refresh_server_statuses(cx, scope);
+            }
         }
     }
 }
@@ -0,0 +1,352 @@
+use makepad_widgets::*;
+
+use crate::data::store::Store;
+use crate::data::tool_audit::{self, ToolAuditAction, ToolAuditEntry};
+
+/// Entries shown at once, the most recent ones matching the filter.
+const MAX_SHOWN_ENTRIES: usize = 100;
+
+/// Characters of the arguments and results shown for each entry.
+const MAX_PREVIEW_CHARS: usize = 300;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    ToolAuditRow = <RoundedView> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 4
+        padding: 10
+        draw_bg: {
+            color: #F9FAFB
+            border_radius: 4.0
+        }
+
+        <View> {
+            width: Fill, height: Fit
+            spacing: 10
+
+            tool = <Label> {
+                width: Fill
+                draw_text: {
+                    text_style: <BOLD_FONT>{font_size: 10},
+                    color: #000
+                }
+            }
+            duration = <Label> {
+                draw_text: {
+                    text_style: <REGULAR_FONT>{font_size: 9},
+                    color: #667085
+                }
+            }
+        }
+
+        details = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #667085
+            }
+        }
+
+        arguments = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #344054
+            }
+        }
+
+        result = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #344054
+            }
+        }
+
+        error = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 9},
+                color: #B42318
+            }
+        }
+    }
+
+    ToolAuditEntries = {{ToolAuditEntries}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 6
+
+        template: <ToolAuditRow> {}
+    }
+
+    // Log of the tools run from the chats, newest first.
+    pub McpToolAudit = {{McpToolAudit}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 10
+
+        <View> {
+            width: Fill, height: Fit
+            spacing: 10
+            align: {y: 0.5}
+
+            filter = <MolyTextInput> {
+                width: Fill, height: Fit
+                padding: {top: 6, bottom: 6, left: 10, right: 10}
+                empty_text: "Filter by server, tool, model, arguments or result"
+                draw_bg: {
+                    color: #fff
+                    border_size: 1.0
+                    border_color_1: #D0D5DD
+                    border_radius: 2.0
+                }
+                draw_text: {
+                    text_style: <REGULAR_FONT>{font_size: 10},
+                    color: #000
+                }
+            }
+
+            errors_only = <CheckBox> {
+                text: "Errors only"
+                draw_text: {
+                    text_style: <REGULAR_FONT>{font_size: 10},
+                    fn get_color(self) -> vec4 {
+                        return #000;
+                    }
+                }
+            }
+
+            export_button = <MolyButton> {
+                width: Fit, height: 30
+                padding: {left: 14, right: 14, top: 0, bottom: 0}
+                text: "Export JSONL"
+                draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+                draw_text: { color: #000 }
+            }
+        }
+
+        status = <Label> {
+            width: Fill
+            draw_text: {
+                wrap: Word
+                text_style: <REGULAR_FONT>{font_size: 10},
+                color: #667085
+            }
+        }
+
+        entries = <ToolAuditEntries> {}
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct ToolAuditEntries {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for ToolAuditEntries {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct McpToolAudit {
+    #[deref]
+    view: View,
+
+    #[rust]
+    initialized: bool,
+}
+
+impl Widget for McpToolAudit {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+
+        if !self.initialized {
+            self.initialized = true;
+            self.refresh(cx, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for McpToolAudit {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        let logged = actions
+            .iter()
+            .any(|action| matches!(action.cast(), ToolAuditAction::Logged));
+
+        if logged
+            || self.text_input(ids!(filter)).changed(actions).is_some()
+            || self.check_box(ids!(errors_only)).changed(actions).is_some()
+        {
+            self.refresh(cx, scope);
+        }
+
+        if self.button(ids!(export_button)).clicked(actions) {
+            let lines = tool_audit::to_json_lines(&self.filtered(cx));
+            let status = match save_log(&lines) {
+                Some(path) => format!("Exported to {}", path.display()),
+                None => {
+                    cx.copy_to_clipboard(&lines);
+                    "Copied to the clipboard".to_string()
+                }
+            };
+            self.label(ids!(status)).set_text(cx, &status);
+        }
+    }
+}
+
+impl McpToolAudit {
+    /// Entries matching the filters, oldest first.
+    fn filtered(&self, cx: &Cx) -> Vec<ToolAuditEntry> {
+        let query = self.text_input(ids!(filter)).text();
+        let errors_only = self.check_box(ids!(errors_only)).active(cx);
+
+        tool_audit::entries()
+            .into_iter()
+            .filter(|entry| !errors_only || entry.is_error)
+            .filter(|entry| entry.matches(&query))
+            .collect()
+    }
+
+    fn refresh(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let store = scope.data.get::<Store>().unwrap();
+        let entries = self.filtered(cx);
+
+        let status = match entries.len() {
+            0 => "No tool calls logged.".to_string(),
+            n if n > MAX_SHOWN_ENTRIES => format!(
+                "Showing the last {} of {} calls, export to see all of them.",
+                MAX_SHOWN_ENTRIES, n
+            ),
+            1 => "1 call".to_string(),
+            n => format!("{} calls", n),
+        };
+        self.label(ids!(status)).set_text(cx, &status);
+
+        let Some(mut list) = self.widget(ids!(entries)).borrow_mut::<ToolAuditEntries>() else {
+            return;
+        };
+
+        list.items.clear();
+        for (index, entry) in entries.iter().rev().take(MAX_SHOWN_ENTRIES).enumerate() {
+            let item = WidgetRef::new_from_ptr(cx, list.template);
+
+            let tool = format!("{} · {}", entry.server, entry.tool);
+            let duration = format!("{} ms", entry.duration_ms);
+            let chat = match store.chats.get_chat_by_id(entry.chat_id) {
+                Some(chat) => format!("\"{}\"", chat.borrow().get_title()),
+                None => "a deleted chat".to_string(),
+            };
+            let details = format!(
+                "{} · in {} · {} · {}",
+                entry
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S"),
+                chat,
+                entry.bot.as_deref().unwrap_or("Unknown model"),
+                entry.approval.description()
+            );
+            let arguments = format!("Arguments: {}", preview(&entry.arguments.to_string()));
+            let result = preview(&entry.result);
+            let (result, error) = if entry.is_error {
+                (String::new(), format!("Error: {}", result))
+            } else {
+                (format!("Result: {}", result), String::new())
+            };
+            let is_error = entry.is_error;
+            let is_ok = !entry.is_error;
+
+            item.apply_over(
+                cx,
+                live! {
+                    tool = { text: (tool) }
+                    duration = { text: (duration) }
+                    details = { text: (details) }
+                    arguments = { text: (arguments) }
+                    result = { text: (result), visible: (is_ok) }
+                    error = { text: (error), visible: (is_error) }
+                },
+            );
+            list.items.insert(LiveId(index as u64), item);
+        }
+        list.redraw(cx);
+    }
+}
+
+fn preview(text: &str) -> String {
+    if text.chars().count() <= MAX_PREVIEW_CHARS {
+        return text.to_string();
+    }
+    let preview = text.chars().take(MAX_PREVIEW_CHARS).collect::<String>();
+    format!("{}…", preview)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_log(lines: &str) -> Option<std::path::PathBuf> {
+    let dirs = directories::UserDirs::new()?;
+    let dir = dirs.download_dir().unwrap_or(dirs.home_dir());
+
+    let file_name = format!(
+        "tool-audit-{}.jsonl",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = dir.join(file_name);
+
+    match std::fs::write(&path, lines) {
+        Ok(()) => Some(path),
+        Err(e) => {
+            ::log::error!(
+                "Failed to export the tool audit log to {}: {}",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_log(_lines: &str) -> Option<std::path::PathBuf> {
+    None
+}
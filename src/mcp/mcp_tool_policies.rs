@@ -0,0 +1,170 @@
+use makepad_widgets::*;
+
+use crate::data::chats::chat::ChatId;
+use crate::data::mcp_servers::{ToolPolicy, ToolPolicyRule};
+use crate::data::store::Store;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    ToolPolicyRow = <View> {
+        width: Fill, height: Fit
+        spacing: 10
+        align: {y: 0.5}
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Down, spacing: 3
+
+            tool = <Label> {
+                draw_text: {
+                    text_style: <BOLD_FONT>{font_size: 10},
+                    color: #000
+                }
+            }
+            scope = <Label> {
+                width: Fill
+                draw_text: {
+                    wrap: Ellipsis
+                    text_style: <REGULAR_FONT>{font_size: 10},
+                    color: #667085
+                }
+            }
+        }
+
+        revoke_button = <MolyButton> {
+            width: Fit, height: 30
+            padding: {left: 14, right: 14, top: 0, bottom: 0}
+            text: "Revoke"
+            draw_bg: { color: #fff, border_size: 1.0, border_color_1: #D0D5DD }
+            draw_text: { color: #B42318 }
+        }
+    }
+
+    // Tool permission rules, including the ones granted from tool requests.
+    pub McpToolPolicies = {{McpToolPolicies}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 8
+
+        template: <ToolPolicyRow> {}
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum McpToolPoliciesAction {
+    None,
+    /// The rule for the tool of the server in the given chat should be removed.
+    Revoke {
+        server: String,
+        tool: String,
+        chat_id: Option<ChatId>,
+    },
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct McpToolPolicies {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+
+    /// The rules shown, by the id of their item.
+    #[rust]
+    rules: Vec<ToolPolicyRule>,
+}
+
+impl Widget for McpToolPolicies {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        if let Event::Actions(actions) = event {
+            for (id, item) in self.items.iter() {
+                let Some(rule) = self.rules.get(id.0 as usize) else {
+                    continue;
+                };
+                if item.button(ids!(revoke_button)).clicked(actions) {
+                    cx.action(McpToolPoliciesAction::Revoke {
+                        server: rule.server.clone(),
+                        tool: rule.tool.clone(),
+                        chat_id: rule.chat_id,
+                    });
+                }
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+fn scope_text(rule: &ToolPolicyRule, store: &Store) -> String {
+    let policy = match rule.policy {
+        ToolPolicy::Allow => "Allowed",
+        ToolPolicy::Ask => "Asks",
+        ToolPolicy::Deny => "Denied",
+    };
+
+    match rule.chat_id {
+        None => format!("{} in all chats", policy),
+        Some(chat_id) => match store.chats.get_chat_by_id(chat_id) {
+            Some(chat) => format!("{} in \"{}\"", policy, chat.borrow().get_title()),
+            None => format!("{} in a deleted chat", policy),
+        },
+    }
+}
+
+impl McpToolPoliciesRef {
+    pub fn set_policies(&self, cx: &mut Cx, store: &Store) {
+        let Some(mut list) = self.borrow_mut() else {
+            return;
+        };
+
+        list.items.clear();
+        let policies = &store.get_mcp_servers_config().tool_policies;
+        for (index, rule) in policies.iter().enumerate() {
+            let item = WidgetRef::new_from_ptr(cx, list.template);
+
+            let tool = if rule.tool == "*" {
+                format!("{} · all tools", rule.server)
+            } else {
+                format!("{} · {}", rule.server, rule.tool)
+            };
+            let scope = scope_text(rule, store);
+            item.apply_over(
+                cx,
+                live! {
+                    tool = { text: (tool) }
+                    scope = { text: (scope) }
+                },
+            );
+
+            list.items.insert(LiveId(index as u64), item);
+        }
+        list.rules = policies.clone();
+        list.redraw(cx);
+    }
+}
@@ -1,9 +1,21 @@
+pub mod mcp_builtin_tools;
+pub mod mcp_import_modal;
+pub mod mcp_inputs_modal;
 pub mod mcp_screen;
+pub mod mcp_server_statuses;
 pub mod mcp_servers;
+pub mod mcp_tool_audit;
+pub mod mcp_tool_policies;
 
 use makepad_widgets::Cx;
 
 pub fn live_design(cx: &mut Cx) {
+    mcp_builtin_tools::live_design(cx);
+    mcp_import_modal::live_design(cx);
+    mcp_inputs_modal::live_design(cx);
     mcp_screen::live_design(cx);
+    mcp_server_statuses::live_design(cx);
+    mcp_tool_audit::live_design(cx);
+    mcp_tool_policies::live_design(cx);
     mcp_servers::live_design(cx);
 }
@@ -1,9 +1,13 @@
 use makepad_widgets::*;
 
 use crate::data::{
-    providers::{Provider, ProviderConnectionStatus, ProviderType},
+    api_keys::ApiKeys,
+    local_servers::LocalServersScan,
+    providers::{HostedTools, Provider, ProviderConnectionStatus, ProviderType},
     store::Store,
 };
+use crate::settings::local_servers_list::{LocalServersListAction, LocalServersListWidgetExt};
+use crate::shared::utils::version::{Pull, Version};
 
 live_design! {
     use link::theme::*;
@@ -14,6 +18,7 @@ live_design! {
     use crate::shared::widgets::*;
     use crate::shared::widgets::MolyButton;
     use crate::shared::resource_imports::*;
+    use crate::settings::local_servers_list::LocalServersList;
 
     FormGroup = <View> {
         flow: Down
@@ -192,6 +197,36 @@ live_design! {
                 spacing: 20
                 align: {x: 0.0, y: 0.5}
 
+                local_servers_group = <FormGroup> {
+                    <View> {
+                        width: Fill, height: Fit
+                        align: {x: 0.0, y: 0.5}
+                        <ModalLabel> {
+                            text: "Local servers"
+                        }
+                        <View> { width: Fill, height: 1 }
+                        scan_local_servers = <MolyButton> {
+                            width: Fit
+                            height: 30
+                            padding: {left: 14, right: 14, top: 0, bottom: 0}
+                            text: "Scan for local servers"
+                            draw_bg: { color: (CTA_BUTTON_COLOR), border_color: (CTA_BUTTON_COLOR) }
+                        }
+                    }
+
+                    local_servers_status = <Label> {
+                        width: Fill
+                        text: "Find Ollama, LM Studio, llama.cpp, vLLM or MolyServer running on this machine."
+                        draw_text: {
+                            wrap: Word
+                            text_style: <REGULAR_FONT>{font_size: 10},
+                            color: #667085
+                        }
+                    }
+
+                    local_servers = <LocalServersList> {}
+                }
+
                 <FormGroup> {
                     <ModalLabel> {
                         text: "Name"
@@ -247,6 +282,7 @@ live_design! {
                         radio_deepinquire = <CustomProviderRadio> { text: "DeepInquire" }
                         radio_moly_server = <CustomProviderRadio> { text: "MolyServer" }
                         radio_openai_realtime = <CustomProviderRadio> { text: "OpenAI Realtime" }
+                        radio_openai_responses = <CustomProviderRadio> { text: "OpenAI Responses" }
                     }
                 }
 
@@ -290,12 +326,21 @@ pub struct AddProviderModal {
 
     #[rust]
     selected_provider: Option<ProviderType>,
+
+    #[rust]
+    local_servers_version: Option<Version>,
 }
 
 impl Widget for AddProviderModal {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         self.view.handle_event(cx, event, scope);
         self.widget_match_event(cx, event, scope);
+
+        let store = scope.data.get::<Store>().unwrap();
+        if let Some(scan) = self.local_servers_version.pull(&store.local_servers) {
+            let scan = scan.clone();
+            self.update_local_servers(cx, &scan, store);
+        }
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
@@ -312,6 +357,19 @@ impl WidgetMatchEvent for AddProviderModal {
             cx.action(AddProviderModalAction::ModalDismissed);
         }
 
+        if self.button(ids!(scan_local_servers)).clicked(actions) {
+            store.scan_local_servers();
+        }
+
+        for action in actions {
+            if let LocalServersListAction::AddServer(server) = action.cast() {
+                store.add_local_server(&server);
+                // Refresh the list to show the server as added
+                let scan = store.local_servers.data().clone();
+                self.update_local_servers(cx, &scan, store);
+            }
+        }
+
         if self.button(ids!(add_server_button)).clicked(actions) {
             self.clear_error_message(cx);
             let api_host = self.text_input(ids!(api_host)).text().trim().to_string();
@@ -331,28 +389,7 @@ impl WidgetMatchEvent for AddProviderModal {
 
             let api_key = self.text_input(ids!(api_key)).text().trim().to_string();
 
-            // Generate a unique ID for the custom provider
-            let provider_id = {
-                // Create a base ID from the provider name
-                let base = name
-                    .to_lowercase()
-                    .replace(" ", "_")
-                    .replace(|c: char| !c.is_alphanumeric() && c != '_', "");
-                let base = if base.is_empty() {
-                    "custom_provider".to_string()
-                } else {
-                    base
-                };
-
-                // Check if this ID already exists and append a number if needed
-                let mut id = base.clone();
-                let mut counter = 1;
-                while store.chats.providers.contains_key(&id) {
-                    id = format!("{}_{}", base, counter);
-                    counter += 1;
-                }
-                id
-            };
+            let provider_id = store.generate_provider_id(&name);
 
             let provider = match self.selected_provider.as_ref().unwrap() {
                 ProviderType::OpenAi => Provider {
@@ -371,6 +408,10 @@ impl WidgetMatchEvent for AddProviderModal {
                     was_customly_added: true,
                     system_prompt: None,
                     tools_enabled: true,
+                    model_rules: None,
+                    api_keys: ApiKeys::default(),
+                    think_tags: None,
+                    hosted_tools: HostedTools::default(),
                 },
                 ProviderType::OpenAiImage => Provider {
                     id: provider_id,
@@ -388,6 +429,10 @@ impl WidgetMatchEvent for AddProviderModal {
                     was_customly_added: true,
                     system_prompt: None,
                     tools_enabled: true,
+                    model_rules: None,
+                    api_keys: ApiKeys::default(),
+                    think_tags: None,
+                    hosted_tools: HostedTools::default(),
                 },
                 ProviderType::MolyServer => Provider {
                     id: provider_id,
@@ -405,6 +450,10 @@ impl WidgetMatchEvent for AddProviderModal {
                     was_customly_added: true,
                     system_prompt: None,
                     tools_enabled: true,
+                    model_rules: None,
+                    api_keys: ApiKeys::default(),
+                    think_tags: None,
+                    hosted_tools: HostedTools::default(),
                 },
                 ProviderType::MoFa => Provider {
                     id: provider_id,
@@ -422,6 +471,10 @@ impl WidgetMatchEvent for AddProviderModal {
                     was_customly_added: true,
                     system_prompt: None,
                     tools_enabled: true,
+                    model_rules: None,
+                    api_keys: ApiKeys::default(),
+                    think_tags: None,
+                    hosted_tools: HostedTools::default(),
                 },
                 ProviderType::DeepInquire => Provider {
                     id: provider_id,
@@ -439,6 +492,10 @@ impl WidgetMatchEvent for AddProviderModal {
                     was_customly_added: true,
                     system_prompt: None,
                     tools_enabled: true,
+                    model_rules: None,
+                    api_keys: ApiKeys::default(),
+                    think_tags: None,
+                    hosted_tools: HostedTools::default(),
                 },
                 ProviderType::OpenAiRealtime => Provider {
                     id: provider_id,
@@ -456,6 +513,31 @@ impl WidgetMatchEvent for AddProviderModal {
                     was_customly_added: true,
                     system_prompt: None,
                     tools_enabled: true,
+                    model_rules: None,
+                    api_keys: ApiKeys::default(),
+                    think_tags: None,
+                    hosted_tools: HostedTools::default(),
+                },
+                ProviderType::OpenAiResponses => Provider {
+                    id: provider_id,
+                    name: name.clone(),
+                    url: api_host.clone(),
+                    api_key: if api_key.is_empty() {
+                        None
+                    } else {
+                        Some(api_key.clone())
+                    },
+                    provider_type: ProviderType::OpenAiResponses,
+                    connection_status: ProviderConnectionStatus::Disconnected,
+                    enabled: true,
+                    models: vec![],
+                    was_customly_added: true,
+                    system_prompt: None,
+                    tools_enabled: true,
+                    model_rules: None,
+                    api_keys: ApiKeys::default(),
+                    think_tags: None,
+                    hosted_tools: HostedTools::default(),
                 },
                 ProviderType::OpenClaw => Provider {
                     id: provider_id,
@@ -473,6 +555,10 @@ impl WidgetMatchEvent for AddProviderModal {
                     was_customly_added: true,
                     system_prompt: None,
                     tools_enabled: true,
+                    model_rules: None,
+                    api_keys: ApiKeys::default(),
+                    think_tags: None,
+                    hosted_tools: HostedTools::default(),
                 },
             };
 
@@ -488,7 +574,8 @@ impl WidgetMatchEvent for AddProviderModal {
                 radios.radio_mofa,
                 radios.radio_deepinquire,
                 radios.radio_moly_server,
-                radios.radio_openai_realtime
+                radios.radio_openai_realtime,
+                radios.radio_openai_responses
             ))
             .selected(cx, actions);
         if let Some(selected) = selected {
@@ -498,6 +585,7 @@ impl WidgetMatchEvent for AddProviderModal {
                 2 => Some(ProviderType::DeepInquire),
                 3 => Some(ProviderType::MolyServer),
                 4 => Some(ProviderType::OpenAiRealtime),
+                5 => Some(ProviderType::OpenAiResponses),
                 _ => Some(ProviderType::OpenAi),
             };
         }
@@ -505,6 +593,33 @@ impl WidgetMatchEvent for AddProviderModal {
 }
 
 impl AddProviderModal {
+    fn update_local_servers(&mut self, cx: &mut Cx, scan: &LocalServersScan, store: &Store) {
+        let status = if scan.scanning {
+            "Scanning for local servers...".to_string()
+        } else {
+            match scan.servers.len() {
+                0 => "No local servers found.".to_string(),
+                1 => "Found 1 local server.".to_string(),
+                count => format!("Found {} local servers.", count),
+            }
+        };
+        self.label(ids!(local_servers_status)).set_text(cx, &status);
+
+        let servers = scan
+            .servers
+            .iter()
+            .map(|server| {
+                let added = store
+                    .provider_for_local_server(server)
+                    .is_some_and(|provider| provider.enabled);
+                (server.clone(), added)
+            })
+            .collect();
+        self.local_servers_list(ids!(local_servers))
+            .set_servers(cx, servers);
+        self.redraw(cx);
+    }
+
     fn set_error_message(&mut self, cx: &mut Cx, message: &str) {
         self.view(ids!(error_view)).set_visible(cx, true);
         self.label(ids!(error_message)).set_text(cx, message);
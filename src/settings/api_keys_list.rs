@@ -0,0 +1,232 @@
+use makepad_widgets::*;
+
+use crate::data::api_keys::{ApiKeys, KeyRotation, KeyUsage};
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    ApiKeyRow = <View> {
+        width: Fill, height: Fit
+        flow: Right
+        spacing: 10
+        align: {x: 0.0, y: 0.5}
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Down
+            spacing: 3
+
+            name = <Label> {
+                draw_text: {
+                    text_style: <BOLD_FONT>{font_size: 11},
+                    color: #000
+                }
+            }
+            details = <Label> {
+                width: Fill
+                draw_text: {
+                    wrap: Ellipsis
+                    text_style: <REGULAR_FONT>{font_size: 10},
+                    color: #667085
+                }
+            }
+        }
+
+        in_use_label = <Label> {
+            visible: false
+            text: "In use"
+            draw_text: {
+                text_style: <REGULAR_FONT>{font_size: 11},
+                color: #667085
+            }
+        }
+
+        use_button = <MolyButton> {
+            width: Fit
+            height: 30
+            padding: {left: 14, right: 14, top: 0, bottom: 0}
+            text: "Use"
+            draw_bg: { color: (CTA_BUTTON_COLOR), border_size: 0 }
+        }
+
+        remove_button = <MolyButton> {
+            width: Fit
+            height: 30
+            padding: {left: 14, right: 14, top: 0, bottom: 0}
+            text: "Remove"
+            draw_bg: { color: #B4605A, border_size: 0 }
+        }
+    }
+
+    pub ApiKeysList = {{ApiKeysList}} {
+        width: Fill,
+        height: Fit,
+        flow: Down,
+        spacing: 10,
+
+        template: <ApiKeyRow> {}
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ApiKeysListAction {
+    None,
+    /// The user picked the key at this index as the selected one.
+    Select(usize),
+    /// The user asked to remove the key at this index.
+    Remove(usize),
+}
+
+/// Lists the API keys of a provider along with their usage.
+#[derive(Live, LiveHook, Widget)]
+pub struct ApiKeysList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    #[rust]
+    api_keys: ApiKeys,
+
+    #[rust]
+    rotation: Option<KeyRotation>,
+
+    /// Usage shown in the rows, to only update them when it changes.
+    #[rust]
+    usage: Option<Vec<KeyUsage>>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for ApiKeysList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        if let Event::Actions(actions) = event {
+            for index in 0..self.api_keys.keys.len() {
+                let Some(item) = self.items.get(&LiveId(index as u64)) else {
+                    continue;
+                };
+                if item.button(ids!(use_button)).clicked(actions) {
+                    cx.action(ApiKeysListAction::Select(index));
+                }
+                if item.button(ids!(remove_button)).clicked(actions) {
+                    cx.action(ApiKeysListAction::Remove(index));
+                }
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.update_usage(cx);
+
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl ApiKeysList {
+    fn update_usage(&mut self, cx: &mut Cx) {
+        let Some(rotation) = &self.rotation else {
+            return;
+        };
+
+        let usage: Vec<KeyUsage> = self
+            .api_keys
+            .keys
+            .iter()
+            .map(|k| rotation.usage(&k.name))
+            .collect();
+        if self.usage.as_ref() == Some(&usage) {
+            return;
+        }
+
+        for (index, (key, usage)) in self.api_keys.keys.iter().zip(&usage).enumerate() {
+            if let Some(item) = self.items.get(&LiveId(index as u64)) {
+                item.label(ids!(details))
+                    .set_text(cx, &key_details(&key.key, usage));
+            }
+        }
+        self.usage = Some(usage);
+    }
+}
+
+impl ApiKeysListRef {
+    pub fn set_api_keys(&self, cx: &mut Cx, api_keys: &ApiKeys, rotation: KeyRotation) {
+        let Some(mut list) = self.borrow_mut() else {
+            return;
+        };
+
+        list.items.clear();
+        for (index, key) in api_keys.keys.iter().enumerate() {
+            let item = WidgetRef::new_from_ptr(cx, list.template);
+
+            let name = &key.name;
+            let selected = index == api_keys.selected;
+            let selectable = !selected;
+            item.apply_over(
+                cx,
+                live! {
+                    name = { text: (name) }
+                    in_use_label = { visible: (selected) }
+                    use_button = { visible: (selectable) }
+                },
+            );
+
+            list.items.insert(LiveId(index as u64), item);
+        }
+
+        list.api_keys = api_keys.clone();
+        list.rotation = Some(rotation);
+        // Details are filled on the next draw.
+        list.usage = None;
+        list.redraw(cx);
+    }
+}
+
+/// A masked version of the key followed by its usage, like `sk-…f3a9 · 12 requests`.
+fn key_details(key: &str, usage: &KeyUsage) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    let masked = if chars.len() > 10 {
+        let start: String = chars[..3].iter().collect();
+        let end: String = chars[chars.len() - 4..].iter().collect();
+        format!("{}…{}", start, end)
+    } else {
+        "•".repeat(chars.len())
+    };
+
+    let mut details = match usage.requests {
+        1 => format!("{} · 1 request", masked),
+        count => format!("{} · {} requests", masked, count),
+    };
+    if let Some(last_used) = usage.last_used {
+        details.push_str(&format!(
+            " · last used {}",
+            last_used.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    if let Some(error) = &usage.last_error {
+        details.push_str(&format!(" · last error: {}", error));
+    }
+    details
+}
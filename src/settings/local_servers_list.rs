@@ -0,0 +1,160 @@
+use makepad_widgets::*;
+
+use crate::data::local_servers::DiscoveredServer;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::widgets::*;
+
+    LocalServerRow = <View> {
+        width: Fill, height: Fit
+        flow: Right
+        spacing: 10
+        align: {x: 0.0, y: 0.5}
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Down
+            spacing: 3
+
+            name = <Label> {
+                draw_text: {
+                    text_style: <BOLD_FONT>{font_size: 11},
+                    color: #000
+                }
+            }
+            details = <Label> {
+                width: Fill
+                draw_text: {
+                    wrap: Ellipsis
+                    text_style: <REGULAR_FONT>{font_size: 10},
+                    color: #667085
+                }
+            }
+        }
+
+        add_button = <MolyButton> {
+            width: Fit
+            height: 30
+            padding: {left: 14, right: 14, top: 0, bottom: 0}
+            text: "Add"
+            draw_bg: { color: (CTA_BUTTON_COLOR), border_size: 0 }
+        }
+
+        added_label = <Label> {
+            visible: false
+            text: "Added"
+            draw_text: {
+                text_style: <REGULAR_FONT>{font_size: 11},
+                color: #667085
+            }
+        }
+    }
+
+    pub LocalServersList = {{LocalServersList}} {
+        width: Fill,
+        height: Fit,
+        flow: Down,
+        spacing: 10,
+
+        template: <LocalServerRow> {}
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum LocalServersListAction {
+    None,
+    /// The user asked to create (or enable) a provider for this server.
+    AddServer(DiscoveredServer),
+}
+
+/// Lists the local inference servers found by a scan, with a button to add each one.
+#[derive(Live, LiveHook, Widget)]
+pub struct LocalServersList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    template: Option<LivePtr>,
+
+    #[rust]
+    servers: Vec<DiscoveredServer>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+}
+
+impl Widget for LocalServersList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_id, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        if let Event::Actions(actions) = event {
+            for (index, server) in self.servers.iter().enumerate() {
+                let Some(item) = self.items.get(&LiveId(index as u64)) else {
+                    continue;
+                };
+                if item.button(ids!(add_button)).clicked(actions) {
+                    cx.action(LocalServersListAction::AddServer(server.clone()));
+                }
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (_id, item) in self.items.iter_mut() {
+            let _ = item.draw_all(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl LocalServersListRef {
+    /// Sets the servers to list, along with whether each one is already added as a provider.
+    pub fn set_servers(&self, cx: &mut Cx, servers: Vec<(DiscoveredServer, bool)>) {
+        let Some(mut list) = self.borrow_mut() else {
+            return;
+        };
+
+        list.items.clear();
+        list.servers.clear();
+        for (index, (server, added)) in servers.into_iter().enumerate() {
+            let item = WidgetRef::new_from_ptr(cx, list.template);
+
+            let models = match server.models.len() {
+                1 => "1 model".to_string(),
+                count => format!("{} models", count),
+            };
+            let name = &server.name;
+            let details = &format!("{} · {}", server.url, models);
+            let addable = !added;
+            item.apply_over(
+                cx,
+                live! {
+                    name = { text: (name) }
+                    details = { text: (details) }
+                    add_button = { visible: (addable) }
+                    added_label = { visible: (added) }
+                },
+            );
+
+            list.items.insert(LiveId(index as u64), item);
+            list.servers.push(server);
+        }
+        list.redraw(cx);
+    }
+}
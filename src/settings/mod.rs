@@ -1,4 +1,6 @@
 pub mod add_provider_modal;
+pub mod api_keys_list;
+pub mod local_servers_list;
 pub mod moly_server_screen;
 pub mod provider_view;
 pub mod providers;
@@ -10,8 +12,10 @@ use makepad_widgets::Cx;
 pub fn live_design(cx: &mut Cx) {
     providers_screen::live_design(cx);
     moly_server_screen::live_design(cx);
+    api_keys_list::live_design(cx);
     provider_view::live_design(cx);
     providers::live_design(cx);
+    local_servers_list::live_design(cx);
     add_provider_modal::live_design(cx);
     sync_modal::live_design(cx);
     utilities_modal::live_design(cx);
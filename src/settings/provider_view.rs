@@ -1,10 +1,19 @@
 use makepad_widgets::*;
+#[cfg(not(target_arch = "wasm32"))]
+use moly_kit::aitk::utils::asynchronous::spawn;
 use moly_kit::prelude::*;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::data::provider_diagnostics::diagnose_provider;
 use crate::data::{
+    api_keys::{KeyRotation, KeyStrategy, NamedApiKey},
+    failover_client::parse_fallback_models,
+    model_rules::{ModelRules, parse_patterns},
     providers::{Provider, ProviderBot, ProviderConnectionStatus, ProviderType},
     store::Store,
 };
+use crate::settings::api_keys_list::{ApiKeysListAction, ApiKeysListWidgetExt};
+use crate::shared::actions::ChatAction;
 
 live_design! {
     use link::theme::*;
@@ -13,6 +22,7 @@ live_design! {
 
     use crate::shared::widgets::*;
     use crate::shared::styles::*;
+    use crate::settings::api_keys_list::ApiKeysList;
 
     REFRESH_ICON = dep("crate://self/resources/images/refresh_icon.png")
     // Tiny space to separate tabular text.
@@ -45,6 +55,44 @@ live_design! {
         height: Fit
     }
 
+    StrategyDropDown = <DropDownFlat> {
+        width: Fit
+        draw_text: {
+            text_style: <REGULAR_FONT>{font_size: 11}
+            fn get_color(self) -> vec4 {
+                return #000
+            }
+        }
+
+        popup_menu: {
+            width: 220, height: Fit,
+            flow: Down
+            padding: <THEME_MSPACE_1> {}
+
+            menu_item: <PopupMenuItem> {
+                width: Fill, height: Fit,
+                align: { y: 0.5 }
+                padding: {left: 15, right: 15, top: 10, bottom: 10}
+
+                draw_text: {
+                    fn get_color(self) -> vec4 {
+                        return mix(#3, #x0, self.hover)
+                    }
+                }
+
+                draw_bg: {
+                    instance color: #f
+                    instance color_active: #f2
+                }
+            }
+
+            draw_bg: {
+                instance color: #f9
+                border_size: 1.0
+            }
+        }
+    }
+
     ModelEntry = {{ModelEntry}} {
         align: {x: 0.5, y: 0.5}
         width: Fill, height: 50
@@ -78,6 +126,14 @@ live_design! {
                 height: Fit, width: Fill
                 align: {x: 1.0, y: 0.5}
                 spacing: 20
+                fallback_models = <MolyTextInput> {
+                    width: 220, height: 30
+                    empty_text: "Fallback models"
+                    draw_text: {
+                        text_style: <REGULAR_FONT>{font_size: 10}
+                        color: #000
+                    }
+                }
                 enabled_switch = <MolySwitch> {
                     // Match the default value to avoid the animation on start.
                     animator: {
@@ -242,7 +298,7 @@ live_design! {
             // API KEY
             <FormGroup> {
                 margin: {top: (MD_GAP)}
-                <Label> {
+                api_key_label = <Label> {
                     text: "API Key"
                     draw_text: {
                         text_style: <BOLD_FONT>{font_size: 12}
@@ -250,7 +306,7 @@ live_design! {
                     }
                 }
 
-                <View> {
+                api_key_row = <View> {
                     align: {x: 0.0, y: 0.5}
                     width: Fill, height: 35
                     api_key = <MolyTextInput> {
@@ -283,6 +339,76 @@ live_design! {
                 }
             }
 
+            // API KEYS
+            <FormGroup> {
+                margin: {top: (MD_GAP)}
+                spacing: 5
+                <View> {
+                    width: Fill, height: Fit
+                    align: {y: 0.5}
+                    spacing: (MD_GAP)
+                    <Label> {
+                        text: "API Keys"
+                        draw_text: {
+                            text_style: <BOLD_FONT>{font_size: 12}
+                            color: #000
+                        }
+                    }
+                    <View> {width: Fill, height: 0}
+                    <Label> {
+                        text: "Strategy"
+                        draw_text: {
+                            text_style: <REGULAR_FONT>{font_size: 11}
+                            color: #555
+                        }
+                    }
+                    key_strategy = <StrategyDropDown> {
+                        labels: ["Manual", "Round robin", "Failover on rate limit"]
+                    }
+                }
+                <Label> {
+                    width: Fill
+                    text: "Add several named keys to rotate between them, or to switch keys without retyping them."
+                    draw_text: {
+                        wrap: Word
+                        text_style: <REGULAR_FONT>{font_size: 10}
+                        color: #555
+                    }
+                }
+
+                api_keys_list = <ApiKeysList> {}
+
+                <View> {
+                    width: Fill, height: Fit
+                    align: {y: 0.5}
+                    spacing: 10
+                    new_key_name = <MolyTextInput> {
+                        width: 150, height: 30
+                        empty_text: "Name"
+                        draw_text: {
+                            text_style: <REGULAR_FONT>{font_size: 11}
+                        }
+                        is_multiline: false
+                    }
+                    new_key_value = <MolyTextInput> {
+                        width: Fill, height: 30
+                        empty_text: "API key"
+                        draw_text: {
+                            text_style: <REGULAR_FONT>{font_size: 11}
+                        }
+                        is_password: true
+                        is_multiline: false
+                    }
+                    add_key_button = <MolyButton> {
+                        width: Fit
+                        height: 30
+                        padding: {left: 14, right: 14, top: 0, bottom: 0}
+                        text: "Add key"
+                        draw_bg: { color: (CTA_BUTTON_COLOR), border_size: 0 }
+                    }
+                }
+            }
+
             // SYSTEM PROMPT
             system_prompt_group = <FormGroup> {
                 margin: {top: (MD_GAP)}
@@ -320,13 +446,123 @@ Moly automatically appends useful context to your prompt, like the time of day."
                 }
             }
 
-            save_provider = <MolyButton> {
+            // MODEL RULES
+            <FormGroup> {
                 margin: {top: (MD_GAP)}
-                width: Fit
-                height: 30
-                padding: {left: 20, right: 20, top: 0, bottom: 0}
-                text: "Save"
-                draw_bg: { color: (CTA_BUTTON_COLOR), border_size: 0 }
+                spacing: 5
+                <Label> {
+                    text: "Model Rules"
+                    draw_text: {
+                        text_style: <BOLD_FONT>{font_size: 12}
+                        color: #000
+                    }
+                }
+                <Label> {
+                    width: Fill
+                    text: "Comma separated globs (gpt-*) or /regular expressions/. Leave include empty to keep all models."
+                    draw_text: {
+                        wrap: Word
+                        text_style: <REGULAR_FONT>{font_size: 10}
+                        color: #555
+                    }
+                }
+
+                <View> {
+                    width: Fill, height: Fit
+                    spacing: 10
+                    models_include = <MolyTextInput> {
+                        width: Fill, height: 30
+                        empty_text: "Include models"
+                        draw_text: {
+                            text_style: <REGULAR_FONT>{font_size: 11}
+                        }
+                        is_multiline: false
+                        autocorrect: Disabled
+                        autocapitalize: None
+                    }
+                    models_exclude = <MolyTextInput> {
+                        width: Fill, height: 30
+                        empty_text: "Exclude models"
+                        draw_text: {
+                            text_style: <REGULAR_FONT>{font_size: 11}
+                        }
+                        is_multiline: false
+                        autocorrect: Disabled
+                        autocapitalize: None
+                    }
+                }
+            }
+
+            <View> {
+                margin: {top: (MD_GAP)}
+                width: Fill, height: Fit
+                spacing: (MD_GAP)
+                save_provider = <MolyButton> {
+                    width: Fit
+                    height: 30
+                    padding: {left: 20, right: 20, top: 0, bottom: 0}
+                    text: "Save"
+                    draw_bg: { color: (CTA_BUTTON_COLOR), border_size: 0 }
+                }
+                diagnose_button = <MolyButton> {
+                    width: Fit
+                    height: 30
+                    padding: {left: 20, right: 20, top: 0, bottom: 0}
+                    text: "Diagnose"
+                    draw_bg: {
+                        color: (TRANSPARENT)
+                        border_color_1: (CTA_BUTTON_COLOR)
+                        border_size: 1.0
+                    }
+                    draw_text: {
+                        color: (CTA_BUTTON_COLOR)
+                    }
+                }
+            }
+
+            // DIAGNOSTICS
+            diagnostics_group = <FormGroup> {
+                margin: {top: (MD_GAP)}
+                visible: false
+                spacing: 5
+
+                <View> {
+                    width: Fill, height: Fit
+                    align: {y: 0.5}
+                    diagnostics_title = <Label> {
+                        text: "Diagnostics"
+                        draw_text: {
+                            text_style: <BOLD_FONT>{font_size: 12}
+                            color: #000
+                        }
+                    }
+                    <View> {width: Fill, height: 0}
+                    copy_report_button = <MolyButton> {
+                        width: Fit
+                        height: 26
+                        padding: {left: 12, right: 12, top: 0, bottom: 0}
+                        text: "Copy report"
+                        draw_bg: { color: (CTA_BUTTON_COLOR), border_size: 0 }
+                    }
+                }
+
+                <RoundedView> {
+                    width: Fill, height: Fit
+                    padding: 10
+                    show_bg: true
+                    draw_bg: {
+                        color: #F5F5F5
+                        border_radius: 3
+                    }
+                    diagnostics_report = <Label> {
+                        width: Fill
+                        draw_text: {
+                            wrap: Word
+                            text_style: <REGULAR_FONT>{font_size: 10}
+                            color: #000
+                        }
+                    }
+                }
             }
 
             provider_features_group = <View> {
@@ -391,6 +627,102 @@ Moly automatically appends useful context to your prompt, like the time of day."
                     }
                 }
 
+                // THINK TAGS
+                think_tags_form_group = <FormGroup> {
+                    visible: false
+                    height: Fit
+
+                    <View> {
+                        margin: {top: (MD_GAP)}
+                        flow: Right
+                        width: Fit, height: Fit
+                        align: {x: 0.5, y: 0.5}
+                        <Label> {
+                            text: "Show <think> tags as reasoning"
+                            draw_text: {
+                                text_style: {font_size: 12}
+                                color: #000
+                            }
+                        }
+
+                        provider_think_tags_switch = <MolySwitch> {
+                            margin: {left: (MD_GAP)}
+                        }
+                    }
+                    <Label> {
+                        width: Fill
+                        margin: {top: 5}
+                        text: "For models that stream their reasoning inline, like Qwen3 or DeepSeek-R1 on local servers."
+                        draw_text: {
+                            wrap: Word
+                            text_style: <REGULAR_FONT>{font_size: 10}
+                            color: #555
+                        }
+                    }
+                }
+
+                // HOSTED TOOLS
+                hosted_tools_form_group = <FormGroup> {
+                    visible: false
+                    height: Fit
+
+                    <View> {
+                        margin: {top: (MD_GAP)}
+                        flow: Right
+                        width: Fit, height: Fit
+                        align: {x: 0.5, y: 0.5}
+                        <Label> {
+                            text: "Web search"
+                            draw_text: {
+                                text_style: {font_size: 12}
+                                color: #000
+                            }
+                        }
+
+                        provider_web_search_switch = <MolySwitch> {
+                            margin: {left: (MD_GAP)}
+                        }
+                    }
+                    <View> {
+                        margin: {top: (MD_GAP)}
+                        flow: Right
+                        width: Fit, height: Fit
+                        align: {x: 0.5, y: 0.5}
+                        <Label> {
+                            text: "Code interpreter"
+                            draw_text: {
+                                text_style: {font_size: 12}
+                                color: #000
+                            }
+                        }
+
+                        provider_code_interpreter_switch = <MolySwitch> {
+                            margin: {left: (MD_GAP)}
+                        }
+                    }
+                    provider_file_search_stores = <MolyTextInput> {
+                        margin: {top: (MD_GAP)}
+                        width: Fill, height: 30
+                        empty_text: "Vector store ids for file search, comma separated"
+                        draw_text: {
+                            text_style: <REGULAR_FONT>{font_size: 11}
+                        }
+                        is_multiline: false
+                        autocorrect: Disabled
+                        autocapitalize: None
+                    }
+                    <Label> {
+                        width: Fill
+                        margin: {top: 5}
+                        text: "Tools run by OpenAI itself. Each call is billed by OpenAI on top of the tokens used. File search is offered when vector stores are set, and applies when saving."
+                        draw_text: {
+                            wrap: Word
+                            text_style: <REGULAR_FONT>{font_size: 10}
+                            color: #555
+                        }
+                    }
+                }
+
                 // MODELS
                 models_label = <Label> {
                     margin: {top: (MD_GAP)}
@@ -477,10 +809,22 @@ struct ProviderView {
 
     #[rust]
     showing_others: bool,
+
+    /// When the listed models come from the models cache, the time they were fetched.
+    #[rust]
+    stale_models_fetched_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    #[rust]
+    diagnosing: bool,
+
+    /// Text of the last diagnostics report, ready to be copied.
+    #[rust]
+    diagnostics_report: Option<String>,
 }
 
 impl Widget for ProviderView {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.ui_runner().handle(cx, event, scope, self);
         self.view.handle_event(cx, event, scope);
         self.widget_match_event(cx, event, scope);
     }
@@ -571,8 +915,22 @@ impl Widget for ProviderView {
             }
         }
 
+        self.stale_models_fetched_at = if store.chats.has_stale_models(&self.provider.id) {
+            store
+                .chats
+                .models_cache
+                .get(&self.provider.id)
+                .map(|cached| cached.fetched_at)
+        } else {
+            None
+        };
+
         self.update_connection_status(cx);
 
+        // Browsers don't allow the low level checks the diagnostics rely on
+        #[cfg(target_arch = "wasm32")]
+        self.button(ids!(diagnose_button)).set_visible(cx, false);
+
         if self.provider.enabled {
             self.view(ids!(refresh_button)).set_visible(cx, true);
         } else {
@@ -620,6 +978,10 @@ impl Widget for ProviderView {
 
                                 item.as_model_entry().set_model_name(&bot.name);
                                 item.as_model_entry().set_model_id(&bot.id.to_string());
+                                item.as_model_entry().set_fallback_models(
+                                    cx,
+                                    store.preferences.fallback_models_for(&bot.id),
+                                );
                                 item.draw_all(cx, scope);
                             }
                             previous_was_header = false;
@@ -633,9 +995,121 @@ impl Widget for ProviderView {
 }
 
 impl ProviderView {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn diagnose(&mut self, cx: &mut Cx, test_model: Option<String>) {
+        self.diagnosing = true;
+        self.diagnostics_report = None;
+        self.view(ids!(diagnostics_group)).set_visible(cx, true);
+        self.label(ids!(diagnostics_title))
+            .set_text(cx, "Diagnostics (running...)");
+        self.label(ids!(diagnostics_report))
+            .set_text(cx, "Running connection checks...");
+        self.button(ids!(copy_report_button)).set_visible(cx, false);
+        self.redraw(cx);
+
+        let provider = self.provider.clone();
+        let ui = self.ui_runner();
+        spawn(async move {
+            let report = diagnose_provider(provider.clone(), test_model).await;
+            ui.defer_with_redraw(move |me, cx, _| {
+                // Ignore reports for a provider that's no longer shown
+                if me.provider.id != provider.id {
+                    return;
+                }
+
+                let title = if report.passed() {
+                    "Diagnostics (all checks passed)"
+                } else {
+                    "Diagnostics (some checks failed)"
+                };
+                let text = report.to_text();
+                me.diagnosing = false;
+                me.label(ids!(diagnostics_title)).set_text(cx, title);
+                me.label(ids!(diagnostics_report)).set_text(cx, &text);
+                me.button(ids!(copy_report_button)).set_visible(cx, true);
+                me.diagnostics_report = Some(text);
+            });
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn diagnose(&mut self, _cx: &mut Cx, _test_model: Option<String>) {
+        ::log::error!("Provider diagnostics are not supported on wasm32");
+    }
+
+    /// Persists a change to the API keys, keeping the single key in sync with the
+    /// selected one so everything using it keeps working.
+    fn save_api_keys(&mut self, cx: &mut Cx, store: &mut Store) {
+        if let Some(selected) = self.provider.api_keys.selected_key() {
+            self.provider.api_key = Some(selected.key.clone());
+        }
+        store.insert_or_update_provider(&self.provider);
+        self.update_api_keys(cx);
+        self.redraw(cx);
+    }
+
+    fn update_api_keys(&mut self, cx: &mut Cx) {
+        let api_keys = &self.provider.api_keys;
+        let strategy = KeyStrategy::ALL
+            .iter()
+            .position(|s| *s == api_keys.strategy)
+            .unwrap_or(0);
+        self.drop_down(ids!(key_strategy))
+            .set_selected_item(cx, strategy);
+        self.api_keys_list(ids!(api_keys_list)).set_api_keys(
+            cx,
+            api_keys,
+            KeyRotation::for_provider(&self.provider.id),
+        );
+
+        // The single key is replaced by the list once it has keys
+        let single_key = api_keys.is_empty();
+        self.label(ids!(api_key_label)).set_visible(cx, single_key);
+        self.view(ids!(api_key_row)).set_visible(cx, single_key);
+        let api_key = self.provider.api_key.as_deref().unwrap_or_default();
+        self.text_input(ids!(api_key)).set_text(cx, api_key);
+    }
+
+    /// Falls back to "Key N" for unnamed keys, and numbers repeated names.
+    fn unique_key_name(&self, name: String) -> String {
+        let keys = &self.provider.api_keys.keys;
+        let base = if name.is_empty() {
+            format!("Key {}", keys.len() + 1)
+        } else {
+            name
+        };
+
+        let mut candidate = base.clone();
+        let mut suffix = 2;
+        while keys.iter().any(|k| k.name == candidate) {
+            candidate = format!("{} ({})", base, suffix);
+            suffix += 1;
+        }
+        candidate
+    }
+
+    fn clear_diagnostics(&mut self, cx: &mut Cx) {
+        self.diagnosing = false;
+        self.diagnostics_report = None;
+        self.view(ids!(diagnostics_group)).set_visible(cx, false);
+    }
+
     fn update_connection_status(&mut self, cx: &mut Cx) {
         let connection_status_label = self.label(ids!(connection_status));
-        connection_status_label.set_text(cx, &self.provider.connection_status.to_human_readable());
+        let status_text = self.provider.connection_status.to_human_readable();
+        if let Some(fetched_at) = self.stale_models_fetched_at {
+            let fetched_at = fetched_at.with_timezone(&chrono::Local);
+            connection_status_label.set_text(
+                cx,
+                &format!(
+                    "{} (showing models cached on {})",
+                    status_text,
+                    fetched_at.format("%Y-%m-%d %H:%M")
+                ),
+            );
+        } else {
+            connection_status_label.set_text(cx, status_text);
+        }
         let text_color = match &self.provider.connection_status {
             ProviderConnectionStatus::Connected => {
                 // green
@@ -692,6 +1166,32 @@ impl WidgetMatchEvent for ProviderView {
             self.redraw(cx);
         }
 
+        // Handle <think> tags parsing enabled/disabled
+        let think_tags_switch = self.check_box(ids!(provider_think_tags_switch));
+        if let Some(think_tags) = think_tags_switch.changed(actions) {
+            self.provider.think_tags = Some(think_tags);
+            store.insert_or_update_provider(&self.provider);
+            self.redraw(cx);
+        }
+
+        // Handle the tools run by the provider enabled/disabled
+        if let Some(web_search) = self
+            .check_box(ids!(provider_web_search_switch))
+            .changed(actions)
+        {
+            self.provider.hosted_tools.web_search = web_search;
+            store.insert_or_update_provider(&self.provider);
+            self.redraw(cx);
+        }
+        if let Some(code_interpreter) = self
+            .check_box(ids!(provider_code_interpreter_switch))
+            .changed(actions)
+        {
+            self.provider.hosted_tools.code_interpreter = code_interpreter;
+            store.insert_or_update_provider(&self.provider);
+            self.redraw(cx);
+        }
+
         for action in actions {
             if let Some(action) = action.downcast_ref::<ModelEntryAction>() {
                 match action {
@@ -719,11 +1219,65 @@ impl WidgetMatchEvent for ProviderView {
                         store.reload_bot_context();
                         self.redraw(cx);
                     }
+                    ModelEntryAction::FallbackModelsChanged(model_id, text) => {
+                        store.preferences.set_fallback_models(
+                            &BotId::new(model_id),
+                            parse_fallback_models(text),
+                        );
+                        cx.action(ChatAction::FallbacksChanged);
+                    }
                     _ => {}
                 }
             }
         }
 
+        if let Some(index) = self.drop_down(ids!(key_strategy)).selected(actions) {
+            self.provider.api_keys.strategy = KeyStrategy::ALL[index];
+            self.save_api_keys(cx, store);
+        }
+
+        if self.button(ids!(add_key_button)).clicked(actions) {
+            let key = self
+                .text_input(ids!(new_key_value))
+                .text()
+                .trim()
+                .to_string();
+            if !key.is_empty() {
+                let name = self
+                    .text_input(ids!(new_key_name))
+                    .text()
+                    .trim()
+                    .to_string();
+                let name = self.unique_key_name(name);
+                self.provider.api_keys.keys.push(NamedApiKey { name, key });
+                self.text_input(ids!(new_key_name)).set_text(cx, "");
+                self.text_input(ids!(new_key_value)).set_text(cx, "");
+                self.save_api_keys(cx, store);
+            }
+        }
+
+        for action in actions {
+            match action.cast() {
+                ApiKeysListAction::Select(index) => {
+                    self.provider.api_keys.selected = index;
+                    self.save_api_keys(cx, store);
+                }
+                ApiKeysListAction::Remove(index) => {
+                    let api_keys = &mut self.provider.api_keys;
+                    api_keys.keys.remove(index);
+                    if api_keys.selected > index || api_keys.selected >= api_keys.keys.len() {
+                        api_keys.selected = api_keys.selected.saturating_sub(1);
+                    }
+                    // The single key mirrored the removed one, so it's revoked too
+                    if api_keys.is_empty() {
+                        self.provider.api_key = None;
+                    }
+                    self.save_api_keys(cx, store);
+                }
+                ApiKeysListAction::None => {}
+            }
+        }
+
         // Handle save
         if self.button(ids!(save_provider)).clicked(actions) {
             self.provider.url = self
@@ -738,7 +1292,9 @@ impl WidgetMatchEvent for ProviderView {
                 .text()
                 .trim()
                 .to_string();
-            if api_key.is_empty() {
+            if let Some(selected) = self.provider.api_keys.selected_key() {
+                self.provider.api_key = Some(selected.key.clone());
+            } else if api_key.is_empty() {
                 self.provider.api_key = None;
             } else {
                 self.provider.api_key = Some(api_key);
@@ -759,6 +1315,26 @@ impl WidgetMatchEvent for ProviderView {
                 }
             }
 
+            if self.provider.provider_type == ProviderType::OpenAiResponses {
+                self.provider.hosted_tools.file_search_vector_stores = parse_patterns(
+                    &self
+                        .view
+                        .text_input(ids!(provider_file_search_stores))
+                        .text(),
+                );
+            }
+
+            // Only store rules if they differ from the defaults, so defaults can evolve
+            let model_rules = ModelRules {
+                include: parse_patterns(&self.view.text_input(ids!(models_include)).text()),
+                exclude: parse_patterns(&self.view.text_input(ids!(models_exclude)).text()),
+            };
+            if model_rules == ModelRules::default_for(&self.provider.provider_type) {
+                self.provider.model_rules = None;
+            } else {
+                self.provider.model_rules = Some(model_rules);
+            }
+
             // Since we auto-fetch the models upon update, also enable it
             self.provider.enabled = true;
             // Clear any previous error state and set to connecting
@@ -775,6 +1351,24 @@ impl WidgetMatchEvent for ProviderView {
             self.redraw(cx);
         }
 
+        if self.button(ids!(diagnose_button)).clicked(actions) && !self.diagnosing {
+            let test_model = store
+                .chats
+                .get_provider_models(&self.provider.id)
+                .into_iter()
+                .find(|bot| bot.enabled)
+                .and_then(|bot| {
+                    RouterClient::unprefix(&bot.id).map(|(_, raw_id)| raw_id.as_str().to_string())
+                });
+            self.diagnose(cx, test_model);
+        }
+
+        if self.button(ids!(copy_report_button)).clicked(actions) {
+            if let Some(report) = &self.diagnostics_report {
+                cx.copy_to_clipboard(report);
+            }
+        }
+
         // Handle refresh button
         if let Some(_fe) = self.view(ids!(refresh_button)).finger_up(actions) {
             // Clear any previous error state and set to connecting
@@ -812,6 +1406,9 @@ impl WidgetMatchEvent for ProviderView {
 impl ProviderViewRef {
     pub fn set_provider(&mut self, cx: &mut Cx, provider: &Provider) {
         if let Some(mut inner) = self.borrow_mut() {
+            if inner.provider.id != provider.id {
+                inner.clear_diagnostics(cx);
+            }
             inner.provider = provider.clone();
             inner.text_input(ids!(model_search_input)).set_text(cx, "");
             inner.showing_others = false;
@@ -825,6 +1422,14 @@ impl ProviderViewRef {
             }
 
             inner.text_input(ids!(api_host)).set_text(cx, &provider.url);
+            inner.update_api_keys(cx);
+            let model_rules = provider.model_rules();
+            inner
+                .text_input(ids!(models_include))
+                .set_text(cx, &model_rules.include.join(", "));
+            inner
+                .text_input(ids!(models_exclude))
+                .set_text(cx, &model_rules.exclude.join(", "));
             inner.label(ids!(name)).set_text(cx, &provider.name);
             inner
                 .label(ids!(provider_type))
@@ -852,12 +1457,45 @@ impl ProviderViewRef {
 
             if provider.provider_type == ProviderType::OpenAiRealtime
                 || provider.provider_type == ProviderType::OpenAi
+                || provider.provider_type == ProviderType::OpenAiResponses
             {
                 inner.view(ids!(tools_form_group)).set_visible(cx, true);
             } else {
                 inner.view(ids!(tools_form_group)).set_visible(cx, false);
             }
 
+            let parses_think_tags = matches!(
+                provider.provider_type,
+                ProviderType::OpenAi | ProviderType::MolyServer
+            );
+            inner
+                .view(ids!(think_tags_form_group))
+                .set_visible(cx, parses_think_tags);
+            if parses_think_tags {
+                inner
+                    .check_box(ids!(provider_think_tags_switch))
+                    .set_active(cx, provider.parses_think_tags());
+            }
+
+            let has_hosted_tools = provider.provider_type == ProviderType::OpenAiResponses;
+            inner
+                .view(ids!(hosted_tools_form_group))
+                .set_visible(cx, has_hosted_tools);
+            if has_hosted_tools {
+                inner
+                    .check_box(ids!(provider_web_search_switch))
+                    .set_active(cx, provider.hosted_tools.web_search);
+                inner
+                    .check_box(ids!(provider_code_interpreter_switch))
+                    .set_active(cx, provider.hosted_tools.code_interpreter);
+                inner
+                    .text_input(ids!(provider_file_search_stores))
+                    .set_text(
+                        cx,
+                        &provider.hosted_tools.file_search_vector_stores.join(", "),
+                    );
+            }
+
             if provider.was_customly_added {
                 inner.view(ids!(remove_provider_view)).set_visible(cx, true);
             } else {
@@ -887,6 +1525,11 @@ struct ModelEntry {
 
     #[rust]
     model_id: String,
+
+    /// Whether the fallback models input was filled from preferences already.
+    /// It's not refilled afterwards to avoid fighting the user while typing.
+    #[rust]
+    fallback_models_filled: bool,
 }
 
 impl Widget for ModelEntry {
@@ -903,7 +1546,10 @@ impl Widget for ModelEntry {
                 cx,
                 live! {
                     height: 60
-                    content = { model_name = { width: Fit } }
+                    content = {
+                        model_name = { width: Fit }
+                        enabled_toggle = { fallback_models = { visible: true } }
+                    }
                     vertical_filler = { visible: true }
                 },
             );
@@ -912,7 +1558,10 @@ impl Widget for ModelEntry {
                 cx,
                 live! {
                     height: 80
-                    content = { model_name = { width: 200 } }
+                    content = {
+                        model_name = { width: 200 }
+                        enabled_toggle = { fallback_models = { visible: false } }
+                    }
                     vertical_filler = { visible: false }
                 },
             );
@@ -934,6 +1583,13 @@ impl WidgetMatchEvent for ModelEntry {
             ));
             self.redraw(cx);
         }
+
+        if let Some(text) = self.text_input(ids!(fallback_models)).changed(actions) {
+            cx.action(ModelEntryAction::FallbackModelsChanged(
+                self.model_id.clone(),
+                text,
+            ));
+        }
     }
 }
 
@@ -949,10 +1605,22 @@ impl ModelEntryRef {
             inner.model_id = id.to_string();
         }
     }
+
+    pub fn set_fallback_models(&mut self, cx: &mut Cx, fallback_models: &[String]) {
+        if let Some(mut inner) = self.borrow_mut() {
+            if !inner.fallback_models_filled {
+                inner.fallback_models_filled = true;
+                inner
+                    .text_input(ids!(fallback_models))
+                    .set_text(cx, &fallback_models.join(", "));
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, DefaultNone)]
 enum ModelEntryAction {
     None,
     ModelEnabledChanged(String, String, bool),
+    FallbackModelsChanged(String, String),
 }
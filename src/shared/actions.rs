@@ -12,6 +12,8 @@ pub enum ChatAction {
     Start(BotId),
     // Select a chat from the chat history
     ChatSelected(ChatId),
+    // The fallback models of a chat or a bot were edited
+    FallbacksChanged,
     None,
 }
 
@@ -3,14 +3,30 @@
 use moly_kit::prelude::*;
 use std::sync::{Arc, Mutex};
 
+use crate::data::failover_client::{FailoverClient, FailoverHandle};
+use crate::data::reasoning::{ReasoningHandle, ReasoningParamsClient};
+use crate::data::tool_selection::{ToolSelectionClient, ToolSelectionHandle};
+
+/// A [`ChatController`] observing the context, with the failover settings, tool
+/// selection and reasoning params of its chat.
+#[derive(Clone)]
+struct ObservedChatController {
+    chat_controller: Arc<Mutex<ChatController>>,
+    failover: FailoverHandle,
+    tools: ToolSelectionHandle,
+    reasoning: ReasoningHandle,
+}
+
 struct InnerBotContext {
     client: Box<dyn BotClient>,
     bots: Vec<Bot>,
+    /// Bots to expose when the client can't list them (e.g. provider unreachable).
+    fallback_bots: Vec<Bot>,
     tool_manager: Option<McpManagerClient>,
     /// Status tracked for compatibility with [`ChatController`].
     status: Status,
     /// [`ChatController`]s "observing" this context. This is glue.
-    chat_controllers: Vec<Arc<Mutex<ChatController>>>,
+    chat_controllers: Vec<ObservedChatController>,
 }
 
 /// A sharable wrapper around a [BotClient] that holds loadeed bots and provides
@@ -53,8 +69,15 @@ impl BotContext {
             let result = self.client().bots().await;
             let (new_bots, errors) = result.into_value_and_errors();
 
-            if let Some(new_bots) = new_bots {
-                self.0.lock().unwrap().bots = new_bots;
+            {
+                let mut inner = self.0.lock().unwrap();
+                let mut bots = new_bots.unwrap_or_else(|| inner.bots.clone());
+                for fallback in &inner.fallback_bots {
+                    if !bots.iter().any(|bot| bot.id == fallback.id) {
+                        bots.push(fallback.clone());
+                    }
+                }
+                inner.bots = bots;
             }
 
             let result = if errors.is_empty() {
@@ -84,6 +107,26 @@ impl BotContext {
         self.bots().into_iter().find(|bot| bot.id == *id)
     }
 
+    /// Sets bots to be listed when the client doesn't return them on [`Self::load`].
+    pub fn set_fallback_bots(&mut self, bots: Vec<Bot>) {
+        self.0.lock().unwrap().fallback_bots = bots;
+    }
+
+    /// Replaces the bots of a provider, e.g. once it answers after being listed from
+    /// the models cache.
+    pub fn replace_provider_bots(&mut self, provider_id: &str, bots: Vec<Bot>) {
+        {
+            let mut inner = self.0.lock().unwrap();
+            let of_provider = |bot: &Bot| {
+                RouterClient::unprefix(&bot.id).is_some_and(|(id, _)| id == provider_id)
+            };
+            inner.bots.retain(|bot| !of_provider(bot));
+            inner.fallback_bots.retain(|bot| !of_provider(bot));
+            inner.bots.extend(bots);
+        }
+        self.synchronize_to_all();
+    }
+
     pub fn tool_manager(&self) -> Option<McpManagerClient> {
         self.0.lock().unwrap().tool_manager.clone()
     }
@@ -96,9 +139,22 @@ impl BotContext {
     /// Copies the data and status from this context into the controller.
     ///
     /// This is a glue function while migrating away from [`BotContext`].
-    fn synchronize_to(&self, chat_controller: &mut ChatController) {
+    ///
+    /// The client is wrapped in a [`FailoverClient`] bound to the chat's failover
+    /// settings, and only sends the tools selected in the chat along with its
+    /// reasoning params.
+    fn synchronize_to(
+        &self,
+        chat_controller: &mut ChatController,
+        observed: &ObservedChatController,
+    ) {
+        let client = ToolSelectionClient::new(self.client(), observed.tools.clone());
+        let client = ReasoningParamsClient::new(Box::new(client), observed.reasoning.clone());
         chat_controller.set_tool_manager(self.tool_manager());
-        chat_controller.set_client(Some(self.client()));
+        chat_controller.set_client(Some(Box::new(FailoverClient::new(
+            Box::new(client),
+            observed.failover.clone(),
+        ))));
         chat_controller.dispatch_mutation(VecMutation::Set(self.bots().clone()));
         chat_controller.dispatch_mutation(ChatStateMutation::SetLoadStatus(
             self.0.lock().unwrap().status,
@@ -107,18 +163,29 @@ impl BotContext {
 
     fn synchronize_to_all(&self) {
         let controllers = self.0.lock().unwrap().chat_controllers.clone();
-        for controller in controllers {
-            let mut controller = controller.lock().unwrap();
-            self.synchronize_to(&mut controller);
+        for observed in controllers {
+            let mut controller = observed.chat_controller.lock().unwrap();
+            self.synchronize_to(&mut controller, &observed);
         }
     }
 
-    pub fn add_chat_controller(&mut self, chat_controller: Arc<Mutex<ChatController>>) {
+    pub fn add_chat_controller(
+        &mut self,
+        chat_controller: Arc<Mutex<ChatController>>,
+        failover: FailoverHandle,
+        tools: ToolSelectionHandle,
+        reasoning: ReasoningHandle,
+    ) {
         self.0
             .lock()
             .unwrap()
             .chat_controllers
-            .push(chat_controller);
+            .push(ObservedChatController {
+                chat_controller,
+                failover,
+                tools,
+                reasoning,
+            });
 
         // Only sync if bots are already loaded
         if !self.0.lock().unwrap().bots.is_empty() {
@@ -129,7 +196,7 @@ impl BotContext {
     pub fn remove_chat_controller(&mut self, chat_controller: &Arc<Mutex<ChatController>>) {
         let ptr = Arc::as_ptr(chat_controller) as usize;
         self.0.lock().unwrap().chat_controllers.retain(|c| {
-            let c_ptr = Arc::as_ptr(c) as usize;
+            let c_ptr = Arc::as_ptr(&c.chat_controller) as usize;
             c_ptr != ptr
         });
     }
@@ -140,6 +207,7 @@ impl<T: BotClient + 'static> From<T> for BotContext {
         BotContext(Arc::new(Mutex::new(InnerBotContext {
             client: Box::new(client),
             bots: Vec::new(),
+            fallback_bots: Vec::new(),
             tool_manager: None,
             status: Status::Idle,
             chat_controllers: Vec::new(),
@@ -6,7 +6,7 @@ use std::{
 use super::super::adapter::Adapter;
 use anyhow::Result;
 use directories::ProjectDirs;
-use futures::StreamExt;
+use futures::{AsyncWriteExt, StreamExt};
 
 const APP_QUALIFIER: &str = "com";
 const APP_ORGANIZATION: &str = "moly-ai";
@@ -78,4 +78,17 @@ impl Adapter for NativeAdapter {
         async_fs::write(path, content).await?;
         Ok(())
     }
+
+    async fn append(&mut self, path: &Path, content: &[u8]) -> Result<()> {
+        let path = validate_and_resolve(path);
+        async_fs::create_dir_all(path.parent().unwrap()).await?;
+        let mut file = async_fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(content).await?;
+        file.flush().await?;
+        Ok(())
+    }
 }
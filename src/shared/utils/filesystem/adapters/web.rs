@@ -50,4 +50,15 @@ impl Adapter for WebAdapter {
         web_fs::write(path, content).await?;
         Ok(())
     }
+
+    async fn append(&mut self, path: &Path, content: &[u8]) -> Result<()> {
+        // OPFS files are small enough here to be rewritten with the new content.
+        let mut current = match web_fs::read(path).await {
+            Ok(current) => current,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        current.extend_from_slice(content);
+        self.write(path, &current).await
+    }
 }
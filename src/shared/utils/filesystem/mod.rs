@@ -61,7 +61,14 @@ use std::{
 #[derive(Debug)]
 pub struct FileSystem<A: Adapter> {
     adapter: Arc<futures::lock::Mutex<A>>,
-    write_queue: mpsc::Sender<(PathBuf, Vec<u8>, oneshot::Sender<Result<()>>)>,
+    write_queue: mpsc::Sender<(PathBuf, Vec<u8>, WriteMode, oneshot::Sender<Result<()>>)>,
+}
+
+/// How a queued write applies its content to the file.
+#[derive(Debug, Clone, Copy)]
+enum WriteMode {
+    Replace,
+    Append,
 }
 
 impl<A: Adapter> Clone for FileSystem<A> {
@@ -75,17 +82,21 @@ impl<A: Adapter> Clone for FileSystem<A> {
 
 impl<A: Adapter> FileSystem<A> {
     fn new(adapter: A) -> Self {
-        let (tx, mut rx) = mpsc::channel::<(PathBuf, Vec<u8>, oneshot::Sender<Result<()>>)>(0);
+        let (tx, mut rx) =
+            mpsc::channel::<(PathBuf, Vec<u8>, WriteMode, oneshot::Sender<Result<()>>)>(0);
         let adapter = Arc::new(futures::lock::Mutex::new(adapter));
 
         let adapter_clone = Arc::clone(&adapter);
         spawn(async move {
-            while let Some((path, content, response)) = rx.next().await {
+            while let Some((path, content, mode, response)) = rx.next().await {
                 let adapter_clone = Arc::clone(&adapter_clone);
                 let path_clone = path.clone();
                 let write_future = async move {
                     let mut adapter = adapter_clone.lock().await;
-                    adapter.write(&path_clone, &content).await
+                    match mode {
+                        WriteMode::Replace => adapter.write(&path_clone, &content).await,
+                        WriteMode::Append => adapter.append(&path_clone, &content).await,
+                    }
                 };
 
                 match write_future.await {
@@ -137,7 +148,6 @@ impl<A: Adapter> FileSystem<A> {
 
     /// Check existence of a file. Errors if it cannot be determined.
     // TODO: Consider using a `metadata` method instead.
-    #[allow(dead_code)]
     pub async fn exists(&self, path: &Path) -> Result<bool> {
         let mut adapter = self.adapter.lock().await;
         adapter.exists(path).await
@@ -158,9 +168,18 @@ impl<A: Adapter> FileSystem<A> {
     /// Write some bytes content to a given path, creating any necessary directories.
     // TODO: Is adapter responsability to create directories, but it shouldn't.
     pub async fn queue_write(&mut self, path: PathBuf, content: Vec<u8>) -> Result<()> {
+        self.queue(path, content, WriteMode::Replace).await
+    }
+
+    /// Append some bytes content to the end of a file, creating it if missing.
+    pub async fn queue_append(&mut self, path: PathBuf, content: Vec<u8>) -> Result<()> {
+        self.queue(path, content, WriteMode::Append).await
+    }
+
+    async fn queue(&mut self, path: PathBuf, content: Vec<u8>, mode: WriteMode) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.write_queue
-            .send((path, content, tx))
+            .send((path, content, mode, tx))
             .await
             .map_err(|e| anyhow!("Failed to send write request: {:?}", e))?;
 